@@ -0,0 +1,168 @@
+//! Running the engine on a graphics card. Engine builds made with
+//! onnxruntime can hand the model to an execution provider other than the
+//! CPU: CUDA on NVIDIA cards, or DirectML on any card under Windows. Such
+//! builds are published next to the plain ones, with the provider after the
+//! platform, like `deep-filter-0.6.0-x86_64-unknown-linux-gnu-cuda`.
+//!
+//! Whether a provider can work here is guessed from the driver files it
+//! needs, and which one a run used from what the engine printed.
+
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Provider {
+    #[default]
+    Cpu,
+    Cuda,
+    DirectMl,
+}
+
+impl Provider {
+    pub const ALL: [Provider; 3] = [Provider::Cpu, Provider::Cuda, Provider::DirectMl];
+
+    /// Name the provider is saved under, and the ending of its builds.
+    pub fn key(self) -> &'static str {
+        match self {
+            Provider::Cpu => "cpu",
+            Provider::Cuda => "cuda",
+            Provider::DirectMl => "directml",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Provider> {
+        Provider::ALL
+            .into_iter()
+            .find(|provider| provider.key() == key)
+    }
+
+    /// How onnxruntime names the provider in what it prints.
+    fn ort_name(self) -> &'static str {
+        match self {
+            Provider::Cpu => "CPUExecutionProvider",
+            Provider::Cuda => "CUDAExecutionProvider",
+            Provider::DirectMl => "DmlExecutionProvider",
+        }
+    }
+
+    /// The ending of this provider's builds, given the ending of the plain
+    /// build for the platform, like `x86_64-pc-windows-msvc.exe`.
+    pub fn target(self, target: &str) -> String {
+        if self == Provider::Cpu {
+            return target.to_string();
+        }
+        match target.strip_suffix(".exe") {
+            Some(stem) => format!("{}-{}.exe", stem, self.key()),
+            None => format!("{}-{}", target, self.key()),
+        }
+    }
+
+    /// Files the provider needs, for the OS named `os`.
+    fn markers(self, os: &str) -> Vec<PathBuf> {
+        let system = || {
+            PathBuf::from(std::env::var_os("SystemRoot").unwrap_or_else(|| "C:\\Windows".into()))
+                .join("System32")
+        };
+        match (self, os) {
+            (Provider::Cuda, "linux") => vec![
+                PathBuf::from("/proc/driver/nvidia/version"),
+                // The driver of the Windows host, under WSL.
+                PathBuf::from("/usr/lib/wsl/lib/libcuda.so.1"),
+            ],
+            (Provider::Cuda, "windows") => vec![system().join("nvcuda.dll")],
+            (Provider::DirectMl, "windows") => vec![system().join("DirectML.dll")],
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Display for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Provider::Cpu => write!(f, "CPU"),
+            Provider::Cuda => write!(f, "CUDA"),
+            Provider::DirectMl => write!(f, "DirectML"),
+        }
+    }
+}
+
+/// The providers this machine looks ready for, fastest first. The CPU is
+/// always there, last.
+pub fn available() -> Vec<Provider> {
+    available_with(std::env::consts::OS, |path| path.exists())
+}
+
+fn available_with(os: &str, exists: impl Fn(&Path) -> bool) -> Vec<Provider> {
+    Provider::ALL
+        .into_iter()
+        .filter(|provider| provider.markers(os).iter().any(|path| exists(path)))
+        .chain([Provider::Cpu])
+        .collect()
+}
+
+/// The provider the engine last named in `output`, the lines it printed
+/// during a run, if it named any.
+pub fn used(output: &[String]) -> Option<Provider> {
+    output.iter().rev().find_map(|line| {
+        Provider::ALL
+            .into_iter()
+            .find(|provider| line.contains(provider.ort_name()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_are_named_after_the_provider() {
+        let linux = "x86_64-unknown-linux-gnu";
+        assert_eq!(Provider::Cpu.target(linux), linux);
+        assert_eq!(
+            Provider::Cuda.target(linux),
+            "x86_64-unknown-linux-gnu-cuda"
+        );
+        assert_eq!(
+            Provider::DirectMl.target("x86_64-pc-windows-msvc.exe"),
+            "x86_64-pc-windows-msvc-directml.exe"
+        );
+        for provider in Provider::ALL {
+            assert_eq!(Provider::from_key(provider.key()), Some(provider));
+        }
+    }
+
+    #[test]
+    fn providers_follow_the_driver_files() {
+        assert_eq!(available_with("linux", |_| false), [Provider::Cpu]);
+        assert_eq!(
+            available_with("linux", |path| path.ends_with("libcuda.so.1")),
+            [Provider::Cuda, Provider::Cpu]
+        );
+        assert_eq!(
+            available_with("windows", |path| path.ends_with("DirectML.dll")),
+            [Provider::DirectMl, Provider::Cpu]
+        );
+        assert_eq!(available_with("macos", |_| true), [Provider::Cpu]);
+    }
+
+    #[test]
+    fn provider_used_is_read_from_the_output() {
+        let output = |lines: &[&str]| {
+            lines
+                .iter()
+                .map(|line| line.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(used(&output(&["Processing a.wav"])), None);
+        assert_eq!(
+            used(&output(&[
+                "Trying CUDAExecutionProvider",
+                "CUDA unavailable, falling back to CPUExecutionProvider",
+            ])),
+            Some(Provider::Cpu)
+        );
+        assert_eq!(
+            used(&output(&["Using DmlExecutionProvider"])),
+            Some(Provider::DirectMl)
+        );
+    }
+}
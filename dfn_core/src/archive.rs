@@ -0,0 +1,232 @@
+//! Cleaning a whole folder tree into another one with the same layout.
+//! Every supported file below the source is cleaned into the matching
+//! folder below the destination, and the other files can be copied over
+//! as they are.
+//!
+//! Finished files are listed in a log in the destination, so an archive
+//! that was interrupted picks up where it stopped when started again.
+
+use crate::{dedup, formats};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Name of the log of finished files, kept in the destination.
+pub const LOG_NAME: &str = ".dfn_gui-archive.log";
+
+/// A source tree and the destination it's mirrored to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Archive {
+    pub source: PathBuf,
+    pub destination: PathBuf,
+}
+
+/// What's left to do for an [`Archive`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Plan {
+    /// Files to clean, each with the folder its output goes to.
+    pub clean: Vec<(PathBuf, PathBuf)>,
+    /// Other files, each with where its copy goes.
+    pub copy: Vec<(PathBuf, PathBuf)>,
+    /// Files finished by an earlier run of the same archive.
+    pub done: usize,
+    /// Files left out as copies of an earlier one, each with that file.
+    pub duplicates: Vec<(PathBuf, PathBuf)>,
+}
+
+impl Archive {
+    pub fn new(source: PathBuf, destination: PathBuf) -> Result<Self, String> {
+        if !source.is_dir() {
+            return Err(format!("{} is not a folder", source.display()));
+        }
+        if destination == source {
+            return Err("The destination must differ from the source".to_string());
+        }
+        Ok(Self {
+            source,
+            destination,
+        })
+    }
+
+    /// Whether `file` is from this archive's source.
+    pub fn contains(&self, file: &Path) -> bool {
+        file.starts_with(&self.source)
+    }
+
+    /// Lists the files still to clean, and to copy when `copy_other` is set.
+    /// With `skip_duplicates`, files to clean that are copies of an earlier
+    /// one, finished or not, are left out.
+    pub fn plan(&self, copy_other: bool, skip_duplicates: bool) -> Result<Plan, String> {
+        let finished = self.finished();
+        let mut seen = dedup::Seen::default();
+        let mut plan = Plan::default();
+        for file in self.files()? {
+            let relative = file.strip_prefix(&self.source).unwrap_or(&file);
+            let supported = formats::is_supported(&file);
+            if !supported && !copy_other {
+                continue;
+            }
+            let original = if supported && skip_duplicates {
+                seen.original_of(&file)?
+            } else {
+                None
+            };
+            if finished.contains(relative) {
+                plan.done += 1;
+            } else if let Some(original) = original {
+                plan.duplicates.push((file.clone(), original));
+            } else if supported {
+                let folder = relative.parent().unwrap_or(Path::new(""));
+                plan.clean
+                    .push((file.clone(), self.destination.join(folder)));
+            } else {
+                plan.copy
+                    .push((file.clone(), self.destination.join(relative)));
+            }
+        }
+        Ok(plan)
+    }
+
+    /// Copies `file` from the source to `to`, and notes it as finished.
+    pub fn copy(&self, file: &Path, to: &Path) -> Result<(), String> {
+        if let Some(dir) = to.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        std::fs::copy(file, to).map_err(|e| format!("Could not copy {}: {}", file.display(), e))?;
+        self.finish(file)
+    }
+
+    /// Notes `file` from the source as finished, so it's skipped when the
+    /// archive is started again.
+    pub fn finish(&self, file: &Path) -> Result<(), String> {
+        let relative = file.strip_prefix(&self.source).unwrap_or(file);
+        std::fs::create_dir_all(&self.destination).map_err(|e| e.to_string())?;
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.destination.join(LOG_NAME))
+            .and_then(|mut log| writeln!(log, "{}", relative.display()))
+            .map_err(|e| format!("Could not update the archive log: {}", e))
+    }
+
+    /// Files noted as finished, relative to the source.
+    fn finished(&self) -> HashSet<PathBuf> {
+        std::fs::read_to_string(self.destination.join(LOG_NAME))
+            .unwrap_or_default()
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(PathBuf::from)
+            .collect()
+    }
+
+    /// The files below the source, in name order, leaving out the
+    /// destination when it's inside the source. Linked folders aren't
+    /// followed, so they can't lead around in circles.
+    fn files(&self) -> Result<Vec<PathBuf>, String> {
+        let mut files = Vec::new();
+        let mut folders = vec![self.source.clone()];
+        while let Some(folder) = folders.pop() {
+            let entries = std::fs::read_dir(&folder)
+                .map_err(|e| format!("Could not read {}: {}", folder.display(), e))?;
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let Ok(kind) = entry.file_type() else {
+                    continue;
+                };
+                if kind.is_dir() {
+                    if path != self.destination {
+                        folders.push(path);
+                    }
+                } else if path.is_file() {
+                    files.push(path);
+                }
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(path: &Path) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, b"data").unwrap();
+    }
+
+    #[test]
+    fn tree_is_mirrored_and_resumed() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("talks");
+        for file in ["a.wav", "2024/b.mp3", "2024/notes.txt"] {
+            touch(&source.join(file));
+        }
+        let destination = source.join("clean");
+        touch(&destination.join("old.wav"));
+        let archive = Archive::new(source.clone(), destination.clone()).unwrap();
+
+        let plan = archive.plan(false, false).unwrap();
+        assert_eq!(
+            plan.clean,
+            [
+                (source.join("2024/b.mp3"), destination.join("2024")),
+                (source.join("a.wav"), destination.clone()),
+            ]
+        );
+        assert!(plan.copy.is_empty());
+
+        let plan = archive.plan(true, false).unwrap();
+        assert_eq!(
+            plan.copy,
+            [(
+                source.join("2024/notes.txt"),
+                destination.join("2024/notes.txt")
+            )]
+        );
+        let (file, to) = &plan.copy[0];
+        archive.copy(file, to).unwrap();
+        assert!(to.exists());
+        archive.finish(&source.join("a.wav")).unwrap();
+
+        let plan = archive.plan(true, false).unwrap();
+        assert_eq!(plan.done, 2);
+        assert!(plan.copy.is_empty());
+        assert_eq!(
+            plan.clean,
+            [(source.join("2024/b.mp3"), destination.join("2024"))]
+        );
+    }
+
+    #[test]
+    fn copies_are_left_out_when_asked() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("talks");
+        touch(&source.join("a.wav"));
+        touch(&source.join("b/a copy.wav"));
+        let destination = dir.path().join("clean");
+        let archive = Archive::new(source.clone(), destination.clone()).unwrap();
+
+        assert_eq!(archive.plan(false, false).unwrap().clean.len(), 2);
+        let plan = archive.plan(false, true).unwrap();
+        assert_eq!(plan.clean, [(source.join("a.wav"), destination.clone())]);
+        assert_eq!(
+            plan.duplicates,
+            [(source.join("b/a copy.wav"), source.join("a.wav"))]
+        );
+
+        archive.finish(&source.join("a.wav")).unwrap();
+        let plan = archive.plan(false, true).unwrap();
+        assert!(plan.clean.is_empty());
+        assert_eq!(plan.duplicates.len(), 1);
+    }
+
+    #[test]
+    fn source_must_be_a_folder_apart_from_the_destination() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_path_buf();
+        assert!(Archive::new(path.clone(), path.clone()).is_err());
+        assert!(Archive::new(path.join("missing"), path.join("clean")).is_err());
+    }
+}
@@ -0,0 +1,501 @@
+use crate::cancel::CancelToken;
+use crate::formats::{self, Decoder};
+use crate::{temp, transcode};
+use rubato::{FftFixedIn, Resampler};
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{CODEC_TYPE_NULL, CodecParameters, DecoderOptions};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatReader;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::probe::Hint;
+
+/// Bit depth assumed for decoded formats that don't carry one, such as MP3.
+const DEFAULT_BITS_PER_SAMPLE: u16 = 16;
+
+/// Frames fed to the resampler per call.
+const RESAMPLE_CHUNK: usize = 1024;
+
+/// Sample rate the DeepFilterNet models operate at.
+pub const ENGINE_SAMPLE_RATE: u32 = 48_000;
+
+/// Format used for intermediate files handed to the engine. Float samples
+/// avoid an extra quantization step before denoising.
+pub const ENGINE_SPEC: hound::WavSpec = hound::WavSpec {
+    channels: 1,
+    sample_rate: ENGINE_SAMPLE_RATE,
+    bits_per_sample: 32,
+    sample_format: hound::SampleFormat::Float,
+};
+
+/// Decoded audio, one `Vec` of normalized samples per channel.
+#[derive(Debug, Clone)]
+pub struct AudioBuffer {
+    pub sample_rate: u32,
+    pub channels: Vec<Vec<f32>>,
+}
+
+impl AudioBuffer {
+    pub fn frames(&self) -> usize {
+        self.channels.first().map(Vec::len).unwrap_or(0)
+    }
+}
+
+pub fn is_wav(path: &Path) -> bool {
+    extension(path).is_some_and(|ext| ext == "wav")
+}
+
+/// The lowercased extension of `path`.
+pub fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+}
+
+/// Format fields from a WAV file's `fmt ` chunk.
+#[derive(Debug, Clone, Copy)]
+pub struct WavHeader {
+    /// The `WAVE_FORMAT_*` tag, resolved through the sub-format of
+    /// `WAVE_FORMAT_EXTENSIBLE` headers.
+    pub format_tag: u16,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub bits_per_sample: u16,
+}
+
+impl WavHeader {
+    pub fn encoding(&self) -> &'static str {
+        match self.format_tag {
+            0x0001 => "PCM",
+            0x0003 => "IEEE float",
+            0x0002 => "Microsoft ADPCM",
+            0x0006 => "A-law",
+            0x0007 => "µ-law",
+            0x0011 => "IMA ADPCM",
+            0x0055 => "MP3",
+            _ => "an unknown encoding",
+        }
+    }
+
+    /// Whether the samples can be read directly. Other encodings are
+    /// converted through Symphonia.
+    pub fn is_pcm(&self) -> bool {
+        matches!(self.format_tag, 0x0001 | 0x0003)
+    }
+}
+
+/// What a `.wav` file actually holds, judged by its first bytes.
+#[derive(Debug, Clone, Copy)]
+pub enum WavContent {
+    Wav(WavHeader),
+    /// Another format under a `.wav` name, such as a renamed MP3.
+    Other(&'static str),
+}
+
+/// Reads the header of a `.wav` file, failing with a precise message when it
+/// is neither a WAV nor a recognizable renamed format.
+pub fn inspect_wav(path: &Path) -> Result<WavContent, String> {
+    let to_read_error = |e: std::io::Error| format!("Could not read WAV header: {}", e);
+    let mut file = std::fs::File::open(path).map_err(to_read_error)?;
+    let mut head = [0; 12];
+    let read = file.read(&mut head).map_err(to_read_error)?;
+    let head = &head[..read];
+
+    if head.len() < 12 || &head[0..4] != b"RIFF" || &head[8..12] != b"WAVE" {
+        return match sniff(head) {
+            Some(kind) => Ok(WavContent::Other(kind)),
+            None => Err("This is not a WAV file: it has no RIFF/WAVE header".to_string()),
+        };
+    }
+
+    loop {
+        let mut chunk_header = [0; 8];
+        file.read_exact(&mut chunk_header)
+            .map_err(|_| "This WAV file has no format chunk".to_string())?;
+        let size = u32::from_le_bytes(chunk_header[4..8].try_into().unwrap()) as usize;
+        if &chunk_header[0..4] != b"fmt " {
+            std::io::copy(
+                &mut (&mut file).take((size + size % 2) as u64),
+                &mut std::io::sink(),
+            )
+            .map_err(to_read_error)?;
+            continue;
+        }
+
+        let mut fmt = vec![0; size];
+        file.read_exact(&mut fmt).map_err(to_read_error)?;
+        if fmt.len() < 16 {
+            return Err("This WAV file has a truncated format chunk".to_string());
+        }
+        let u16_at = |offset: usize| u16::from_le_bytes([fmt[offset], fmt[offset + 1]]);
+        let mut format_tag = u16_at(0);
+        if format_tag == 0xFFFE && fmt.len() >= 26 {
+            format_tag = u16_at(24);
+        }
+        return Ok(WavContent::Wav(WavHeader {
+            format_tag,
+            channels: u16_at(2),
+            sample_rate: u32::from_le_bytes(fmt[4..8].try_into().unwrap()),
+            bits_per_sample: u16_at(14),
+        }));
+    }
+}
+
+/// Names the format starting with `head`, for the ones Symphonia can decode.
+fn sniff(head: &[u8]) -> Option<&'static str> {
+    match head {
+        [b'I', b'D', b'3', ..] => Some("MP3 audio"),
+        [0xFF, second, ..] if second & 0xE0 == 0xE0 => Some("MP3 audio"),
+        [b'f', b'L', b'a', b'C', ..] => Some("FLAC audio"),
+        [b'O', b'g', b'g', b'S', ..] => Some("Ogg audio"),
+        [_, _, _, _, b'f', b't', b'y', b'p', ..] => Some("MP4/M4A audio"),
+        [0x1A, 0x45, 0xDF, 0xA3, ..] => Some("Matroska/WebM"),
+        _ => None,
+    }
+}
+
+/// Reads any supported input file, the way [`formats`] says. The returned
+/// spec describes the source format as closely as a WAV can represent it.
+pub fn decode(path: &Path) -> Result<(hound::WavSpec, AudioBuffer), String> {
+    decode_cancellable(path, &CancelToken::new())
+}
+
+/// [`decode`], stopping early once `cancel` is cancelled.
+pub fn decode_cancellable(
+    path: &Path,
+    cancel: &CancelToken,
+) -> Result<(hound::WavSpec, AudioBuffer), String> {
+    match formats::of(path).map(|format| format.decoder) {
+        Some(Decoder::Wav) => {}
+        Some(Decoder::Ffmpeg) => return decode_converted(path, cancel),
+        Some(Decoder::Symphonia) | None => {
+            return decode_compressed(path, cancel).or_else(|e| {
+                // Streams Symphonia has no decoder for, like Opus in Matroska.
+                if transcode::ffmpeg_path().is_some() && !cancel.is_cancelled() {
+                    decode_converted(path, cancel)
+                } else {
+                    Err(e)
+                }
+            });
+        }
+    }
+
+    // Symphonia probes the content rather than trusting the extension, so it
+    // also handles renamed files and compressed WAV encodings.
+    let result = match inspect_wav(path)? {
+        WavContent::Wav(header) if header.is_pcm() => read_wav(path),
+        WavContent::Wav(header) => decode_compressed(path, cancel).map_err(|e| {
+            format!(
+                "This WAV file is encoded as {} and could not be converted to PCM: {}",
+                header.encoding(),
+                e
+            )
+        }),
+        WavContent::Other(kind) => decode_compressed(path, cancel).map_err(|e| {
+            format!(
+                "This file is named .wav but holds {}, which could not be decoded: {}",
+                kind, e
+            )
+        }),
+    };
+    // Not worded as a broken file when it was only cancelled.
+    cancel.check().and(result)
+}
+
+/// Summary of an input file, as shown before processing.
+#[derive(Debug, Clone)]
+pub struct FileInfo {
+    pub codec: String,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<usize>,
+    pub bits_per_sample: Option<u32>,
+    /// Missing for streams that don't declare their length, like some MP3s.
+    pub duration: Option<Duration>,
+    /// File size in bytes.
+    pub size: u64,
+}
+
+/// Reads the format of the audio track in `path` without decoding it.
+pub fn probe(path: &Path) -> Result<FileInfo, String> {
+    let size = std::fs::metadata(path)
+        .map_err(|e| format!("Could not open audio file: {}", e))?
+        .len();
+    let format = open_format(path)?;
+    let (_, params) = audio_track(format.as_ref())?;
+
+    let codec = symphonia::default::get_codecs()
+        .get_codec(params.codec)
+        .map(|codec| codec.short_name.to_string())
+        .unwrap_or_else(|| String::from("Unknown codec"));
+    let duration = params
+        .n_frames
+        .zip(params.sample_rate)
+        .filter(|&(_, rate)| rate > 0)
+        .map(|(frames, rate)| Duration::from_secs_f64(frames as f64 / rate as f64));
+    Ok(FileInfo {
+        codec,
+        sample_rate: params.sample_rate,
+        channels: params.channels.map(|channels| channels.count()),
+        bits_per_sample: params.bits_per_sample,
+        duration,
+        size,
+    })
+}
+
+fn open_format(path: &Path) -> Result<Box<dyn FormatReader>, String> {
+    let file =
+        std::fs::File::open(path).map_err(|e| format!("Could not open audio file: {}", e))?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = extension(path) {
+        hint.with_extension(&ext);
+    }
+
+    symphonia::default::get_probe()
+        .format(&hint, stream, &Default::default(), &Default::default())
+        .map(|probed| probed.format)
+        .map_err(|e| format!("Could not decode audio: {}", e))
+}
+
+/// The ID and parameters of the first audio track.
+fn audio_track(format: &dyn FormatReader) -> Result<(u32, CodecParameters), String> {
+    format
+        .tracks()
+        .iter()
+        .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+        .map(|track| (track.id, track.codec_params.clone()))
+        .ok_or_else(|| "The file contains no audio track".to_string())
+}
+
+/// Decodes `path` by having ffmpeg convert it to WAV first, for formats
+/// Symphonia can't read.
+fn decode_converted(
+    path: &Path,
+    cancel: &CancelToken,
+) -> Result<(hound::WavSpec, AudioBuffer), String> {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let job = temp::TempStore::open().job(&stem)?;
+    let wav_path = job.file("converted.wav");
+    transcode::to_wav(path, &wav_path, cancel)?;
+    let (mut spec, buffer) = read_wav(&wav_path)?;
+    // The float samples are ffmpeg's; the source has no bit depth of its own.
+    spec.bits_per_sample = DEFAULT_BITS_PER_SAMPLE;
+    spec.sample_format = hound::SampleFormat::Int;
+    Ok((spec, buffer))
+}
+
+fn decode_compressed(
+    path: &Path,
+    cancel: &CancelToken,
+) -> Result<(hound::WavSpec, AudioBuffer), String> {
+    let to_decode_error = |e: SymphoniaError| format!("Could not decode audio: {}", e);
+    let mut format = open_format(path)?;
+    let (track_id, params) = audio_track(format.as_ref())?;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&params, &DecoderOptions::default())
+        .map_err(to_decode_error)?;
+
+    let mut sample_rate = params.sample_rate.unwrap_or(0);
+    let mut channels: Vec<Vec<f32>> = Vec::new();
+    loop {
+        cancel.check()?;
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                break;
+            }
+            Err(SymphoniaError::ResetRequired) => break,
+            Err(e) => return Err(to_decode_error(e)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // Corrupt packets are skipped, like most players do.
+            Err(SymphoniaError::DecodeError(_)) => continue,
+            Err(e) => return Err(to_decode_error(e)),
+        };
+        let spec = *decoded.spec();
+        let channel_count = spec.channels.count();
+        let frames = decoded.frames();
+        sample_rate = spec.rate;
+        if channels.is_empty() {
+            channels = vec![Vec::new(); channel_count];
+        }
+
+        let mut samples = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        samples.copy_planar_ref(decoded);
+        for (channel, planar) in channels
+            .iter_mut()
+            .zip(samples.samples().chunks_exact(frames.max(1)))
+        {
+            channel.extend_from_slice(planar);
+        }
+    }
+
+    if channels.is_empty() || sample_rate == 0 {
+        return Err("The file contains no decodable audio".to_string());
+    }
+
+    let spec = hound::WavSpec {
+        channels: channels.len() as u16,
+        sample_rate,
+        bits_per_sample: params
+            .bits_per_sample
+            .map(|bits| bits as u16)
+            .unwrap_or(DEFAULT_BITS_PER_SAMPLE),
+        sample_format: hound::SampleFormat::Int,
+    };
+    Ok((
+        spec,
+        AudioBuffer {
+            sample_rate,
+            channels,
+        },
+    ))
+}
+
+pub fn read_wav(path: &Path) -> Result<(hound::WavSpec, AudioBuffer), String> {
+    let mut reader =
+        hound::WavReader::open(path).map_err(|e| format!("Could not open WAV file: {}", e))?;
+    let spec = reader.spec();
+    let channel_count = spec.channels as usize;
+
+    let interleaved: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader
+            .samples::<f32>()
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("Could not decode WAV samples: {}", e))?,
+        hound::SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|s| s as f32 / scale))
+                .collect::<Result<_, _>>()
+                .map_err(|e| format!("Could not decode WAV samples: {}", e))?
+        }
+    };
+
+    let mut channels = vec![Vec::with_capacity(interleaved.len() / channel_count); channel_count];
+    for frame in interleaved.chunks_exact(channel_count) {
+        for (channel, sample) in channels.iter_mut().zip(frame) {
+            channel.push(*sample);
+        }
+    }
+
+    Ok((
+        spec,
+        AudioBuffer {
+            sample_rate: spec.sample_rate,
+            channels,
+        },
+    ))
+}
+
+/// Writes `buffer` using the bit depth and sample format of `spec`, clamping
+/// and rounding samples as needed. The sample rate and channel count are taken
+/// from the buffer.
+pub fn write_wav(path: &Path, spec: hound::WavSpec, buffer: &AudioBuffer) -> Result<(), String> {
+    let spec = hound::WavSpec {
+        channels: buffer.channels.len() as u16,
+        sample_rate: buffer.sample_rate,
+        ..spec
+    };
+    let mut writer = hound::WavWriter::create(path, spec)
+        .map_err(|e| format!("Could not create WAV file: {}", e))?;
+
+    let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+    for frame in 0..buffer.frames() {
+        for channel in &buffer.channels {
+            let sample = channel[frame];
+            let result = match spec.sample_format {
+                hound::SampleFormat::Float => writer.write_sample(sample),
+                hound::SampleFormat::Int => {
+                    let value = (sample * scale).round().clamp(-scale, scale - 1.0) as i32;
+                    writer.write_sample(value)
+                }
+            };
+            result.map_err(|e| format!("Could not write WAV samples: {}", e))?;
+        }
+    }
+
+    writer
+        .finalize()
+        .map_err(|e| format!("Could not finalize WAV file: {}", e))
+}
+
+pub fn resample(
+    buffer: &AudioBuffer,
+    target_rate: u32,
+    cancel: &CancelToken,
+    on_progress: &mut dyn FnMut(f32),
+) -> Result<AudioBuffer, String> {
+    if buffer.sample_rate == target_rate || buffer.frames() == 0 {
+        return Ok(AudioBuffer {
+            sample_rate: target_rate,
+            channels: buffer.channels.clone(),
+        });
+    }
+
+    let channel_count = buffer.channels.len();
+    let mut resampler = FftFixedIn::<f32>::new(
+        buffer.sample_rate as usize,
+        target_rate as usize,
+        RESAMPLE_CHUNK,
+        2,
+        channel_count,
+    )
+    .map_err(|e| format!("Could not create resampler: {}", e))?;
+
+    let total = buffer.frames();
+    let expected = (total as u64 * target_rate as u64).div_ceil(buffer.sample_rate as u64) as usize;
+    let delay = resampler.output_delay();
+    let mut output = vec![Vec::with_capacity(expected + delay); channel_count];
+    let to_resample_error = |e: rubato::ResampleError| format!("Resampling failed: {}", e);
+
+    let mut position = 0;
+    while position < total {
+        cancel.check()?;
+        let needed = resampler.input_frames_next();
+        let end = (position + needed).min(total);
+        let chunk: Vec<&[f32]> = buffer
+            .channels
+            .iter()
+            .map(|channel| &channel[position..end])
+            .collect();
+        let processed = if end - position == needed {
+            resampler.process(&chunk, None)
+        } else {
+            resampler.process_partial(Some(&chunk), None)
+        }
+        .map_err(to_resample_error)?;
+        for (out, samples) in output.iter_mut().zip(processed) {
+            out.extend(samples);
+        }
+        position = end;
+        on_progress(position as f32 / total as f32 * 100.0);
+    }
+
+    // Flush the samples still held back by the resampler's delay line.
+    while output[0].len() < expected + delay {
+        let processed = resampler
+            .process_partial::<&[f32]>(None, None)
+            .map_err(to_resample_error)?;
+        for (out, samples) in output.iter_mut().zip(processed) {
+            out.extend(samples);
+        }
+    }
+
+    for channel in &mut output {
+        channel.drain(..delay);
+        channel.truncate(expected);
+    }
+
+    Ok(AudioBuffer {
+        sample_rate: target_rate,
+        channels: output,
+    })
+}
@@ -0,0 +1,98 @@
+//! Timing the engine on a fixed reference clip, so results are comparable
+//! across machines and engine builds.
+
+use crate::audio::{self, AudioBuffer};
+use crate::cancel::CancelToken;
+use crate::engine;
+use crate::temp;
+use std::f32::consts::TAU;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Length of the reference clip.
+const REFERENCE_SECONDS: u32 = 20;
+
+/// Times the engine is run per benchmark.
+pub const RUNS: usize = 5;
+
+#[derive(Debug, Clone)]
+pub struct Summary {
+    pub runs: usize,
+    pub min_realtime_factor: f32,
+    pub avg_realtime_factor: f32,
+    pub engine_options: engine::Options,
+}
+
+/// The reference clip: a voice-like harmonic tone with a slow syllable
+/// envelope over broadband noise. It is generated rather than shipped, and is
+/// identical on every run and machine.
+pub fn reference_clip() -> AudioBuffer {
+    let rate = audio::ENGINE_SAMPLE_RATE;
+    let mut seed: u32 = 0x2545_f491;
+    let samples = (0..rate * REFERENCE_SECONDS)
+        .map(|i| {
+            let t = i as f32 / rate as f32;
+            let pitch = 140.0 + 25.0 * (TAU * 0.7 * t).sin();
+            let voice: f32 = (1..=8)
+                .map(|harmonic| (TAU * pitch * harmonic as f32 * t).sin() / harmonic as f32)
+                .sum();
+            let envelope = (TAU * 3.0 * t).sin().max(0.0);
+
+            // xorshift keeps the noise deterministic without a dependency.
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            let noise = seed as f32 / u32::MAX as f32 * 2.0 - 1.0;
+
+            0.25 * voice * envelope + 0.05 * noise
+        })
+        .collect();
+
+    AudioBuffer {
+        sample_rate: rate,
+        channels: vec![samples],
+    }
+}
+
+/// Runs the engine over the reference clip `runs` times, calling `on_run`
+/// with the number of completed runs after each one. Cancelling `cancel`
+/// stops the benchmark. `transcript` gets what the engine printed in the
+/// latest run.
+pub fn run(
+    bin_path: &Path,
+    engine_options: engine::Options,
+    runs: usize,
+    cancel: &CancelToken,
+    transcript: &engine::Transcript,
+    on_run: &mut dyn FnMut(usize),
+) -> Result<Summary, String> {
+    let work_dir = temp::TempStore::open().job("benchmark")?;
+    let clip_path = work_dir.file("reference.wav");
+    audio::write_wav(&clip_path, audio::ENGINE_SPEC, &reference_clip())?;
+
+    let clip_duration = Duration::from_secs(REFERENCE_SECONDS as u64);
+    let limits = engine::Limits::for_duration(REFERENCE_SECONDS as f32, 10.0);
+    let mut factors = Vec::with_capacity(runs);
+    for run in 0..runs {
+        let started = Instant::now();
+        engine::run(
+            bin_path,
+            &clip_path,
+            &work_dir.file("out"),
+            &engine_options,
+            &limits,
+            cancel,
+            transcript,
+            &mut |_| {},
+        )?;
+        factors.push(clip_duration.as_secs_f32() / started.elapsed().as_secs_f32());
+        on_run(run + 1);
+    }
+
+    Ok(Summary {
+        runs,
+        min_realtime_factor: factors.iter().copied().fold(f32::INFINITY, f32::min),
+        avg_realtime_factor: factors.iter().sum::<f32>() / runs.max(1) as f32,
+        engine_options,
+    })
+}
@@ -0,0 +1,55 @@
+//! Stopping long-running work. One [`CancelToken`] is handed to everything a
+//! task runs, whether that's decoding, resampling, the engine, ffmpeg or a
+//! download. Each checks it between small steps, or while polling a child
+//! process, so a cancelled task ends within a fraction of a second. Each
+//! removes what it wrote on the way out.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Error work stopped through its token fails with.
+pub const CANCELLED: &str = "Processing was cancelled";
+
+/// Shared between a task and whoever may cancel it; clones see the same
+/// state.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asks the work holding this token to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Fails with [`CANCELLED`] once cancelled, for checks between steps.
+    pub fn check(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err(CANCELLED.to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_share_the_cancellation() {
+        let token = CancelToken::new();
+        let held = token.clone();
+        assert_eq!(held.check(), Ok(()));
+        token.cancel();
+        assert!(held.is_cancelled());
+        assert_eq!(held.check(), Err(CANCELLED.to_string()));
+    }
+}
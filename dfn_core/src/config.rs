@@ -0,0 +1,575 @@
+//! Settings stored as TOML. Processing options are written the same way
+//! wherever they're kept, so a file with them can be read back by any part
+//! of the app. Missing or unreadable entries fall back to their defaults.
+//!
+//! A [`FOLDER_PROFILE`] file in a folder overrides the processing options it
+//! lists for every file cleaned from that folder or the folders below it,
+//! e.g. to give each podcast project its own output folder and naming.
+//! Only [`FOLDER_PROFILE_KEYS`] are taken from it, as such a file can come
+//! with any downloaded folder.
+
+use crate::denoiser::Backend;
+use crate::pipeline::{ChannelRouting, ChannelSplit, OutputFormat, ProcessingOptions};
+use std::path::{Component, Path, PathBuf};
+use toml_edit::{DocumentMut, Item, Value, value};
+
+/// Name of the file whose options apply to everything cleaned from its
+/// folder, see [`apply_folder_profile`].
+pub const FOLDER_PROFILE: &str = ".dfn_gui.toml";
+
+/// Entries a [`FOLDER_PROFILE`] may set: where outputs go and how they're
+/// named, and how the audio is processed. Which backend runs, where audio is
+/// sent and what the engine is run with stay as the user set them.
+pub const FOLDER_PROFILE_KEYS: [&str; 19] = [
+    "output_dir",
+    "output_suffix",
+    "output_format",
+    "bitrate_kbps",
+    "version_outputs",
+    "preserve_format",
+    "channels",
+    "split_channels",
+    "remove_dc_offset",
+    "trim_silence",
+    "silence_threshold_db",
+    "noise_reduction",
+    "normalize",
+    "normalize_peak_db",
+    "keep_metadata",
+    "keep_broadcast_chunks",
+    "export_labels",
+    "write_provenance",
+    "skip_duplicates",
+];
+
+/// Processing options written the way the settings file has them, e.g. to
+/// keep them along with a run.
+pub fn options_to_toml(options: &ProcessingOptions) -> String {
+    let mut document = DocumentMut::new();
+    write_options(options, &mut document);
+    document.to_string()
+}
+
+/// Reads options written by [`options_to_toml`]. Missing entries fall back
+/// to their defaults.
+pub fn options_from_toml(text: &str) -> Option<ProcessingOptions> {
+    text.parse::<DocumentMut>()
+        .ok()
+        .map(|document| read_options(&document))
+}
+
+pub fn read_options(document: &DocumentMut) -> ProcessingOptions {
+    let mut options = ProcessingOptions::default();
+    apply_options(document, &mut options);
+    options
+}
+
+/// Overwrites the entries of `options` that `document` has.
+fn apply_options(document: &DocumentMut, options: &mut ProcessingOptions) {
+    let get = |key: &str| document.get(key).and_then(Item::as_value);
+
+    for (key, target) in [
+        ("preserve_format", &mut options.preserve_format),
+        ("remove_dc_offset", &mut options.remove_dc_offset),
+        ("trim_silence", &mut options.trim_silence),
+        ("normalize", &mut options.normalize),
+        ("low_priority", &mut options.low_priority),
+        ("keep_metadata", &mut options.keep_metadata),
+        ("keep_broadcast_chunks", &mut options.keep_broadcast_chunks),
+        ("export_labels", &mut options.export_labels),
+        ("write_provenance", &mut options.write_provenance),
+        ("version_outputs", &mut options.version_outputs),
+        ("skip_duplicates", &mut options.skip_duplicates),
+        ("remux_video", &mut options.remux_video),
+    ] {
+        if let Some(value) = get(key).and_then(|value| value.as_bool()) {
+            *target = value;
+        }
+    }
+    for (key, target) in [
+        ("silence_threshold_db", &mut options.silence_threshold_db),
+        ("noise_reduction", &mut options.noise_reduction),
+        ("normalize_peak_db", &mut options.normalize_peak_db),
+        ("timeout_factor", &mut options.timeout_factor),
+    ] {
+        if let Some(value) = number(document, key) {
+            *target = value as f32;
+        }
+    }
+    if let Some(value) = get("bitrate_kbps").and_then(|value| value.as_integer()) {
+        options.bitrate_kbps = value.clamp(0, u32::MAX as i64) as u32;
+    }
+    if let Some(value) = get("cpu_threads").and_then(|value| value.as_integer()) {
+        options.cpu_threads = value.max(0) as usize;
+    }
+
+    let text = |key: &str| get(key).and_then(|value| value.as_str());
+    if let Some(format) = text("output_format").and_then(|key| {
+        OutputFormat::ALL
+            .into_iter()
+            .find(|format| format_key(*format) == key)
+    }) {
+        options.output_format = format;
+    }
+    if let Some(channels) = text("channels").and_then(parse_routing) {
+        options.channels = channels;
+    }
+    if let Some(split) = text("split_channels").and_then(|key| {
+        ChannelSplit::ALL
+            .into_iter()
+            .find(|split| split_key(*split) == key)
+    }) {
+        options.split_channels = split;
+    }
+    if let Some(backend) = text("backend").and_then(|key| {
+        Backend::ALL
+            .into_iter()
+            .find(|backend| backend_key(*backend) == key)
+    }) {
+        options.backend = backend;
+    }
+    if let Some(url) = text("server_url") {
+        options.server.url = url.to_string();
+    }
+    if let Some(token) = text("server_token") {
+        options.server.token = token.to_string();
+    }
+    if let Some(dir) = text("output_dir") {
+        options.output_dir = (!dir.is_empty()).then(|| PathBuf::from(dir));
+    }
+    if let Some(suffix) = text("output_suffix") {
+        options.output_suffix = suffix.to_string();
+    }
+    // Written as `NAME=value` strings, which read the same in TOML and JSON.
+    if let Some(entries) = get("engine_env").and_then(|value| value.as_array()) {
+        options.engine_env = entries
+            .iter()
+            .filter_map(|entry| entry.as_str()?.split_once('='))
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+    }
+}
+
+/// The [`FOLDER_PROFILE`] nearest to `input`, in its folder or the folders
+/// above.
+pub fn folder_profile(input: &Path) -> Option<PathBuf> {
+    input
+        .ancestors()
+        .skip(1)
+        .map(|folder| folder.join(FOLDER_PROFILE))
+        .find(|profile| profile.is_file())
+}
+
+/// Applies the folder profile for `input` to `options`, returning the
+/// profile if there is one. Entries other than [`FOLDER_PROFILE_KEYS`] are
+/// left out, and a relative `output_dir` counts from the profile's folder.
+/// As a profile can come with a downloaded folder, one whose outputs would
+/// land outside that folder is refused: an absolute `output_dir`, one that
+/// climbs out with `..`, or an `output_suffix` with a path separator.
+pub fn apply_folder_profile(
+    input: &Path,
+    options: &mut ProcessingOptions,
+) -> Result<Option<PathBuf>, String> {
+    let Some(profile) = folder_profile(input) else {
+        return Ok(None);
+    };
+    let mut document = std::fs::read_to_string(&profile)
+        .map_err(|e| e.to_string())
+        .and_then(|text| text.parse::<DocumentMut>().map_err(|e| e.to_string()))
+        .map_err(|e| format!("Could not read {}: {}", profile.display(), e))?;
+    document.retain(|key, _| {
+        let allowed = FOLDER_PROFILE_KEYS.contains(&key);
+        if !allowed {
+            tracing::warn!("Ignoring {} in {}", key, profile.display());
+        }
+        allowed
+    });
+    let refuse = |entry: &str| {
+        format!(
+            "{} in {} would write outside its folder",
+            entry,
+            profile.display()
+        )
+    };
+    let mut profiled = options.clone();
+    apply_options(&document, &mut profiled);
+    if document.contains_key("output_dir")
+        && let Some(dir) = profiled.output_dir.as_mut()
+    {
+        if !dir
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(refuse("output_dir"));
+        }
+        if let Some(folder) = profile.parent() {
+            *dir = folder.join(&*dir);
+        }
+    }
+    if document.contains_key("output_suffix") && profiled.output_suffix.contains(['/', '\\']) {
+        return Err(refuse("output_suffix"));
+    }
+    *options = profiled;
+    Ok(Some(profile))
+}
+
+/// Writes `options` into `document` the way the settings file has them.
+pub fn write_options(options: &ProcessingOptions, document: &mut DocumentMut) {
+    document["preserve_format"] = value(options.preserve_format);
+    document["channels"] = value(routing_key(options.channels));
+    document["split_channels"] = value(split_key(options.split_channels));
+    document["remove_dc_offset"] = value(options.remove_dc_offset);
+    document["trim_silence"] = value(options.trim_silence);
+    document["silence_threshold_db"] = float(options.silence_threshold_db);
+    document["noise_reduction"] = float(options.noise_reduction);
+    document["normalize"] = value(options.normalize);
+    document["normalize_peak_db"] = float(options.normalize_peak_db);
+    document["timeout_factor"] = float(options.timeout_factor);
+    document["low_priority"] = value(options.low_priority);
+    document["output_format"] = value(format_key(options.output_format));
+    document["bitrate_kbps"] = value(i64::from(options.bitrate_kbps));
+    document["keep_metadata"] = value(options.keep_metadata);
+    document["keep_broadcast_chunks"] = value(options.keep_broadcast_chunks);
+    document["export_labels"] = value(options.export_labels);
+    document["write_provenance"] = value(options.write_provenance);
+    document["remux_video"] = value(options.remux_video);
+    document["cpu_threads"] = value(options.cpu_threads as i64);
+    document["backend"] = value(backend_key(options.backend));
+    document["server_url"] = value(&options.server.url);
+    document["server_token"] = value(&options.server.token);
+    document["output_suffix"] = value(&options.output_suffix);
+    document["version_outputs"] = value(options.version_outputs);
+    document["skip_duplicates"] = value(options.skip_duplicates);
+    document["engine_env"] = value(
+        options
+            .engine_env
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<toml_edit::Array>(),
+    );
+    if let Some(dir) = &options.output_dir {
+        document["output_dir"] = value(dir.to_string_lossy().as_ref());
+    }
+}
+
+/// `value` as a TOML float, written as short as it reads back, so 0.1 isn't
+/// saved as 0.10000000149011612.
+pub fn float(value: f32) -> Item {
+    toml_edit::value(value.to_string().parse::<f64>().unwrap_or_default())
+}
+
+/// The entries of `document` as one JSON object.
+pub fn document_to_json(document: &DocumentMut) -> serde_json::Value {
+    document
+        .iter()
+        .filter_map(|(key, item)| Some((key.to_string(), toml_to_json(item.as_value()?))))
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+fn toml_to_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::String(text) => text.value().as_str().into(),
+        Value::Integer(number) => (*number.value()).into(),
+        Value::Float(number) => (*number.value()).into(),
+        Value::Boolean(flag) => (*flag.value()).into(),
+        Value::Datetime(datetime) => datetime.value().to_string().into(),
+        Value::Array(array) => array.iter().map(toml_to_json).collect(),
+        Value::InlineTable(table) => table
+            .iter()
+            .map(|(key, value)| (key.to_string(), toml_to_json(value)))
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+    }
+}
+
+/// Reads a JSON object whose values are strings, numbers, booleans, arrays
+/// or objects, as written by [`document_to_json`], into a document its entries
+/// can be read from like the settings file.
+pub fn json_to_document(json: &str) -> Result<DocumentMut, String> {
+    let not_settings = || String::from("The file isn't a JSON object of settings");
+    let entries: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(json).map_err(|_| not_settings())?;
+    let mut document = DocumentMut::new();
+    for (key, value) in &entries {
+        let value = json_to_toml(value).ok_or_else(not_settings)?;
+        document.insert(key, Item::Value(value));
+    }
+    Ok(document)
+}
+
+/// `value` as TOML, which has no `null`.
+fn json_to_toml(value: &serde_json::Value) -> Option<Value> {
+    Some(match value {
+        serde_json::Value::Null => return None,
+        serde_json::Value::Bool(flag) => (*flag).into(),
+        serde_json::Value::Number(number) => match number.as_i64() {
+            Some(number) => number.into(),
+            None => number.as_f64()?.into(),
+        },
+        serde_json::Value::String(text) => text.as_str().into(),
+        serde_json::Value::Array(values) => values
+            .iter()
+            .map(json_to_toml)
+            .collect::<Option<toml_edit::Array>>()?
+            .into(),
+        serde_json::Value::Object(entries) => entries
+            .iter()
+            .map(|(key, value)| Some((key.as_str(), json_to_toml(value)?)))
+            .collect::<Option<toml_edit::InlineTable>>()?
+            .into(),
+    })
+}
+
+/// Reads a number. Whole numbers may be written without a fraction, which
+/// TOML reads as integers.
+pub fn number(document: &DocumentMut, key: &str) -> Option<f64> {
+    document
+        .get(key)
+        .and_then(Item::as_value)
+        .and_then(|value| {
+            value
+                .as_float()
+                .or_else(|| value.as_integer().map(|value| value as f64))
+        })
+}
+
+/// Name an output format is saved under.
+fn format_key(format: OutputFormat) -> &'static str {
+    match format {
+        OutputFormat::Wav => "wav",
+        OutputFormat::Flac => "flac",
+        OutputFormat::Mp3 => "mp3",
+        OutputFormat::Ogg => "ogg",
+        OutputFormat::SameAsInput => "same_as_input",
+    }
+}
+
+/// Name a channel routing is saved under: `keep`, `mono`, or `channel_N`
+/// counting from 1.
+fn routing_key(routing: ChannelRouting) -> String {
+    match routing {
+        ChannelRouting::Keep => String::from("keep"),
+        ChannelRouting::Mono => String::from("mono"),
+        ChannelRouting::Only(index) => format!("channel_{}", index + 1),
+    }
+}
+
+fn parse_routing(key: &str) -> Option<ChannelRouting> {
+    match key {
+        "keep" => Some(ChannelRouting::Keep),
+        "mono" => Some(ChannelRouting::Mono),
+        _ => key
+            .strip_prefix("channel_")?
+            .parse::<usize>()
+            .ok()
+            .filter(|&channel| channel > 0)
+            .map(|channel| ChannelRouting::Only(channel - 1)),
+    }
+}
+
+/// Name a channel split is saved under.
+fn split_key(split: ChannelSplit) -> &'static str {
+    match split {
+        ChannelSplit::Off => "off",
+        ChannelSplit::Recombined => "recombined",
+        ChannelSplit::Files => "files",
+    }
+}
+
+/// Name a backend is saved under.
+pub(crate) fn backend_key(backend: Backend) -> &'static str {
+    match backend {
+        Backend::ExternalBinary => "external_binary",
+        Backend::InProcess => "in_process",
+        Backend::Remote => "remote",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn options_survive_a_round_trip() {
+        let options = ProcessingOptions {
+            trim_silence: true,
+            channels: ChannelRouting::Only(1),
+            split_channels: ChannelSplit::Files,
+            noise_reduction: 72.5,
+            output_format: OutputFormat::Flac,
+            cpu_threads: 3,
+            backend: Backend::Remote,
+            output_dir: Some(PathBuf::from("C:\\Audio \"clean\"")),
+            output_suffix: "_clean".to_string(),
+            engine_env: vec![
+                ("RUST_LOG".to_string(), "debug".to_string()),
+                ("ORT_FLAGS".to_string(), "a=b \"c\"".to_string()),
+            ],
+            ..ProcessingOptions::default()
+        };
+        assert_eq!(options_from_toml(&options_to_toml(&options)), Some(options));
+    }
+
+    #[test]
+    fn missing_and_invalid_entries_fall_back_to_defaults() {
+        let options =
+            options_from_toml("noise_reduction = 80\noutput_format = \"aiff\"\ncpu_threads = -2\n")
+                .unwrap();
+        assert_eq!(options.noise_reduction, 80.0);
+        assert_eq!(options.output_format, OutputFormat::Wav);
+        assert_eq!(options.cpu_threads, 0);
+        assert_eq!(
+            options.timeout_factor,
+            ProcessingOptions::default().timeout_factor
+        );
+    }
+
+    #[test]
+    fn folder_profiles_override_what_they_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("podcast");
+        std::fs::create_dir_all(project.join("episode 1")).unwrap();
+        std::fs::write(
+            project.join(FOLDER_PROFILE),
+            "output_dir = \"masters\"\noutput_suffix = \"_clean\"\noutput_format = \"flac\"\n",
+        )
+        .unwrap();
+        let saved = ProcessingOptions {
+            noise_reduction: 80.0,
+            output_suffix: "_dfn".to_string(),
+            ..ProcessingOptions::default()
+        };
+
+        let mut options = saved.clone();
+        let input = project.join("episode 1").join("take.wav");
+        let profile = apply_folder_profile(&input, &mut options).unwrap();
+        assert_eq!(profile, Some(project.join(FOLDER_PROFILE)));
+        assert_eq!(options.output_dir, Some(project.join("masters")));
+        assert_eq!(options.output_suffix, "_clean");
+        assert_eq!(options.output_format, OutputFormat::Flac);
+        assert_eq!(options.noise_reduction, 80.0);
+
+        let mut options = saved.clone();
+        let outside = dir.path().join("take.wav");
+        assert_eq!(apply_folder_profile(&outside, &mut options), Ok(None));
+        assert_eq!(options, saved);
+
+        std::fs::write(project.join(FOLDER_PROFILE), "output_dir = ").unwrap();
+        assert!(apply_folder_profile(&input, &mut options).is_err());
+    }
+
+    #[test]
+    fn folder_profiles_cannot_write_outside_their_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("take.wav");
+        let saved = ProcessingOptions::default();
+        for entry in [
+            "output_dir = \"/tmp/elsewhere\"",
+            "output_dir = \"..\"",
+            "output_dir = \"masters/../../elsewhere\"",
+            "output_suffix = \"/../../elsewhere\"",
+            "output_suffix = \"\\\\..\\\\elsewhere\"",
+        ] {
+            std::fs::write(dir.path().join(FOLDER_PROFILE), entry).unwrap();
+            let mut options = saved.clone();
+            assert!(
+                apply_folder_profile(&input, &mut options).is_err(),
+                "{}",
+                entry
+            );
+            assert_eq!(options, saved);
+        }
+
+        std::fs::write(
+            dir.path().join(FOLDER_PROFILE),
+            "output_dir = \"./masters/2024\"\noutput_suffix = \"_v1..2\"",
+        )
+        .unwrap();
+        let mut options = saved.clone();
+        apply_folder_profile(&input, &mut options).unwrap();
+        assert_eq!(options.output_dir, Some(dir.path().join("./masters/2024")));
+        assert_eq!(options.output_suffix, "_v1..2");
+    }
+
+    #[test]
+    fn folder_profiles_cannot_change_where_audio_goes() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(FOLDER_PROFILE),
+            concat!(
+                "backend = \"remote\"\n",
+                "server_url = \"https://example.com\"\n",
+                "server_token = \"theirs\"\n",
+                "engine_env = [\"LD_PRELOAD=/tmp/x.so\"]\n",
+                "cpu_threads = 64\n",
+                "output_suffix = \"_clean\"\n",
+            ),
+        )
+        .unwrap();
+        let mut saved = ProcessingOptions::default();
+        saved.server.url = "https://mine.example".to_string();
+        saved.server.token = "mine".to_string();
+
+        let mut options = saved.clone();
+        apply_folder_profile(&dir.path().join("take.wav"), &mut options).unwrap();
+        assert_eq!(options.output_suffix, "_clean");
+        assert_eq!(
+            ProcessingOptions {
+                output_suffix: saved.output_suffix.clone(),
+                ..options
+            },
+            saved
+        );
+    }
+
+    #[test]
+    fn options_survive_a_round_trip_through_json() {
+        let options = ProcessingOptions {
+            output_dir: Some(PathBuf::from("C:\\Audio \"clean\"")),
+            output_suffix: "_a: b".to_string(),
+            channels: ChannelRouting::Mono,
+            noise_reduction: 72.5,
+            engine_env: vec![("OMP_NUM_THREADS".to_string(), "2".to_string())],
+            ..ProcessingOptions::default()
+        };
+        let mut document = DocumentMut::new();
+        write_options(&options, &mut document);
+        let json = document_to_json(&document).to_string();
+        assert!(json.starts_with(r#"{"preserve_format":false,"channels":"mono","#));
+        let document = json_to_document(&json).unwrap();
+        assert_eq!(read_options(&document), options);
+
+        // Laid out by hand, the way people edit JSON.
+        let document =
+            json_to_document("{\n  \"noise_reduction\": 60,\n  \"trim_silence\": true\n}\n")
+                .unwrap();
+        let options = read_options(&document);
+        assert_eq!(options.noise_reduction, 60.0);
+        assert!(options.trim_silence);
+
+        // Slashes may be escaped by whatever saved the file last.
+        let document = json_to_document(r#"{"output_suffix": "a\/b"}"#).unwrap();
+        assert_eq!(read_options(&document).output_suffix, "a/b");
+
+        assert!(json_to_document("noise_reduction = 60").is_err());
+        assert!(json_to_document(r#"{"noise_reduction": null}"#).is_err());
+    }
+
+    #[test]
+    fn floats_are_written_as_entered() {
+        let options = ProcessingOptions {
+            silence_threshold_db: -40.1,
+            noise_reduction: 100.0,
+            ..ProcessingOptions::default()
+        };
+        let toml = options_to_toml(&options);
+        assert!(toml.contains("silence_threshold_db = -40.1\n"));
+        assert!(toml.contains("noise_reduction = 100.0\n"));
+    }
+
+    #[test]
+    fn unparsable_text_is_rejected() {
+        assert_eq!(options_from_toml("noise_reduction = "), None);
+    }
+}
@@ -0,0 +1,260 @@
+//! A quick guess at whether a recording holds speech or music, so users can
+//! be warned before a long run that the engine, which is tuned for speech,
+//! is likely to eat into the music.
+//!
+//! The guess works on the loudness and zero crossings of short blocks:
+//! speech pauses between syllables and alternates voiced and hissing sounds,
+//! while music keeps going and often has a steady beat.
+
+use crate::audio::AudioBuffer;
+
+/// Only the start of a recording is looked at.
+const ANALYSED_SECONDS: f32 = 30.0;
+
+/// Recordings shorter than this are too short to tell.
+const MIN_SECONDS: f32 = 3.0;
+
+/// Length of the blocks loudness and zero crossings are measured over.
+const BLOCK_SECONDS: f32 = 0.025;
+
+/// Length of the surroundings a block's loudness is compared with.
+const CONTEXT_SECONDS: f32 = 1.0;
+
+/// Blocks below this share of their surroundings' loudness count as pauses.
+const PAUSE_LEVEL: f32 = 0.5;
+
+/// Blocks quieter than this RMS are left out of the zero crossing count.
+const SILENT_RMS: f32 = 1e-3;
+
+/// Beats between 60 and 200 per minute are looked for.
+const BEAT_SECONDS: std::ops::RangeInclusive<f32> = 0.3..=1.0;
+
+/// Music pauses in fewer blocks than this share.
+const MUSIC_MAX_PAUSES: f32 = 0.15;
+
+/// Music with a beat repeats its loudness changes at least this closely.
+const MUSIC_MIN_BEAT: f32 = 0.4;
+
+/// Without a beat, music varies its zero crossings less than this.
+const MUSIC_MAX_CROSSING_VARIATION: f32 = 0.4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Content {
+    Speech,
+    Music,
+}
+
+/// What the guess is based on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Features {
+    /// Share of blocks much quieter than their surroundings.
+    pub pauses: f32,
+    /// Standard deviation of the zero crossing rate over its mean.
+    pub crossing_variation: f32,
+    /// How strongly loudness changes repeat at a tempo, 0 to 1.
+    pub beat: f32,
+}
+
+impl Features {
+    /// Measures the start of `buffer`, or `None` when it's too short or
+    /// silent to tell.
+    pub fn measure(buffer: &AudioBuffer) -> Option<Self> {
+        let rate = buffer.sample_rate as f32;
+        let frames = buffer.frames().min((rate * ANALYSED_SECONDS) as usize);
+        if buffer.channels.is_empty() || (frames as f32) < rate * MIN_SECONDS {
+            return None;
+        }
+        let count = buffer.channels.len() as f32;
+        let mono: Vec<f32> = (0..frames)
+            .map(|frame| {
+                buffer
+                    .channels
+                    .iter()
+                    .map(|channel| channel[frame])
+                    .sum::<f32>()
+                    / count
+            })
+            .collect();
+
+        let block = ((rate * BLOCK_SECONDS) as usize).max(2);
+        let blocks: Vec<(f32, f32)> = mono
+            .chunks_exact(block)
+            .map(|samples| {
+                let rms = (samples.iter().map(|s| s * s).sum::<f32>() / block as f32).sqrt();
+                let crossings = samples
+                    .windows(2)
+                    .filter(|pair| (pair[0] < 0.0) != (pair[1] < 0.0))
+                    .count();
+                (rms, crossings as f32 / block as f32)
+            })
+            .collect();
+        let levels: Vec<f32> = blocks.iter().map(|(rms, _)| *rms).collect();
+        if levels.iter().all(|&rms| rms < SILENT_RMS) {
+            return None;
+        }
+
+        Some(Self {
+            pauses: pauses(&levels, (CONTEXT_SECONDS / BLOCK_SECONDS) as usize),
+            crossing_variation: variation(
+                blocks
+                    .iter()
+                    .filter(|(rms, _)| *rms >= SILENT_RMS)
+                    .map(|(_, rate)| *rate),
+            ),
+            beat: beat(&levels),
+        })
+    }
+
+    pub fn content(&self) -> Content {
+        let steady =
+            self.beat >= MUSIC_MIN_BEAT || self.crossing_variation < MUSIC_MAX_CROSSING_VARIATION;
+        if self.pauses < MUSIC_MAX_PAUSES && steady {
+            Content::Music
+        } else {
+            Content::Speech
+        }
+    }
+}
+
+/// What the start of `buffer` most likely holds, or `None` when it's too
+/// short or silent to tell.
+pub fn classify(buffer: &AudioBuffer) -> Option<Content> {
+    Features::measure(buffer).map(|features| features.content())
+}
+
+/// Share of `levels` below [`PAUSE_LEVEL`] of the mean of the `context`
+/// blocks around them.
+fn pauses(levels: &[f32], context: usize) -> f32 {
+    let half = context.max(1) / 2;
+    let quiet = (0..levels.len())
+        .filter(|&index| {
+            let around = &levels[index.saturating_sub(half)..(index + half + 1).min(levels.len())];
+            let mean = around.iter().sum::<f32>() / around.len() as f32;
+            levels[index] < mean * PAUSE_LEVEL
+        })
+        .count();
+    quiet as f32 / levels.len() as f32
+}
+
+/// Standard deviation of `values` over their mean.
+fn variation(values: impl Iterator<Item = f32>) -> f32 {
+    let values: Vec<f32> = values.collect();
+    let mean = values.iter().sum::<f32>() / values.len().max(1) as f32;
+    if mean <= 0.0 {
+        return 0.0;
+    }
+    let variance = values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f32>()
+        / values.len() as f32;
+    variance.sqrt() / mean
+}
+
+/// Highest normalized autocorrelation of the rises in `levels` at a lag
+/// within [`BEAT_SECONDS`].
+fn beat(levels: &[f32]) -> f32 {
+    let rises: Vec<f32> = levels
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).max(0.0))
+        .collect();
+    let mean = rises.iter().sum::<f32>() / rises.len().max(1) as f32;
+    let centered: Vec<f32> = rises.iter().map(|rise| rise - mean).collect();
+    let energy: f32 = centered.iter().map(|value| value * value).sum();
+    if energy <= 0.0 {
+        return 0.0;
+    }
+    let lags = (BEAT_SECONDS.start() / BLOCK_SECONDS) as usize
+        ..=(BEAT_SECONDS.end() / BLOCK_SECONDS) as usize;
+    lags.filter(|&lag| lag < centered.len())
+        .map(|lag| {
+            centered
+                .iter()
+                .zip(&centered[lag..])
+                .map(|(a, b)| a * b)
+                .sum::<f32>()
+                / energy
+        })
+        .fold(0.0, f32::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::TAU;
+
+    const RATE: u32 = 16_000;
+
+    /// Deterministic noise in -1..1.
+    fn noise(seed: &mut u32) -> f32 {
+        *seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (*seed >> 8) as f32 / (1 << 23) as f32 - 1.0
+    }
+
+    /// Syllables of voiced sound and hiss, with pauses of varying length.
+    fn speech(seconds: f32) -> AudioBuffer {
+        let mut seed = 1;
+        let mut samples = Vec::new();
+        let mut syllable = 0;
+        while (samples.len() as f32) < RATE as f32 * seconds {
+            let pitch = 110.0 + 40.0 * (syllable % 5) as f32;
+            let voiced = (RATE as f32 * 0.15) as usize;
+            for i in 0..voiced {
+                let t = i as f32 / RATE as f32;
+                let envelope = (std::f32::consts::PI * i as f32 / voiced as f32).sin();
+                let sample = (1..=4)
+                    .map(|harmonic| (TAU * pitch * harmonic as f32 * t).sin() / harmonic as f32)
+                    .sum::<f32>();
+                samples.push(0.3 * envelope * sample);
+            }
+            if syllable % 3 == 0 {
+                samples.extend((0..RATE as usize / 20).map(|_| 0.1 * noise(&mut seed)));
+            }
+            let pause = RATE as usize / 10 * (1 + syllable % 3);
+            samples.extend((0..pause).map(|_| 0.002 * noise(&mut seed)));
+            syllable += 1;
+        }
+        AudioBuffer {
+            sample_rate: RATE,
+            channels: vec![samples],
+        }
+    }
+
+    /// Chords that change every half second under a kick drum at 120 BPM.
+    fn music(seconds: f32) -> AudioBuffer {
+        let frames = (RATE as f32 * seconds) as usize;
+        let chords = [[220.0, 277.2, 329.6], [196.0, 246.9, 293.7]];
+        let samples = (0..frames)
+            .map(|i| {
+                let t = i as f32 / RATE as f32;
+                let beat = t % 0.5;
+                let chord = chords[(t / 2.0) as usize % 2];
+                let tones: f32 = chord.iter().map(|f| (TAU * f * t).sin()).sum::<f32>() / 3.0;
+                let kick = (TAU * 60.0 * beat).sin() * (-beat * 20.0).exp();
+                0.3 * tones + 0.5 * kick
+            })
+            .collect();
+        AudioBuffer {
+            sample_rate: RATE,
+            channels: vec![samples; 2],
+        }
+    }
+
+    #[test]
+    fn speech_and_music_are_told_apart() {
+        let speech = Features::measure(&speech(10.0)).unwrap();
+        assert_eq!(speech.content(), Content::Speech, "{:?}", speech);
+        let music = Features::measure(&music(10.0)).unwrap();
+        assert_eq!(music.content(), Content::Music, "{:?}", music);
+    }
+
+    #[test]
+    fn short_or_silent_audio_is_not_judged() {
+        assert_eq!(classify(&music(1.0)), None);
+        let silence = AudioBuffer {
+            sample_rate: RATE,
+            channels: vec![vec![0.0; RATE as usize * 5]],
+        };
+        assert_eq!(classify(&silence), None);
+    }
+}
@@ -0,0 +1,70 @@
+//! Spotting inputs that are exact copies of one another, as big batches
+//! often hold the same recording twice under different names. Files are
+//! compared by the SHA-256 of their contents, and only hashed once another
+//! file of the same size turns up, so batches without copies are barely
+//! slowed down.
+
+use crate::provenance;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// The files of a batch seen so far, by size, each with its hash once
+/// that was needed.
+#[derive(Debug, Default)]
+pub struct Seen {
+    by_size: HashMap<u64, Vec<(PathBuf, Option<String>)>>,
+}
+
+impl Seen {
+    /// The earlier file `input` is an exact copy of, if any. Otherwise
+    /// `input` is remembered for the files after it.
+    pub fn original_of(&mut self, input: &Path) -> Result<Option<PathBuf>, String> {
+        let size = std::fs::metadata(input)
+            .map_err(|e| format!("Could not read {}: {}", input.display(), e))?
+            .len();
+        let files = self.by_size.entry(size).or_default();
+        if files.is_empty() {
+            files.push((input.to_path_buf(), None));
+            return Ok(None);
+        }
+        // SHA-256 rather than a faster hash, to reuse the aws-lc-rs already
+        // pulled in for the checksums of updates.
+        let hash = provenance::sha256_file(input)?;
+        for (file, file_hash) in files.iter_mut() {
+            if file_hash.is_none() {
+                *file_hash = Some(provenance::sha256_file(file)?);
+            }
+            if file_hash.as_ref() == Some(&hash) {
+                return Ok(Some(file.clone()));
+            }
+        }
+        files.push((input.to_path_buf(), Some(hash)));
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copies_are_traced_to_the_first_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = |name: &str, data: &[u8]| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, data).unwrap();
+            path
+        };
+        let a = file("a.wav", b"take one");
+        let b = file("b.wav", b"take two");
+        let c = file("c.wav", b"take one");
+        let d = file("d.wav", b"a longer take");
+
+        let mut seen = Seen::default();
+        assert_eq!(seen.original_of(&a), Ok(None));
+        assert_eq!(seen.original_of(&b), Ok(None));
+        assert_eq!(seen.original_of(&c), Ok(Some(a.clone())));
+        assert_eq!(seen.original_of(&d), Ok(None));
+        assert!(seen.original_of(&dir.path().join("missing.wav")).is_err());
+    }
+}
@@ -0,0 +1,259 @@
+//! Backends that do the actual noise removal. The pipeline only talks to the
+//! [`Denoiser`] trait, so backends can be swapped or mocked.
+
+use crate::accel::{self, Provider};
+use crate::audio;
+use crate::cancel::CancelToken;
+use crate::engine;
+use crate::i18n::tr;
+use crate::plugin;
+use crate::remote;
+use crate::speed;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+pub trait Denoiser: Send + Sync {
+    /// Cleans the WAV file at `input` into a WAV file of the same name in
+    /// `output_dir`, reporting progress in percent where the backend can.
+    /// Fails with [`crate::cancel::CANCELLED`] once `cancel` is cancelled.
+    fn process_file(
+        &self,
+        input: &Path,
+        output_dir: &Path,
+        limits: &engine::Limits,
+        cancel: &CancelToken,
+        on_progress: &mut dyn FnMut(f32),
+    ) -> Result<(), String>;
+
+    /// Version of the engine doing the work, where the backend can tell.
+    fn version(&self) -> Option<String> {
+        None
+    }
+
+    /// Where the engine ran the model in the latest file, where the backend
+    /// can tell.
+    fn provider(&self) -> Option<Provider> {
+        None
+    }
+
+    /// The command line and output of the engine for the latest file, where
+    /// the backend runs one.
+    fn transcript(&self) -> Option<engine::Transcript> {
+        None
+    }
+}
+
+/// Available backends, as offered in the settings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Backend {
+    #[default]
+    ExternalBinary,
+    /// The engine's plugin inside the app, see [`InProcess`].
+    InProcess,
+    /// A DeepFilterNet server, see [`remote`].
+    Remote,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 3] = [Backend::ExternalBinary, Backend::InProcess, Backend::Remote];
+}
+
+impl std::fmt::Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Backend::ExternalBinary => write!(f, "{}", tr!("backend-external-binary")),
+            Backend::InProcess => write!(f, "{}", tr!("backend-in-process")),
+            Backend::Remote => write!(f, "{}", tr!("backend-remote")),
+        }
+    }
+}
+
+/// The downloaded `deep-filter` binary, run once per file. It doesn't report
+/// progress, which is estimated from its usual [`speed`] instead.
+pub struct ExternalBinary {
+    pub bin_path: PathBuf,
+    pub options: engine::Options,
+    /// See [`Denoiser::provider`].
+    used: Mutex<Option<Provider>>,
+    transcript: engine::Transcript,
+}
+
+impl ExternalBinary {
+    pub fn new(bin_path: PathBuf, options: engine::Options) -> Self {
+        Self {
+            bin_path,
+            options,
+            used: Mutex::new(None),
+            transcript: engine::Transcript::default(),
+        }
+    }
+}
+
+impl Denoiser for ExternalBinary {
+    fn process_file(
+        &self,
+        input: &Path,
+        output_dir: &Path,
+        limits: &engine::Limits,
+        cancel: &CancelToken,
+        on_progress: &mut dyn FnMut(f32),
+    ) -> Result<(), String> {
+        let version = self.version().unwrap_or_else(|| "unknown".to_string());
+        let speeds = speed::SpeedLog::open().ok();
+        let audio = audio::probe(input).ok().and_then(|info| info.duration);
+        let realtime_factor = speeds
+            .as_ref()
+            .and_then(|speeds| speeds.realtime_factor(&version));
+        let started = Instant::now();
+        engine::run(
+            &self.bin_path,
+            input,
+            output_dir,
+            &self.options,
+            limits,
+            cancel,
+            &self.transcript,
+            &mut |elapsed| {
+                if let (Some(audio), Some(factor)) = (audio, realtime_factor) {
+                    on_progress(speed::estimate(elapsed, audio, factor));
+                }
+            },
+        )?;
+        // Plain builds don't name a provider, and only run on the CPU.
+        let used = accel::used(&self.transcript.lines()).unwrap_or_default();
+        *self.used.lock().unwrap() = Some(used);
+        if let (Some(speeds), Some(audio)) = (speeds, audio)
+            && let Err(e) = speeds.record(&version, audio, started.elapsed())
+        {
+            tracing::warn!("{}", e);
+        }
+        Ok(())
+    }
+
+    fn version(&self) -> Option<String> {
+        engine::version(&self.bin_path).ok()
+    }
+
+    fn provider(&self) -> Option<Provider> {
+        *self.used.lock().unwrap()
+    }
+
+    fn transcript(&self) -> Option<engine::Transcript> {
+        Some(self.transcript.clone())
+    }
+}
+
+/// The engine's LADSPA plugin, run inside the app rather than as a process
+/// per file. Each channel goes through an instance of its own, and the delay
+/// the plugin reports is trimmed so the output lines up with the input. The
+/// engine options and limits are for the binary and don't apply.
+pub struct InProcess {
+    plugin: Arc<plugin::Plugin>,
+}
+
+/// Frames cleaned between checks for cancelling and progress reports.
+const BLOCK_FRAMES: usize = audio::ENGINE_SAMPLE_RATE as usize / 10;
+
+/// The installed plugin once loaded, which it then stays for every file.
+static LOADED: Mutex<Option<Arc<plugin::Plugin>>> = Mutex::new(None);
+
+impl InProcess {
+    pub fn new(plugin: Arc<plugin::Plugin>) -> Self {
+        Self { plugin }
+    }
+
+    /// Whether files can be cleaned in-process: in builds with the
+    /// `in-process` feature, once the plugin is installed.
+    pub fn is_installed() -> bool {
+        cfg!(feature = "in-process") && plugin::installed_plugin().is_some()
+    }
+
+    /// The installed plugin, loaded the first time it's needed.
+    pub fn installed() -> Result<Self, String> {
+        if !cfg!(feature = "in-process") {
+            return Err("This build can't clean files in-process".to_string());
+        }
+        let mut loaded = LOADED.lock().unwrap();
+        if let Some(plugin) = &*loaded {
+            return Ok(Self::new(plugin.clone()));
+        }
+        let path = plugin::installed_plugin().ok_or("The plugin isn't installed")?;
+        let plugin = Arc::new(plugin::Plugin::load(&path)?);
+        *loaded = Some(plugin.clone());
+        Ok(Self::new(plugin))
+    }
+}
+
+impl Denoiser for InProcess {
+    fn process_file(
+        &self,
+        input: &Path,
+        output_dir: &Path,
+        _limits: &engine::Limits,
+        cancel: &CancelToken,
+        on_progress: &mut dyn FnMut(f32),
+    ) -> Result<(), String> {
+        let (_, buffer) = audio::read_wav(input)?;
+        let buffer = audio::resample(&buffer, audio::ENGINE_SAMPLE_RATE, cancel, &mut |_| {})?;
+        let frames = buffer.frames();
+        let blocks = frames.div_ceil(BLOCK_FRAMES).max(1) * buffer.channels.len();
+        let mut done = 0;
+        let mut channels = Vec::with_capacity(buffer.channels.len());
+        for samples in &buffer.channels {
+            let mut instance = self.plugin.instantiate(audio::ENGINE_SAMPLE_RATE)?;
+            let mut cleaned = vec![0.0; frames];
+            for (input, output) in samples
+                .chunks(BLOCK_FRAMES)
+                .zip(cleaned.chunks_mut(BLOCK_FRAMES))
+            {
+                cancel.check()?;
+                instance.process(input, output);
+                done += 1;
+                on_progress(done as f32 / blocks as f32 * 100.0);
+            }
+            // Silence flushes out what the plugin still holds back.
+            let latency = instance.latency();
+            if latency > 0 {
+                let mut tail = vec![0.0; latency];
+                instance.process(&vec![0.0; latency], &mut tail);
+                cleaned.extend(tail);
+                cleaned.drain(..latency);
+            }
+            channels.push(cleaned);
+        }
+        audio::write_wav(
+            &output_dir.join(input.file_name().unwrap_or_default()),
+            audio::ENGINE_SPEC,
+            &audio::AudioBuffer {
+                sample_rate: audio::ENGINE_SAMPLE_RATE,
+                channels,
+            },
+        )
+    }
+}
+
+/// A self-hosted server the file is uploaded to.
+pub struct Remote {
+    pub server: remote::Server,
+}
+
+impl Denoiser for Remote {
+    fn process_file(
+        &self,
+        input: &Path,
+        output_dir: &Path,
+        limits: &engine::Limits,
+        cancel: &CancelToken,
+        on_progress: &mut dyn FnMut(f32),
+    ) -> Result<(), String> {
+        remote::run(
+            &self.server,
+            input,
+            &output_dir.join(input.file_name().unwrap_or_default()),
+            limits,
+            cancel,
+            on_progress,
+        )
+    }
+}
@@ -0,0 +1,695 @@
+//! Finding the engine binary and downloading it. Downloads reach the network
+//! through [`Network`], so they can be tested without one, and run side by
+//! side in a [`DownloadManager`].
+
+use crate::cancel::CancelToken;
+use bytes::Bytes;
+use futures_util::future::BoxFuture;
+use futures_util::stream::BoxStream;
+use futures_util::{FutureExt, StreamExt};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Name the engine is kept under in the data folder.
+pub const BIN_NAME: &str = if cfg!(windows) {
+    "deep-filter.exe"
+} else {
+    "deep-filter"
+};
+
+/// The engine release the app was made for.
+pub const RELEASE_TAG: &str = "v0.5.6";
+
+/// Where the engine release for this platform is published.
+pub fn release_url() -> Result<String, String> {
+    release_url_for(RELEASE_TAG)
+}
+
+/// Where the engine release tagged `tag` is published for this platform.
+pub fn release_url_for(tag: &str) -> Result<String, String> {
+    Ok(format!(
+        "https://github.com/Rikorose/DeepFilterNet/releases/download/{}/deep-filter-{}-{}",
+        tag,
+        tag.trim_start_matches('v'),
+        target()?
+    ))
+}
+
+/// The platform part of the engine's release file names.
+fn target() -> Result<&'static str, String> {
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    return Ok("x86_64-unknown-linux-musl");
+
+    #[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+    return Ok("aarch64-unknown-linux-gnu");
+
+    #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
+    return Ok("aarch64-apple-darwin");
+
+    #[cfg(all(target_os = "windows", target_arch = "x86_64"))]
+    return Ok("x86_64-pc-windows-msvc.exe");
+
+    #[allow(unreachable_code)]
+    Err("Unsupported OS/Architecture".to_string())
+}
+
+/// Where `url` of an official release file is found on `mirror`, which keeps
+/// the files under their original names.
+pub fn mirror_url(mirror: &str, url: &str) -> String {
+    let file_name = url.rsplit('/').next().unwrap_or(url);
+    format!("{}/{}", mirror.trim_end_matches('/'), file_name)
+}
+
+/// Where the engine goes in `dir`.
+pub fn binary_path(dir: &Path) -> PathBuf {
+    dir.join(BIN_NAME)
+}
+
+/// The engine in `dir`, if it's there.
+pub fn installed_in(dir: &Path) -> Option<PathBuf> {
+    let bin_path = binary_path(dir);
+    bin_path.exists().then_some(bin_path)
+}
+
+/// The engine in the app's data folder.
+pub fn installed_binary() -> Result<PathBuf, String> {
+    let dir = crate::data_dir().ok_or("Could not find project directories")?;
+    installed_in(&dir).ok_or_else(|| "Binary not found".to_string())
+}
+
+/// Where the engine is written while it's downloaded, so an unfinished
+/// download is never taken for the engine.
+pub fn partial_path(bin_path: &Path) -> PathBuf {
+    let mut name = bin_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    bin_path.with_file_name(name)
+}
+
+/// Removes what a download cut short left behind. Where open files can't be
+/// removed, the next download overwrites it instead.
+pub fn discard_partial() {
+    if let Some(dir) = crate::data_dir() {
+        let _ = std::fs::remove_file(partial_path(&binary_path(&dir)));
+    }
+}
+
+/// Copies `file`, an engine obtained some other way, into place at
+/// `bin_path`, for installing without a connection.
+pub fn install_from(file: &Path, bin_path: &Path) -> Result<PathBuf, String> {
+    if let Some(dir) = bin_path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let partial = partial_path(bin_path);
+    std::fs::copy(file, &partial).map_err(|e| format!("Could not copy the engine: {}", e))?;
+    let placed = std::fs::File::open(&partial)
+        .map(|copy| make_executable(&copy))
+        .and_then(|_| std::fs::rename(&partial, bin_path));
+    if let Err(e) = placed {
+        let _ = std::fs::remove_file(&partial);
+        return Err(e.to_string());
+    }
+    Ok(bin_path.to_path_buf())
+}
+
+fn make_executable(file: &std::fs::File) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(meta) = file.metadata() {
+            let mut perms = meta.permissions();
+            perms.set_mode(0o755);
+            let _ = file.set_permissions(perms);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = file;
+}
+
+/// Where connectivity is probed: the host releases are downloaded from.
+const PROBE_ADDRESS: &str = "github.com:443";
+
+/// How long the probe waits for the lookup and connection.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Whether the internet can be reached, judged by looking up GitHub and
+/// connecting to it. Tells being offline apart from a download that failed
+/// for other reasons.
+pub async fn is_online() -> bool {
+    is_reachable(PROBE_ADDRESS).await
+}
+
+async fn is_reachable(address: &str) -> bool {
+    let connect = tokio::net::TcpStream::connect(address);
+    matches!(
+        tokio::time::timeout(PROBE_TIMEOUT, connect).await,
+        Ok(Ok(_))
+    )
+}
+
+/// A response whose body is still to be read.
+pub struct Response {
+    /// Length of the body, if the server tells.
+    pub length: Option<u64>,
+    pub chunks: BoxStream<'static, Result<Bytes, String>>,
+}
+
+pub trait Network: Send + Sync {
+    /// Requests `url`, failing on error statuses.
+    fn get(&self, url: &str) -> BoxFuture<'static, Result<Response, String>>;
+
+    /// Size in bytes of the file at `url`, if the server tells without
+    /// sending it.
+    fn size(&self, url: &str) -> BoxFuture<'static, Option<u64>>;
+}
+
+/// A client set up the way every request of the app is made, the remote
+/// server's and the webhook's included: with its timeouts and user agent.
+/// Its pooled connections belong to the runtime that opened them, so a
+/// client is built for each runtime rather than shared between them.
+pub fn client() -> reqwest::Client {
+    reqwest::Client::builder()
+        // GitHub's API turns away requests that don't name their client.
+        .user_agent(concat!("dfn_gui/", env!("CARGO_PKG_VERSION")))
+        .connect_timeout(Duration::from_secs(15))
+        // Only between reads, so large downloads on slow connections still
+        // finish while stalled ones fail.
+        .read_timeout(Duration::from_secs(30))
+        // Release files are served through a redirect.
+        .redirect(reqwest::redirect::Policy::limited(10))
+        .build()
+        .unwrap_or_default()
+}
+
+/// The network, reached over HTTP through a [`client`] of its own, kept for
+/// the downloads of the app's runtime.
+pub struct Http {
+    client: reqwest::Client,
+}
+
+impl Default for Http {
+    fn default() -> Self {
+        Self { client: client() }
+    }
+}
+
+impl Network for Http {
+    fn get(&self, url: &str) -> BoxFuture<'static, Result<Response, String>> {
+        let request = self.client.get(url).send();
+        async move {
+            let response = request
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| e.to_string())?;
+            Ok(Response {
+                length: response.content_length(),
+                chunks: response
+                    .bytes_stream()
+                    .map(|chunk| chunk.map_err(|e| e.to_string()))
+                    .boxed(),
+            })
+        }
+        .boxed()
+    }
+
+    fn size(&self, url: &str) -> BoxFuture<'static, Option<u64>> {
+        let request = self.client.head(url).send();
+        async move {
+            let response = request.await.ok()?;
+            // `content_length` describes the (empty) body of the HEAD
+            // response itself, so read the header.
+            response
+                .headers()
+                .get(reqwest::header::CONTENT_LENGTH)?
+                .to_str()
+                .ok()?
+                .parse()
+                .ok()
+                .filter(|size| *size > 0)
+        }
+        .boxed()
+    }
+}
+
+/// A download under way. It's written to [`partial_path`] and only moved
+/// into place once complete.
+pub struct Download {
+    chunks: BoxStream<'static, Result<Bytes, String>>,
+    file: std::fs::File,
+    total: u64,
+    downloaded: u64,
+    bin_path: PathBuf,
+}
+
+/// What a [`Download::step`] led to.
+pub enum Step {
+    /// A chunk was written; the download is this far, in percent.
+    Progress(f32, Download),
+    /// The engine is in place at the path, or the download failed.
+    Finished(Result<PathBuf, String>),
+}
+
+/// Starts downloading `url` from `network` as the engine at `bin_path`,
+/// creating its folder if needed.
+pub async fn start(
+    network: &dyn Network,
+    url: &str,
+    bin_path: PathBuf,
+) -> Result<Download, String> {
+    let response = network.get(url).await?;
+    if let Some(dir) = bin_path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let file = std::fs::File::create(partial_path(&bin_path)).map_err(|e| e.to_string())?;
+    Ok(Download {
+        chunks: response.chunks,
+        file,
+        total: response.length.unwrap_or(0),
+        downloaded: 0,
+        bin_path,
+    })
+}
+
+impl Download {
+    /// Writes the next chunk, or moves the engine into place after the last.
+    pub async fn step(mut self) -> Step {
+        match self.chunks.next().await {
+            Some(Ok(chunk)) => {
+                if let Err(e) = self.file.write_all(&chunk) {
+                    return Step::Finished(Err(self.abort(e.to_string())));
+                }
+                self.downloaded += chunk.len() as u64;
+                let percent = if self.total > 0 {
+                    (self.downloaded as f32 / self.total as f32) * 100.0
+                } else {
+                    0.0
+                };
+                Step::Progress(percent, self)
+            }
+            Some(Err(e)) => Step::Finished(Err(self.abort(e))),
+            None => Step::Finished(self.finish()),
+        }
+    }
+
+    /// Removes the partial file, passing `error` on.
+    fn abort(self, error: String) -> String {
+        drop(self.file);
+        let _ = std::fs::remove_file(partial_path(&self.bin_path));
+        error
+    }
+
+    fn finish(self) -> Result<PathBuf, String> {
+        make_executable(&self.file);
+        drop(self.file);
+        std::fs::rename(partial_path(&self.bin_path), &self.bin_path).map_err(|e| e.to_string())?;
+        Ok(self.bin_path)
+    }
+}
+
+/// Tells apart the downloads of a [`DownloadManager`].
+pub type DownloadId = u64;
+
+/// Error a cancelled download finishes with.
+pub const CANCELLED: &str = "Download cancelled";
+
+/// What a download of a [`DownloadManager`] reports, tagged with its id so
+/// the events of downloads running side by side can be told apart.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// The download is this far, in percent.
+    Progress(DownloadId, f32),
+    /// The file is in place at the path, or the download failed.
+    Finished(DownloadId, Result<PathBuf, String>),
+}
+
+/// Runs downloads side by side over one [`Network`]. Each can be cancelled
+/// on its own.
+pub struct DownloadManager {
+    network: Arc<dyn Network>,
+    next_id: AtomicU64,
+    /// Cancel flags of the downloads under way.
+    running: Arc<Mutex<HashMap<DownloadId, CancelToken>>>,
+}
+
+impl Default for DownloadManager {
+    fn default() -> Self {
+        Self::new(Arc::new(Http::default()))
+    }
+}
+
+/// Where a download of a [`DownloadManager`] has got to.
+enum Stage {
+    Start(BoxFuture<'static, Result<String, String>>, PathBuf),
+    Downloading(Download),
+    Finished,
+}
+
+/// A download's place among those under way, given up when it's dropped.
+struct Ticket {
+    id: DownloadId,
+    cancel: CancelToken,
+    running: Arc<Mutex<HashMap<DownloadId, CancelToken>>>,
+}
+
+impl Drop for Ticket {
+    fn drop(&mut self) {
+        self.running.lock().unwrap().remove(&self.id);
+    }
+}
+
+impl DownloadManager {
+    pub fn new(network: Arc<dyn Network>) -> Self {
+        Self {
+            network,
+            next_id: AtomicU64::new(0),
+            running: Arc::default(),
+        }
+    }
+
+    /// The network the downloads go through, for other requests to share.
+    pub fn network(&self) -> Arc<dyn Network> {
+        self.network.clone()
+    }
+
+    /// Downloads the file at the URL `url` resolves to as `path`. Nothing
+    /// happens until the events are read, and the last one is
+    /// [`Event::Finished`].
+    pub fn download(
+        &self,
+        url: impl Future<Output = Result<String, String>> + Send + 'static,
+        path: PathBuf,
+    ) -> (DownloadId, BoxStream<'static, Event>) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let cancel = CancelToken::new();
+        self.running.lock().unwrap().insert(id, cancel.clone());
+        let ticket = Ticket {
+            id,
+            cancel,
+            running: self.running.clone(),
+        };
+        let network = self.network.clone();
+        let events = futures_util::stream::unfold(
+            (ticket, Stage::Start(url.boxed(), path)),
+            move |(ticket, stage)| {
+                let network = network.clone();
+                async move {
+                    let download = match stage {
+                        Stage::Start(url, path) => {
+                            let started = match url.await {
+                                Ok(url) => {
+                                    tracing::info!("Downloading {} to {}", url, path.display());
+                                    start(network.as_ref(), &url, path).await
+                                }
+                                Err(e) => Err(e),
+                            };
+                            let (event, stage) = match started {
+                                Ok(download) => {
+                                    (Event::Progress(id, 0.0), Stage::Downloading(download))
+                                }
+                                Err(e) => (Event::Finished(id, Err(e)), Stage::Finished),
+                            };
+                            return Some((event, (ticket, stage)));
+                        }
+                        Stage::Downloading(download) => download,
+                        Stage::Finished => return None,
+                    };
+                    if ticket.cancel.is_cancelled() {
+                        let error = download.abort(CANCELLED.to_string());
+                        return Some((Event::Finished(id, Err(error)), (ticket, Stage::Finished)));
+                    }
+                    let (event, stage) = match download.step().await {
+                        Step::Progress(percent, download) => {
+                            (Event::Progress(id, percent), Stage::Downloading(download))
+                        }
+                        Step::Finished(result) => (Event::Finished(id, result), Stage::Finished),
+                    };
+                    Some((event, (ticket, stage)))
+                }
+            },
+        );
+        (id, events.boxed())
+    }
+
+    /// Stops the download, which then finishes with [`CANCELLED`] and
+    /// leaves nothing behind.
+    pub fn cancel(&self, id: DownloadId) {
+        if let Some(cancel) = self.running.lock().unwrap().get(&id) {
+            cancel.cancel();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serves `chunks` for every request.
+    struct Canned {
+        chunks: Vec<Result<&'static [u8], &'static str>>,
+        length: Option<u64>,
+    }
+
+    impl Network for Canned {
+        fn get(&self, _url: &str) -> BoxFuture<'static, Result<Response, String>> {
+            let chunks: Vec<_> = self
+                .chunks
+                .iter()
+                .map(|chunk| chunk.map(Bytes::from_static).map_err(|e| e.to_string()))
+                .collect();
+            let length = self.length;
+            async move {
+                Ok(Response {
+                    length,
+                    chunks: futures_util::stream::iter(chunks).boxed(),
+                })
+            }
+            .boxed()
+        }
+
+        fn size(&self, _url: &str) -> BoxFuture<'static, Option<u64>> {
+            let length = self.length;
+            async move { length }.boxed()
+        }
+    }
+
+    /// Never answers.
+    struct Offline;
+
+    impl Network for Offline {
+        fn get(&self, _url: &str) -> BoxFuture<'static, Result<Response, String>> {
+            async { Err("no network".to_string()) }.boxed()
+        }
+
+        fn size(&self, _url: &str) -> BoxFuture<'static, Option<u64>> {
+            async { None }.boxed()
+        }
+    }
+
+    async fn run(download: Download) -> (Vec<f32>, Result<PathBuf, String>) {
+        let mut progress = Vec::new();
+        let mut download = download;
+        loop {
+            match download.step().await {
+                Step::Progress(percent, next) => {
+                    progress.push(percent);
+                    download = next;
+                }
+                Step::Finished(result) => return (progress, result),
+            }
+        }
+    }
+
+    #[test]
+    fn release_files_are_named_after_the_tag() {
+        let Ok(url) = release_url_for("v0.6.0-pre") else {
+            return;
+        };
+        assert!(url.starts_with(
+            "https://github.com/Rikorose/DeepFilterNet/releases/download/v0.6.0-pre/deep-filter-0.6.0-pre-"
+        ));
+        assert_eq!(release_url(), release_url_for(RELEASE_TAG));
+    }
+
+    #[test]
+    fn mirror_keeps_the_release_file_name() {
+        assert_eq!(
+            mirror_url(
+                "https://mirror.example.com/dfn/",
+                "https://github.com/x/releases/download/v1/deep-filter-1"
+            ),
+            "https://mirror.example.com/dfn/deep-filter-1"
+        );
+    }
+
+    #[test]
+    fn partial_downloads_get_their_own_name() {
+        assert_eq!(
+            partial_path(Path::new("/data/deep-filter")),
+            Path::new("/data/deep-filter.part")
+        );
+    }
+
+    #[tokio::test]
+    async fn complete_download_is_moved_into_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let network = Canned {
+            chunks: vec![Ok(b"deep"), Ok(b"-filter")],
+            length: Some(11),
+        };
+        assert_eq!(installed_in(dir.path()), None);
+
+        let download = start(&network, "url", binary_path(dir.path()))
+            .await
+            .unwrap();
+        let (progress, result) = run(download).await;
+
+        assert_eq!(progress.len(), 2);
+        assert_eq!(progress.last(), Some(&100.0));
+        let bin_path = result.unwrap();
+        assert_eq!(installed_in(dir.path()), Some(bin_path.clone()));
+        assert_eq!(std::fs::read(&bin_path).unwrap(), b"deep-filter");
+        assert!(!partial_path(&bin_path).exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&bin_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o111, 0o111);
+        }
+    }
+
+    #[tokio::test]
+    async fn unknown_length_reports_no_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let network = Canned {
+            chunks: vec![Ok(b"deep-filter")],
+            length: None,
+        };
+        let download = start(&network, "url", binary_path(dir.path()))
+            .await
+            .unwrap();
+        let (progress, result) = run(download).await;
+        assert_eq!(progress, [0.0]);
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn interrupted_download_leaves_nothing_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        let network = Canned {
+            chunks: vec![Ok(b"deep"), Err("connection reset")],
+            length: Some(11),
+        };
+        let bin_path = binary_path(dir.path());
+        let download = start(&network, "url", bin_path.clone()).await.unwrap();
+        let (_, result) = run(download).await;
+
+        assert_eq!(result, Err("connection reset".to_string()));
+        assert!(!bin_path.exists());
+        assert!(!partial_path(&bin_path).exists());
+    }
+
+    fn manager(network: impl Network + 'static) -> DownloadManager {
+        DownloadManager::new(Arc::new(network))
+    }
+
+    #[tokio::test]
+    async fn downloads_run_side_by_side() {
+        let dir = tempfile::tempdir().unwrap();
+        let downloads = manager(Canned {
+            chunks: vec![Ok(b"deep"), Ok(b"-filter")],
+            length: Some(11),
+        });
+        let (engine, engine_events) = downloads.download(
+            async { Ok("engine".to_string()) },
+            dir.path().join("engine"),
+        );
+        let (model, model_events) =
+            downloads.download(async { Ok("model".to_string()) }, dir.path().join("model"));
+        assert_ne!(engine, model);
+
+        let events: Vec<_> = futures_util::stream::select(engine_events, model_events)
+            .collect()
+            .await;
+        for (id, name) in [(engine, "engine"), (model, "model")] {
+            assert!(events.contains(&Event::Progress(id, 100.0)));
+            assert!(events.contains(&Event::Finished(id, Ok(dir.path().join(name)))));
+        }
+        assert!(downloads.running.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn cancelled_download_leaves_nothing_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        let downloads = manager(Canned {
+            chunks: vec![Ok(b"deep"), Ok(b"-filter")],
+            length: Some(11),
+        });
+        let path = binary_path(dir.path());
+        let (id, mut events) = downloads.download(async { Ok("url".to_string()) }, path.clone());
+
+        assert_eq!(events.next().await, Some(Event::Progress(id, 0.0)));
+        downloads.cancel(id);
+        assert_eq!(
+            events.next().await,
+            Some(Event::Finished(id, Err(CANCELLED.to_string())))
+        );
+        assert_eq!(events.next().await, None);
+        assert!(!path.exists());
+        assert!(!partial_path(&path).exists());
+    }
+
+    #[tokio::test]
+    async fn unresolved_url_finishes_the_download() {
+        let dir = tempfile::tempdir().unwrap();
+        let downloads = manager(Offline);
+        let path = binary_path(dir.path());
+        let (id, events) = downloads.download(async { Err("no release".to_string()) }, path);
+
+        let events: Vec<_> = events.collect().await;
+        assert_eq!(events, [Event::Finished(id, Err("no release".to_string()))]);
+        assert!(downloads.running.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_request_creates_no_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let bin_path = binary_path(dir.path());
+        let result = start(&Offline, "url", bin_path.clone()).await;
+
+        assert_eq!(result.err(), Some("no network".to_string()));
+        assert!(!partial_path(&bin_path).exists());
+    }
+
+    #[test]
+    fn engine_is_installed_from_a_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("deep-filter-download");
+        std::fs::write(&file, b"deep-filter").unwrap();
+        let bin_path = binary_path(&dir.path().join("data"));
+
+        assert_eq!(install_from(&file, &bin_path), Ok(bin_path.clone()));
+        assert_eq!(std::fs::read(&bin_path).unwrap(), b"deep-filter");
+        assert!(!partial_path(&bin_path).exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&bin_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o111, 0o111);
+        }
+
+        let missing = install_from(&dir.path().join("missing"), &bin_path);
+        assert!(missing.is_err());
+    }
+
+    #[tokio::test]
+    async fn reachability_follows_the_listener() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        assert!(is_reachable(&address).await);
+        drop(listener);
+        assert!(!is_reachable(&address).await);
+    }
+}
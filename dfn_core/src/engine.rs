@@ -0,0 +1,351 @@
+//! Running the external `deep-filter` binary.
+
+use crate::cancel::{CANCELLED, CancelToken};
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::process::{Child, Command as StdCommand, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+/// How often the child is checked for exit, timeout and stalls.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Number of trailing output lines of a run kept, for the Logs tab and in
+/// the error of a run that failed.
+const OUTPUT_LINES: usize = 1000;
+
+/// Time allowed on top of the duration-based timeout, covering model loading.
+const STARTUP_ALLOWANCE: Duration = Duration::from_secs(30);
+
+/// How long the engine may go without printing anything or using CPU time
+/// before it is considered hung.
+const STALL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Model built into the downloaded binary, which is the one it runs.
+pub const MODEL: &str = "DeepFilterNet3";
+
+/// Niceness applied in background priority mode.
+#[cfg(unix)]
+const BACKGROUND_NICENESS: i32 = 10;
+
+#[cfg(windows)]
+const BELOW_NORMAL_PRIORITY_CLASS: u32 = 0x0000_4000;
+
+/// How the engine process is launched.
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Run with reduced CPU priority so the rest of the system stays responsive.
+    pub low_priority: bool,
+    /// Upper bound on the worker threads the engine spawns; `None` leaves it
+    /// to the engine.
+    pub threads: Option<usize>,
+    /// Environment variables set for the engine, e.g. `RUST_LOG` or `ORT_*`
+    /// ones. They win over those set for `threads`.
+    pub env: Vec<(String, String)>,
+}
+
+/// Bounds on how long a single engine run may take.
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    pub timeout: Duration,
+    pub stall_timeout: Duration,
+}
+
+impl Limits {
+    /// Limits for `audio_seconds` of input, allowing `timeout_factor` times
+    /// realtime.
+    pub fn for_duration(audio_seconds: f32, timeout_factor: f32) -> Self {
+        Self {
+            timeout: STARTUP_ALLOWANCE
+                + Duration::from_secs_f32(audio_seconds.max(0.0) * timeout_factor),
+            stall_timeout: STALL_TIMEOUT,
+        }
+    }
+}
+
+/// The command line of a run and what the engine printed in it, kept for
+/// the Logs tab and problem reports. Clones share it, so it can be read
+/// while the run goes on; each run of its own keeps its own.
+#[derive(Debug, Clone, Default)]
+pub struct Transcript(Arc<Mutex<TranscriptLines>>);
+
+#[derive(Debug, Default)]
+struct TranscriptLines {
+    command: Option<String>,
+    lines: VecDeque<String>,
+}
+
+impl Transcript {
+    /// Command line the engine was run with, once it was.
+    pub fn command(&self) -> Option<String> {
+        self.0.lock().unwrap().command.clone()
+    }
+
+    /// What the engine printed to stdout and stderr, oldest line first.
+    pub fn lines(&self) -> Vec<String> {
+        self.0.lock().unwrap().lines.iter().cloned().collect()
+    }
+
+    fn start(&self, command: String) {
+        let mut transcript = self.0.lock().unwrap();
+        transcript.command = Some(command);
+        transcript.lines.clear();
+    }
+
+    fn push(&self, line: String) {
+        push_capped(&mut self.0.lock().unwrap().lines, line, OUTPUT_LINES);
+    }
+}
+
+/// Cleans `input_path` into `output_dir`, killing the engine if it exceeds
+/// `limits` or `cancel` is cancelled. The command line and what the engine
+/// prints go to `transcript`, replacing those of an earlier run. `on_tick`
+/// is called with how long the engine has been running each time it's
+/// checked on.
+#[allow(clippy::too_many_arguments)]
+pub fn run(
+    bin_path: &Path,
+    input_path: &Path,
+    output_dir: &Path,
+    options: &Options,
+    limits: &Limits,
+    cancel: &CancelToken,
+    transcript: &Transcript,
+    on_tick: &mut dyn FnMut(Duration),
+) -> Result<(), String> {
+    let mut command = command(bin_path, options);
+    if let Some(threads) = options.threads {
+        // The engine's thread pools size themselves from these.
+        command
+            .env("RAYON_NUM_THREADS", threads.to_string())
+            .env("OMP_NUM_THREADS", threads.to_string());
+    }
+    command.envs(options.env.iter().map(|(name, value)| (name, value)));
+    command.arg(input_path).arg("-o").arg(output_dir);
+    let command_line = format!("{:?}", command);
+    info!("Running {}", command_line);
+    transcript.start(command_line);
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run AI engine: {}", e))?;
+
+    let last_activity = Arc::new(Mutex::new(Instant::now()));
+    let readers = [
+        child
+            .stdout
+            .take()
+            .map(|pipe| capture(pipe, transcript.clone(), last_activity.clone())),
+        child
+            .stderr
+            .take()
+            .map(|pipe| capture(pipe, transcript.clone(), last_activity.clone())),
+    ];
+
+    let started = Instant::now();
+    let mut last_cpu_time = cpu_time(&child);
+    // Without it a long quiet stretch can't be told from a hang, so only
+    // the overall timeout applies.
+    let watch_stalls = last_cpu_time.is_some();
+    if !watch_stalls {
+        info!("The engine's CPU time can't be read, so it's only stopped on the timeout");
+    }
+    let failure = loop {
+        let exited = match child.try_wait() {
+            Ok(Some(status)) => {
+                info!("The engine exited with {}", status);
+                if status.success() {
+                    Some(None)
+                } else {
+                    Some(Some("DeepFilterNet failed to process the file".to_string()))
+                }
+            }
+            Ok(None) => None,
+            Err(e) => break Some(format!("Lost track of the AI engine: {}", e)),
+        };
+        if let Some(failure) = exited {
+            // Let the readers drain whatever the engine printed last. This is
+            // skipped after a kill, where a stray grandchild could keep the
+            // pipes open indefinitely.
+            for reader in readers.into_iter().flatten() {
+                let _ = reader.join();
+            }
+            break failure;
+        }
+
+        if cancel.is_cancelled() {
+            info!("Stopping the engine, the run was cancelled");
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(CANCELLED.to_string());
+        }
+
+        let cpu_time = cpu_time(&child);
+        if cpu_time.is_some() && cpu_time != last_cpu_time {
+            last_cpu_time = cpu_time;
+            *last_activity.lock().unwrap() = Instant::now();
+        }
+
+        let idle = last_activity.lock().unwrap().elapsed();
+        if started.elapsed() > limits.timeout {
+            warn!(
+                "Stopping the engine, it took longer than {:?}",
+                limits.timeout
+            );
+            let _ = child.kill();
+            let _ = child.wait();
+            break Some(format!(
+                "The engine did not finish within {} s and was stopped",
+                limits.timeout.as_secs()
+            ));
+        }
+        if watch_stalls && idle > limits.stall_timeout {
+            warn!("Stopping the engine, it was idle for {:?}", idle);
+            let _ = child.kill();
+            let _ = child.wait();
+            break Some(format!(
+                "The engine stopped responding for {} s and was stopped",
+                idle.as_secs()
+            ));
+        }
+
+        on_tick(started.elapsed());
+        std::thread::sleep(POLL_INTERVAL);
+    };
+
+    match failure {
+        None => Ok(()),
+        Some(message) => {
+            let lines = transcript.lines();
+            if lines.is_empty() {
+                Err(message)
+            } else {
+                Err(format!("{}\n{}", message, lines.join("\n")))
+            }
+        }
+    }
+}
+
+/// Version of the engine at `bin_path`, as it reports it.
+pub fn version(bin_path: &Path) -> Result<String, String> {
+    let output = StdCommand::new(bin_path)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("Failed to run AI engine: {}", e))?;
+    // Printed as `deep-filter 0.5.6`.
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .nth(1)
+        .map(str::to_string)
+        .ok_or_else(|| "The engine didn't report its version".to_string())
+}
+
+#[cfg(unix)]
+fn command(bin_path: &Path, options: &Options) -> StdCommand {
+    if options.low_priority {
+        // `nice` execs the engine in place, so the child keeps its PID.
+        let mut command = StdCommand::new("nice");
+        command
+            .arg("-n")
+            .arg(BACKGROUND_NICENESS.to_string())
+            .arg(bin_path);
+        command
+    } else {
+        StdCommand::new(bin_path)
+    }
+}
+
+#[cfg(windows)]
+fn command(bin_path: &Path, options: &Options) -> StdCommand {
+    use std::os::windows::process::CommandExt;
+
+    let mut command = StdCommand::new(bin_path);
+    if options.low_priority {
+        command.creation_flags(BELOW_NORMAL_PRIORITY_CLASS);
+    }
+    command
+}
+
+fn capture(
+    pipe: impl Read + Send + 'static,
+    transcript: Transcript,
+    last_activity: Arc<Mutex<Instant>>,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        for line in BufReader::new(pipe).lines() {
+            let Ok(line) = line else { break };
+            debug!("engine: {}", line);
+            transcript.push(line);
+            *last_activity.lock().unwrap() = Instant::now();
+        }
+    })
+}
+
+/// Appends `line`, dropping the oldest one once there are `cap`.
+fn push_capped(lines: &mut VecDeque<String>, line: String, cap: usize) {
+    if lines.len() == cap {
+        lines.pop_front();
+    }
+    lines.push_back(line);
+}
+
+/// CPU time consumed so far by `child`, in units of the platform's own. Only
+/// changes between readings matter.
+#[cfg(target_os = "linux")]
+fn cpu_time(child: &Child) -> Option<u64> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", child.id())).ok()?;
+    // Fields after the parenthesized command name, starting with the state.
+    let mut fields = stat.rsplit_once(')')?.1.split_whitespace();
+    let user: u64 = fields.nth(11)?.parse().ok()?;
+    let system: u64 = fields.next()?.parse().ok()?;
+    Some(user + system)
+}
+
+#[cfg(target_os = "macos")]
+fn cpu_time(child: &Child) -> Option<u64> {
+    let mut usage = std::mem::MaybeUninit::<libc::rusage_info_v2>::zeroed();
+    // SAFETY: `usage` is large enough for the V2 flavor asked for.
+    let result = unsafe {
+        libc::proc_pid_rusage(
+            child.id() as libc::c_int,
+            libc::RUSAGE_INFO_V2,
+            usage.as_mut_ptr().cast(),
+        )
+    };
+    if result != 0 {
+        return None;
+    }
+    // SAFETY: filled in by the successful call.
+    let usage = unsafe { usage.assume_init() };
+    Some(usage.ri_user_time + usage.ri_system_time)
+}
+
+#[cfg(windows)]
+fn cpu_time(child: &Child) -> Option<u64> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Foundation::FILETIME;
+    use windows_sys::Win32::System::Threading::GetProcessTimes;
+
+    let mut times = [FILETIME {
+        dwLowDateTime: 0,
+        dwHighDateTime: 0,
+    }; 4];
+    let [creation, exit, kernel, user] = &mut times;
+    // SAFETY: the handle stays open as long as `child`, and each time points
+    // to a FILETIME.
+    if unsafe { GetProcessTimes(child.as_raw_handle(), creation, exit, kernel, user) } == 0 {
+        return None;
+    }
+    let ticks =
+        |time: &FILETIME| (u64::from(time.dwHighDateTime) << 32) | u64::from(time.dwLowDateTime);
+    Some(ticks(kernel) + ticks(user))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn cpu_time(_child: &Child) -> Option<u64> {
+    None
+}
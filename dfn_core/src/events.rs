@@ -0,0 +1,181 @@
+//! Progress of a run as newline-delimited JSON, one object per line, for
+//! tools driving the app from the command line. Every event names the input
+//! it's about; `event` tells the kind:
+//!
+//! - `queued`: the input will be cleaned.
+//! - `progress`: the run reached `percent` within `phase`.
+//! - `done`: the output was written, with how long it took and, where they
+//!   were measured, the levels before and after and a quality score from 1
+//!   to 5 (see [`metrics::Comparison::quality_score`]). Runs of the engine
+//!   also name the `provider` the model ran on (see [`crate::accel`]).
+//! - `error`: the input couldn't be cleaned, with why.
+//! - `duplicate`: the input was skipped as an exact copy of the `original`
+//!   cleaned before it (see [`crate::dedup`]).
+
+use crate::metrics;
+use crate::pipeline::{Phase, RunReport};
+use serde_json::{Value, json};
+use std::path::Path;
+
+pub enum Event<'a> {
+    Queued {
+        input: &'a Path,
+    },
+    Progress {
+        input: &'a Path,
+        phase: Phase,
+        percent: f32,
+    },
+    Done {
+        input: &'a Path,
+        report: &'a RunReport,
+    },
+    Error {
+        input: &'a Path,
+        message: &'a str,
+    },
+    Duplicate {
+        input: &'a Path,
+        original: &'a Path,
+    },
+}
+
+impl Event<'_> {
+    /// The event as a single line of JSON.
+    pub fn to_json(&self) -> String {
+        let event = match self {
+            Event::Queued { input } => json!({"event": "queued", "input": path(input)}),
+            Event::Progress {
+                input,
+                phase,
+                percent,
+            } => json!({
+                "event": "progress",
+                "input": path(input),
+                "phase": phase.key(),
+                "percent": number(*percent),
+            }),
+            Event::Done { input, report } => {
+                let mut event = json!({
+                    "event": "done",
+                    "input": path(input),
+                    "output": path(&report.output_path),
+                    "audio_seconds": number(report.audio_duration.as_secs_f32()),
+                    "elapsed_seconds": number(report.elapsed.as_secs_f32()),
+                    "realtime_factor": number(report.realtime_factor()),
+                });
+                if let Some(provider) = report.provider {
+                    event["provider"] = json!(provider.key());
+                }
+                if let Some(levels) = &report.levels {
+                    event["before"] = self::levels(&levels.before);
+                    event["after"] = self::levels(&levels.after);
+                    event["snr_improvement_db"] = number(levels.snr_improvement_db());
+                    event["quality_score"] = number(levels.quality_score());
+                }
+                event
+            }
+            Event::Error { input, message } => json!({
+                "event": "error",
+                "input": path(input),
+                "message": message,
+            }),
+            Event::Duplicate { input, original } => json!({
+                "event": "duplicate",
+                "input": path(input),
+                "original": path(original),
+            }),
+        };
+        event.to_string()
+    }
+}
+
+fn levels(levels: &metrics::Levels) -> Value {
+    json!({
+        "peak_db": number(levels.peak_db),
+        "rms_db": number(levels.rms_db),
+        "noise_floor_db": number(levels.noise_floor_db),
+        "signal_db": number(levels.signal_db),
+    })
+}
+
+pub(crate) fn path(path: &Path) -> Value {
+    Value::from(path.to_string_lossy())
+}
+
+/// `value` to two decimals. JSON has no infinity, so levels of silence and
+/// other non-finite values come out as `null`.
+pub(crate) fn number(value: f32) -> Value {
+    // Rounded as f64, so 33.33 doesn't come out as 33.33000183105469.
+    Value::from((f64::from(value) * 100.0).round() / 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accel;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    #[test]
+    fn strings_are_escaped() {
+        let event = Event::Error {
+            input: Path::new("C:\\audio\\\"take\".wav"),
+            message: "line one\nline two",
+        };
+        assert_eq!(
+            event.to_json(),
+            r#"{"event":"error","input":"C:\\audio\\\"take\".wav","message":"line one\nline two"}"#
+        );
+    }
+
+    #[test]
+    fn progress_names_the_phase() {
+        let event = Event::Progress {
+            input: Path::new("a.wav"),
+            phase: Phase::Denoising,
+            percent: 33.333,
+        };
+        assert_eq!(
+            event.to_json(),
+            r#"{"event":"progress","input":"a.wav","phase":"denoising","percent":33.33}"#
+        );
+    }
+
+    #[test]
+    fn done_carries_the_metrics() {
+        let report = RunReport {
+            output_path: PathBuf::from("dnf_clean/a.wav"),
+            other_outputs: Vec::new(),
+            audio_duration: Duration::from_secs(10),
+            elapsed: Duration::from_secs(2),
+            levels: Some(metrics::Comparison {
+                before: metrics::Levels {
+                    peak_db: -1.0,
+                    rms_db: -20.0,
+                    noise_floor_db: -50.0,
+                    signal_db: -15.0,
+                },
+                after: metrics::Levels {
+                    peak_db: -2.0,
+                    rms_db: -22.0,
+                    noise_floor_db: f32::NEG_INFINITY,
+                    signal_db: -16.0,
+                },
+            }),
+            provider: Some(accel::Provider::Cuda),
+        };
+        let json = Event::Done {
+            input: Path::new("a.wav"),
+            report: &report,
+        }
+        .to_json();
+        assert!(json.starts_with(
+            r#"{"event":"done","input":"a.wav","output":"dnf_clean/a.wav","audio_seconds":10.0,"elapsed_seconds":2.0,"realtime_factor":5.0,"provider":"cuda","#
+        ));
+        assert!(json.contains(
+            r#""after":{"peak_db":-2.0,"rms_db":-22.0,"noise_floor_db":null,"signal_db":-16.0}"#
+        ));
+        assert!(json.ends_with(r#""quality_score":5.0}"#));
+    }
+}
@@ -0,0 +1,250 @@
+//! Every file format the app reads or writes, in one table. The file dialog,
+//! drop and control socket checks, decoding, encoding and size estimates all
+//! look formats up here, so supporting another one is a new [`FORMATS`] row.
+
+use crate::audio;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+/// What a file holds, which decides how it's offered and written back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Audio,
+    /// Only the first audio track is cleaned; written back by remuxing.
+    Video,
+}
+
+/// How a format is read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decoder {
+    /// Read directly when it holds PCM, through Symphonia otherwise.
+    Wav,
+    /// Symphonia, or ffmpeg for streams Symphonia can't handle.
+    Symphonia,
+    /// Converted to WAV with ffmpeg first.
+    Ffmpeg,
+}
+
+/// How a format is written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Encoder {
+    /// Written by the app itself.
+    Wav,
+    /// Lossless through ffmpeg's `codec`.
+    Lossless { codec: &'static str },
+    /// Lossy through ffmpeg's `codec`, at a bitrate in `bitrates_kbps`.
+    Lossy {
+        codec: &'static str,
+        bitrates_kbps: RangeInclusive<u32>,
+    },
+    /// Not written; the audio of video goes to the container it came from.
+    None,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Format {
+    /// Lowercase, without the dot.
+    pub extension: &'static str,
+    pub kind: Kind,
+    pub decoder: Decoder,
+    pub encoder: Encoder,
+    /// Whether tags written to it can carry cover art.
+    pub holds_cover: bool,
+}
+
+/// The formats accepted as input, audio first.
+pub const FORMATS: &[Format] = &[
+    Format {
+        extension: "wav",
+        kind: Kind::Audio,
+        decoder: Decoder::Wav,
+        encoder: Encoder::Wav,
+        holds_cover: false,
+    },
+    Format {
+        extension: "flac",
+        kind: Kind::Audio,
+        decoder: Decoder::Symphonia,
+        encoder: Encoder::Lossless { codec: "flac" },
+        holds_cover: true,
+    },
+    Format {
+        extension: "mp3",
+        kind: Kind::Audio,
+        decoder: Decoder::Symphonia,
+        encoder: Encoder::Lossy {
+            codec: "libmp3lame",
+            bitrates_kbps: 32..=320,
+        },
+        holds_cover: true,
+    },
+    Format {
+        extension: "m4a",
+        kind: Kind::Audio,
+        decoder: Decoder::Symphonia,
+        encoder: Encoder::Lossy {
+            codec: "aac",
+            bitrates_kbps: 32..=512,
+        },
+        holds_cover: true,
+    },
+    Format {
+        extension: "ogg",
+        kind: Kind::Audio,
+        decoder: Decoder::Symphonia,
+        encoder: Encoder::Lossy {
+            codec: "libvorbis",
+            bitrates_kbps: 45..=500,
+        },
+        holds_cover: false,
+    },
+    Format {
+        extension: "opus",
+        kind: Kind::Audio,
+        decoder: Decoder::Ffmpeg,
+        encoder: Encoder::Lossy {
+            codec: "libopus",
+            bitrates_kbps: 6..=510,
+        },
+        holds_cover: false,
+    },
+    Format {
+        extension: "mp4",
+        kind: Kind::Video,
+        decoder: Decoder::Symphonia,
+        encoder: Encoder::None,
+        holds_cover: false,
+    },
+    Format {
+        extension: "mkv",
+        kind: Kind::Video,
+        decoder: Decoder::Symphonia,
+        encoder: Encoder::None,
+        holds_cover: false,
+    },
+    Format {
+        extension: "mov",
+        kind: Kind::Video,
+        decoder: Decoder::Symphonia,
+        encoder: Encoder::None,
+        holds_cover: false,
+    },
+];
+
+/// Extension the audio of video inputs is written with when the output
+/// keeps the input's format.
+pub const VIDEO_AUDIO_EXTENSION: &str = "m4a";
+
+/// Share of the WAV size FLAC takes for cleaned speech, whose quiet
+/// stretches are near silence. Music takes more.
+const FLAC_RATIO: f64 = 0.3;
+
+/// The format with `extension`, in any case.
+pub fn find(extension: &str) -> Option<&'static Format> {
+    FORMATS
+        .iter()
+        .find(|format| format.extension.eq_ignore_ascii_case(extension))
+}
+
+/// The format of `path`, going by its extension.
+pub fn of(path: &Path) -> Option<&'static Format> {
+    find(&audio::extension(path)?)
+}
+
+/// Extensions of the formats of `kind`.
+pub fn extensions(kind: Kind) -> Vec<&'static str> {
+    FORMATS
+        .iter()
+        .filter(|format| format.kind == kind)
+        .map(|format| format.extension)
+        .collect()
+}
+
+/// Every extension accepted as input, audio first.
+pub fn supported_extensions() -> Vec<&'static str> {
+    FORMATS.iter().map(|format| format.extension).collect()
+}
+
+/// Whether `path` is accepted as input. Picking, dropping and the control
+/// socket all check this.
+pub fn is_supported(path: &Path) -> bool {
+    of(path).is_some()
+}
+
+pub fn is_video(path: &Path) -> bool {
+    of(path).is_some_and(|format| format.kind == Kind::Video)
+}
+
+impl Format {
+    /// Encoder arguments for ffmpeg, with `bitrate_kbps` brought into the
+    /// range the codec takes. Empty for formats ffmpeg doesn't write.
+    pub fn codec_args(&self, bitrate_kbps: u32) -> Vec<String> {
+        match &self.encoder {
+            Encoder::Lossless { codec } => vec![String::from("-c:a"), codec.to_string()],
+            Encoder::Lossy {
+                codec,
+                bitrates_kbps,
+            } => vec![
+                String::from("-c:a"),
+                codec.to_string(),
+                String::from("-b:a"),
+                format!(
+                    "{}k",
+                    bitrate_kbps.clamp(*bitrates_kbps.start(), *bitrates_kbps.end())
+                ),
+            ],
+            Encoder::Wav | Encoder::None => Vec::new(),
+        }
+    }
+
+    /// Rough size in bytes of `seconds` of audio in this format, given its
+    /// size as PCM and the bitrate lossy formats are written at.
+    pub fn estimate_size(&self, seconds: f64, pcm_bytes: f64, bitrate_kbps: u32) -> Option<f64> {
+        match &self.encoder {
+            // The header of a plain WAV file.
+            Encoder::Wav => Some(pcm_bytes + 44.0),
+            Encoder::Lossless { .. } => Some(pcm_bytes * FLAC_RATIO),
+            Encoder::Lossy { bitrates_kbps, .. } => {
+                let kbps = bitrate_kbps.clamp(*bitrates_kbps.start(), *bitrates_kbps.end());
+                Some(seconds * kbps as f64 * 1000.0 / 8.0)
+            }
+            Encoder::None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_are_found_by_extension_in_any_case() {
+        assert_eq!(of(Path::new("take.WAV")).unwrap().extension, "wav");
+        assert!(is_video(Path::new("/clips/Interview.Mkv")));
+        assert!(!is_supported(Path::new("notes.txt")));
+        assert!(!is_supported(Path::new("no_extension")));
+        assert_eq!(extensions(Kind::Video), ["mp4", "mkv", "mov"]);
+    }
+
+    #[test]
+    fn extensions_are_listed_once() {
+        let extensions = supported_extensions();
+        for (index, extension) in extensions.iter().enumerate() {
+            assert!(
+                !extensions[index + 1..].contains(extension),
+                "{}",
+                extension
+            );
+            assert_eq!(*extension, extension.to_lowercase());
+        }
+    }
+
+    #[test]
+    fn bitrates_are_kept_in_the_codec_range() {
+        let mp3 = find("mp3").unwrap();
+        assert_eq!(mp3.codec_args(400), ["-c:a", "libmp3lame", "-b:a", "320k"]);
+        assert_eq!(find("opus").unwrap().codec_args(192)[3], "192k");
+        assert_eq!(find("flac").unwrap().codec_args(192), ["-c:a", "flac"]);
+        assert!(find("wav").unwrap().codec_args(192).is_empty());
+    }
+}
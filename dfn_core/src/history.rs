@@ -0,0 +1,541 @@
+//! Log of every processing run, kept in an SQLite database in the app's data
+//! folder so it survives restarts.
+
+use crate::config;
+use crate::pipeline::ProcessingOptions;
+use rusqlite::{Connection, params};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Most entries listed at once.
+const LIMIT: usize = 200;
+
+/// A run as it's recorded.
+pub struct Run {
+    pub input_path: PathBuf,
+    pub output_path: Option<PathBuf>,
+    pub audio_duration: Option<Duration>,
+    pub elapsed: Option<Duration>,
+    pub options: ProcessingOptions,
+    /// Why the run failed, `None` if it succeeded.
+    pub error: Option<String>,
+    pub started: SystemTime,
+    pub finished: SystemTime,
+}
+
+/// A run noted as started that was never recorded, as a session that
+/// crashed or was killed leaves it.
+#[derive(Debug, Clone)]
+pub struct Unfinished {
+    pub id: i64,
+    pub input_path: PathBuf,
+    /// The file the run was about to write, see
+    /// [`crate::pipeline::planned_output`].
+    pub output_path: PathBuf,
+    pub options: ProcessingOptions,
+    pub started: SystemTime,
+    /// The session that noted the run, see [`History::is_running`]. `None`
+    /// for runs noted before sessions were.
+    pub owner: Option<String>,
+}
+
+/// A run as it's listed.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub id: i64,
+    pub input_path: PathBuf,
+    pub output_path: Option<PathBuf>,
+    pub audio_duration: Option<Duration>,
+    pub elapsed: Option<Duration>,
+    pub error: Option<String>,
+    /// When the run started, in local time.
+    pub started: String,
+}
+
+/// Which runs are listed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Outcome {
+    #[default]
+    All,
+    Succeeded,
+    Failed,
+}
+
+impl Outcome {
+    pub const ALL: [Outcome; 3] = [Outcome::All, Outcome::Succeeded, Outcome::Failed];
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use crate::i18n::tr;
+        match self {
+            Outcome::All => write!(f, "{}", tr!("history-outcome-all")),
+            Outcome::Succeeded => write!(f, "{}", tr!("history-outcome-succeeded")),
+            Outcome::Failed => write!(f, "{}", tr!("history-outcome-failed")),
+        }
+    }
+}
+
+pub struct History {
+    connection: Connection,
+    /// Folder of the session locks, next to the database.
+    sessions: PathBuf,
+    session: Option<Session>,
+}
+
+/// This process's hold on the history. Runs are noted with its name, and it
+/// keeps a lock on a file of that name for as long as it's open, which the
+/// system lets go of when the process dies. A run whose session's lock can
+/// be taken was therefore cut short, even with other sessions running.
+struct Session {
+    name: String,
+    lock: Option<File>,
+    path: PathBuf,
+}
+
+impl History {
+    /// Opens the database in the app's data folder, creating it on first use.
+    pub fn open() -> Result<Self, String> {
+        let dir = crate::data_dir().ok_or("Could not find project directories")?;
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Could not create data folder: {}", e))?;
+        Self::open_at(&dir.join("history.sqlite3"))
+    }
+
+    /// Opens the database at `path`, creating it on first use.
+    pub fn open_at(path: &Path) -> Result<Self, String> {
+        let connection =
+            Connection::open(path).map_err(|e| format!("Could not open the history: {}", e))?;
+        connection
+            .execute_batch(
+                "CREATE TABLE IF NOT EXISTS runs (
+                    id INTEGER PRIMARY KEY,
+                    input_path TEXT NOT NULL,
+                    output_path TEXT,
+                    audio_seconds REAL,
+                    elapsed_seconds REAL,
+                    options TEXT NOT NULL,
+                    error TEXT,
+                    started_at INTEGER NOT NULL,
+                    finished_at INTEGER NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS unfinished (
+                    id INTEGER PRIMARY KEY,
+                    input_path TEXT NOT NULL,
+                    output_path TEXT NOT NULL,
+                    options TEXT NOT NULL,
+                    started_at INTEGER NOT NULL,
+                    owner TEXT
+                )",
+            )
+            .map_err(|e| format!("Could not set up the history: {}", e))?;
+        // Databases from before runs were noted with their session. Fails
+        // when the column is already there.
+        let _ = connection.execute("ALTER TABLE unfinished ADD COLUMN owner TEXT", []);
+        let sessions = path.with_extension("sessions");
+        // Without a session, runs are still noted, only without an owner.
+        let session = Session::start(&sessions)
+            .inspect_err(|e| tracing::warn!("{}", e))
+            .ok();
+        Ok(Self {
+            connection,
+            sessions,
+            session,
+        })
+    }
+
+    /// Notes a run as started, until it's recorded. Runs still noted at the
+    /// next start never finished.
+    pub fn begin(
+        &self,
+        input_path: &Path,
+        output_path: &Path,
+        options: &ProcessingOptions,
+        started: SystemTime,
+    ) -> Result<(), String> {
+        self.connection
+            .execute(
+                "INSERT INTO unfinished (input_path, output_path, options, started_at, owner)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    input_path.to_string_lossy(),
+                    output_path.to_string_lossy(),
+                    stored_options(options),
+                    unix_seconds(started),
+                    self.session.as_ref().map(|session| session.name.as_str()),
+                ],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Could not note the run: {}", e))
+    }
+
+    pub fn record(&self, run: &Run) -> Result<(), String> {
+        self.connection
+            .execute(
+                "DELETE FROM unfinished WHERE input_path = ?1 AND started_at = ?2",
+                params![run.input_path.to_string_lossy(), unix_seconds(run.started)],
+            )
+            .map_err(|e| format!("Could not record the run: {}", e))?;
+        self.connection
+            .execute(
+                "INSERT INTO runs (input_path, output_path, audio_seconds, elapsed_seconds,
+                    options, error, started_at, finished_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    run.input_path.to_string_lossy(),
+                    run.output_path
+                        .as_ref()
+                        .map(|path| path.to_string_lossy().into_owned()),
+                    run.audio_duration.map(|duration| duration.as_secs_f64()),
+                    run.elapsed.map(|duration| duration.as_secs_f64()),
+                    stored_options(&run.options),
+                    run.error,
+                    unix_seconds(run.started),
+                    unix_seconds(run.finished),
+                ],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Could not record the run: {}", e))
+    }
+
+    /// Runs noted as started that were never recorded, oldest first.
+    pub fn unfinished(&self) -> Result<Vec<Unfinished>, String> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT id, input_path, output_path, options, started_at, owner
+                 FROM unfinished
+                 ORDER BY started_at, id",
+            )
+            .map_err(|e| format!("Could not read the history: {}", e))?;
+        statement
+            .query_map([], |row| {
+                Ok(Unfinished {
+                    id: row.get(0)?,
+                    input_path: PathBuf::from(row.get::<_, String>(1)?),
+                    output_path: PathBuf::from(row.get::<_, String>(2)?),
+                    options: config::options_from_toml(&row.get::<_, String>(3)?)
+                        .unwrap_or_default(),
+                    started: UNIX_EPOCH + Duration::from_secs(row.get::<_, i64>(4)?.max(0) as u64),
+                    owner: row.get(5)?,
+                })
+            })
+            .and_then(|rows| rows.collect())
+            .map_err(|e| format!("Could not read the history: {}", e))
+    }
+
+    /// Whether the session that noted `run` is still open, in this process
+    /// or another, so the run may well be going on.
+    pub fn is_running(&self, run: &Unfinished) -> bool {
+        let Some(owner) = &run.owner else {
+            return false;
+        };
+        if self
+            .session
+            .as_ref()
+            .is_some_and(|session| &session.name == owner)
+        {
+            return true;
+        }
+        match File::open(self.sessions.join(owner).with_extension("lock")) {
+            // Let go of right away, as the session is over.
+            Ok(lock) => lock.try_lock().is_err(),
+            // Closed, or never got to take its lock.
+            Err(_) => false,
+        }
+    }
+
+    /// Stops listing the unfinished run `id`.
+    pub fn forget(&self, id: i64) -> Result<(), String> {
+        self.connection
+            .execute("DELETE FROM unfinished WHERE id = ?1", [id])
+            .map(|_| ())
+            .map_err(|e| format!("Could not update the history: {}", e))
+    }
+
+    /// The latest runs whose input or output path contains `query`, newest
+    /// first.
+    pub fn search(&self, query: &str, outcome: Outcome) -> Result<Vec<Entry>, String> {
+        let pattern = format!(
+            "%{}%",
+            query
+                .trim()
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT id, input_path, output_path, audio_seconds, elapsed_seconds, error,
+                    datetime(started_at, 'unixepoch', 'localtime')
+                 FROM runs
+                 WHERE (input_path LIKE ?1 ESCAPE '\\' OR output_path LIKE ?1 ESCAPE '\\')
+                    AND (?2 = 0 OR (?2 = 1) = (error IS NULL))
+                 ORDER BY started_at DESC, id DESC
+                 LIMIT ?3",
+            )
+            .map_err(|e| format!("Could not read the history: {}", e))?;
+        let outcome = match outcome {
+            Outcome::All => 0,
+            Outcome::Succeeded => 1,
+            Outcome::Failed => 2,
+        };
+        statement
+            .query_map(params![pattern, outcome, LIMIT as i64], entry)
+            .and_then(|rows| rows.collect())
+            .map_err(|e| format!("Could not read the history: {}", e))
+    }
+
+    /// The outputs made from `input_path` by runs that succeeded, newest
+    /// first, each from the latest run that wrote it.
+    pub fn versions(&self, input_path: &Path) -> Result<Vec<Entry>, String> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT id, input_path, output_path, audio_seconds, elapsed_seconds, error,
+                    datetime(started_at, 'unixepoch', 'localtime')
+                 FROM runs
+                 WHERE input_path = ?1 AND error IS NULL AND output_path IS NOT NULL
+                 ORDER BY started_at DESC, id DESC
+                 LIMIT ?2",
+            )
+            .map_err(|e| format!("Could not read the history: {}", e))?;
+        let entries: Vec<Entry> = statement
+            .query_map(params![input_path.to_string_lossy(), LIMIT as i64], entry)
+            .and_then(|rows| rows.collect())
+            .map_err(|e| format!("Could not read the history: {}", e))?;
+        let mut outputs = std::collections::HashSet::new();
+        Ok(entries
+            .into_iter()
+            .filter(|entry| outputs.insert(entry.output_path.clone()))
+            .collect())
+    }
+
+    /// Options the run `id` was made with.
+    pub fn options(&self, id: i64) -> Result<ProcessingOptions, String> {
+        let text: String = self
+            .connection
+            .query_row("SELECT options FROM runs WHERE id = ?1", [id], |row| {
+                row.get(0)
+            })
+            .map_err(|e| format!("Could not read the history: {}", e))?;
+        config::options_from_toml(&text)
+            .ok_or_else(|| "The run's options could not be read".to_string())
+    }
+}
+
+impl Session {
+    /// A new session with its lock in `dir`, named after the process and
+    /// the time, so a later process given the same id gets a name of its
+    /// own.
+    fn start(dir: &Path) -> Result<Self, String> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Could not create the sessions folder: {}", e))?;
+        remove_ended_sessions(dir);
+        let started = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_nanos());
+        let name = format!("{}-{}", std::process::id(), started);
+        let path = dir.join(&name).with_extension("lock");
+        let lock =
+            File::create(&path).map_err(|e| format!("Could not create the session lock: {}", e))?;
+        lock.lock()
+            .map_err(|e| format!("Could not lock the session: {}", e))?;
+        Ok(Self {
+            name,
+            lock: Some(lock),
+            path,
+        })
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        // Windows won't remove a file that's open.
+        drop(self.lock.take());
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Removes the locks in `dir` left by sessions that crashed or were killed.
+/// Their runs count as cut short with or without them.
+fn remove_ended_sessions(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let ended = File::open(&path).is_ok_and(|lock| lock.try_lock().is_ok());
+        if ended {
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+}
+
+/// An entry from a row of `id, input_path, output_path, audio_seconds,
+/// elapsed_seconds, error, started`.
+fn entry(row: &rusqlite::Row) -> rusqlite::Result<Entry> {
+    Ok(Entry {
+        id: row.get(0)?,
+        input_path: PathBuf::from(row.get::<_, String>(1)?),
+        output_path: row.get::<_, Option<String>>(2)?.map(PathBuf::from),
+        audio_duration: row.get::<_, Option<f64>>(3)?.map(Duration::from_secs_f64),
+        elapsed: row.get::<_, Option<f64>>(4)?.map(Duration::from_secs_f64),
+        error: row.get(5)?,
+        started: row.get(6)?,
+    })
+}
+
+/// `options` as they're kept: the server token stays in the settings only.
+fn stored_options(options: &ProcessingOptions) -> String {
+    let mut options = options.clone();
+    options.server.token.clear();
+    config::options_to_toml(&options)
+}
+
+fn unix_seconds(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(input: &str, error: Option<&str>) -> Run {
+        Run {
+            input_path: PathBuf::from(input),
+            output_path: error
+                .is_none()
+                .then(|| PathBuf::from(input).with_extension("out")),
+            audio_duration: Some(Duration::from_secs(60)),
+            elapsed: Some(Duration::from_secs(6)),
+            options: ProcessingOptions::default(),
+            error: error.map(str::to_string),
+            started: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            finished: UNIX_EPOCH + Duration::from_secs(1_700_000_006),
+        }
+    }
+
+    fn inputs(entries: &[Entry]) -> Vec<&Path> {
+        entries
+            .iter()
+            .map(|entry| entry.input_path.as_path())
+            .collect()
+    }
+
+    #[test]
+    fn runs_are_listed_newest_first_and_filtered_by_outcome() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::open_at(&dir.path().join("history.sqlite3")).unwrap();
+        history.record(&run("/audio/interview.wav", None)).unwrap();
+        history
+            .record(&run("/audio/podcast.mp3", Some("The engine crashed")))
+            .unwrap();
+
+        let all = history.search("", Outcome::All).unwrap();
+        assert_eq!(
+            inputs(&all),
+            [
+                Path::new("/audio/podcast.mp3"),
+                Path::new("/audio/interview.wav")
+            ]
+        );
+        assert_eq!(all[0].error.as_deref(), Some("The engine crashed"));
+        assert_eq!(all[1].audio_duration, Some(Duration::from_secs(60)));
+
+        let succeeded = history.search("", Outcome::Succeeded).unwrap();
+        assert_eq!(inputs(&succeeded), [Path::new("/audio/interview.wav")]);
+        let failed = history.search("", Outcome::Failed).unwrap();
+        assert_eq!(inputs(&failed), [Path::new("/audio/podcast.mp3")]);
+    }
+
+    #[test]
+    fn search_matches_paths_literally() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::open_at(&dir.path().join("history.sqlite3")).unwrap();
+        history.record(&run("/audio/100%_take.wav", None)).unwrap();
+        history.record(&run("/audio/100_take.wav", None)).unwrap();
+
+        let found = history.search("100%", Outcome::All).unwrap();
+        assert_eq!(inputs(&found), [Path::new("/audio/100%_take.wav")]);
+        // Outputs are searched too.
+        let found = history.search("100_take.out", Outcome::All).unwrap();
+        assert_eq!(inputs(&found), [Path::new("/audio/100_take.wav")]);
+    }
+
+    #[test]
+    fn versions_are_the_outputs_of_one_input() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::open_at(&dir.path().join("history.sqlite3")).unwrap();
+        let mut second = run("/audio/take.wav", None);
+        second.output_path = Some(PathBuf::from("/audio/take_v2.out"));
+        history.record(&run("/audio/take.wav", None)).unwrap();
+        history.record(&second).unwrap();
+        // Written again over the first output.
+        history.record(&run("/audio/take.wav", None)).unwrap();
+        history
+            .record(&run("/audio/take.wav", Some("The engine crashed")))
+            .unwrap();
+        history.record(&run("/audio/other.wav", None)).unwrap();
+
+        let versions = history.versions(Path::new("/audio/take.wav")).unwrap();
+        let outputs: Vec<_> = versions
+            .iter()
+            .filter_map(|entry| entry.output_path.as_deref())
+            .collect();
+        assert_eq!(
+            outputs,
+            [
+                Path::new("/audio/take.out"),
+                Path::new("/audio/take_v2.out")
+            ]
+        );
+        assert_eq!(versions[0].id, 3);
+    }
+
+    #[test]
+    fn runs_stay_unfinished_until_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::open_at(&dir.path().join("history.sqlite3")).unwrap();
+        let done = run("/audio/interview.wav", None);
+        let crashed = run("/audio/podcast.mp3", None);
+        for run in [&done, &crashed] {
+            history
+                .begin(
+                    &run.input_path,
+                    run.output_path.as_deref().unwrap(),
+                    &run.options,
+                    run.started,
+                )
+                .unwrap();
+        }
+        history.record(&done).unwrap();
+
+        let unfinished = history.unfinished().unwrap();
+        assert_eq!(unfinished.len(), 1);
+        assert_eq!(unfinished[0].input_path, crashed.input_path);
+        assert_eq!(unfinished[0].output_path, Path::new("/audio/podcast.out"));
+        assert_eq!(unfinished[0].started, crashed.started);
+        history.forget(unfinished[0].id).unwrap();
+        assert!(history.unfinished().unwrap().is_empty());
+    }
+
+    #[test]
+    fn options_are_kept_without_the_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::open_at(&dir.path().join("history.sqlite3")).unwrap();
+        let mut recorded = run("/audio/interview.wav", None);
+        recorded.options.noise_reduction = 55.0;
+        recorded.options.server.url = "https://denoise.example.com".to_string();
+        recorded.options.server.token = "secret".to_string();
+        history.record(&recorded).unwrap();
+
+        let id = history.search("", Outcome::All).unwrap()[0].id;
+        let options = history.options(id).unwrap();
+        assert_eq!(options.noise_reduction, 55.0);
+        assert_eq!(options.server.url, "https://denoise.example.com");
+        assert_eq!(options.server.token, "");
+    }
+}
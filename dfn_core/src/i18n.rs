@@ -0,0 +1,266 @@
+//! Translations of the interface. Each language is a Fluent file in
+//! `locales/`, compiled into the binary, and strings are looked up with
+//! [`tr!`].
+//!
+//! Only the part of Fluent the app uses is understood: `key = text`
+//! messages, indented continuation lines, `#` comments, `{ $variable }`
+//! placeables and quoted literals like `{ "{" }`. Selectors and terms are
+//! not. Strings missing from a translation fall back to English.
+//!
+//! Adding a language takes a copy of `locales/en.ftl` with the texts
+//! translated and an entry in [`LANGUAGES`]. Error details coming from the
+//! engine and the pipeline stay in English.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub struct Language {
+    /// ISO 639-1 code, matched against the system locale.
+    pub code: &'static str,
+    /// Name of the language in itself, as shown in the picker.
+    pub name: &'static str,
+    source: &'static str,
+}
+
+/// Shipped languages. English comes first and is the fallback.
+pub const LANGUAGES: &[Language] = &[
+    Language {
+        code: "en",
+        name: "English",
+        source: include_str!("../../locales/en.ftl"),
+    },
+    Language {
+        code: "de",
+        name: "Deutsch",
+        source: include_str!("../../locales/de.ftl"),
+    },
+    Language {
+        code: "fr",
+        name: "Français",
+        source: include_str!("../../locales/fr.ftl"),
+    },
+];
+
+/// Index into [`LANGUAGES`] of the language in use.
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+
+static CATALOGS: OnceLock<Vec<HashMap<&'static str, String>>> = OnceLock::new();
+
+/// Language setting: follow the system, or a fixed language.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Choice {
+    #[default]
+    System,
+    Language(&'static str),
+}
+
+impl Choice {
+    /// All choices, as offered in the settings.
+    pub fn all() -> Vec<Choice> {
+        std::iter::once(Choice::System)
+            .chain(
+                LANGUAGES
+                    .iter()
+                    .map(|language| Choice::Language(language.code)),
+            )
+            .collect()
+    }
+
+    /// Name the choice is saved under.
+    pub fn key(self) -> &'static str {
+        match self {
+            Choice::System => "system",
+            Choice::Language(code) => code,
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Choice> {
+        Choice::all().into_iter().find(|choice| choice.key() == key)
+    }
+}
+
+impl fmt::Display for Choice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Choice::System => write!(f, "{}", tr!("language-system")),
+            Choice::Language(code) => {
+                let name = LANGUAGES
+                    .iter()
+                    .find(|language| language.code == *code)
+                    .map_or(*code, |language| language.name);
+                write!(f, "{}", name)
+            }
+        }
+    }
+}
+
+/// Switches the interface to `choice`. Takes effect on the next redraw.
+pub fn apply(choice: Choice) {
+    let code = match choice {
+        Choice::System => system_language(),
+        Choice::Language(code) => code,
+    };
+    let index = LANGUAGES
+        .iter()
+        .position(|language| language.code == code)
+        .unwrap_or(0);
+    CURRENT.store(index, Ordering::Relaxed);
+}
+
+/// The shipped language matching the system locale, or English.
+fn system_language() -> &'static str {
+    let Some(locale) = sys_locale::get_locale() else {
+        return "en";
+    };
+    let code = locale
+        .split(['-', '_', '.'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    LANGUAGES
+        .iter()
+        .find(|language| language.code == code)
+        .map_or("en", |language| language.code)
+}
+
+/// Looks up `key` in the current language and fills in `args`. Use [`tr!`]
+/// instead of calling this directly.
+pub fn translate(key: &str, args: &[(&str, String)]) -> String {
+    let catalogs = CATALOGS.get_or_init(|| {
+        LANGUAGES
+            .iter()
+            .map(|language| parse(language.source))
+            .collect()
+    });
+    let Some(message) = catalogs[CURRENT.load(Ordering::Relaxed)]
+        .get(key)
+        .or_else(|| catalogs[0].get(key))
+    else {
+        return key.to_string();
+    };
+
+    let mut translated = String::new();
+    let mut rest = message.as_str();
+    while let Some(start) = rest.find('{') {
+        translated.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        let placeable = rest[start + 1..start + end].trim();
+        if let Some(name) = placeable.strip_prefix('$') {
+            match args.iter().find(|(arg, _)| *arg == name) {
+                Some((_, value)) => translated.push_str(value),
+                None => translated.push_str(&rest[start..start + end + 1]),
+            }
+        } else if let Some(literal) = placeable
+            .strip_prefix('"')
+            .and_then(|placeable| placeable.strip_suffix('"'))
+        {
+            translated.push_str(literal);
+        }
+        rest = &rest[start + end + 1..];
+    }
+    translated.push_str(rest);
+    translated
+}
+
+/// Reads the messages of a Fluent file.
+fn parse(source: &'static str) -> HashMap<&'static str, String> {
+    let mut messages: HashMap<&'static str, String> = HashMap::new();
+    let mut current: Option<&'static str> = None;
+    for line in source.lines() {
+        if line.starts_with([' ', '\t']) && !line.trim().is_empty() {
+            if let Some(message) = current.and_then(|key| messages.get_mut(key)) {
+                if !message.is_empty() {
+                    message.push('\n');
+                }
+                message.push_str(line.trim());
+            }
+            continue;
+        }
+        current = None;
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            let key = key.trim();
+            messages.insert(key, value.trim().to_string());
+            current = Some(key);
+        }
+    }
+    messages
+}
+
+/// Translates a message, e.g. `tr!("status-saved-to", path = path.display())`.
+/// Arguments are formatted with `Display`.
+#[macro_export]
+macro_rules! tr {
+    ($key:literal $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::i18n::translate($key, &[$((stringify!($name), $value.to_string())),*])
+    };
+}
+pub use crate::tr;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    /// Variables a message refers to.
+    fn variables(message: &str) -> BTreeSet<&str> {
+        message
+            .split('{')
+            .skip(1)
+            .filter_map(|placeable| placeable.split('}').next())
+            .filter_map(|placeable| placeable.trim().strip_prefix('$'))
+            .collect()
+    }
+
+    #[test]
+    fn translations_match_english() {
+        let english = parse(LANGUAGES[0].source);
+        for language in &LANGUAGES[1..] {
+            let messages = parse(language.source);
+            let mut keys: Vec<_> = messages.keys().collect();
+            let mut english_keys: Vec<_> = english.keys().collect();
+            keys.sort();
+            english_keys.sort();
+            assert_eq!(keys, english_keys, "messages of {}", language.code);
+
+            for (key, message) in &messages {
+                assert_eq!(
+                    variables(message),
+                    variables(&english[key]),
+                    "variables of {} in {}",
+                    key,
+                    language.code
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn parse_joins_continuation_lines_and_skips_comments() {
+        let messages =
+            parse("# A comment\nfirst = One\n    and more\n\n## Section\nsecond = Two\n");
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages["first"], "One\nand more");
+        assert_eq!(messages["second"], "Two");
+    }
+
+    #[test]
+    fn translate_fills_in_arguments() {
+        assert_eq!(
+            tr!("toast-report-saved", path = "/tmp/report.zip"),
+            "Report saved to /tmp/report.zip"
+        );
+        // Missing arguments stay visible rather than vanishing.
+        assert_eq!(
+            translate("toast-report-saved", &[]),
+            "Report saved to { $path }"
+        );
+        assert_eq!(translate("no-such-message", &[]), "no-such-message");
+    }
+}
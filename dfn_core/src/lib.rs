@@ -0,0 +1,132 @@
+//! Everything behind the window: finding and downloading the engine, reading
+//! and writing audio, the processing pipeline, settings as TOML and the
+//! history of runs. Nothing here depends on the GUI toolkit, so it can be
+//! driven and tested on its own.
+//!
+//! The outside world is reached through seams tests can replace: the
+//! [`denoiser::Denoiser`] trait for the engine process, the
+//! [`download::Network`] trait for downloads, and folders passed in rather
+//! than looked up. Folders that are looked up come from [`data_dir`],
+//! [`config_dir`] and [`cache_dir`], which follow [`set_portable`]. In a
+//! Flatpak sandbox they are the app's own folders under `~/.var/app`.
+
+pub mod accel;
+pub mod archive;
+pub mod audio;
+pub mod benchmark;
+pub mod cancel;
+pub mod config;
+pub mod content;
+pub mod dedup;
+pub mod denoiser;
+pub mod download;
+pub mod engine;
+pub mod events;
+pub mod formats;
+pub mod history;
+pub mod i18n;
+pub mod metadata;
+pub mod metrics;
+pub mod partial;
+pub mod pipeline;
+pub mod plugin;
+pub mod provenance;
+pub mod queue;
+pub mod remote;
+pub mod schedule;
+pub mod speed;
+pub mod temp;
+pub mod transcode;
+pub mod update;
+pub mod webhook;
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// File next to the executable that makes the app portable.
+pub const PORTABLE_FLAG: &str = "portable.flag";
+
+/// Folder everything is kept in when portable, `None` otherwise.
+static PORTABLE: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// Keeps the engine, settings, history and logs in a `data` folder next to
+/// the executable instead of the user's folders, when `requested` or when
+/// a [`PORTABLE_FLAG`] file sits there. Only takes effect before the first
+/// folder is looked up, and never in a sandbox, whose executable folder is
+/// read-only.
+pub fn set_portable(requested: bool) {
+    let _ = PORTABLE.set(detect_portable(requested));
+}
+
+fn detect_portable(requested: bool) -> Option<PathBuf> {
+    if sandboxed() {
+        return None;
+    }
+    let exe = std::env::current_exe().ok()?;
+    portable_dir_in(exe.parent()?, requested)
+}
+
+fn portable_dir_in(exe_dir: &Path, requested: bool) -> Option<PathBuf> {
+    (requested || exe_dir.join(PORTABLE_FLAG).exists()).then(|| exe_dir.join("data"))
+}
+
+/// The folder everything is kept in, if the app runs portable.
+pub fn portable_dir() -> Option<&'static Path> {
+    PORTABLE.get_or_init(|| detect_portable(false)).as_deref()
+}
+
+/// Whether the app runs in a Flatpak sandbox. There it only sees its own
+/// folders, and reaches the desktop through portals.
+pub fn sandboxed() -> bool {
+    static SANDBOXED: OnceLock<bool> = OnceLock::new();
+    *SANDBOXED.get_or_init(|| cfg!(target_os = "linux") && Path::new("/.flatpak-info").exists())
+}
+
+fn project_dirs() -> Option<directories::ProjectDirs> {
+    directories::ProjectDirs::from("com", "deepfilternet", "deepfilternet-gui")
+}
+
+/// Folder the app keeps its engine, history and other data in.
+pub fn data_dir() -> Option<PathBuf> {
+    match portable_dir() {
+        Some(dir) => Some(dir.to_path_buf()),
+        None => project_dirs().map(|dirs| dirs.data_local_dir().to_path_buf()),
+    }
+}
+
+/// Folder the settings are kept in.
+pub fn config_dir() -> Option<PathBuf> {
+    match portable_dir() {
+        Some(dir) => Some(dir.to_path_buf()),
+        None => project_dirs().map(|dirs| dirs.config_dir().to_path_buf()),
+    }
+}
+
+/// Folder for files that can be made again, like scratch files of runs.
+pub fn cache_dir() -> Option<PathBuf> {
+    match portable_dir() {
+        Some(dir) => Some(dir.join("cache")),
+        None => project_dirs().map(|dirs| dirs.cache_dir().to_path_buf()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flag_file_makes_the_app_portable() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(portable_dir_in(dir.path(), false), None);
+        assert_eq!(
+            portable_dir_in(dir.path(), true),
+            Some(dir.path().join("data"))
+        );
+
+        std::fs::write(dir.path().join(PORTABLE_FLAG), "").unwrap();
+        assert_eq!(
+            portable_dir_in(dir.path(), false),
+            Some(dir.path().join("data"))
+        );
+    }
+}
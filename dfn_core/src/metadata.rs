@@ -0,0 +1,249 @@
+//! Carrying tags over from the input to the cleaned output.
+//!
+//! Outputs encoded with ffmpeg get their tags from ffmpeg itself (see
+//! [`crate::transcode::encode`]); this module covers WAV outputs, which are
+//! written directly and hold tags as extra RIFF chunks.
+
+use crate::audio;
+use lofty::config::WriteOptions;
+use lofty::file::TaggedFileExt;
+use lofty::tag::{TagExt, TagType};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Copies the tags of `input`, including cover art, into the WAV at
+/// `output` as an ID3 tag. The ID3 tag of an MP3 or WAV input is copied
+/// whole, chapters included; other tags, such as FLAC and OGG comments, M4A
+/// tags or the INFO chunk of a WAV, are carried over field by field, without
+/// chapters. Inputs without tags, and those whose tags can't be read, are
+/// left alone.
+pub fn copy_to_wav(input: &Path, output: &Path) -> Result<(), String> {
+    if matches!(audio::extension(input).as_deref(), Some("wav" | "mp3"))
+        && let Some(tag) = id3::no_tag_ok(id3::Tag::read_from_path(input))
+            .map_err(|e| format!("Could not read the input's tags: {}", e))?
+    {
+        return tag
+            .write_to_path(output, id3::Version::Id3v24)
+            .map_err(|e| format!("Could not write tags to the output: {}", e));
+    }
+
+    let file = match lofty::read_from_path(input) {
+        Ok(file) => file,
+        Err(e) => {
+            tracing::info!(
+                "Not carrying tags over from {}, they can't be read: {}",
+                input.display(),
+                e
+            );
+            return Ok(());
+        }
+    };
+    let Some(tag) = file.primary_tag().or_else(|| file.first_tag()) else {
+        return Ok(());
+    };
+    let mut tag = tag.clone();
+    tag.re_map(TagType::Id3v2);
+    if tag.is_empty() {
+        return Ok(());
+    }
+    tag.save_to_path(output, WriteOptions::default())
+        .map_err(|e| format!("Could not write tags to the output: {}", e))
+}
+
+/// RIFF chunks carried over by [`copy_broadcast_chunks`].
+const BROADCAST_CHUNKS: [&[u8; 4]; 2] = [b"bext", b"iXML"];
+
+/// Offset of the 64-bit `TimeReference` field in a `bext` chunk.
+const BEXT_TIME_REFERENCE: usize = 338;
+
+/// Copies the Broadcast Wave `bext` and `iXML` chunks of a WAV input into the
+/// WAV at `output`, so timecode and originator info survive cleaning. The
+/// timecode, which counts samples, is rescaled when `output_rate` differs
+/// from the input's sample rate.
+pub fn copy_broadcast_chunks(input: &Path, output: &Path, output_rate: u32) -> Result<(), String> {
+    if !audio::is_wav(input) {
+        return Ok(());
+    }
+    let to_read_error = |e: std::io::Error| format!("Could not read the input's chunks: {}", e);
+    let mut file = File::open(input).map_err(to_read_error)?;
+
+    let mut header = [0; 12];
+    file.read_exact(&mut header).map_err(to_read_error)?;
+    if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+        return Ok(());
+    }
+
+    let mut input_rate = None;
+    let mut chunks = Vec::new();
+    loop {
+        let mut chunk_header = [0; 8];
+        match file.read_exact(&mut chunk_header) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(to_read_error(e)),
+        }
+        let id: [u8; 4] = chunk_header[0..4].try_into().unwrap();
+        let size = u32::from_le_bytes(chunk_header[4..8].try_into().unwrap());
+        let padded_size = size as u64 + (size as u64 & 1);
+
+        if &id == b"fmt " || BROADCAST_CHUNKS.contains(&&id) {
+            let mut data = vec![0; size as usize];
+            file.read_exact(&mut data).map_err(to_read_error)?;
+            file.seek(SeekFrom::Current((padded_size - size as u64) as i64))
+                .map_err(to_read_error)?;
+            if &id == b"fmt " {
+                input_rate = data
+                    .get(4..8)
+                    .map(|rate| u32::from_le_bytes(rate.try_into().unwrap()));
+            } else {
+                chunks.push((id, data));
+            }
+        } else {
+            file.seek(SeekFrom::Current(padded_size as i64))
+                .map_err(to_read_error)?;
+        }
+    }
+
+    if chunks.is_empty() {
+        return Ok(());
+    }
+    for (id, data) in &mut chunks {
+        if id == b"bext"
+            && let (Some(rate), Some(field)) = (
+                input_rate.filter(|&rate| rate > 0 && rate != output_rate),
+                data.get_mut(BEXT_TIME_REFERENCE..BEXT_TIME_REFERENCE + 8),
+            )
+        {
+            let samples = u64::from_le_bytes((&*field).try_into().unwrap());
+            let rescaled = (samples as u128 * output_rate as u128 / rate as u128) as u64;
+            field.copy_from_slice(&rescaled.to_le_bytes());
+        }
+    }
+
+    let to_write_error = |e: std::io::Error| format!("Could not write chunks to the output: {}", e);
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(output)
+        .map_err(to_write_error)?;
+    let mut end = file.seek(SeekFrom::End(0)).map_err(to_write_error)?;
+    if end % 2 == 1 {
+        file.write_all(&[0]).map_err(to_write_error)?;
+        end += 1;
+    }
+    for (id, data) in &chunks {
+        file.write_all(id).map_err(to_write_error)?;
+        file.write_all(&(data.len() as u32).to_le_bytes())
+            .map_err(to_write_error)?;
+        file.write_all(data).map_err(to_write_error)?;
+        if data.len() % 2 == 1 {
+            file.write_all(&[0]).map_err(to_write_error)?;
+        }
+        end += 8 + data.len() as u64 + (data.len() as u64 & 1);
+    }
+    file.seek(SeekFrom::Start(4)).map_err(to_write_error)?;
+    file.write_all(&((end - 8) as u32).to_le_bytes())
+        .map_err(to_write_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use id3::TagLike;
+    use lofty::picture::{MimeType, Picture, PictureType};
+    use lofty::tag::{Accessor, Tag};
+
+    fn wav(path: &Path) {
+        audio::write_wav(
+            path,
+            audio::ENGINE_SPEC,
+            &audio::AudioBuffer {
+                sample_rate: audio::ENGINE_SAMPLE_RATE,
+                channels: vec![vec![0.0; 480]],
+            },
+        )
+        .unwrap();
+    }
+
+    /// A FLAC file with a stream info block and no audio.
+    fn flac(path: &Path) {
+        let mut bytes = b"fLaC".to_vec();
+        // The last metadata block, stream info, 34 bytes long.
+        bytes.extend([0x80, 0, 0, 34]);
+        bytes.extend(4096u16.to_be_bytes());
+        bytes.extend(4096u16.to_be_bytes());
+        bytes.extend([0; 6]);
+        // 48 kHz, one channel, 16 bits, no samples.
+        bytes.extend(((48_000u64 << 44) | (15 << 36)).to_be_bytes());
+        bytes.extend([0; 16]);
+        std::fs::write(path, bytes).unwrap();
+    }
+
+    fn tag(tag_type: TagType) -> Tag {
+        let mut tag = Tag::new(tag_type);
+        tag.set_title(String::from("Episode 12"));
+        tag.set_artist(String::from("The Show"));
+        tag
+    }
+
+    #[test]
+    fn flac_comments_and_cover_reach_a_wav_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("episode.flac");
+        let output = dir.path().join("out.wav");
+        flac(&input);
+        wav(&output);
+        let mut comments = tag(TagType::VorbisComments);
+        comments.push_picture(
+            Picture::unchecked(vec![0x89, b'P', b'N', b'G'])
+                .pic_type(PictureType::CoverFront)
+                .mime_type(MimeType::Png)
+                .build(),
+        );
+        comments
+            .save_to_path(&input, WriteOptions::default())
+            .unwrap();
+
+        copy_to_wav(&input, &output).unwrap();
+
+        let copied = id3::Tag::read_from_path(&output).unwrap();
+        assert_eq!(copied.title(), Some("Episode 12"));
+        assert_eq!(copied.artist(), Some("The Show"));
+        assert_eq!(copied.pictures().count(), 1);
+        assert_eq!(std::fs::metadata(&output).unwrap().len() % 2, 0);
+    }
+
+    #[test]
+    fn wav_info_chunk_reaches_the_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("take.wav");
+        let output = dir.path().join("out.wav");
+        wav(&input);
+        wav(&output);
+        tag(TagType::RiffInfo)
+            .save_to_path(&input, WriteOptions::default())
+            .unwrap();
+
+        copy_to_wav(&input, &output).unwrap();
+
+        assert_eq!(
+            id3::Tag::read_from_path(&output).unwrap().title(),
+            Some("Episode 12")
+        );
+    }
+
+    #[test]
+    fn unreadable_tags_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("take.ogg");
+        let output = dir.path().join("out.wav");
+        std::fs::write(&input, b"not an ogg file").unwrap();
+        wav(&output);
+        let before = std::fs::read(&output).unwrap();
+
+        copy_to_wav(&input, &output).unwrap();
+
+        assert_eq!(std::fs::read(&output).unwrap(), before);
+    }
+}
@@ -0,0 +1,237 @@
+//! Level measurements for judging how much noise a run removed, and where.
+
+use crate::audio::AudioBuffer;
+use std::path::Path;
+
+/// Length of the blocks levels are measured over.
+const BLOCK_SECONDS: f32 = 0.05;
+
+/// Share of the quietest blocks taken as the noise floor.
+const NOISE_PERCENTILE: f32 = 0.1;
+
+/// Share of the quietest blocks below the level taken as the signal.
+const SIGNAL_PERCENTILE: f32 = 0.95;
+
+/// Reported for digital silence instead of negative infinity.
+const SILENCE_DB: f32 = -120.0;
+
+/// Output SNR, in dB, that earns the best quality score.
+const CLEAN_SNR_DB: f32 = 40.0;
+
+/// How far the louder passages may drop, in dB, before the score is lowered
+/// for speech the denoiser ate into.
+const SIGNAL_LOSS_ALLOWANCE_DB: f32 = 3.0;
+
+/// Further drop of the louder passages, in dB, that costs one point.
+const SIGNAL_LOSS_DB_PER_POINT: f32 = 3.0;
+
+/// Scores below this are flagged as likely failed.
+pub const POOR_QUALITY: f32 = 2.5;
+
+/// Length of the blocks attenuation is judged over.
+const REGION_SECONDS: f32 = 0.25;
+
+/// Blocks quieter than this before denoising are ignored, as there was
+/// nothing to attenuate.
+const REGION_FLOOR_DB: f32 = -60.0;
+
+/// Levels of one clip, in dBFS.
+#[derive(Debug, Clone, Copy)]
+pub struct Levels {
+    pub peak_db: f32,
+    pub rms_db: f32,
+    /// Level of the quieter passages, where mostly noise remains.
+    pub noise_floor_db: f32,
+    /// Level of the louder passages, where the wanted signal dominates.
+    pub signal_db: f32,
+}
+
+impl Levels {
+    pub fn measure(buffer: &AudioBuffer) -> Self {
+        let mut peak = 0.0f32;
+        let mut sum_squares = 0.0f64;
+        for channel in &buffer.channels {
+            for &sample in channel {
+                peak = peak.max(sample.abs());
+                sum_squares += (sample * sample) as f64;
+            }
+        }
+        let samples = (buffer.frames() * buffer.channels.len()).max(1);
+
+        let block = ((buffer.sample_rate as f32 * BLOCK_SECONDS) as usize).max(1);
+        let mut blocks: Vec<f32> = (0..buffer.frames())
+            .step_by(block)
+            .map(|start| {
+                let end = (start + block).min(buffer.frames());
+                let (sum, count) = buffer
+                    .channels
+                    .iter()
+                    .flat_map(|channel| &channel[start..end])
+                    .fold((0.0f32, 0usize), |(sum, count), sample| {
+                        (sum + sample * sample, count + 1)
+                    });
+                to_db((sum / count.max(1) as f32).sqrt())
+            })
+            .collect();
+        blocks.sort_by(f32::total_cmp);
+        let percentile = |share: f32| {
+            let index = ((blocks.len() as f32 - 1.0) * share).round() as usize;
+            blocks.get(index).copied().unwrap_or(SILENCE_DB)
+        };
+
+        Self {
+            peak_db: to_db(peak),
+            rms_db: to_db((sum_squares / samples as f64).sqrt() as f32),
+            noise_floor_db: percentile(NOISE_PERCENTILE),
+            signal_db: percentile(SIGNAL_PERCENTILE),
+        }
+    }
+
+    /// Estimated signal-to-noise ratio in dB.
+    pub fn snr_db(&self) -> f32 {
+        self.signal_db - self.noise_floor_db
+    }
+}
+
+/// Levels before and after denoising.
+#[derive(Debug, Clone, Copy)]
+pub struct Comparison {
+    pub before: Levels,
+    pub after: Levels,
+}
+
+impl Comparison {
+    pub fn snr_improvement_db(&self) -> f32 {
+        self.after.snr_db() - self.before.snr_db()
+    }
+
+    /// Rough quality of the output from 1 (bad) to 5 (clean), in the spirit
+    /// of a mean opinion score. It goes by the noise left in the output and
+    /// is lowered when the louder passages, mostly speech, lost level.
+    pub fn quality_score(&self) -> f32 {
+        let snr = self.after.snr_db().clamp(0.0, CLEAN_SNR_DB);
+        let loss = self.before.signal_db - self.after.signal_db - SIGNAL_LOSS_ALLOWANCE_DB;
+        let penalty = loss.max(0.0) / SIGNAL_LOSS_DB_PER_POINT;
+        (1.0 + 4.0 * snr / CLEAN_SNR_DB - penalty).clamp(1.0, 5.0)
+    }
+
+    /// Whether denoising likely failed or hurt intelligibility.
+    pub fn is_poor(&self) -> bool {
+        self.quality_score() < POOR_QUALITY
+    }
+}
+
+fn to_db(amplitude: f32) -> f32 {
+    if amplitude > 0.0 {
+        (20.0 * amplitude.log10()).max(SILENCE_DB)
+    } else {
+        SILENCE_DB
+    }
+}
+
+/// A stretch of audio the denoiser turned down heavily.
+#[derive(Debug, Clone, Copy)]
+pub struct Region {
+    pub start: f32,
+    pub end: f32,
+    /// Largest drop in level within the region, in dB.
+    pub attenuation_db: f32,
+}
+
+/// Finds the stretches where `after` is at least `threshold_db` quieter than
+/// `before`. Both buffers must have the same sample rate.
+pub fn attenuated_regions(
+    before: &AudioBuffer,
+    after: &AudioBuffer,
+    threshold_db: f32,
+) -> Vec<Region> {
+    let block = ((before.sample_rate as f32 * REGION_SECONDS) as usize).max(1);
+    let frames = before.frames().min(after.frames());
+    let rms_db = |buffer: &AudioBuffer, start: usize, end: usize| {
+        let (sum, count) = buffer
+            .channels
+            .iter()
+            .flat_map(|channel| &channel[start..end])
+            .fold((0.0f32, 0usize), |(sum, count), sample| {
+                (sum + sample * sample, count + 1)
+            });
+        to_db((sum / count.max(1) as f32).sqrt())
+    };
+
+    let mut regions: Vec<Region> = Vec::new();
+    for start in (0..frames).step_by(block) {
+        let end = (start + block).min(frames);
+        let level = rms_db(before, start, end);
+        let attenuation = level - rms_db(after, start, end);
+        if level < REGION_FLOOR_DB || attenuation < threshold_db {
+            continue;
+        }
+
+        let start_seconds = start as f32 / before.sample_rate as f32;
+        let end_seconds = end as f32 / before.sample_rate as f32;
+        match regions.last_mut() {
+            Some(last) if last.end >= start_seconds => {
+                last.end = end_seconds;
+                last.attenuation_db = last.attenuation_db.max(attenuation);
+            }
+            _ => regions.push(Region {
+                start: start_seconds,
+                end: end_seconds,
+                attenuation_db: attenuation,
+            }),
+        }
+    }
+    regions
+}
+
+/// Writes `regions` as an Audacity label track.
+pub fn write_labels(path: &Path, regions: &[Region]) -> Result<(), String> {
+    let labels: String = regions
+        .iter()
+        .map(|region| {
+            format!(
+                "{:.6}\t{:.6}\t-{:.0} dB\n",
+                region.start, region.end, region.attenuation_db
+            )
+        })
+        .collect();
+    std::fs::write(path, labels).map_err(|e| format!("Could not write label track: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn levels(noise_floor_db: f32, signal_db: f32) -> Levels {
+        Levels {
+            peak_db: signal_db + 6.0,
+            rms_db: signal_db - 6.0,
+            noise_floor_db,
+            signal_db,
+        }
+    }
+
+    #[test]
+    fn quality_follows_the_noise_left_and_the_speech_lost() {
+        let clean = Comparison {
+            before: levels(-40.0, -15.0),
+            after: levels(-70.0, -16.0),
+        };
+        assert_eq!(clean.quality_score(), 5.0);
+        assert!(!clean.is_poor());
+
+        let noisy = Comparison {
+            before: levels(-30.0, -15.0),
+            after: levels(-35.0, -15.0),
+        };
+        assert_eq!(noisy.quality_score(), 3.0);
+
+        // Quiet now, but mostly because the voice went with the noise.
+        let eaten = Comparison {
+            before: levels(-40.0, -15.0),
+            after: levels(-70.0, -30.0),
+        };
+        assert_eq!(eaten.quality_score(), 1.0);
+        assert!(eaten.is_poor());
+    }
+}
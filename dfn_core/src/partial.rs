@@ -0,0 +1,164 @@
+//! Outputs left half-written by runs that never finished, as a crash or a
+//! kill leaves them. Runs are noted in the history as they start and
+//! crossed off as they're recorded, so the ones still noted at the next
+//! start were cut short. Their scratch folders are removed on their own
+//! (see [`crate::temp`]); their outputs are only found here, for the user
+//! to delete or clean again.
+//!
+//! Only outputs that are obviously broken are reported: empty files, and
+//! WAV files whose header doesn't match their length, which is how a WAV
+//! file looks when writing it stopped halfway.
+
+use crate::history::{History, Unfinished};
+use std::io::Read;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// The unfinished runs that left a partial output behind. The others are
+/// forgotten, as there's nothing left of them to clean up. Runs of sessions
+/// still open, such as another instance cleaning a file right now, are left
+/// alone: their outputs are still being written.
+pub fn find(history: &History) -> Result<Vec<Unfinished>, String> {
+    let mut found = Vec::new();
+    for run in history.unfinished()? {
+        if history.is_running(&run) {
+            continue;
+        }
+        if is_partial(&run.output_path, run.started) {
+            found.push(run);
+        } else {
+            history.forget(run.id)?;
+        }
+    }
+    Ok(found)
+}
+
+/// Whether `path` was written since `since` and is obviously incomplete.
+pub fn is_partial(path: &Path, since: SystemTime) -> bool {
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return false;
+    };
+    // An output from an earlier run that this one never got to.
+    if metadata.modified().is_ok_and(|modified| modified < since) {
+        return false;
+    }
+    if metadata.len() == 0 {
+        return true;
+    }
+    let is_wav = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("wav"));
+    is_wav && riff_size(path).is_none_or(|size| size + 8 != metadata.len())
+}
+
+/// The size a RIFF file's header gives for it, less the 8 bytes before.
+fn riff_size(path: &Path) -> Option<u64> {
+    let mut header = [0; 12];
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .ok()?;
+    (&header[0..4] == b"RIFF" && &header[8..12] == b"WAVE")
+        .then(|| u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::{self, AudioBuffer};
+    use crate::pipeline::ProcessingOptions;
+    use std::time::Duration;
+
+    #[test]
+    fn empty_and_cut_off_outputs_are_partial() {
+        let dir = tempfile::tempdir().unwrap();
+        let since = SystemTime::now() - Duration::from_secs(60);
+        let complete = dir.path().join("complete.wav");
+        let buffer = AudioBuffer {
+            sample_rate: 48_000,
+            channels: vec![vec![0.0; 480]],
+        };
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 48_000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        audio::write_wav(&complete, spec, &buffer).unwrap();
+        let cut_off = dir.path().join("cut_off.wav");
+        let data = std::fs::read(&complete).unwrap();
+        std::fs::write(&cut_off, &data[..data.len() / 2]).unwrap();
+        let empty = dir.path().join("empty.mp3");
+        std::fs::write(&empty, b"").unwrap();
+        let mp3 = dir.path().join("encoded.mp3");
+        std::fs::write(&mp3, b"ID3").unwrap();
+
+        assert!(!is_partial(&complete, since));
+        assert!(is_partial(&cut_off, since));
+        assert!(is_partial(&empty, since));
+        assert!(!is_partial(&mp3, since));
+        assert!(!is_partial(&dir.path().join("missing.wav"), since));
+        // Left by an earlier run.
+        assert!(!is_partial(
+            &empty,
+            SystemTime::now() + Duration::from_secs(60)
+        ));
+    }
+
+    #[test]
+    fn only_runs_with_partial_outputs_are_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::open_at(&dir.path().join("history.sqlite3")).unwrap();
+        let since = SystemTime::now() - Duration::from_secs(60);
+        let options = ProcessingOptions::default();
+        let empty = dir.path().join("a.wav");
+        std::fs::write(&empty, b"").unwrap();
+        history
+            .begin(Path::new("/audio/a.wav"), &empty, &options, since)
+            .unwrap();
+        history
+            .begin(
+                Path::new("/audio/b.wav"),
+                &dir.path().join("b.wav"),
+                &options,
+                since,
+            )
+            .unwrap();
+
+        // As noted by a session that was killed.
+        drop(history);
+        let history = History::open_at(&dir.path().join("history.sqlite3")).unwrap();
+
+        let found = find(&history).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].output_path, empty);
+        assert_eq!(history.unfinished().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn runs_of_open_sessions_are_left_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.sqlite3");
+        let running = History::open_at(&path).unwrap();
+        let since = SystemTime::now() - Duration::from_secs(60);
+        let options = ProcessingOptions::default();
+        // Being written, so its header isn't final yet.
+        let writing = dir.path().join("a.wav");
+        std::fs::write(&writing, b"").unwrap();
+        running
+            .begin(Path::new("/audio/a.wav"), &writing, &options, since)
+            .unwrap();
+        running
+            .begin(
+                Path::new("/audio/b.wav"),
+                &dir.path().join("b.wav"),
+                &options,
+                since,
+            )
+            .unwrap();
+
+        let other = History::open_at(&path).unwrap();
+        assert!(find(&other).unwrap().is_empty());
+        assert!(find(&running).unwrap().is_empty());
+        assert_eq!(other.unfinished().unwrap().len(), 2);
+    }
+}
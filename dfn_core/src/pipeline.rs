@@ -0,0 +1,1189 @@
+//! File processing as a chain of stages:
+//! Decode → Resample → pre-processing → Denoise → Mix → Normalize → Encode.
+//!
+//! Stages share a [`Job`] and know nothing about the GUI, so each one can be
+//! driven and inspected on its own.
+
+use crate::accel;
+use crate::audio::{self, AudioBuffer};
+use crate::cancel::CancelToken;
+use crate::denoiser::{self, Denoiser};
+use crate::engine;
+use crate::formats;
+use crate::i18n::tr;
+use crate::metadata;
+use crate::metrics;
+use crate::provenance;
+use crate::remote;
+use crate::temp;
+use crate::transcode;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Audio kept around detected sound when trimming, so onsets aren't clipped.
+const TRIM_PADDING_SECONDS: f32 = 0.01;
+
+/// Level drop from denoising above which a stretch is labelled as noisy.
+const HEAVY_ATTENUATION_DB: f32 = 12.0;
+
+/// Format written when the output isn't matched to the source: 16-bit PCM,
+/// like the engine's own output.
+const DEFAULT_OUTPUT_SPEC: hound::WavSpec = hound::WavSpec {
+    channels: 1,
+    sample_rate: audio::ENGINE_SAMPLE_RATE,
+    bits_per_sample: 16,
+    sample_format: hound::SampleFormat::Int,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessingOptions {
+    /// Resample and requantize the engine output back to the input's format.
+    pub preserve_format: bool,
+    /// Which channels of the input are cleaned, and how.
+    pub channels: ChannelRouting,
+    /// Clean each channel on its own, e.g. for an interview with one voice
+    /// per channel, and how to write them.
+    pub split_channels: ChannelSplit,
+    pub remove_dc_offset: bool,
+    pub trim_silence: bool,
+    /// Level in dBFS below which leading/trailing audio counts as silence.
+    pub silence_threshold_db: f32,
+    /// Share of the denoised signal in the output, in percent. Anything below
+    /// 100 blends the original back in.
+    pub noise_reduction: f32,
+    pub normalize: bool,
+    /// Peak level in dBFS the output is scaled to when normalizing.
+    pub normalize_peak_db: f32,
+    /// How many times the input's duration the engine may run before it is
+    /// considered hung.
+    pub timeout_factor: f32,
+    /// Run the engine with reduced CPU priority.
+    pub low_priority: bool,
+    pub output_format: OutputFormat,
+    /// Bitrate for lossy output formats, in kbit/s.
+    pub bitrate_kbps: u32,
+    /// Copy tags, chapters and cover art from the input to the output.
+    pub keep_metadata: bool,
+    /// Copy Broadcast Wave timecode and iXML from WAV inputs to WAV outputs.
+    pub keep_broadcast_chunks: bool,
+    /// Write an Audacity label track of heavily attenuated stretches next to
+    /// the output.
+    pub export_labels: bool,
+    /// Write how the output was made to a `.dfn.json` file next to it, see
+    /// [`provenance`].
+    pub write_provenance: bool,
+    /// Put the cleaned audio of video inputs back into a copy of the video.
+    /// Silence isn't trimmed then, as that would break lip sync.
+    pub remux_video: bool,
+    /// Worker threads the engine may use; 0 means no limit.
+    pub cpu_threads: usize,
+    pub backend: denoiser::Backend,
+    /// Server used by the remote backend.
+    pub server: remote::Server,
+    /// Folder outputs are written to; `None` puts them in a `dnf_clean`
+    /// folder next to each input.
+    pub output_dir: Option<PathBuf>,
+    /// Appended to the input's file name to name the output.
+    pub output_suffix: String,
+    /// Keep earlier outputs of the same input, numbering the new one like
+    /// `take.v2.wav`, instead of replacing them. The versions and the
+    /// options each was made with are listed by [`provenance`].
+    pub version_outputs: bool,
+    /// Skip inputs that are exact copies of one cleaned earlier in the same
+    /// batch (see [`crate::dedup`]).
+    pub skip_duplicates: bool,
+    /// Environment variables the engine is run with, as names and values.
+    /// Entries without a name are left out.
+    pub engine_env: Vec<(String, String)>,
+}
+
+impl Default for ProcessingOptions {
+    fn default() -> Self {
+        Self {
+            preserve_format: false,
+            channels: ChannelRouting::Keep,
+            split_channels: ChannelSplit::Off,
+            remove_dc_offset: false,
+            trim_silence: false,
+            silence_threshold_db: -60.0,
+            noise_reduction: 100.0,
+            normalize: false,
+            normalize_peak_db: -1.0,
+            timeout_factor: 10.0,
+            low_priority: false,
+            output_format: OutputFormat::Wav,
+            bitrate_kbps: 192,
+            keep_metadata: true,
+            keep_broadcast_chunks: true,
+            export_labels: false,
+            write_provenance: false,
+            remux_video: false,
+            cpu_threads: 0,
+            backend: denoiser::Backend::default(),
+            server: remote::Server::default(),
+            output_dir: None,
+            output_suffix: String::new(),
+            version_outputs: false,
+            skip_duplicates: false,
+            engine_env: Vec::new(),
+        }
+    }
+}
+
+impl ProcessingOptions {
+    /// How the engine process should be launched for these options.
+    pub fn engine_options(&self) -> engine::Options {
+        engine::Options {
+            low_priority: self.low_priority,
+            threads: (self.cpu_threads > 0).then_some(self.cpu_threads),
+            env: self
+                .engine_env
+                .iter()
+                .filter(|(name, _)| !name.trim().is_empty())
+                .map(|(name, value)| (name.trim().to_string(), value.clone()))
+                .collect(),
+        }
+    }
+
+    /// The selected backend, using the engine at `bin_path` where it needs one.
+    pub fn denoiser(&self, bin_path: Option<&Path>) -> Result<Arc<dyn Denoiser>, String> {
+        Ok(match self.backend {
+            denoiser::Backend::ExternalBinary => Arc::new(denoiser::ExternalBinary::new(
+                bin_path
+                    .ok_or("Binary missing during processing")?
+                    .to_path_buf(),
+                self.engine_options(),
+            )),
+            denoiser::Backend::InProcess => Arc::new(denoiser::InProcess::installed()?),
+            denoiser::Backend::Remote => Arc::new(denoiser::Remote {
+                server: self.server.clone(),
+            }),
+        })
+    }
+}
+
+/// Steps of a processing run, as reported through progress callbacks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Phase {
+    #[default]
+    Decoding,
+    /// Converting the input to the engine's sample rate.
+    Resampling,
+    Preprocessing,
+    Denoising,
+    Mixing,
+    Measuring,
+    Normalizing,
+    /// Converting the cleaned audio back to the source sample rate.
+    Restoring,
+    Encoding,
+    Remuxing,
+}
+
+impl Phase {
+    /// Name of the phase in machine-readable output.
+    pub fn key(self) -> &'static str {
+        match self {
+            Phase::Decoding => "decoding",
+            Phase::Resampling => "resampling",
+            Phase::Preprocessing => "preprocessing",
+            Phase::Denoising => "denoising",
+            Phase::Mixing => "mixing",
+            Phase::Measuring => "measuring",
+            Phase::Normalizing => "normalizing",
+            Phase::Restoring => "restoring",
+            Phase::Encoding => "encoding",
+            Phase::Remuxing => "remuxing",
+        }
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            Phase::Decoding => tr!("phase-decoding"),
+            Phase::Resampling => tr!("phase-resampling"),
+            Phase::Preprocessing => tr!("phase-preprocessing"),
+            Phase::Denoising => tr!("phase-denoising"),
+            Phase::Mixing => tr!("phase-mixing"),
+            Phase::Measuring => tr!("phase-measuring"),
+            Phase::Normalizing => tr!("phase-normalizing"),
+            Phase::Restoring => tr!("phase-restoring"),
+            Phase::Encoding => tr!("phase-encoding"),
+            Phase::Remuxing => tr!("phase-remuxing"),
+        }
+    }
+}
+
+/// State shared by the stages of one processing run.
+pub struct Job {
+    pub input_path: PathBuf,
+    pub output_path: PathBuf,
+    /// Files written besides the output, one per further channel when
+    /// channels are written to files of their own.
+    pub other_outputs: Vec<PathBuf>,
+    /// Scratch folder owned by this job, removed with it.
+    pub temp: temp::JobDir,
+    /// Format of the input file, filled in by [`Decode`].
+    pub source_spec: hound::WavSpec,
+    /// Length of the input audio, filled in by [`Decode`].
+    pub source_duration: Duration,
+    pub buffer: AudioBuffer,
+    /// The audio as it was right before denoising, kept for [`Mix`].
+    pub original: Option<AudioBuffer>,
+    /// Levels before and after denoising, filled in by [`Measure`].
+    pub levels: Option<metrics::Comparison>,
+    /// Stretches the denoiser turned down heavily, filled in by [`Measure`].
+    pub attenuated: Vec<metrics::Region>,
+    /// Set from outside to stop the run at the next opportunity.
+    pub cancel: CancelToken,
+}
+
+impl Job {
+    pub fn new(input_path: &Path, output_path: PathBuf, temp: temp::JobDir) -> Self {
+        Self {
+            input_path: input_path.to_path_buf(),
+            output_path,
+            other_outputs: Vec::new(),
+            temp,
+            source_spec: DEFAULT_OUTPUT_SPEC,
+            source_duration: Duration::ZERO,
+            buffer: AudioBuffer {
+                sample_rate: audio::ENGINE_SAMPLE_RATE,
+                channels: Vec::new(),
+            },
+            original: None,
+            levels: None,
+            attenuated: Vec::new(),
+            cancel: CancelToken::new(),
+        }
+    }
+}
+
+/// Where the cleaned version of `input_path` is written as WAV.
+pub fn output_path_for(input_path: &Path, options: &ProcessingOptions) -> PathBuf {
+    let output_dir = match &options.output_dir {
+        Some(dir) => dir.clone(),
+        None => input_path
+            .parent()
+            .unwrap_or(Path::new("."))
+            .join("dnf_clean"),
+    };
+    let stem = input_path.file_stem().unwrap_or_default().to_string_lossy();
+    output_dir.join(format!("{}{}.wav", stem, options.output_suffix))
+}
+
+/// The file a run of `input_path` with `options` started now writes first:
+/// its output, or the first channel's when channels are written to files of
+/// their own.
+pub fn planned_output(input_path: &Path, options: &ProcessingOptions) -> PathBuf {
+    let remuxed = options.remux_video
+        && options.split_channels != ChannelSplit::Files
+        && formats::is_video(input_path);
+    let extension = if remuxed {
+        input_path
+            .extension()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned()
+    } else {
+        options.output_format.extension(input_path)
+    };
+    let mut path = output_path_for(input_path, options).with_extension(&extension);
+    if options.split_channels == ChannelSplit::Files {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        path = path.with_file_name(format!("{}.ch1.{}", stem, extension));
+    }
+    if options.version_outputs {
+        path = unused_version(&path);
+    }
+    spare_input(path, input_path)
+}
+
+/// What a run is expected to write, worked out before it starts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputEstimate {
+    /// Extension of the output, which names its format.
+    pub extension: String,
+    /// Approximate size in bytes.
+    pub size: u64,
+}
+
+/// What cleaning `input_path`, described by `info`, with `options` writes;
+/// for the first file when channels are written to files of their own.
+/// Exact for WAV, rough for the compressed formats, and `None` when the
+/// input's length isn't known or the format can't be told.
+pub fn estimate_output(
+    input_path: &Path,
+    info: &audio::FileInfo,
+    options: &ProcessingOptions,
+) -> Option<OutputEstimate> {
+    let seconds = info.duration?.as_secs_f64();
+    let (sample_rate, bits) = if options.preserve_format {
+        (
+            info.sample_rate.unwrap_or(DEFAULT_OUTPUT_SPEC.sample_rate),
+            info.bits_per_sample
+                .unwrap_or(DEFAULT_OUTPUT_SPEC.bits_per_sample as u32),
+        )
+    } else {
+        (
+            DEFAULT_OUTPUT_SPEC.sample_rate,
+            DEFAULT_OUTPUT_SPEC.bits_per_sample as u32,
+        )
+    };
+    let frames = (seconds * sample_rate as f64).round();
+    let channels = match options.channels {
+        ChannelRouting::Keep if options.split_channels == ChannelSplit::Files => 1,
+        ChannelRouting::Keep => info.channels.unwrap_or(1),
+        ChannelRouting::Mono | ChannelRouting::Only(_) => 1,
+    };
+    let pcm = frames * (channels * bits.div_ceil(8) as usize) as f64;
+    let extension = options.output_format.extension(input_path);
+    let size = formats::find(&extension)?.estimate_size(seconds, pcm, options.bitrate_kbps)?;
+    Some(OutputEstimate {
+        extension,
+        size: size as u64,
+    })
+}
+
+/// `path`, or if it's taken the first of `name.v2.ext`, `name.v3.ext`, …
+/// that isn't.
+pub fn unused_version(path: &Path) -> PathBuf {
+    with_version(path, unused_version_of_all(&[path.to_path_buf()]))
+}
+
+/// The first version that's free for every one of `paths`, so files
+/// written together get the same one.
+fn unused_version_of_all(paths: &[PathBuf]) -> u32 {
+    (1..)
+        .find(|&version| {
+            paths
+                .iter()
+                .all(|path| !with_version(path, version).exists())
+        })
+        .unwrap_or(1)
+}
+
+/// `path` as version `version`, the first being `path` itself.
+fn with_version(path: &Path, version: u32) -> PathBuf {
+    if version == 1 {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    path.with_file_name(format!("{}.v{}{}", stem, version, extension))
+}
+
+/// `path`, or the next version of it if it's `input`, which is never
+/// written over, as when the output folder is the input's own.
+pub fn spare_input(path: PathBuf, input: &Path) -> PathBuf {
+    let same = match (std::fs::canonicalize(&path), std::fs::canonicalize(input)) {
+        (Ok(path), Ok(input)) => path == input,
+        _ => false,
+    };
+    if same { unused_version(&path) } else { path }
+}
+
+pub trait Stage: Send {
+    fn phase(&self) -> Phase;
+
+    /// Transforms `job` in place, reporting progress in percent.
+    fn process(&self, job: &mut Job, on_progress: &mut dyn FnMut(f32)) -> Result<(), String>;
+}
+
+#[derive(Default)]
+pub struct Pipeline {
+    stages: Vec<Box<dyn Stage>>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stage(mut self, stage: impl Stage + 'static) -> Self {
+        self.stages.push(Box::new(stage));
+        self
+    }
+
+    /// The standard chain for `options`, cleaning with `denoiser`.
+    pub fn for_options(options: &ProcessingOptions, denoiser: Arc<dyn Denoiser>) -> Self {
+        let mut pipeline = Self::new()
+            .stage(Decode)
+            .stage(Resample(SampleRate::Engine));
+        if options.channels != ChannelRouting::Keep {
+            pipeline = pipeline.stage(RouteChannels(options.channels));
+        }
+        if options.remove_dc_offset {
+            pipeline = pipeline.stage(RemoveDcOffset);
+        }
+        if options.trim_silence && !options.remux_video {
+            pipeline = pipeline.stage(TrimSilence {
+                threshold_db: options.silence_threshold_db,
+            });
+        }
+        pipeline = pipeline.stage(Denoise {
+            denoiser,
+            timeout_factor: options.timeout_factor,
+            split: options.split_channels != ChannelSplit::Off,
+        });
+        if options.noise_reduction < 100.0 {
+            pipeline = pipeline.stage(Mix {
+                wet: options.noise_reduction / 100.0,
+            });
+        }
+        pipeline = pipeline.stage(Measure);
+        if options.normalize {
+            pipeline = pipeline.stage(Normalize {
+                peak_db: options.normalize_peak_db,
+            });
+        }
+        let bit_depth = if options.preserve_format {
+            pipeline = pipeline.stage(Resample(SampleRate::Source));
+            BitDepth::Source
+        } else {
+            BitDepth::Default
+        };
+        // Separate channels don't go back into one video.
+        let remux = options.remux_video && options.split_channels != ChannelSplit::Files;
+        pipeline = pipeline.stage(Encode {
+            bit_depth,
+            format: options.output_format,
+            bitrate_kbps: options.bitrate_kbps,
+            keep_metadata: options.keep_metadata,
+            keep_broadcast_chunks: options.keep_broadcast_chunks,
+            versioned: options.version_outputs,
+            split: options.split_channels == ChannelSplit::Files,
+            remux,
+        });
+        if remux {
+            pipeline = pipeline.stage(Remux {
+                bitrate_kbps: options.bitrate_kbps,
+                versioned: options.version_outputs,
+            });
+        }
+        if options.export_labels {
+            pipeline = pipeline.stage(ExportLabels);
+        }
+        pipeline
+    }
+
+    /// Runs every stage on `job`, reporting the current phase and the overall
+    /// progress in percent. The output of a failed or cancelled run is
+    /// removed.
+    pub fn run(
+        &self,
+        job: &mut Job,
+        on_progress: &mut dyn FnMut(Phase, f32),
+    ) -> Result<(), String> {
+        let started = SystemTime::now();
+        let count = self.stages.len() as f32;
+        let result = self
+            .stages
+            .iter()
+            .enumerate()
+            .try_for_each(|(index, stage)| {
+                job.cancel.check()?;
+                let phase = stage.phase();
+                tracing::debug!("Starting the {:?} stage", phase);
+                on_progress(phase, index as f32 / count * 100.0);
+                stage.process(job, &mut |progress| {
+                    on_progress(phase, (index as f32 + progress / 100.0) / count * 100.0)
+                })
+            });
+
+        if result.is_err() {
+            for path in std::iter::once(&job.output_path).chain(&job.other_outputs) {
+                discard_output(path, started);
+            }
+        }
+        result
+    }
+}
+
+/// Removes the output of a failed run, unless it's a file left by an
+/// earlier run that this one never got to.
+fn discard_output(path: &Path, since: SystemTime) {
+    let written = std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .is_ok_and(|modified| modified >= since);
+    if written {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Outcome of a successful run.
+#[derive(Debug, Clone)]
+pub struct RunReport {
+    pub output_path: PathBuf,
+    /// See [`Job::other_outputs`].
+    pub other_outputs: Vec<PathBuf>,
+    /// Length of the input audio.
+    pub audio_duration: Duration,
+    /// Wall-clock time the whole pipeline took.
+    pub elapsed: Duration,
+    pub levels: Option<metrics::Comparison>,
+    /// Where the engine ran the model, where the backend can tell.
+    pub provider: Option<accel::Provider>,
+}
+
+impl RunReport {
+    /// How many seconds of audio were processed per second of wall-clock time.
+    pub fn realtime_factor(&self) -> f32 {
+        self.audio_duration.as_secs_f32() / self.elapsed.as_secs_f32().max(f32::EPSILON)
+    }
+}
+
+/// Processes `input_path` with the standard pipeline. Cancelling `cancel`
+/// stops the run.
+pub fn run(
+    input_path: &Path,
+    denoiser: Arc<dyn Denoiser>,
+    options: &ProcessingOptions,
+    cancel: CancelToken,
+    on_progress: &mut dyn FnMut(Phase, f32),
+) -> Result<RunReport, String> {
+    let started = Instant::now();
+    let stem = input_path.file_stem().unwrap_or_default().to_string_lossy();
+    let temp = temp::TempStore::open().job(&stem)?;
+    let mut job = Job::new(input_path, output_path_for(input_path, options), temp);
+    job.cancel = cancel;
+    Pipeline::for_options(options, denoiser.clone()).run(&mut job, on_progress)?;
+    let report = RunReport {
+        output_path: job.output_path,
+        other_outputs: job.other_outputs,
+        audio_duration: job.source_duration,
+        elapsed: started.elapsed(),
+        levels: job.levels,
+        provider: denoiser.provider(),
+    };
+    // The output is there either way, so it isn't held against the run.
+    if options.write_provenance
+        && let Err(e) =
+            provenance::write(input_path, &report, options, denoiser.version().as_deref())
+    {
+        tracing::warn!("{}", e);
+    }
+    if options.version_outputs
+        && let Err(e) = provenance::record_version(
+            &provenance::manifest_path(&output_path_for(input_path, options)),
+            &report,
+            options,
+        )
+    {
+        tracing::warn!("{}", e);
+    }
+    Ok(report)
+}
+
+/// Reads the input file into the job buffer.
+pub struct Decode;
+
+impl Stage for Decode {
+    fn phase(&self) -> Phase {
+        Phase::Decoding
+    }
+
+    fn process(&self, job: &mut Job, _on_progress: &mut dyn FnMut(f32)) -> Result<(), String> {
+        let (spec, buffer) = audio::decode_cancellable(&job.input_path, &job.cancel)?;
+        job.source_spec = spec;
+        job.source_duration =
+            Duration::from_secs_f64(buffer.frames() as f64 / buffer.sample_rate as f64);
+        job.buffer = buffer;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleRate {
+    /// The rate the engine models run at.
+    Engine,
+    /// The rate of the input file.
+    Source,
+}
+
+pub struct Resample(pub SampleRate);
+
+impl Stage for Resample {
+    fn phase(&self) -> Phase {
+        match self.0 {
+            SampleRate::Engine => Phase::Resampling,
+            SampleRate::Source => Phase::Restoring,
+        }
+    }
+
+    fn process(&self, job: &mut Job, on_progress: &mut dyn FnMut(f32)) -> Result<(), String> {
+        let target = match self.0 {
+            SampleRate::Engine => audio::ENGINE_SAMPLE_RATE,
+            SampleRate::Source => job.source_spec.sample_rate,
+        };
+        if job.buffer.sample_rate != target {
+            job.buffer = audio::resample(&job.buffer, target, &job.cancel, on_progress)?;
+        }
+        Ok(())
+    }
+}
+
+/// Which channels of the input go through the pipeline.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChannelRouting {
+    /// Every channel, cleaned as recorded.
+    #[default]
+    Keep,
+    /// All channels averaged into one, e.g. for a voice recorded on one
+    /// channel of a stereo file.
+    Mono,
+    /// Only the channel at this index, counting from 0.
+    Only(usize),
+}
+
+impl ChannelRouting {
+    /// The routings offered in the options.
+    pub const CHOICES: [ChannelRouting; 4] = [
+        ChannelRouting::Keep,
+        ChannelRouting::Mono,
+        ChannelRouting::Only(0),
+        ChannelRouting::Only(1),
+    ];
+}
+
+impl std::fmt::Display for ChannelRouting {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&match self {
+            ChannelRouting::Keep => tr!("channels-keep"),
+            ChannelRouting::Mono => tr!("channels-mono"),
+            ChannelRouting::Only(0) => tr!("channels-left"),
+            ChannelRouting::Only(1) => tr!("channels-right"),
+            ChannelRouting::Only(index) => tr!("channels-only", channel = index + 1),
+        })
+    }
+}
+
+/// Turns the channels of the job buffer into those picked by a
+/// [`ChannelRouting`].
+pub struct RouteChannels(pub ChannelRouting);
+
+impl Stage for RouteChannels {
+    fn phase(&self) -> Phase {
+        Phase::Preprocessing
+    }
+
+    fn process(&self, job: &mut Job, _on_progress: &mut dyn FnMut(f32)) -> Result<(), String> {
+        let frames = job.buffer.frames();
+        let channels = &mut job.buffer.channels;
+        match self.0 {
+            ChannelRouting::Keep => {}
+            ChannelRouting::Mono => {
+                if channels.len() > 1 {
+                    let count = channels.len() as f32;
+                    let mixed = (0..frames)
+                        .map(|frame| {
+                            channels.iter().map(|channel| channel[frame]).sum::<f32>() / count
+                        })
+                        .collect();
+                    *channels = vec![mixed];
+                }
+            }
+            ChannelRouting::Only(index) => {
+                if index >= channels.len() {
+                    return Err(format!(
+                        "Channel {} was picked, but the input has {}",
+                        index + 1,
+                        channels.len()
+                    ));
+                }
+                *channels = vec![channels.swap_remove(index)];
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether the channels of the input are cleaned one at a time, so the
+/// engine isn't thrown off by channels that don't belong together, like the
+/// two microphones of an interview.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChannelSplit {
+    /// Channels are cleaned together.
+    #[default]
+    Off,
+    /// Cleaned one at a time, then put back into one file.
+    Recombined,
+    /// Cleaned one at a time and written to a mono file each, named like
+    /// `take.ch2.wav`.
+    Files,
+}
+
+impl ChannelSplit {
+    pub const ALL: [ChannelSplit; 3] = [
+        ChannelSplit::Off,
+        ChannelSplit::Recombined,
+        ChannelSplit::Files,
+    ];
+}
+
+impl std::fmt::Display for ChannelSplit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&match self {
+            ChannelSplit::Off => tr!("split-off"),
+            ChannelSplit::Recombined => tr!("split-recombined"),
+            ChannelSplit::Files => tr!("split-files"),
+        })
+    }
+}
+
+/// Subtracts the mean of each channel so the waveform is centered on zero.
+pub struct RemoveDcOffset;
+
+impl Stage for RemoveDcOffset {
+    fn phase(&self) -> Phase {
+        Phase::Preprocessing
+    }
+
+    fn process(&self, job: &mut Job, _on_progress: &mut dyn FnMut(f32)) -> Result<(), String> {
+        for channel in &mut job.buffer.channels {
+            if channel.is_empty() {
+                continue;
+            }
+            let mean = channel.iter().map(|&s| s as f64).sum::<f64>() / channel.len() as f64;
+            let mean = mean as f32;
+            for sample in channel.iter_mut() {
+                *sample -= mean;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Drops leading and trailing frames where every channel stays below
+/// `threshold_db` (dBFS).
+pub struct TrimSilence {
+    pub threshold_db: f32,
+}
+
+impl Stage for TrimSilence {
+    fn phase(&self) -> Phase {
+        Phase::Preprocessing
+    }
+
+    fn process(&self, job: &mut Job, _on_progress: &mut dyn FnMut(f32)) -> Result<(), String> {
+        let buffer = &mut job.buffer;
+        let threshold = 10f32.powf(self.threshold_db / 20.0);
+        let is_loud = |frame: usize| {
+            buffer
+                .channels
+                .iter()
+                .any(|channel| channel[frame].abs() > threshold)
+        };
+
+        let frames = buffer.frames();
+        let Some(first) = (0..frames).find(|&frame| is_loud(frame)) else {
+            return Err("The file is silent below the trim threshold".to_string());
+        };
+        let last = (0..frames)
+            .rev()
+            .find(|&frame| is_loud(frame))
+            .unwrap_or(first);
+
+        let padding = (buffer.sample_rate as f32 * TRIM_PADDING_SECONDS) as usize;
+        let start = first.saturating_sub(padding);
+        let end = (last + 1 + padding).min(frames);
+        for channel in &mut buffer.channels {
+            channel.truncate(end);
+            channel.drain(..start);
+        }
+        Ok(())
+    }
+}
+
+/// Runs the denoiser on the job buffer. Mono and stereo audio is cleaned in
+/// one go unless `split` is set; anything wider is cleaned one channel at a
+/// time, since the engine only handles up to two channels.
+pub struct Denoise {
+    pub denoiser: Arc<dyn Denoiser>,
+    /// See [`ProcessingOptions::timeout_factor`].
+    pub timeout_factor: f32,
+    pub split: bool,
+}
+
+impl Stage for Denoise {
+    fn phase(&self) -> Phase {
+        Phase::Denoising
+    }
+
+    fn process(&self, job: &mut Job, on_progress: &mut dyn FnMut(f32)) -> Result<(), String> {
+        let engine_output_dir = job.temp.file("out");
+        let duration = job.buffer.frames() as f32 / job.buffer.sample_rate as f32;
+        let limits = engine::Limits::for_duration(duration, self.timeout_factor);
+        let groups: Vec<Vec<Vec<f32>>> = if self.split || job.buffer.channels.len() > 2 {
+            job.buffer
+                .channels
+                .iter()
+                .map(|channel| vec![channel.clone()])
+                .collect()
+        } else {
+            vec![job.buffer.channels.clone()]
+        };
+
+        let group_count = groups.len();
+        let mut cleaned = Vec::with_capacity(job.buffer.channels.len());
+        let mut sample_rate = job.buffer.sample_rate;
+        for (index, channels) in groups.into_iter().enumerate() {
+            let file_name = format!("part_{}.wav", index + 1);
+            let part_path = job.temp.file(&file_name);
+            let part = AudioBuffer {
+                sample_rate: job.buffer.sample_rate,
+                channels,
+            };
+            audio::write_wav(&part_path, audio::ENGINE_SPEC, &part)?;
+            self.denoiser.process_file(
+                &part_path,
+                &engine_output_dir,
+                &limits,
+                &job.cancel,
+                &mut |progress| {
+                    on_progress((index as f32 + progress / 100.0) / group_count as f32 * 100.0)
+                },
+            )?;
+
+            let (_, part) = audio::read_wav(&engine_output_dir.join(&file_name))?;
+            sample_rate = part.sample_rate;
+            cleaned.extend(part.channels);
+            on_progress((index + 1) as f32 / group_count as f32 * 100.0);
+        }
+
+        // Channels can differ by a few frames after the engine's own resampling.
+        let frames = cleaned.iter().map(Vec::len).min().unwrap_or(0);
+        for channel in &mut cleaned {
+            channel.truncate(frames);
+        }
+
+        let denoised = AudioBuffer {
+            sample_rate,
+            channels: cleaned,
+        };
+        job.original = Some(std::mem::replace(&mut job.buffer, denoised));
+        Ok(())
+    }
+}
+
+/// Blends the pre-denoising audio back in, `wet` being the share (0–1) of the
+/// denoised signal.
+pub struct Mix {
+    pub wet: f32,
+}
+
+impl Stage for Mix {
+    fn phase(&self) -> Phase {
+        Phase::Mixing
+    }
+
+    fn process(&self, job: &mut Job, _on_progress: &mut dyn FnMut(f32)) -> Result<(), String> {
+        let Some(original) = &job.original else {
+            return Ok(());
+        };
+        let wet = self.wet.clamp(0.0, 1.0);
+        for (channel, dry) in job.buffer.channels.iter_mut().zip(&original.channels) {
+            for (sample, dry) in channel.iter_mut().zip(dry) {
+                *sample = *sample * wet + dry * (1.0 - wet);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compares the levels of the pre-denoising audio with the job buffer.
+pub struct Measure;
+
+impl Stage for Measure {
+    fn phase(&self) -> Phase {
+        Phase::Measuring
+    }
+
+    fn process(&self, job: &mut Job, _on_progress: &mut dyn FnMut(f32)) -> Result<(), String> {
+        if let Some(original) = &job.original {
+            job.levels = Some(metrics::Comparison {
+                before: metrics::Levels::measure(original),
+                after: metrics::Levels::measure(&job.buffer),
+            });
+            job.attenuated =
+                metrics::attenuated_regions(original, &job.buffer, HEAVY_ATTENUATION_DB);
+        }
+        Ok(())
+    }
+}
+
+/// Scales the buffer so its highest peak sits at `peak_db` (dBFS).
+pub struct Normalize {
+    pub peak_db: f32,
+}
+
+impl Stage for Normalize {
+    fn phase(&self) -> Phase {
+        Phase::Normalizing
+    }
+
+    fn process(&self, job: &mut Job, _on_progress: &mut dyn FnMut(f32)) -> Result<(), String> {
+        let peak = job
+            .buffer
+            .channels
+            .iter()
+            .flatten()
+            .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+        if peak == 0.0 {
+            return Ok(());
+        }
+        let gain = 10f32.powf(self.peak_db / 20.0) / peak;
+        for sample in job.buffer.channels.iter_mut().flatten() {
+            *sample *= gain;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitDepth {
+    /// 16-bit PCM.
+    Default,
+    /// The input file's bit depth and sample format.
+    Source,
+}
+
+/// Container and codec of the cleaned file. Everything but WAV is encoded
+/// with ffmpeg.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Wav,
+    Flac,
+    Mp3,
+    Ogg,
+    /// Whatever format the input was in.
+    SameAsInput,
+}
+
+impl OutputFormat {
+    pub const ALL: [OutputFormat; 5] = [
+        OutputFormat::Wav,
+        OutputFormat::Flac,
+        OutputFormat::Mp3,
+        OutputFormat::Ogg,
+        OutputFormat::SameAsInput,
+    ];
+
+    /// Whether the bitrate setting applies to this format.
+    pub fn is_lossy(self) -> bool {
+        matches!(
+            self,
+            OutputFormat::Mp3 | OutputFormat::Ogg | OutputFormat::SameAsInput
+        )
+    }
+
+    /// Extension of files written in this format for `input_path`.
+    fn extension(self, input_path: &Path) -> String {
+        match self {
+            OutputFormat::Wav => String::from("wav"),
+            OutputFormat::Flac => String::from("flac"),
+            OutputFormat::Mp3 => String::from("mp3"),
+            OutputFormat::Ogg => String::from("ogg"),
+            // Audio from video containers is kept as AAC.
+            OutputFormat::SameAsInput if formats::is_video(input_path) => {
+                String::from(formats::VIDEO_AUDIO_EXTENSION)
+            }
+            OutputFormat::SameAsInput => {
+                audio::extension(input_path).unwrap_or_else(|| String::from("wav"))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&match self {
+            OutputFormat::Wav => tr!("format-wav"),
+            OutputFormat::Flac => tr!("format-flac"),
+            OutputFormat::Mp3 => tr!("format-mp3"),
+            OutputFormat::Ogg => tr!("format-ogg"),
+            OutputFormat::SameAsInput => tr!("format-same-as-input"),
+        })
+    }
+}
+
+/// Writes the job buffer to the output path, adjusting its extension to the
+/// output format. With `split`, each channel goes to a file of its own.
+pub struct Encode {
+    pub bit_depth: BitDepth,
+    pub format: OutputFormat,
+    pub bitrate_kbps: u32,
+    pub keep_metadata: bool,
+    pub keep_broadcast_chunks: bool,
+    /// Number the output rather than replace one already there.
+    pub versioned: bool,
+    /// Write every channel to its own file, named like `take.ch2.wav`.
+    pub split: bool,
+    /// Leave video inputs to [`Remux`], which encodes their audio itself so
+    /// it's only compressed once.
+    pub remux: bool,
+}
+
+impl Stage for Encode {
+    fn phase(&self) -> Phase {
+        Phase::Encoding
+    }
+
+    fn process(&self, job: &mut Job, _on_progress: &mut dyn FnMut(f32)) -> Result<(), String> {
+        if self.remux && formats::is_video(&job.input_path) {
+            return Ok(());
+        }
+        if let Some(parent) = job.output_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Could not create output directory: {}", e))?;
+        }
+        let spec = match self.bit_depth {
+            BitDepth::Default => DEFAULT_OUTPUT_SPEC,
+            BitDepth::Source => job.source_spec,
+        };
+
+        let extension = self.format.extension(&job.input_path);
+        job.output_path.set_extension(&extension);
+        if !self.split || job.buffer.channels.len() < 2 {
+            if self.versioned {
+                job.output_path = unused_version(&job.output_path);
+            }
+            job.output_path = spare_input(job.output_path.clone(), &job.input_path);
+            return self.write(job, &job.output_path, &extension, spec, &job.buffer);
+        }
+
+        let stem = job
+            .output_path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        let channel_paths: Vec<PathBuf> = (1..=job.buffer.channels.len())
+            .map(|channel| {
+                job.output_path
+                    .with_file_name(format!("{}.ch{}.{}", stem, channel, extension))
+            })
+            .collect();
+        let version = if self.versioned {
+            unused_version_of_all(&channel_paths)
+        } else {
+            1
+        };
+        let mut paths = Vec::with_capacity(channel_paths.len());
+        for (path, channel) in channel_paths.iter().zip(&job.buffer.channels) {
+            let path = spare_input(with_version(path, version), &job.input_path);
+            let mono = AudioBuffer {
+                sample_rate: job.buffer.sample_rate,
+                channels: vec![channel.clone()],
+            };
+            // Listed first, so a failed run removes what it wrote.
+            job.other_outputs.push(path.clone());
+            self.write(job, &path, &extension, spec, &mono)?;
+            paths.push(path);
+        }
+        job.output_path = paths.remove(0);
+        job.other_outputs = paths;
+        Ok(())
+    }
+}
+
+impl Encode {
+    /// Writes `buffer` to `path`, a file with `extension`.
+    fn write(
+        &self,
+        job: &Job,
+        path: &Path,
+        extension: &str,
+        spec: hound::WavSpec,
+        buffer: &AudioBuffer,
+    ) -> Result<(), String> {
+        if extension == "wav" {
+            audio::write_wav(path, spec, buffer)?;
+            if self.keep_metadata {
+                metadata::copy_to_wav(&job.input_path, path)?;
+            }
+            if self.keep_broadcast_chunks {
+                metadata::copy_broadcast_chunks(&job.input_path, path, buffer.sample_rate)?;
+            }
+            return Ok(());
+        }
+
+        let wav_path = job.temp.file("encoded.wav");
+        audio::write_wav(&wav_path, spec, buffer)?;
+        transcode::encode(
+            &wav_path,
+            path,
+            &formats::find(extension)
+                .map(|format| format.codec_args(self.bitrate_kbps))
+                .unwrap_or_default(),
+            self.keep_metadata.then_some(job.input_path.as_path()),
+            &job.cancel,
+        )
+    }
+}
+
+/// Writes a copy of a video input with its audio replaced by the cleaned
+/// audio, as the output. It's named like the audio output would be, keeping
+/// the video's extension. The audio is encoded straight from the job buffer,
+/// [`Encode`] having left video inputs alone. Audio inputs pass through
+/// unchanged.
+pub struct Remux {
+    pub bitrate_kbps: u32,
+    /// Number the output rather than replace one already there.
+    pub versioned: bool,
+}
+
+impl Stage for Remux {
+    fn phase(&self) -> Phase {
+        Phase::Remuxing
+    }
+
+    fn process(&self, job: &mut Job, _on_progress: &mut dyn FnMut(f32)) -> Result<(), String> {
+        if !formats::is_video(&job.input_path) {
+            return Ok(());
+        }
+        if let Some(parent) = job.output_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Could not create output directory: {}", e))?;
+        }
+
+        let audio_path = job.temp.file("remux.wav");
+        audio::write_wav(&audio_path, audio::ENGINE_SPEC, &job.buffer)?;
+        let mut video_path = job
+            .output_path
+            .with_extension(job.input_path.extension().unwrap_or_default());
+        if self.versioned {
+            video_path = unused_version(&video_path);
+        }
+        let video_path = spare_input(video_path, &job.input_path);
+        transcode::remux(
+            &job.input_path,
+            &audio_path,
+            &video_path,
+            self.bitrate_kbps,
+            &job.cancel,
+        )?;
+        job.output_path = video_path;
+        Ok(())
+    }
+}
+
+/// Writes the regions found by [`Measure`] as an Audacity label track next to
+/// the output, named after it with a `.labels.txt` extension.
+pub struct ExportLabels;
+
+impl Stage for ExportLabels {
+    fn phase(&self) -> Phase {
+        Phase::Encoding
+    }
+
+    fn process(&self, job: &mut Job, _on_progress: &mut dyn FnMut(f32)) -> Result<(), String> {
+        metrics::write_labels(
+            &job.output_path.with_extension("labels.txt"),
+            &job.attenuated,
+        )
+    }
+}
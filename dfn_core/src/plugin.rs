@@ -0,0 +1,408 @@
+//! DeepFilterNet's LADSPA plugin, loaded into the app and run block by block.
+//! It's the engine built as a library with its model inside, released next
+//! to the `deep-filter` binary, so audio can be cleaned as it comes in rather
+//! than a file at a time.
+//!
+//! Only as much of LADSPA is hosted as the plugin needs: its single-channel
+//! variant, with one audio input and output, and control inputs left at
+//! their defaults but for the attenuation limit.
+
+use crate::download;
+use libloading::Library;
+use std::ffi::{CStr, c_char, c_int, c_ulong, c_void};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Name the plugin is kept under in the data folder.
+pub const PLUGIN_NAME: &str = if cfg!(windows) {
+    "deep_filter_ladspa.dll"
+} else if cfg!(target_os = "macos") {
+    "libdeep_filter_ladspa.dylib"
+} else {
+    "libdeep_filter_ladspa.so"
+};
+
+/// Label of the single-channel plugin in the library.
+const LABEL: &str = "deep_filter_mono";
+
+/// Start of the name of the control input for the attenuation limit, in dB.
+const ATTENUATION_PORT: &str = "Attenuation Limit";
+
+/// Name hosts read a plugin's delay in frames from, on a control output.
+const LATENCY_PORT: &str = "latency";
+
+/// Where the plugin release for this platform is published. Releases only
+/// have it for Linux; elsewhere it's installed from a file built from the
+/// DeepFilterNet sources.
+pub fn release_url() -> Result<String, String> {
+    let target = if cfg!(all(target_os = "linux", target_arch = "x86_64")) {
+        "x86_64-unknown-linux-gnu"
+    } else if cfg!(all(target_os = "linux", target_arch = "aarch64")) {
+        "aarch64-unknown-linux-gnu"
+    } else {
+        return Err("The plugin isn't released for this platform".to_string());
+    };
+    Ok(format!(
+        "https://github.com/Rikorose/DeepFilterNet/releases/download/{}/libdeep_filter_ladspa-{}-{}.so",
+        download::RELEASE_TAG,
+        download::RELEASE_TAG.trim_start_matches('v'),
+        target
+    ))
+}
+
+/// Where the plugin goes in `dir`.
+pub fn plugin_path(dir: &Path) -> PathBuf {
+    dir.join(PLUGIN_NAME)
+}
+
+/// The plugin in the app's data folder, if it's there.
+pub fn installed_plugin() -> Option<PathBuf> {
+    let path = plugin_path(&crate::data_dir()?);
+    path.exists().then_some(path)
+}
+
+// The parts of `ladspa.h` the host uses.
+const PORT_INPUT: c_int = 0x1;
+const PORT_OUTPUT: c_int = 0x2;
+const PORT_CONTROL: c_int = 0x4;
+const PORT_AUDIO: c_int = 0x8;
+
+const HINT_BOUNDED_BELOW: c_int = 0x1;
+const HINT_BOUNDED_ABOVE: c_int = 0x2;
+const HINT_SAMPLE_RATE: c_int = 0x8;
+const HINT_LOGARITHMIC: c_int = 0x10;
+const HINT_DEFAULT_MASK: c_int = 0x3C0;
+const HINT_DEFAULT_MINIMUM: c_int = 0x40;
+const HINT_DEFAULT_LOW: c_int = 0x80;
+const HINT_DEFAULT_MIDDLE: c_int = 0xC0;
+const HINT_DEFAULT_HIGH: c_int = 0x100;
+const HINT_DEFAULT_MAXIMUM: c_int = 0x140;
+const HINT_DEFAULT_0: c_int = 0x200;
+const HINT_DEFAULT_1: c_int = 0x240;
+const HINT_DEFAULT_100: c_int = 0x280;
+const HINT_DEFAULT_440: c_int = 0x2C0;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct PortRangeHint {
+    hint: c_int,
+    lower: f32,
+    upper: f32,
+}
+
+type Handle = *mut c_void;
+
+#[repr(C)]
+struct Descriptor {
+    unique_id: c_ulong,
+    label: *const c_char,
+    properties: c_int,
+    name: *const c_char,
+    maker: *const c_char,
+    copyright: *const c_char,
+    port_count: c_ulong,
+    port_descriptors: *const c_int,
+    port_names: *const *const c_char,
+    port_range_hints: *const PortRangeHint,
+    implementation_data: *mut c_void,
+    instantiate: unsafe extern "C" fn(*const Descriptor, c_ulong) -> Handle,
+    connect_port: unsafe extern "C" fn(Handle, c_ulong, *mut f32),
+    activate: Option<unsafe extern "C" fn(Handle)>,
+    run: unsafe extern "C" fn(Handle, c_ulong),
+    run_adding: Option<unsafe extern "C" fn(Handle, c_ulong)>,
+    set_run_adding_gain: Option<unsafe extern "C" fn(Handle, f32)>,
+    deactivate: Option<unsafe extern "C" fn(Handle)>,
+    cleanup: unsafe extern "C" fn(Handle),
+}
+
+/// A port of the plugin, as its descriptor lists it.
+#[derive(Debug, Clone)]
+struct Port {
+    kind: c_int,
+    name: String,
+    range: PortRangeHint,
+}
+
+impl Port {
+    fn is(&self, kind: c_int) -> bool {
+        self.kind & kind == kind
+    }
+}
+
+/// The plugin library, loaded. Instances keep it loaded as long as they run.
+pub struct Plugin {
+    library: Arc<Library>,
+    descriptor: *const Descriptor,
+    ports: Vec<Port>,
+}
+
+// The descriptor is read-only data living as long as the library, which
+// LADSPA allows hosts to share between threads.
+unsafe impl Send for Plugin {}
+unsafe impl Sync for Plugin {}
+
+impl Plugin {
+    /// Loads the plugin at `path`, failing if it isn't DeepFilterNet's.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let error = |e: &dyn std::fmt::Display| {
+            format!("Could not load the plugin {}: {}", path.display(), e)
+        };
+        // SAFETY: loading runs the library's initializers, which is what
+        // installing the plugin was for.
+        let library = unsafe { Library::new(path) }.map_err(|e| error(&e))?;
+        // SAFETY: the signature `ladspa.h` gives the entry point.
+        let entry = unsafe {
+            library
+                .get::<unsafe extern "C" fn(c_ulong) -> *const Descriptor>(b"ladspa_descriptor\0")
+        }
+        .map_err(|e| error(&e))?;
+        let descriptor = (0..)
+            // SAFETY: the plugin lists its descriptors until a null one.
+            .map(|index| unsafe { entry(index) })
+            .take_while(|descriptor| !descriptor.is_null())
+            // SAFETY: non-null descriptors point at static data of the
+            // library, and their strings are null-terminated.
+            .find(|&descriptor| unsafe { text((*descriptor).label) } == LABEL)
+            .ok_or_else(|| error(&format!("it has no {} plugin", LABEL)))?;
+        // SAFETY: as above, with as many ports listed as it counts.
+        let ports = unsafe {
+            let descriptor = &*descriptor;
+            (0..descriptor.port_count as usize)
+                .map(|index| Port {
+                    kind: *descriptor.port_descriptors.add(index),
+                    name: text(*descriptor.port_names.add(index)),
+                    range: *descriptor.port_range_hints.add(index),
+                })
+                .collect::<Vec<_>>()
+        };
+        let audio = |kind| {
+            ports
+                .iter()
+                .filter(|port| port.is(PORT_AUDIO | kind))
+                .count()
+        };
+        if audio(PORT_INPUT) != 1 || audio(PORT_OUTPUT) != 1 {
+            return Err(error(&"it doesn't have one audio input and one output"));
+        }
+        Ok(Self {
+            library: Arc::new(library),
+            descriptor,
+            ports,
+        })
+    }
+
+    /// An instance of the plugin, cleaning audio at `sample_rate`.
+    pub fn instantiate(&self, sample_rate: u32) -> Result<Instance, String> {
+        // SAFETY: the descriptor is valid as long as the library is loaded.
+        let descriptor = unsafe { &*self.descriptor };
+        // SAFETY: instantiating is how the descriptor is meant to be used.
+        let handle = unsafe { (descriptor.instantiate)(self.descriptor, sample_rate as c_ulong) };
+        if handle.is_null() {
+            return Err(format!(
+                "The plugin could not be started at {} Hz",
+                sample_rate
+            ));
+        }
+        let mut instance = Instance {
+            handle,
+            descriptor: self.descriptor,
+            _library: self.library.clone(),
+            controls: self
+                .ports
+                .iter()
+                .map(|port| default_value(port.range, sample_rate))
+                .collect(),
+            input: self.position(|port| port.is(PORT_AUDIO | PORT_INPUT)),
+            output: self.position(|port| port.is(PORT_AUDIO | PORT_OUTPUT)),
+            attenuation: self.find(|port| {
+                port.is(PORT_CONTROL | PORT_INPUT) && port.name.starts_with(ATTENUATION_PORT)
+            }),
+            latency: self.find(|port| {
+                port.is(PORT_CONTROL | PORT_OUTPUT) && port.name.eq_ignore_ascii_case(LATENCY_PORT)
+            }),
+            bounds: self.ports.iter().map(|port| port.range).collect(),
+            buffer: Vec::new(),
+        };
+        for (index, port) in self.ports.iter().enumerate() {
+            if port.is(PORT_CONTROL) {
+                // SAFETY: the controls are boxed, so they stay where they
+                // are for as long as the instance lives.
+                unsafe {
+                    (descriptor.connect_port)(
+                        handle,
+                        index as c_ulong,
+                        &mut instance.controls[index],
+                    )
+                };
+            }
+        }
+        if let Some(activate) = descriptor.activate {
+            // SAFETY: every control port is connected.
+            unsafe { activate(handle) };
+        }
+        Ok(instance)
+    }
+
+    fn find(&self, matches: impl Fn(&Port) -> bool) -> Option<usize> {
+        self.ports.iter().position(matches)
+    }
+
+    /// Index of a port [`Plugin::load`] made sure is there.
+    fn position(&self, matches: impl Fn(&Port) -> bool) -> usize {
+        self.find(matches).unwrap_or_default()
+    }
+}
+
+/// A running instance of the plugin. It keeps its own state between blocks,
+/// so each stream of audio needs its own.
+pub struct Instance {
+    handle: Handle,
+    descriptor: *const Descriptor,
+    _library: Arc<Library>,
+    /// Values of the control ports, which the plugin reads and writes here.
+    /// Audio ports have an unused slot.
+    controls: Box<[f32]>,
+    bounds: Vec<PortRangeHint>,
+    input: usize,
+    output: usize,
+    attenuation: Option<usize>,
+    latency: Option<usize>,
+    /// The block being cleaned, copied as LADSPA hosts hand over writable
+    /// buffers.
+    buffer: Vec<f32>,
+}
+
+// LADSPA lets an instance move between threads as long as only one runs it
+// at a time, which `&mut self` makes sure of.
+unsafe impl Send for Instance {}
+
+impl Instance {
+    /// Sets how far noise is turned down at most, in dB. 100 removes it
+    /// whole; less leaves some in, which sounds more natural.
+    pub fn set_attenuation(&mut self, db: f32) {
+        if let Some(index) = self.attenuation {
+            let range = self.bounds[index];
+            let mut db = db;
+            if range.hint & HINT_BOUNDED_BELOW != 0 {
+                db = db.max(range.lower);
+            }
+            if range.hint & HINT_BOUNDED_ABOVE != 0 {
+                db = db.min(range.upper);
+            }
+            self.controls[index] = db;
+        }
+    }
+
+    /// Frames the output lags behind the input, where the plugin tells.
+    pub fn latency(&self) -> usize {
+        self.latency
+            .map(|index| self.controls[index].max(0.0) as usize)
+            .unwrap_or(0)
+    }
+
+    /// Cleans `input` into `output`, which must be as long.
+    pub fn process(&mut self, input: &[f32], output: &mut [f32]) {
+        assert_eq!(input.len(), output.len());
+        self.buffer.clear();
+        self.buffer.extend_from_slice(input);
+        // SAFETY: both buffers hold the frames run is told about, and stay
+        // put while it runs.
+        unsafe {
+            let descriptor = &*self.descriptor;
+            (descriptor.connect_port)(self.handle, self.input as c_ulong, self.buffer.as_mut_ptr());
+            (descriptor.connect_port)(self.handle, self.output as c_ulong, output.as_mut_ptr());
+            (descriptor.run)(self.handle, input.len() as c_ulong);
+        }
+    }
+}
+
+impl Drop for Instance {
+    fn drop(&mut self) {
+        // SAFETY: the handle is valid until cleaned up, which is done once.
+        unsafe {
+            let descriptor = &*self.descriptor;
+            if let Some(deactivate) = descriptor.deactivate {
+                deactivate(self.handle);
+            }
+            (descriptor.cleanup)(self.handle);
+        }
+    }
+}
+
+/// # Safety
+///
+/// `text` is null or a null-terminated string.
+unsafe fn text(text: *const c_char) -> String {
+    if text.is_null() {
+        return String::new();
+    }
+    // SAFETY: as promised by the caller.
+    unsafe { CStr::from_ptr(text) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// The value a control starts at, by the rules of `ladspa.h`: its hinted
+/// default, or its lower bound, or zero.
+fn default_value(range: PortRangeHint, sample_rate: u32) -> f32 {
+    let scale = if range.hint & HINT_SAMPLE_RATE != 0 {
+        sample_rate as f32
+    } else {
+        1.0
+    };
+    let (lower, upper) = (range.lower * scale, range.upper * scale);
+    let between = |upper_share: f32| {
+        if range.hint & HINT_LOGARITHMIC != 0 && lower > 0.0 && upper > 0.0 {
+            (lower.ln() * (1.0 - upper_share) + upper.ln() * upper_share).exp()
+        } else {
+            lower * (1.0 - upper_share) + upper * upper_share
+        }
+    };
+    match range.hint & HINT_DEFAULT_MASK {
+        HINT_DEFAULT_MINIMUM => lower,
+        HINT_DEFAULT_LOW => between(0.25),
+        HINT_DEFAULT_MIDDLE => between(0.5),
+        HINT_DEFAULT_HIGH => between(0.75),
+        HINT_DEFAULT_MAXIMUM => upper,
+        HINT_DEFAULT_0 => 0.0,
+        HINT_DEFAULT_1 => 1.0,
+        HINT_DEFAULT_100 => 100.0,
+        HINT_DEFAULT_440 => 440.0,
+        _ if range.hint & HINT_BOUNDED_BELOW != 0 => lower,
+        _ => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(hint: c_int, lower: f32, upper: f32) -> PortRangeHint {
+        PortRangeHint { hint, lower, upper }
+    }
+
+    #[test]
+    fn controls_start_at_their_hinted_defaults() {
+        let bounded = HINT_BOUNDED_BELOW | HINT_BOUNDED_ABOVE;
+        let at =
+            |default, lower, upper| default_value(range(bounded | default, lower, upper), 48_000);
+        assert_eq!(at(HINT_DEFAULT_MAXIMUM, 0.0, 100.0), 100.0);
+        assert_eq!(at(HINT_DEFAULT_MIDDLE, -20.0, 20.0), 0.0);
+        assert_eq!(at(HINT_DEFAULT_LOW, 0.0, 100.0), 25.0);
+        assert_eq!(at(HINT_DEFAULT_440, 0.0, 1000.0), 440.0);
+        assert_eq!(at(0, -15.0, 35.0), -15.0);
+        assert_eq!(default_value(range(0, -15.0, 35.0), 48_000), 0.0);
+    }
+
+    #[test]
+    fn defaults_follow_logarithmic_and_sample_rate_hints() {
+        let log = HINT_BOUNDED_BELOW | HINT_BOUNDED_ABOVE | HINT_LOGARITHMIC;
+        let middle = default_value(range(log | HINT_DEFAULT_MIDDLE, 10.0, 1000.0), 48_000);
+        assert!((middle - 100.0).abs() < 0.01, "{}", middle);
+        let nyquist = default_value(
+            range(HINT_SAMPLE_RATE | HINT_DEFAULT_MAXIMUM, 0.0, 0.5),
+            48_000,
+        );
+        assert_eq!(nyquist, 24_000.0);
+    }
+}
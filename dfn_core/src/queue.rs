@@ -0,0 +1,236 @@
+//! Files waiting to be cleaned. Each keeps the options it was added with, so
+//! a batch can mix settings, and a priority: the most urgent file is cleaned
+//! first, and files of the same priority in the order they were added.
+//!
+//! The files can be saved as a list, plain text or M3U with one path per
+//! line, to be queued again later or on another machine.
+
+use crate::i18n::tr;
+use crate::pipeline::ProcessingOptions;
+use std::path::{Path, PathBuf};
+
+/// First line of an M3U list.
+const M3U_HEADER: &str = "#EXTM3U";
+
+/// How soon a file is cleaned, compared to the others waiting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    /// Cleaned before anything else waiting.
+    High,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 3] = [Priority::High, Priority::Normal, Priority::Low];
+}
+
+impl std::fmt::Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Priority::Low => write!(f, "{}", tr!("priority-low")),
+            Priority::Normal => write!(f, "{}", tr!("priority-normal")),
+            Priority::High => write!(f, "{}", tr!("priority-high")),
+        }
+    }
+}
+
+pub type ItemId = u64;
+
+/// A file waiting to be cleaned.
+#[derive(Debug, Clone)]
+pub struct Item {
+    pub id: ItemId,
+    pub input: PathBuf,
+    /// The options at the time the file was added.
+    pub options: ProcessingOptions,
+    pub priority: Priority,
+}
+
+#[derive(Debug, Default)]
+pub struct Queue {
+    /// In the order they were added.
+    items: Vec<Item>,
+    next_id: ItemId,
+}
+
+impl Queue {
+    /// Adds `input`, to be cleaned with `options`.
+    pub fn push(
+        &mut self,
+        input: PathBuf,
+        options: ProcessingOptions,
+        priority: Priority,
+    ) -> ItemId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.items.push(Item {
+            id,
+            input,
+            options,
+            priority,
+        });
+        id
+    }
+
+    /// Takes the file to clean next.
+    pub fn take_next(&mut self) -> Option<Item> {
+        let id = self.ordered().next()?.id;
+        self.remove(id)
+    }
+
+    /// The files waiting, in the order they'll be cleaned.
+    pub fn ordered(&self) -> impl Iterator<Item = &Item> {
+        let mut items: Vec<&Item> = self.items.iter().collect();
+        // Stable, so files of the same priority keep their order.
+        items.sort_by_key(|item| std::cmp::Reverse(item.priority));
+        items.into_iter()
+    }
+
+    pub fn remove(&mut self, id: ItemId) -> Option<Item> {
+        let index = self.items.iter().position(|item| item.id == id)?;
+        Some(self.items.remove(index))
+    }
+
+    pub fn set_priority(&mut self, id: ItemId, priority: Priority) {
+        if let Some(item) = self.items.iter_mut().find(|item| item.id == id) {
+            item.priority = priority;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+/// Whether `path` is named as an M3U list.
+fn is_m3u(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            extension.eq_ignore_ascii_case("m3u") || extension.eq_ignore_ascii_case("m3u8")
+        })
+}
+
+/// `inputs` as a list, one path per line, under an M3U header when `m3u`.
+pub fn list_text<'a>(inputs: impl IntoIterator<Item = &'a Path>, m3u: bool) -> String {
+    let mut text = String::new();
+    if m3u {
+        text.push_str(M3U_HEADER);
+        text.push('\n');
+    }
+    for input in inputs {
+        text.push_str(&input.to_string_lossy());
+        text.push('\n');
+    }
+    text
+}
+
+/// The paths in a list, skipping blank lines and `#` comments like M3U
+/// directives. Relative paths are taken from `base`, the list's folder.
+pub fn parse_list(text: &str, base: &Path) -> Vec<PathBuf> {
+    text.lines()
+        .map(|line| line.trim().trim_start_matches('\u{feff}'))
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| base.join(line))
+        .collect()
+}
+
+/// Saves `inputs` as a list at `path`, in M3U when it's named `.m3u` or
+/// `.m3u8`.
+pub fn write_list<'a>(
+    path: &Path,
+    inputs: impl IntoIterator<Item = &'a Path>,
+) -> Result<(), String> {
+    std::fs::write(path, list_text(inputs, is_m3u(path)))
+        .map_err(|e| format!("Could not save the list: {}", e))
+}
+
+/// Reads the paths in the list at `path`.
+pub fn read_list(path: &Path) -> Result<Vec<PathBuf>, String> {
+    let text =
+        std::fs::read_to_string(path).map_err(|e| format!("Could not read the list: {}", e))?;
+    Ok(parse_list(&text, path.parent().unwrap_or(Path::new(""))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue(files: &[(&str, Priority)]) -> Queue {
+        let mut queue = Queue::default();
+        for (file, priority) in files {
+            queue.push(file.into(), ProcessingOptions::default(), *priority);
+        }
+        queue
+    }
+
+    fn drain(queue: &mut Queue) -> Vec<String> {
+        std::iter::from_fn(|| queue.take_next())
+            .map(|item| item.input.display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn urgent_files_jump_the_queue() {
+        let mut queue = queue(&[
+            ("a.wav", Priority::Normal),
+            ("b.wav", Priority::Low),
+            ("c.wav", Priority::Normal),
+            ("rush.wav", Priority::High),
+        ]);
+        assert_eq!(queue.len(), 4);
+        assert_eq!(drain(&mut queue), ["rush.wav", "a.wav", "c.wav", "b.wav"]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn items_keep_their_options() {
+        let mut queue = Queue::default();
+        let mut options = ProcessingOptions {
+            noise_reduction: 40.0,
+            ..ProcessingOptions::default()
+        };
+        queue.push("a.wav".into(), options.clone(), Priority::Normal);
+        options.noise_reduction = 80.0;
+        let b = queue.push("b.wav".into(), options, Priority::Normal);
+
+        queue.set_priority(b, Priority::High);
+        let next = queue.take_next().unwrap();
+        assert_eq!(next.input, PathBuf::from("b.wav"));
+        assert_eq!(next.options.noise_reduction, 80.0);
+        assert_eq!(queue.take_next().unwrap().options.noise_reduction, 40.0);
+    }
+
+    #[test]
+    fn removed_files_are_not_cleaned() {
+        let mut queue = queue(&[("a.wav", Priority::Normal)]);
+        let b = queue.push("b.wav".into(), ProcessingOptions::default(), Priority::High);
+        assert!(queue.remove(b).is_some());
+        assert!(queue.remove(b).is_none());
+        assert_eq!(drain(&mut queue), ["a.wav"]);
+    }
+
+    #[test]
+    fn lists_round_trip_and_resolve_relative_paths() {
+        let inputs = [
+            Path::new("/audio/take 1.wav"),
+            Path::new("/audio/take 2.mp3"),
+        ];
+        let m3u = list_text(inputs, true);
+        assert_eq!(m3u, "#EXTM3U\n/audio/take 1.wav\n/audio/take 2.mp3\n");
+        assert_eq!(parse_list(&m3u, Path::new("/lists")), inputs);
+        assert_eq!(list_text(inputs, false).lines().count(), 2);
+
+        let written = "#EXTM3U\r\n#EXTINF:12,Take 3\r\n\r\ntakes/3.flac\r\n";
+        assert_eq!(
+            parse_list(written, Path::new("/lists")),
+            [PathBuf::from("/lists/takes/3.flac")]
+        );
+    }
+}
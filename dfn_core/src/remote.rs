@@ -0,0 +1,344 @@
+//! Cleaning files on a self-hosted DeepFilterNet server.
+//!
+//! The server speaks a small plain-text protocol:
+//!
+//! - `POST /jobs` with a WAV body starts a job and answers with its id.
+//! - `GET /jobs/<id>` answers `queued`, `running <percent>`, `done` or
+//!   `failed <reason>`.
+//! - `GET /jobs/<id>/result` answers with the cleaned WAV once done.
+//! - `DELETE /jobs/<id>` discards the job.
+//!
+//! Requests carry `Authorization: Bearer <token>` when a token is set.
+
+use crate::cancel::{CANCELLED, CancelToken};
+use crate::download;
+use crate::engine;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+/// How often the job status is polled.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often a request under way checks whether the job has to end.
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long discarding a job on the server may take.
+const DISCARD_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Server address and credentials.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Server {
+    /// Base URL, e.g. `https://denoise.example.com`.
+    pub url: String,
+    /// Bearer token; empty for servers without authentication.
+    pub token: String,
+}
+
+/// Cleans `input_path` on `server` and saves the result as `output_path`,
+/// giving up once `limits.timeout` has passed or `cancel` is cancelled.
+pub fn run(
+    server: &Server,
+    input_path: &Path,
+    output_path: &Path,
+    limits: &engine::Limits,
+    cancel: &CancelToken,
+    on_progress: &mut dyn FnMut(f32),
+) -> Result<(), String> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| format!("Could not start the network runtime: {}", e))?;
+    // Built for this runtime, which is dropped with its connections.
+    runtime.block_on(run_job(
+        &download::client(),
+        server,
+        input_path,
+        output_path,
+        limits,
+        cancel,
+        on_progress,
+    ))
+}
+
+async fn run_job(
+    client: &reqwest::Client,
+    server: &Server,
+    input_path: &Path,
+    output_path: &Path,
+    limits: &engine::Limits,
+    cancel: &CancelToken,
+    on_progress: &mut dyn FnMut(f32),
+) -> Result<(), String> {
+    let base = server.url.trim_end_matches('/');
+    if base.is_empty() {
+        return Err("No server URL is set".to_string());
+    }
+    let request = |method: reqwest::Method, url: String| {
+        let request = client.request(method, url);
+        if server.token.is_empty() {
+            request
+        } else {
+            request.bearer_auth(&server.token)
+        }
+    };
+    let stop = Stop {
+        cancel,
+        started: Instant::now(),
+        timeout: limits.timeout,
+    };
+
+    let wav = std::fs::read(input_path).map_err(|e| format!("Could not read audio: {}", e))?;
+    let id = stop
+        .race(async {
+            send(
+                request(reqwest::Method::POST, format!("{}/jobs", base))
+                    .header(reqwest::header::CONTENT_TYPE, "audio/wav")
+                    .body(wav),
+            )
+            .await?
+            .text()
+            .await
+            .map_err(|e| format!("Could not read the server's reply: {}", e))
+        })
+        .await?
+        .trim()
+        .to_string();
+    let job_url = format!("{}/jobs/{}", base, id);
+    info!("Started server job {}", job_url);
+
+    let result = loop {
+        if let Some(reason) = stop.reason() {
+            break Err(reason);
+        }
+        let status = stop
+            .race(async {
+                let response = send(request(reqwest::Method::GET, job_url.clone())).await?;
+                Ok(response.text().await.unwrap_or_default())
+            })
+            .await;
+        let status = match status {
+            Ok(status) => status,
+            Err(e) => break Err(e),
+        };
+        let (state, detail) = status.trim().split_once(' ').unwrap_or((status.trim(), ""));
+        debug!("Server job status: {}", status.trim());
+        match state {
+            "queued" => {}
+            "running" => {
+                if let Ok(percent) = detail.parse::<f32>() {
+                    on_progress(percent.clamp(0.0, 100.0));
+                }
+            }
+            "done" => {
+                break stop
+                    .race(download(
+                        request(reqwest::Method::GET, format!("{}/result", job_url)),
+                        output_path,
+                    ))
+                    .await;
+            }
+            "failed" => break Err(format!("The server could not clean the file: {}", detail)),
+            _ => {
+                break Err(format!(
+                    "Unexpected reply from the server: {}",
+                    status.trim()
+                ));
+            }
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    };
+
+    // Bounded on its own, as the job may have ended through the stop.
+    let discard = request(reqwest::Method::DELETE, job_url).send();
+    let _ = tokio::time::timeout(DISCARD_TIMEOUT, discard).await;
+    result
+}
+
+/// What ends a job early: cancelling it, or running out of time.
+struct Stop<'a> {
+    cancel: &'a CancelToken,
+    started: Instant,
+    timeout: Duration,
+}
+
+impl Stop<'_> {
+    /// Why the job has to end now, if it does.
+    fn reason(&self) -> Option<String> {
+        if self.cancel.is_cancelled() {
+            return Some(CANCELLED.to_string());
+        }
+        (self.started.elapsed() > self.timeout).then(|| {
+            format!(
+                "The server did not finish within {} s",
+                self.timeout.as_secs()
+            )
+        })
+    }
+
+    /// Waits for `work`, unless the job has to end first, so a stalled
+    /// server doesn't hold up Cancel.
+    async fn race<T>(&self, work: impl Future<Output = Result<T, String>>) -> Result<T, String> {
+        let stopped = async {
+            loop {
+                if let Some(reason) = self.reason() {
+                    return reason;
+                }
+                tokio::time::sleep(STOP_CHECK_INTERVAL).await;
+            }
+        };
+        tokio::select! {
+            result = work => result,
+            reason = stopped => Err(reason),
+        }
+    }
+}
+
+/// Sends `request`, turning error statuses into messages.
+async fn send(request: reqwest::RequestBuilder) -> Result<reqwest::Response, String> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Could not reach the server: {}", e))?;
+    let status = response.status();
+    if status.is_success() {
+        Ok(response)
+    } else {
+        let reason = response.text().await.unwrap_or_default();
+        Err(format!("The server answered {}: {}", status, reason.trim()))
+    }
+}
+
+async fn download(request: reqwest::RequestBuilder, output_path: &Path) -> Result<(), String> {
+    let wav = send(request)
+        .await?
+        .bytes()
+        .await
+        .map_err(|e| format!("Could not download the result: {}", e))?;
+    if let Some(dir) = output_path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Could not create output directory: {}", e))?;
+    }
+    std::fs::write(output_path, wav).map_err(|e| format!("Could not save the result: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    /// Serves every job as done at once, with `result` as the cleaned file,
+    /// keeping connections open between requests.
+    fn serve_done(result: &'static [u8]) -> Server {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { break };
+                std::thread::spawn(move || answer(stream, result));
+            }
+        });
+        Server {
+            url,
+            token: String::new(),
+        }
+    }
+
+    fn answer(stream: std::net::TcpStream, result: &[u8]) {
+        use std::io::{BufRead, BufReader, Write};
+
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut stream = stream;
+        loop {
+            let mut request_line = String::new();
+            if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+                return;
+            }
+            let mut length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = header.split_once(':')
+                    && name.eq_ignore_ascii_case("content-length")
+                {
+                    length = value.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            let mut parts = request_line.split_whitespace();
+            let reply: &[u8] = match (parts.next(), parts.next()) {
+                (Some("POST"), _) => b"7",
+                (Some("GET"), Some(path)) if path.ends_with("/result") => result,
+                (Some("GET"), _) => b"done",
+                _ => b"",
+            };
+            let head = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", reply.len());
+            if stream.write_all(head.as_bytes()).is_err() || stream.write_all(reply).is_err() {
+                return;
+            }
+        }
+    }
+
+    #[test]
+    fn jobs_run_one_after_another_on_the_same_server() {
+        let server = serve_done(b"RIFF cleaned");
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("take.wav");
+        std::fs::write(&input, b"RIFF").unwrap();
+        let limits = engine::Limits::for_duration(60.0, 1.0);
+
+        for name in ["first.wav", "second.wav"] {
+            let output = dir.path().join(name);
+            run(
+                &server,
+                &input,
+                &output,
+                &limits,
+                &CancelToken::new(),
+                &mut |_| {},
+            )
+            .unwrap();
+            assert_eq!(std::fs::read(&output).unwrap(), b"RIFF cleaned");
+        }
+    }
+
+    #[test]
+    fn cancel_ends_a_request_the_server_never_answers() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = Server {
+            url: format!("http://{}", listener.local_addr().unwrap()),
+            token: String::new(),
+        };
+        // Takes the upload and never answers.
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buffer = [0; 4096];
+            while stream.read(&mut buffer).is_ok_and(|read| read > 0) {}
+        });
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("take.wav");
+        std::fs::write(&input, b"RIFF").unwrap();
+        let cancel = CancelToken::new();
+        let cancel_later = cancel.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(200));
+            cancel_later.cancel();
+        });
+
+        let started = Instant::now();
+        let result = run(
+            &server,
+            &input,
+            &dir.path().join("out.wav"),
+            &engine::Limits::for_duration(60.0, 1.0),
+            &cancel,
+            &mut |_| {},
+        );
+        assert_eq!(result, Err(CANCELLED.to_string()));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
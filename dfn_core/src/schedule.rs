@@ -0,0 +1,101 @@
+//! Starting work at a set time of day, like a heavy batch at 2 AM while
+//! nobody needs the machine. Times are local, in the system's time zone
+//! with its daylight saving rules.
+
+use jiff::Zoned;
+use jiff::civil::Time;
+use std::time::SystemTime;
+
+/// The time of day in `text`, like `2:00` or `14:30`, as hours and minutes.
+pub fn parse_time(text: &str) -> Option<(u32, u32)> {
+    let (hour, minute) = text.trim().split_once(':')?;
+    let hour: u32 = hour.trim().parse().ok()?;
+    let minute = minute.trim();
+    if minute.len() != 2 {
+        return None;
+    }
+    let minute: u32 = minute.parse().ok()?;
+    (hour < 24 && minute < 60).then_some((hour, minute))
+}
+
+/// When `hour:minute`, local time, next comes round after now.
+pub fn next(hour: u32, minute: u32) -> Result<SystemTime, String> {
+    next_after(&Zoned::now(), hour, minute).map(|at| at.timestamp().into())
+}
+
+/// When `hour:minute` next comes round after `now`, in its time zone. A time
+/// skipped by a change to summer time comes round as the clocks skip past
+/// it, so 2:30 starts at 3:30 on that day.
+fn next_after(now: &Zoned, hour: u32, minute: u32) -> Result<Zoned, String> {
+    let error = |e: jiff::Error| format!("Could not work out the start time: {}", e);
+    let time = Time::new(hour as i8, minute as i8, 0, 0).map_err(error)?;
+    let today = now
+        .date()
+        .to_datetime(time)
+        .to_zoned(now.time_zone().clone())
+        .map_err(error)?;
+    if today > *now {
+        return Ok(today);
+    }
+    let tomorrow = now.date().tomorrow().map_err(error)?;
+    tomorrow
+        .to_datetime(time)
+        .to_zoned(now.time_zone().clone())
+        .map_err(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jiff::civil::date;
+    use jiff::tz::TimeZone;
+    use std::time::Duration;
+
+    /// US Eastern time, which skipped from 2:00 to 3:00 on 2024-03-10.
+    fn new_york() -> TimeZone {
+        TimeZone::posix("EST5EDT,M3.2.0,M11.1.0").unwrap()
+    }
+
+    #[test]
+    fn times_of_day_are_read() {
+        assert_eq!(parse_time("2:00"), Some((2, 0)));
+        assert_eq!(parse_time(" 14:30 "), Some((14, 30)));
+        assert_eq!(parse_time("02:05"), Some((2, 5)));
+        for text in ["24:00", "7:60", "7:5", "7", "", "a:bc"] {
+            assert_eq!(parse_time(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn next_start_is_today_or_tomorrow() {
+        let now = date(2024, 1, 10)
+            .at(14, 0, 0, 0)
+            .to_zoned(new_york())
+            .unwrap();
+        let at = next_after(&now, 2, 0).unwrap();
+        assert_eq!(at.datetime(), date(2024, 1, 11).at(2, 0, 0, 0));
+        let at = next_after(&now, 18, 30).unwrap();
+        assert_eq!(at.datetime(), date(2024, 1, 10).at(18, 30, 0, 0));
+
+        let now = SystemTime::now();
+        let at = next(2, 0).unwrap();
+        assert!(at > now);
+        assert!(at <= now + Duration::from_secs(25 * 60 * 60));
+    }
+
+    #[test]
+    fn skipped_times_start_once_the_clocks_skip_past() {
+        let now = date(2024, 3, 10)
+            .at(0, 30, 0, 0)
+            .to_zoned(new_york())
+            .unwrap();
+        let at = next_after(&now, 2, 30).unwrap();
+        assert_eq!(at.datetime(), date(2024, 3, 10).at(3, 30, 0, 0));
+        // Three hours after 23:30 the night before, as on any other night.
+        let evening = date(2024, 3, 9)
+            .at(23, 30, 0, 0)
+            .to_zoned(new_york())
+            .unwrap();
+        assert_eq!(evening.duration_until(&at).as_secs(), 3 * 60 * 60);
+    }
+}
@@ -0,0 +1,134 @@
+//! How fast the engine cleans audio on this machine, learned from earlier
+//! runs. The engine doesn't report progress, so its progress is estimated
+//! from how long it has been running and how fast it usually is.
+//!
+//! Speeds are kept per engine version in `engine-speed.txt` of the data
+//! folder, one `<version> <realtime factor>` line each.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Weight of the latest run in the remembered speed, so it follows changes
+/// of the machine without jumping on a single odd run.
+const SMOOTHING: f32 = 0.3;
+
+/// Estimates stop here until the run ends, as it may be slower than usual.
+const MAX_ESTIMATE: f32 = 95.0;
+
+/// Runs shorter than this say more about start-up than about speed.
+const MIN_RECORDED_AUDIO: Duration = Duration::from_secs(5);
+
+/// The speeds remembered on this machine.
+#[derive(Debug, Clone)]
+pub struct SpeedLog {
+    path: PathBuf,
+}
+
+impl SpeedLog {
+    /// The speeds in the app's data folder.
+    pub fn open() -> Result<Self, String> {
+        let dir = crate::data_dir().ok_or("Could not find project directories")?;
+        Ok(Self::open_at(&dir.join("engine-speed.txt")))
+    }
+
+    /// The speeds in the file at `path`, made on first record.
+    pub fn open_at(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+
+    /// Seconds of audio the engine of `version` usually cleans per second.
+    pub fn realtime_factor(&self, version: &str) -> Option<f32> {
+        self.entries()
+            .into_iter()
+            .find(|(entry, _)| entry == version)
+            .map(|(_, factor)| factor)
+    }
+
+    /// Takes a run of the engine of `version` that cleaned `audio` in
+    /// `elapsed` into account.
+    pub fn record(&self, version: &str, audio: Duration, elapsed: Duration) -> Result<(), String> {
+        if audio < MIN_RECORDED_AUDIO || elapsed.is_zero() {
+            return Ok(());
+        }
+        let factor = audio.as_secs_f32() / elapsed.as_secs_f32();
+        let mut entries = self.entries();
+        match entries.iter_mut().find(|(entry, _)| entry == version) {
+            Some((_, known)) => *known += (factor - *known) * SMOOTHING,
+            None => entries.push((version.to_string(), factor)),
+        }
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Could not create data folder: {}", e))?;
+        }
+        let lines: String = entries
+            .iter()
+            .map(|(version, factor)| format!("{} {}\n", version, factor))
+            .collect();
+        std::fs::write(&self.path, lines)
+            .map_err(|e| format!("Could not write {}: {}", self.path.display(), e))
+    }
+
+    /// Versions with their speeds; an unreadable file counts as empty.
+    fn entries(&self) -> Vec<(String, f32)> {
+        std::fs::read_to_string(&self.path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| {
+                let (version, factor) = line.split_once(' ')?;
+                let factor: f32 = factor.trim().parse().ok()?;
+                (factor.is_finite() && factor > 0.0).then(|| (version.to_string(), factor))
+            })
+            .collect()
+    }
+}
+
+/// Progress in percent of a run cleaning `audio` that has been going for
+/// `elapsed`, at `realtime_factor`.
+pub fn estimate(elapsed: Duration, audio: Duration, realtime_factor: f32) -> f32 {
+    let expected = audio.as_secs_f32() / realtime_factor;
+    if expected <= 0.0 {
+        return 0.0;
+    }
+    (elapsed.as_secs_f32() / expected * 100.0).min(MAX_ESTIMATE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speeds_are_kept_per_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = SpeedLog::open_at(&dir.path().join("data").join("engine-speed.txt"));
+        assert_eq!(log.realtime_factor("0.5.6"), None);
+
+        let minute = Duration::from_secs(60);
+        log.record("0.5.6", minute, Duration::from_secs(6)).unwrap();
+        log.record("0.5.5", minute, Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(log.realtime_factor("0.5.6"), Some(10.0));
+        assert_eq!(log.realtime_factor("0.5.5"), Some(1.0));
+
+        // A slower run pulls the speed down, but only part of the way.
+        log.record("0.5.6", minute, Duration::from_secs(12))
+            .unwrap();
+        assert_eq!(log.realtime_factor("0.5.6"), Some(8.5));
+        // Too short to tell.
+        log.record("0.5.6", Duration::from_secs(1), Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(log.realtime_factor("0.5.6"), Some(8.5));
+    }
+
+    #[test]
+    fn estimates_stop_short_of_done() {
+        let minute = Duration::from_secs(60);
+        assert_eq!(estimate(Duration::from_secs(3), minute, 10.0), 50.0);
+        assert_eq!(
+            estimate(Duration::from_secs(30), minute, 10.0),
+            MAX_ESTIMATE
+        );
+        assert_eq!(estimate(Duration::ZERO, Duration::ZERO, 10.0), 0.0);
+    }
+}
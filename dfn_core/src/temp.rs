@@ -0,0 +1,151 @@
+//! Scratch files of runs, kept in a folder of the app's cache. Each job gets
+//! a folder of its own, removed with the [`JobDir`] however the job ends.
+//! Folders left by a session that crashed or was killed are removed by
+//! [`TempStore::remove_stale`] at the next start.
+//!
+//! A job holds a lock on a file in its folder for as long as it runs, which
+//! the system lets go of when the process dies. A folder whose lock can be
+//! taken is therefore left over, even with other sessions running.
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// File each job folder is locked through.
+const LOCK_FILE: &str = ".lock";
+
+/// Where the scratch folders of jobs are made.
+#[derive(Debug, Clone)]
+pub struct TempStore {
+    root: PathBuf,
+}
+
+/// The scratch folder of a job, removed with everything in it when dropped.
+#[derive(Debug)]
+pub struct JobDir {
+    path: PathBuf,
+    lock: Option<File>,
+}
+
+impl TempStore {
+    /// The store in the app's cache folder, or in the system's temporary
+    /// folder if there's none.
+    pub fn open() -> Self {
+        Self::open_at(match crate::cache_dir() {
+            Some(dir) => dir.join("temp"),
+            None => std::env::temp_dir().join("dfn_gui"),
+        })
+    }
+
+    /// The store in `root`, made on first use.
+    pub fn open_at(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// A new scratch folder for a job, named after `name`.
+    pub fn job(&self, name: &str) -> Result<JobDir, String> {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        let path = self.root.join(format!(
+            "{}-{}-{}",
+            name,
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&path)
+            .map_err(|e| format!("Could not create temporary directory: {}", e))?;
+        // From here on, dropping the folder removes it.
+        let mut job = JobDir { path, lock: None };
+        let lock = File::create(job.path.join(LOCK_FILE))
+            .map_err(|e| format!("Could not create temporary directory: {}", e))?;
+        lock.lock()
+            .map_err(|e| format!("Could not lock temporary directory: {}", e))?;
+        job.lock = Some(lock);
+        Ok(job)
+    }
+
+    /// Removes the folders of jobs no longer running, returning how many
+    /// were removed.
+    pub fn remove_stale(&self) -> usize {
+        let Ok(entries) = std::fs::read_dir(&self.root) else {
+            return 0;
+        };
+        entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir() && is_stale(path))
+            .filter(|path| std::fs::remove_dir_all(path).is_ok())
+            .count()
+    }
+}
+
+/// Whether no job holds the lock of the folder at `path`.
+fn is_stale(path: &Path) -> bool {
+    match File::open(path.join(LOCK_FILE)) {
+        // Let go of right away, so the folder can be removed.
+        Ok(lock) => lock.try_lock().is_ok(),
+        // Left by a job that died before it could take the lock.
+        Err(_) => true,
+    }
+}
+
+impl JobDir {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Where the job's scratch file `name` goes.
+    pub fn file(&self, name: impl AsRef<Path>) -> PathBuf {
+        self.path.join(name)
+    }
+}
+
+impl Drop for JobDir {
+    fn drop(&mut self) {
+        // Windows won't remove a file that's open.
+        drop(self.lock.take());
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn job_folders_are_removed_when_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TempStore::open_at(dir.path().to_path_buf());
+        let job = store.job("take").unwrap();
+        let other = store.job("take").unwrap();
+        assert_ne!(job.path(), other.path());
+
+        std::fs::write(job.file("encoded.wav"), b"RIFF").unwrap();
+        let path = job.path().to_path_buf();
+        drop(job);
+        assert!(!path.exists());
+        assert!(other.path().exists());
+    }
+
+    #[test]
+    fn only_folders_of_ended_jobs_are_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TempStore::open_at(dir.path().to_path_buf());
+        let running = store.job("running").unwrap();
+        // As left by a session that was killed.
+        let ended = dir.path().join("ended-1-0");
+        std::fs::create_dir(&ended).unwrap();
+        std::fs::write(ended.join(LOCK_FILE), b"").unwrap();
+        std::fs::write(ended.join("part.wav"), b"RIFF").unwrap();
+
+        assert_eq!(store.remove_stale(), 1);
+        assert!(!ended.exists());
+        assert!(running.path().exists());
+    }
+
+    #[test]
+    fn missing_store_has_nothing_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TempStore::open_at(dir.path().join("missing"));
+        assert_eq!(store.remove_stale(), 0);
+    }
+}
@@ -0,0 +1,174 @@
+//! Encoding to compressed formats, and decoding those Symphonia can't read,
+//! through an external `ffmpeg`, which is used when it is installed rather
+//! than downloaded like the engine. A cancelled or failed ffmpeg is stopped
+//! and what it wrote is removed.
+
+use crate::cancel::{CANCELLED, CancelToken};
+use crate::formats;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command as StdCommand, Stdio};
+use std::time::Duration;
+
+/// How often a running ffmpeg is checked on.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[cfg(windows)]
+const FFMPEG_NAME: &str = "ffmpeg.exe";
+#[cfg(not(windows))]
+const FFMPEG_NAME: &str = "ffmpeg";
+
+/// Locates `ffmpeg` on the `PATH`.
+pub fn ffmpeg_path() -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(FFMPEG_NAME))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Converts the WAV at `input` into `output`, letting ffmpeg pick the codec
+/// from the output extension. `codec_args`, from [`formats::Format::codec_args`],
+/// are passed before the output path.
+/// Tags, chapters and cover art are copied from `tags_from` when given.
+pub fn encode(
+    input: &Path,
+    output: &Path,
+    codec_args: &[String],
+    tags_from: Option<&Path>,
+    cancel: &CancelToken,
+) -> Result<(), String> {
+    let ffmpeg = ffmpeg_path().ok_or_else(|| {
+        format!(
+            "Saving as .{} needs ffmpeg, which was not found on PATH",
+            output.extension().unwrap_or_default().to_string_lossy()
+        )
+    })?;
+
+    let mut command = ffmpeg_command(ffmpeg);
+    command.arg("-i").arg(input);
+    if let Some(source) = tags_from {
+        command
+            .arg("-i")
+            .arg(source)
+            .args(["-map", "0:a", "-map_metadata", "1"]);
+        let holds_cover = formats::of(output).is_some_and(|format| format.holds_cover);
+        // Cover art shows up as a video stream; real video must not be copied.
+        if holds_cover && !formats::is_video(source) {
+            command.args([
+                "-map",
+                "1:v?",
+                "-c:v",
+                "copy",
+                "-disposition:v",
+                "attached_pic",
+            ]);
+        }
+    }
+    command.args(codec_args).arg(output);
+    run(command, output, "encode the output", cancel)
+}
+
+/// Converts the first audio track of `input` into a float WAV at `output`.
+pub fn to_wav(input: &Path, output: &Path, cancel: &CancelToken) -> Result<(), String> {
+    let ffmpeg = ffmpeg_path().ok_or_else(|| {
+        format!(
+            "Reading .{} files needs ffmpeg, which was not found on PATH",
+            input.extension().unwrap_or_default().to_string_lossy()
+        )
+    })?;
+
+    let mut command = ffmpeg_command(ffmpeg);
+    command
+        .arg("-i")
+        .arg(input)
+        .args(["-map", "0:a:0", "-c:a", "pcm_f32le"])
+        .arg(output);
+    run(command, output, "convert the input", cancel)
+}
+
+/// Writes a copy of `video` to `output` with its audio replaced by `audio`,
+/// an uncompressed WAV. Video and subtitle streams are copied as they are;
+/// the audio is encoded to AAC at `bitrate_kbps`, its only lossy step.
+pub fn remux(
+    video: &Path,
+    audio: &Path,
+    output: &Path,
+    bitrate_kbps: u32,
+    cancel: &CancelToken,
+) -> Result<(), String> {
+    let ffmpeg =
+        ffmpeg_path().ok_or("Writing cleaned video needs ffmpeg, which was not found on PATH")?;
+
+    let mut command = ffmpeg_command(ffmpeg);
+    command
+        .arg("-i")
+        .arg(video)
+        .arg("-i")
+        .arg(audio)
+        .args(["-map", "0", "-map", "-0:a", "-map", "1:a"])
+        .args(["-c", "copy", "-c:a", "aac", "-b:a"])
+        .arg(format!("{}k", bitrate_kbps))
+        .arg(output);
+    run(command, output, "write the video", cancel)
+}
+
+fn ffmpeg_command(ffmpeg: PathBuf) -> StdCommand {
+    let mut command = StdCommand::new(ffmpeg);
+    command
+        .arg("-hide_banner")
+        .arg("-loglevel")
+        .arg("error")
+        .arg("-y")
+        .stdin(Stdio::null());
+    command
+}
+
+/// Runs ffmpeg writing `output`, removing the file again if it fails or
+/// `cancel` is cancelled first.
+fn run(
+    mut command: StdCommand,
+    output: &Path,
+    action: &str,
+    cancel: &CancelToken,
+) -> Result<(), String> {
+    let mut child = command
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+    // Read on the side, so a full pipe can't hold ffmpeg up.
+    let stderr = child.stderr.take().map(|mut pipe| {
+        std::thread::spawn(move || {
+            let mut text = String::new();
+            let _ = pipe.read_to_string(&mut text);
+            text
+        })
+    });
+
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Ok(status),
+            Ok(None) => {}
+            Err(e) => break Err(format!("Lost track of ffmpeg: {}", e)),
+        }
+        if cancel.is_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            break Err(CANCELLED.to_string());
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    };
+    let result = status.and_then(|status| {
+        if status.success() {
+            return Ok(());
+        }
+        let stderr = stderr
+            .and_then(|reader| reader.join().ok())
+            .unwrap_or_default();
+        Err(format!("ffmpeg could not {}\n{}", action, stderr.trim()))
+    });
+    if result.is_err() {
+        let _ = std::fs::remove_file(output);
+    }
+    result
+}
@@ -0,0 +1,658 @@
+//! Updates of the app itself from its GitHub releases. Each release carries
+//! a build for every platform next to a `.sha256` file, and a build is only
+//! put in place once it matches its checksum. The [`Channel`] decides
+//! whether pre-releases of the app and the engine are offered.
+//!
+//! The engine download is looked up in the engine's releases, so a new
+//! release is picked up without a new build of the app. What was found is
+//! kept for a while in the cache folder, and used when GitHub can't be
+//! reached, as is the release the app was made for if nothing was found yet.
+//! Builds for a graphics card (see [`crate::accel`]) are only used where a
+//! release has one.
+
+use crate::accel::Provider;
+use crate::download::{self, Network, Response};
+use crate::i18n::tr;
+use futures_util::StreamExt;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const API: &str = "https://api.github.com/repos";
+
+const APP_REPO: &str = "hahouari/dfn_gui";
+
+const ENGINE_REPO: &str = "Rikorose/DeepFilterNet";
+
+const DOWNLOADS: &str = "https://github.com/hahouari/dfn_gui/releases/download";
+
+/// How long a looked up engine download is used before looking again.
+const ENGINE_LOOKUP_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Which releases are offered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Channel {
+    #[default]
+    Stable,
+    /// Pre-releases as well, of the app and the engine.
+    Beta,
+}
+
+impl Channel {
+    pub const ALL: [Channel; 2] = [Channel::Stable, Channel::Beta];
+
+    /// Name the channel is saved under.
+    pub fn key(self) -> &'static str {
+        match self {
+            Channel::Stable => "stable",
+            Channel::Beta => "beta",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Channel> {
+        Channel::ALL
+            .into_iter()
+            .find(|channel| channel.key() == key)
+    }
+}
+
+impl std::fmt::Display for Channel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Channel::Stable => write!(f, "{}", tr!("channel-stable")),
+            Channel::Beta => write!(f, "{}", tr!("channel-beta")),
+        }
+    }
+}
+
+/// A published version of the app.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Release {
+    /// The version, without the tag's `v`.
+    pub version: String,
+    /// The release notes, in Markdown.
+    pub notes: String,
+    /// Marked as a pre-release on GitHub.
+    pub prerelease: bool,
+    tag: String,
+    /// Where the files published with the release are downloaded from.
+    assets: Vec<String>,
+}
+
+/// A release as GitHub's API describes it.
+#[derive(Deserialize)]
+struct ApiRelease {
+    tag_name: String,
+    body: Option<String>,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    assets: Vec<ApiAsset>,
+}
+
+#[derive(Deserialize)]
+struct ApiAsset {
+    browser_download_url: String,
+}
+
+/// What the API answers: one release, or a list of them newest first.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ApiAnswer {
+    One(ApiRelease),
+    List(Vec<ApiRelease>),
+}
+
+impl Release {
+    /// Reads the release GitHub's API describes in `json`, or the first of
+    /// the releases it lists.
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let release = match serde_json::from_str(json) {
+            Ok(ApiAnswer::One(release)) => release,
+            Ok(ApiAnswer::List(releases)) => releases
+                .into_iter()
+                .next()
+                .ok_or("There are no releases yet")?,
+            Err(_) => return Err("GitHub didn't answer with a release".to_string()),
+        };
+        Ok(Self {
+            version: release.tag_name.trim_start_matches('v').to_string(),
+            notes: release.body.unwrap_or_default(),
+            prerelease: release.prerelease,
+            tag: release.tag_name,
+            assets: release
+                .assets
+                .into_iter()
+                .map(|asset| asset.browser_download_url)
+                .collect(),
+        })
+    }
+
+    /// Where the build for this platform is published.
+    fn build_url(&self) -> Result<String, String> {
+        Ok(format!("{}/{}/{}", DOWNLOADS, self.tag, build_name()?))
+    }
+}
+
+/// Name the build for this platform is published under.
+pub fn build_name() -> Result<&'static str, String> {
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    return Ok("dfn_gui-x86_64-unknown-linux-gnu");
+
+    #[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+    return Ok("dfn_gui-aarch64-unknown-linux-gnu");
+
+    #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
+    return Ok("dfn_gui-aarch64-apple-darwin");
+
+    #[cfg(all(target_os = "windows", target_arch = "x86_64"))]
+    return Ok("dfn_gui-x86_64-pc-windows-msvc.exe");
+
+    #[allow(unreachable_code)]
+    Err("Unsupported OS/Architecture".to_string())
+}
+
+/// Whether `version` comes after `current`. Both are dotted numbers,
+/// optionally followed by a `-` and a pre-release, which comes before the
+/// release it leads up to. Pre-releases are ordered as SemVer says: part by
+/// part, numbers by value and before words, so `beta.10` follows `beta.9`.
+/// Build details after a `+` are left out.
+pub fn is_newer(version: &str, current: &str) -> bool {
+    /// A part of a pre-release. Numbers come first.
+    #[derive(PartialEq, Eq, PartialOrd, Ord)]
+    enum Identifier<'a> {
+        Number(u64),
+        Word(&'a str),
+    }
+
+    /// The numbers, whether it's a release, and the pre-release.
+    fn order(version: &str) -> Option<(Vec<u64>, bool, Vec<Identifier<'_>>)> {
+        let version = version.trim_start_matches('v').split('+').next()?;
+        let (numbers, prerelease) = version.split_once('-').unwrap_or((version, ""));
+        let numbers = numbers
+            .split('.')
+            .map(|part| part.parse().ok())
+            .collect::<Option<_>>()?;
+        let prerelease = prerelease
+            .split('.')
+            .filter(|part| !part.is_empty())
+            .map(|part| match part.parse() {
+                Ok(number) if part.bytes().all(|byte| byte.is_ascii_digit()) => {
+                    Identifier::Number(number)
+                }
+                _ => Identifier::Word(part),
+            })
+            .collect::<Vec<_>>();
+        Some((numbers, prerelease.is_empty(), prerelease))
+    }
+    match (order(version), order(current)) {
+        (Some(version), Some(current)) => version > current,
+        _ => false,
+    }
+}
+
+/// Whether `version` is a pre-release, like `0.3.0-beta.1`.
+pub fn is_prerelease(version: &str) -> bool {
+    version
+        .split('+')
+        .next()
+        .is_some_and(|version| version.contains('-'))
+}
+
+/// The release to offer on `channel` to users of version `current`: a newer
+/// one, or on the stable channel the latest stable release to go back to
+/// from a pre-release.
+pub async fn check(
+    network: &dyn Network,
+    current: &str,
+    channel: Channel,
+) -> Result<Option<Release>, String> {
+    let release = latest(network, APP_REPO, channel).await?;
+    let back_to_stable =
+        channel == Channel::Stable && is_prerelease(current) && release.version != current;
+    Ok((is_newer(&release.version, current) || back_to_stable).then_some(release))
+}
+
+/// Where the engine is downloaded from on `channel`: the build for this
+/// platform and `provider` in the newest release, stable or not depending
+/// on `channel`.
+pub async fn engine_url(
+    network: &dyn Network,
+    channel: Channel,
+    provider: Provider,
+) -> Result<String, String> {
+    let name = match provider {
+        Provider::Cpu => format!("engine-{}.url", channel.key()),
+        provider => format!("engine-{}-{}.url", channel.key(), provider.key()),
+    };
+    let cache = crate::cache_dir().map(|dir| dir.join(name));
+    engine_url_cached(network, channel, provider, cache.as_deref()).await
+}
+
+/// [`engine_url`], with what was looked up last kept at `cache`.
+async fn engine_url_cached(
+    network: &dyn Network,
+    channel: Channel,
+    provider: Provider,
+    cache: Option<&Path>,
+) -> Result<String, String> {
+    let cached = cache.and_then(read_cached);
+    if let Some((url, age)) = &cached
+        && *age < ENGINE_LOOKUP_TTL
+    {
+        return Ok(url.clone());
+    }
+    let targets: Vec<String> = engine_targets()
+        .iter()
+        .map(|target| provider.target(target))
+        .collect();
+    let found = latest(network, ENGINE_REPO, channel)
+        .await
+        .and_then(|release| engine_asset(&release.assets, &targets).map(str::to_string));
+    match found {
+        Ok(url) => {
+            if let Some(path) = cache {
+                let _ = std::fs::create_dir_all(path.parent().unwrap_or(path));
+                let _ = std::fs::write(path, &url);
+            }
+            Ok(url)
+        }
+        Err(e) => {
+            tracing::warn!("Could not look up the engine release: {}", e);
+            match cached {
+                Some((url, _)) => Ok(url),
+                None if provider == Provider::Cpu => download::release_url(),
+                None => Err(e),
+            }
+        }
+    }
+}
+
+/// The URL kept at `path`, and how long ago it was looked up.
+fn read_cached(path: &Path) -> Option<(String, Duration)> {
+    let url = std::fs::read_to_string(path).ok()?;
+    let age = std::fs::metadata(path).ok()?.modified().ok()?.elapsed();
+    // A clock set back makes it look new, which it may well be.
+    Some((url.trim().to_string(), age.unwrap_or_default())).filter(|(url, _)| !url.is_empty())
+}
+
+/// Endings of the engine builds that run here, best first. The musl builds
+/// run on any Linux, the glibc ones only next to glibc.
+fn engine_targets() -> Vec<String> {
+    let arch = std::env::consts::ARCH;
+    match std::env::consts::OS {
+        "linux" if cfg!(target_env = "musl") => vec![format!("{}-unknown-linux-musl", arch)],
+        "linux" => vec![
+            format!("{}-unknown-linux-musl", arch),
+            format!("{}-unknown-linux-gnu", arch),
+        ],
+        "macos" => vec![format!("{}-apple-darwin", arch)],
+        "windows" => vec![
+            format!("{}-pc-windows-msvc.exe", arch),
+            format!("{}-pc-windows-gnu.exe", arch),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+/// The engine build among the release files `assets` ending in the first
+/// of `targets` that has one.
+fn engine_asset<'a>(assets: &'a [String], targets: &[String]) -> Result<&'a str, String> {
+    targets
+        .iter()
+        .find_map(|target| {
+            assets.iter().find(|url| {
+                let name = url.rsplit('/').next().unwrap_or(url);
+                name.starts_with("deep-filter-") && name.ends_with(target.as_str())
+            })
+        })
+        .map(String::as_str)
+        .ok_or_else(|| "The engine release has no build for this platform".to_string())
+}
+
+/// The newest release of the GitHub repository `repo` on `channel`.
+async fn latest(network: &dyn Network, repo: &str, channel: Channel) -> Result<Release, String> {
+    let url = match channel {
+        Channel::Stable => format!("{}/{}/releases/latest", API, repo),
+        // Listed newest first, pre-releases included.
+        Channel::Beta => format!("{}/{}/releases?per_page=1", API, repo),
+    };
+    let json = read_all(network.get(&url).await?).await?;
+    Release::parse(&String::from_utf8_lossy(&json))
+}
+
+/// Downloads the build of `release` for this platform next to `exe`, once
+/// it matches its published checksum, and returns where it was written.
+pub async fn download(
+    network: &dyn Network,
+    release: &Release,
+    exe: &Path,
+) -> Result<PathBuf, String> {
+    let url = release.build_url()?;
+    let checksum = read_all(network.get(&format!("{}.sha256", url)).await?).await?;
+    // `sha256sum` output: the digest, then the file name.
+    let expected = String::from_utf8_lossy(&checksum)
+        .split_whitespace()
+        .next()
+        .map(str::to_ascii_lowercase)
+        .ok_or("The checksum file is empty")?;
+    let build = read_all(network.get(&url).await?).await?;
+    if sha256(&build) != expected {
+        return Err("The download doesn't match its checksum".to_string());
+    }
+
+    let staged = suffixed(exe, "new");
+    std::fs::write(&staged, &build).map_err(|e| format!("Could not save the update: {}", e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755));
+    }
+    Ok(staged)
+}
+
+/// Puts the build at `staged` in place of `exe`. A running executable can't
+/// be overwritten on Windows but can be renamed, so `exe` is moved aside
+/// first and removed on the next start by [`remove_replaced`].
+pub fn install(staged: &Path, exe: &Path) -> Result<(), String> {
+    let replaced = suffixed(exe, "old");
+    let _ = std::fs::remove_file(&replaced);
+    std::fs::rename(exe, &replaced).map_err(|e| format!("Could not replace the app: {}", e))?;
+    if let Err(e) = std::fs::rename(staged, exe) {
+        let _ = std::fs::rename(&replaced, exe);
+        return Err(format!("Could not replace the app: {}", e));
+    }
+    let _ = std::fs::remove_file(&replaced);
+    Ok(())
+}
+
+/// Removes what [`install`] left of the previous version.
+pub fn remove_replaced(exe: &Path) {
+    let _ = std::fs::remove_file(suffixed(exe, "old"));
+}
+
+/// `path` with `.suffix` added to its file name.
+fn suffixed(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+async fn read_all(response: Response) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    let mut chunks = response.chunks;
+    while let Some(chunk) = chunks.next().await {
+        body.extend_from_slice(&chunk?);
+    }
+    Ok(body)
+}
+
+/// SHA-256 digest of `data` in lowercase hex.
+fn sha256(data: &[u8]) -> String {
+    aws_lc_rs::digest::digest(&aws_lc_rs::digest::SHA256, data)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use futures_util::FutureExt;
+    use futures_util::future::BoxFuture;
+
+    /// Serves the body listed for each URL.
+    struct Canned(Vec<(String, &'static [u8])>);
+
+    impl Network for Canned {
+        fn get(&self, url: &str) -> BoxFuture<'static, Result<Response, String>> {
+            let body = self
+                .0
+                .iter()
+                .find(|(canned, _)| canned == url)
+                .map(|(_, body)| Bytes::from_static(body))
+                .ok_or_else(|| format!("404: {}", url));
+            async move {
+                let body = body?;
+                Ok(Response {
+                    length: Some(body.len() as u64),
+                    chunks: futures_util::stream::iter([Ok(body)]).boxed(),
+                })
+            }
+            .boxed()
+        }
+
+        fn size(&self, _url: &str) -> BoxFuture<'static, Option<u64>> {
+            async { None }.boxed()
+        }
+    }
+
+    fn release() -> Release {
+        Release::parse(r#"{"tag_name": "v0.2.0", "body": "Fixes"}"#).unwrap()
+    }
+
+    #[test]
+    fn release_is_read_from_the_api_answer() {
+        let json = r#"{"url":"x","tag_name":"v1.2.0","author":{"login":"a"},
+            "body":"What's new:\r\n- \"Quotes\" \u00e9 \ud83d\ude00\n- C:\\temp","assets":[]}"#;
+        let release = Release::parse(json).unwrap();
+        assert_eq!(release.version, "1.2.0");
+        assert!(!release.prerelease);
+        assert_eq!(
+            release.notes,
+            "What's new:\r\n- \"Quotes\" é 😀\n- C:\\temp"
+        );
+
+        let release =
+            Release::parse(r#"[{"tag_name":"0.3.0-rc.1","prerelease": true,"body":null}]"#)
+                .unwrap();
+        assert_eq!(release.version, "0.3.0-rc.1");
+        assert!(release.prerelease);
+        assert_eq!(release.notes, "");
+        assert!(Release::parse(r#"{"message":"Not Found"}"#).is_err());
+        assert!(Release::parse("[]").is_err());
+    }
+
+    #[test]
+    fn versions_compare_by_number() {
+        assert!(is_newer("0.1.10", "0.1.9"));
+        assert!(is_newer("v1.0.0", "0.9.9"));
+        assert!(is_newer("0.2", "0.1.5"));
+        assert!(!is_newer("0.1.0", "0.1.0"));
+        assert!(!is_newer("nightly", "0.1.0"));
+        assert!(!is_newer("0.1.0+linux", "0.1.0"));
+    }
+
+    #[test]
+    fn prereleases_come_before_their_release() {
+        assert!(is_newer("0.2.0-beta.1", "0.1.0"));
+        assert!(is_newer("0.2.0", "0.2.0-beta.1"));
+        assert!(is_newer("0.2.0-beta.2", "0.2.0-beta.1"));
+        assert!(!is_newer("0.1.0-beta", "0.1.0"));
+        assert!(is_newer("0.2.0-rc.1", "0.2.0-beta.3"));
+        assert!(is_newer("0.2.0", "0.2.0-rc.2"));
+        assert!(!is_newer("0.2.0-rc.2", "0.2.0"));
+        assert!(is_prerelease("0.2.0-rc.1"));
+        assert!(!is_prerelease("0.2.0+build-5"));
+    }
+
+    #[test]
+    fn prerelease_numbers_compare_by_value() {
+        assert!(is_newer("0.2.0-beta.10", "0.2.0-beta.9"));
+        assert!(!is_newer("0.2.0-beta.9", "0.2.0-beta.10"));
+        assert!(is_newer("0.2.0-beta.1.1", "0.2.0-beta.1"));
+        assert!(is_newer("0.2.0-beta.x", "0.2.0-beta.99"));
+    }
+
+    fn answering(url: String, json: &'static [u8]) -> Canned {
+        Canned(vec![(url, json)])
+    }
+
+    #[tokio::test]
+    async fn only_newer_releases_are_offered() {
+        let stable = format!("{}/{}/releases/latest", API, APP_REPO);
+        let newer = answering(stable.clone(), br#"{"tag_name":"v9.0.0"}"#);
+        let offered = check(&newer, "0.1.0", Channel::Stable).await.unwrap();
+        assert_eq!(offered.map(|release| release.version), Some("9.0.0".into()));
+        let same = answering(stable, br#"{"tag_name":"v0.1.0"}"#);
+        assert_eq!(check(&same, "0.1.0", Channel::Stable).await, Ok(None));
+    }
+
+    #[tokio::test]
+    async fn beta_channel_offers_prereleases() {
+        let beta = format!("{}/{}/releases?per_page=1", API, APP_REPO);
+        let network = answering(beta, br#"[{"tag_name":"v0.2.0-beta.1","prerelease":true}]"#);
+        let offered = check(&network, "0.1.0", Channel::Beta).await.unwrap();
+        assert!(offered.is_some_and(|release| release.prerelease));
+        // The stable channel doesn't ask for the list.
+        assert!(check(&network, "0.1.0", Channel::Stable).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn stable_channel_offers_a_way_back_from_prereleases() {
+        let stable = format!("{}/{}/releases/latest", API, APP_REPO);
+        let network = answering(stable, br#"{"tag_name":"v0.1.0"}"#);
+        let offered = check(&network, "0.2.0-beta.1", Channel::Stable)
+            .await
+            .unwrap();
+        assert_eq!(offered.map(|release| release.version), Some("0.1.0".into()));
+        assert_eq!(check(&network, "0.1.0", Channel::Stable).await, Ok(None));
+    }
+
+    /// Files of an engine release, as GitHub lists them.
+    const ENGINE_RELEASE: &[u8] = br#"{"tag_name":"v0.6.0","assets":[
+        {"name":"DeepFilterNet3_onnx.tar.gz","browser_download_url":"https://x/v0.6.0/DeepFilterNet3_onnx.tar.gz"},
+        {"name":"deep-filter-0.6.0-x86_64-unknown-linux-gnu-cuda","browser_download_url":"https://x/v0.6.0/deep-filter-0.6.0-x86_64-unknown-linux-gnu-cuda"},
+        {"name":"deep-filter-0.6.0-x86_64-unknown-linux-gnu","browser_download_url":"https://x/v0.6.0/deep-filter-0.6.0-x86_64-unknown-linux-gnu"},
+        {"name":"deep-filter-0.6.0-x86_64-unknown-linux-musl","browser_download_url":"https://x/v0.6.0/deep-filter-0.6.0-x86_64-unknown-linux-musl"},
+        {"name":"deep-filter-0.6.0-aarch64-apple-darwin","browser_download_url":"https://x/v0.6.0/deep-filter-0.6.0-aarch64-apple-darwin"}]}"#;
+
+    #[test]
+    fn engine_build_is_matched_to_the_platform() {
+        let assets = Release::parse(&String::from_utf8_lossy(ENGINE_RELEASE))
+            .unwrap()
+            .assets;
+        assert_eq!(assets.len(), 5);
+        let linux = [
+            "x86_64-unknown-linux-musl".to_string(),
+            "x86_64-unknown-linux-gnu".to_string(),
+        ];
+        assert_eq!(
+            engine_asset(&assets, &linux),
+            Ok("https://x/v0.6.0/deep-filter-0.6.0-x86_64-unknown-linux-musl")
+        );
+        assert_eq!(
+            engine_asset(&assets, &linux[1..]),
+            Ok("https://x/v0.6.0/deep-filter-0.6.0-x86_64-unknown-linux-gnu")
+        );
+        let cuda: Vec<String> = linux
+            .iter()
+            .map(|target| Provider::Cuda.target(target))
+            .collect();
+        assert_eq!(
+            engine_asset(&assets, &cuda),
+            Ok("https://x/v0.6.0/deep-filter-0.6.0-x86_64-unknown-linux-gnu-cuda")
+        );
+        let windows = ["x86_64-pc-windows-msvc.exe".to_string()];
+        assert!(engine_asset(&assets, &windows).is_err());
+    }
+
+    #[tokio::test]
+    async fn engine_lookup_is_cached() {
+        let Some(target) = engine_targets().into_iter().next() else {
+            return;
+        };
+        let json: &'static str = format!(
+            r#"[{{"tag_name":"v0.6.0-pre","assets":[{{"browser_download_url":"https://x/deep-filter-0.6.0-pre-{}"}}]}}]"#,
+            target
+        )
+        .leak();
+        let beta = format!("{}/{}/releases?per_page=1", API, ENGINE_REPO);
+        let network = answering(beta, json.as_bytes());
+        let dir = tempfile::tempdir().unwrap();
+        let cache = dir.path().join("engine-beta.url");
+        let found = format!("https://x/deep-filter-0.6.0-pre-{}", target);
+        assert_eq!(
+            engine_url_cached(&network, Channel::Beta, Provider::Cpu, Some(&cache)).await,
+            Ok(found.clone())
+        );
+
+        // Offline, what was found last is used even once it's old.
+        let looked_up = std::time::SystemTime::now() - 2 * ENGINE_LOOKUP_TTL;
+        std::fs::File::options()
+            .write(true)
+            .open(&cache)
+            .unwrap()
+            .set_modified(looked_up)
+            .unwrap();
+        let offline = Canned(Vec::new());
+        assert_eq!(
+            engine_url_cached(&offline, Channel::Beta, Provider::Cpu, Some(&cache)).await,
+            Ok(found)
+        );
+    }
+
+    #[tokio::test]
+    async fn engine_falls_back_to_the_release_made_for() {
+        let offline = Canned(Vec::new());
+        for channel in Channel::ALL {
+            assert_eq!(
+                engine_url_cached(&offline, channel, Provider::Cpu, None).await,
+                download::release_url()
+            );
+        }
+        assert!(
+            engine_url_cached(&offline, Channel::Stable, Provider::Cuda, None)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn download_is_checked_against_its_checksum() {
+        let Ok(url) = release().build_url() else {
+            return;
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let exe = dir.path().join("dfn_gui");
+        let digest = sha256(b"new build");
+        let checksum: &'static str = format!("{}  {}\n", digest, build_name().unwrap()).leak();
+
+        let network = Canned(vec![
+            (url.clone(), b"new build"),
+            (format!("{}.sha256", url), checksum.as_bytes()),
+        ]);
+        let staged = download(&network, &release(), &exe).await.unwrap();
+        assert_eq!(staged, dir.path().join("dfn_gui.new"));
+        assert_eq!(std::fs::read(&staged).unwrap(), b"new build");
+
+        let tampered = Canned(vec![
+            (url.clone(), b"other build"),
+            (format!("{}.sha256", url), checksum.as_bytes()),
+        ]);
+        std::fs::remove_file(&staged).unwrap();
+        assert!(download(&tampered, &release(), &exe).await.is_err());
+        assert!(!staged.exists());
+    }
+
+    #[test]
+    fn install_swaps_the_executable() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = dir.path().join("dfn_gui");
+        let staged = dir.path().join("dfn_gui.new");
+        std::fs::write(&exe, "old").unwrap();
+        std::fs::write(&staged, "new").unwrap();
+
+        install(&staged, &exe).unwrap();
+        assert_eq!(std::fs::read_to_string(&exe).unwrap(), "new");
+        assert!(!staged.exists());
+        assert!(!suffixed(&exe, "old").exists());
+
+        // A staged build that went missing leaves the app as it was.
+        assert!(install(&staged, &exe).is_err());
+        assert_eq!(std::fs::read_to_string(&exe).unwrap(), "new");
+    }
+}
@@ -0,0 +1,291 @@
+//! Summaries of finished batches, posted as JSON to a URL the user sets up,
+//! for servers and scripts waiting on the results. The body looks like
+//!
+//! ```json
+//! {"event":"batch_finished","succeeded":1,"failed":1,"poor_quality":0,"duplicates":1,
+//!  "audio_seconds":61.2,"elapsed_seconds":4.8,"files":[
+//!   {"input":"a.wav","output":"dnf_clean/a.wav","audio_seconds":61.2,"elapsed_seconds":4.8,
+//!    "provider":"cpu","quality_score":4.1,"poor_quality":false},
+//!   {"input":"b.mp3","error":"..."},
+//!   {"input":"a copy.wav","duplicate_of":"a.wav"}]}
+//! ```
+//!
+//! Inputs skipped as copies of an earlier one (see [`crate::dedup`]) are
+//! listed last and count as `duplicates`, not as succeeded or failed.
+//!
+//! Files whose quality score is below [`crate::metrics::POOR_QUALITY`] count as
+//! `poor_quality`, as denoising likely failed or hurt them. Files without
+//! measured levels have no score.
+//!
+//! Requests carry the secret in [`SECRET_HEADER`] when one is set, so the
+//! receiver can tell them from others.
+
+use crate::download;
+use crate::events::{number, path};
+use crate::pipeline::RunReport;
+use serde_json::json;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Header the secret is sent in.
+pub const SECRET_HEADER: &str = "X-DFN-Secret";
+
+/// How often a summary is sent before giving up.
+const ATTEMPTS: u32 = 3;
+
+/// Wait before the first retry; it doubles with each further one.
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Webhook {
+    /// Where summaries are posted; empty for none.
+    pub url: String,
+    pub secret: String,
+}
+
+impl Webhook {
+    pub fn is_set(&self) -> bool {
+        !self.url.trim().is_empty()
+    }
+}
+
+/// The summary of a batch, given each input with how cleaning it went, and
+/// each skipped duplicate with the input it's a copy of.
+pub fn summary(
+    results: &[(PathBuf, Result<RunReport, String>)],
+    duplicates: &[(PathBuf, PathBuf)],
+) -> String {
+    let mut files: Vec<_> = results
+        .iter()
+        .map(|(input, result)| match result {
+            Ok(report) => {
+                let mut file = json!({
+                    "input": path(input),
+                    "output": path(&report.output_path),
+                    "audio_seconds": number(report.audio_duration.as_secs_f32()),
+                    "elapsed_seconds": number(report.elapsed.as_secs_f32()),
+                });
+                if let Some(provider) = report.provider {
+                    file["provider"] = json!(provider.key());
+                }
+                if let Some(levels) = &report.levels {
+                    file["quality_score"] = number(levels.quality_score());
+                    file["poor_quality"] = json!(levels.is_poor());
+                }
+                file
+            }
+            Err(e) => json!({"input": path(input), "error": e}),
+        })
+        .collect();
+    files.extend(
+        duplicates
+            .iter()
+            .map(|(input, original)| json!({"input": path(input), "duplicate_of": path(original)})),
+    );
+    let reports = results
+        .iter()
+        .filter_map(|(_, result)| result.as_ref().ok());
+    let succeeded = reports.clone().count();
+    let poor = reports
+        .clone()
+        .filter(|report| report.levels.is_some_and(|levels| levels.is_poor()))
+        .count();
+    json!({
+        "event": "batch_finished",
+        "succeeded": succeeded,
+        "failed": results.len() - succeeded,
+        "poor_quality": poor,
+        "duplicates": duplicates.len(),
+        "audio_seconds": number(
+            reports
+                .clone()
+                .map(|r| r.audio_duration.as_secs_f32())
+                .sum(),
+        ),
+        "elapsed_seconds": number(reports.map(|r| r.elapsed.as_secs_f32()).sum()),
+        "files": files,
+    })
+    .to_string()
+}
+
+/// Posts `body` to `webhook`, retrying failed connections and server errors.
+pub async fn post(webhook: &Webhook, body: String) -> Result<(), String> {
+    post_with(webhook, body, RETRY_DELAY).await
+}
+
+async fn post_with(webhook: &Webhook, body: String, retry_delay: Duration) -> Result<(), String> {
+    // The caller's runtime may be a short-lived one of its own.
+    let client = download::client();
+    let mut delay = retry_delay;
+    let mut attempt = 1;
+    loop {
+        let mut request = client
+            .post(webhook.url.trim())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.clone());
+        if !webhook.secret.is_empty() {
+            request = request.header(SECRET_HEADER, &webhook.secret);
+        }
+        let error = match request.send().await {
+            Ok(response) if response.status().is_success() => {
+                info!("Sent the batch summary to {}", webhook.url);
+                return Ok(());
+            }
+            // The receiver turned it down; sending it again won't change that.
+            Ok(response) if response.status().is_client_error() => {
+                return Err(format!("The webhook answered {}", response.status()));
+            }
+            Ok(response) => format!("The webhook answered {}", response.status()),
+            Err(e) => format!("Could not reach the webhook: {}", e),
+        };
+        if attempt == ATTEMPTS {
+            return Err(error);
+        }
+        warn!("{}, trying again in {:?}", error, delay);
+        tokio::time::sleep(delay).await;
+        delay *= 2;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Answers one request per status in `statuses`, passing on what each
+    /// request looked like.
+    async fn serve(statuses: &'static [u16]) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for status in statuses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buffer = [0; 4096];
+                // Reads up to the end of the body, which is the end of the JSON.
+                while !request.ends_with(b"}") {
+                    let read = stream.read(&mut buffer).await.unwrap();
+                    if read == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buffer[..read]);
+                }
+                requests.push(String::from_utf8_lossy(&request).into_owned());
+                let response = format!(
+                    "HTTP/1.1 {} Status\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    status
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+            requests
+        });
+        (url, server)
+    }
+
+    fn report(output: &str, seconds: u64) -> RunReport {
+        RunReport {
+            output_path: PathBuf::from(output),
+            other_outputs: Vec::new(),
+            audio_duration: Duration::from_secs(seconds),
+            elapsed: Duration::from_secs(1),
+            levels: None,
+            provider: None,
+        }
+    }
+
+    #[test]
+    fn summary_counts_successes_and_failures() {
+        // The voice went with the noise.
+        let levels = |signal_db| metrics::Levels {
+            peak_db: signal_db + 6.0,
+            rms_db: signal_db - 6.0,
+            noise_floor_db: -70.0,
+            signal_db,
+        };
+        let poor = RunReport {
+            levels: Some(metrics::Comparison {
+                before: levels(-15.0),
+                after: levels(-30.0),
+            }),
+            ..report("out/c.wav", 5)
+        };
+        let summary = summary(
+            &[
+                (PathBuf::from("a.wav"), Ok(report("out/a.wav", 10))),
+                (
+                    PathBuf::from("b.wav"),
+                    Err("Could not read audio".to_string()),
+                ),
+                (PathBuf::from("c.wav"), Ok(poor)),
+            ],
+            &[(PathBuf::from("d.wav"), PathBuf::from("a.wav"))],
+        );
+        assert!(summary.starts_with(
+            r#"{"event":"batch_finished","succeeded":2,"failed":1,"poor_quality":1,"duplicates":1,"audio_seconds":15.0,"elapsed_seconds":2.0,"files":["#
+        ));
+        assert!(summary.ends_with(r#"{"input":"d.wav","duplicate_of":"a.wav"}]}"#));
+        assert!(summary.contains(r#"{"input":"b.wav","error":"Could not read audio"}"#));
+        assert!(summary.contains(r#""quality_score":1.0,"poor_quality":true}"#));
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_retried_with_the_secret() {
+        let (url, server) = serve(&[503, 200]).await;
+        let webhook = Webhook {
+            url,
+            secret: "s3cret".to_string(),
+        };
+
+        post_with(&webhook, "{}".to_string(), Duration::ZERO)
+            .await
+            .unwrap();
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests.len(), 2);
+        for request in requests {
+            assert!(request.starts_with("POST /hook "));
+            assert!(request.to_lowercase().contains("x-dfn-secret: s3cret"));
+            assert!(request.ends_with("{}"));
+        }
+    }
+
+    #[tokio::test]
+    async fn client_errors_are_not_retried() {
+        let (url, server) = serve(&[401]).await;
+        let webhook = Webhook {
+            url,
+            secret: String::new(),
+        };
+
+        let result = post_with(&webhook, "{}".to_string(), Duration::ZERO).await;
+
+        assert_eq!(
+            result.err().as_deref(),
+            Some("The webhook answered 401 Unauthorized")
+        );
+        let requests = server.await.unwrap();
+        assert!(!requests[0].to_lowercase().contains("x-dfn-secret"));
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_the_last_attempt() {
+        let (url, server) = serve(&[500, 500, 500]).await;
+        let webhook = Webhook {
+            url,
+            secret: String::new(),
+        };
+
+        let result = post_with(&webhook, "{}".to_string(), Duration::ZERO).await;
+
+        assert_eq!(
+            result.err().as_deref(),
+            Some("The webhook answered 500 Internal Server Error")
+        );
+        assert_eq!(server.await.unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,219 @@
+//! Running the engine, with shell scripts standing in for it.
+#![cfg(unix)]
+
+use dfn_core::cancel::{CANCELLED, CancelToken};
+use dfn_core::engine::{self, Limits, Options, Transcript};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+const SCRIPTS: [(&str, &str); 6] = [
+    (
+        "clean",
+        "mkdir -p \"$3\" && cp \"$1\" \"$3/\" && echo cleaned",
+    ),
+    ("fail", "echo 'Error: unsupported input' >&2; exit 1"),
+    ("hang", "sleep 30"),
+    (
+        "busy",
+        "end=$(($(date +%s) + 2)); while [ \"$(date +%s)\" -lt $end ]; do :; done",
+    ),
+    (
+        "env",
+        "echo \"log=$RUST_LOG threads=$OMP_NUM_THREADS\"; exit 1",
+    ),
+    ("version", "echo 'deep-filter 0.5.6'"),
+];
+
+/// Path of the fake engine `name`. All of them are written before any is
+/// run, since a script still open for writing in one test can't be run by
+/// another.
+fn engine(name: &str) -> PathBuf {
+    static DIR: OnceLock<tempfile::TempDir> = OnceLock::new();
+    let dir = DIR.get_or_init(|| {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        for (name, script) in SCRIPTS {
+            let path = dir.path().join(name);
+            std::fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        dir
+    });
+    dir.path().join(name)
+}
+
+fn run(name: &str, input: &Path, output_dir: &Path, limits: &Limits) -> Result<(), String> {
+    engine::run(
+        &engine(name),
+        input,
+        output_dir,
+        &Options::default(),
+        limits,
+        &CancelToken::new(),
+        &Transcript::default(),
+        &mut |_| {},
+    )
+}
+
+#[test]
+fn successful_run_writes_the_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("take.wav");
+    std::fs::write(&input, b"audio").unwrap();
+
+    run(
+        "clean",
+        &input,
+        &dir.path().join("out"),
+        &Limits::for_duration(1.0, 10.0),
+    )
+    .unwrap();
+
+    assert_eq!(
+        std::fs::read(dir.path().join("out/take.wav")).unwrap(),
+        b"audio"
+    );
+}
+
+#[test]
+fn failed_run_reports_what_the_engine_printed() {
+    let dir = tempfile::tempdir().unwrap();
+    let error = run(
+        "fail",
+        &dir.path().join("take.wav"),
+        dir.path(),
+        &Limits::for_duration(1.0, 10.0),
+    )
+    .unwrap_err();
+
+    assert!(error.starts_with("DeepFilterNet failed to process the file\n"));
+    assert!(error.ends_with("Error: unsupported input"));
+}
+
+#[test]
+fn each_run_keeps_its_own_transcript() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("take.wav");
+    std::fs::write(&input, b"audio").unwrap();
+    let limits = Limits::for_duration(1.0, 10.0);
+    let failed = Transcript::default();
+    let cleaned = Transcript::default();
+
+    let cancel = CancelToken::new();
+    let run = |name: &str, transcript: &Transcript| {
+        engine::run(
+            &engine(name),
+            &input,
+            &dir.path().join("out"),
+            &Options::default(),
+            &limits,
+            &cancel,
+            transcript,
+            &mut |_| {},
+        )
+    };
+    run("fail", &failed).unwrap_err();
+    run("clean", &cleaned).unwrap();
+
+    assert!(failed.command().unwrap().contains("fail"));
+    assert_eq!(failed.lines(), ["Error: unsupported input"]);
+    assert!(cleaned.command().unwrap().contains("clean"));
+    assert_eq!(cleaned.lines(), ["cleaned"]);
+}
+
+#[test]
+fn slow_run_is_stopped() {
+    let dir = tempfile::tempdir().unwrap();
+    let limits = Limits {
+        timeout: Duration::from_millis(300),
+        stall_timeout: Duration::from_secs(60),
+    };
+    let error = run("hang", &dir.path().join("take.wav"), dir.path(), &limits).unwrap_err();
+
+    assert!(error.contains("did not finish"), "{}", error);
+}
+
+#[test]
+fn idle_run_is_stopped() {
+    let dir = tempfile::tempdir().unwrap();
+    let limits = Limits {
+        timeout: Duration::from_secs(60),
+        stall_timeout: Duration::from_millis(300),
+    };
+    let error = run("hang", &dir.path().join("take.wav"), dir.path(), &limits).unwrap_err();
+
+    assert!(error.contains("stopped responding"), "{}", error);
+}
+
+#[test]
+fn quiet_run_using_the_cpu_is_not_stopped() {
+    let dir = tempfile::tempdir().unwrap();
+    let limits = Limits {
+        timeout: Duration::from_secs(60),
+        stall_timeout: Duration::from_millis(500),
+    };
+    run("busy", &dir.path().join("take.wav"), dir.path(), &limits).unwrap();
+}
+
+#[test]
+fn cancelled_run_is_stopped() {
+    let dir = tempfile::tempdir().unwrap();
+    let cancel = CancelToken::new();
+    cancel.cancel();
+    let error = engine::run(
+        &engine("hang"),
+        &dir.path().join("take.wav"),
+        dir.path(),
+        &Options::default(),
+        &Limits::for_duration(1.0, 10.0),
+        &cancel,
+        &Transcript::default(),
+        &mut |_| {},
+    )
+    .unwrap_err();
+
+    assert_eq!(error, CANCELLED);
+}
+
+#[test]
+fn environment_overrides_reach_the_engine() {
+    let dir = tempfile::tempdir().unwrap();
+    let options = Options {
+        threads: Some(2),
+        env: vec![
+            ("RUST_LOG".to_string(), "debug".to_string()),
+            ("OMP_NUM_THREADS".to_string(), "5".to_string()),
+        ],
+        ..Options::default()
+    };
+    let error = engine::run(
+        &engine("env"),
+        &dir.path().join("take.wav"),
+        dir.path(),
+        &options,
+        &Limits::for_duration(1.0, 10.0),
+        &CancelToken::new(),
+        &Transcript::default(),
+        &mut |_| {},
+    )
+    .unwrap_err();
+
+    assert!(error.ends_with("log=debug threads=5"), "{}", error);
+}
+
+#[test]
+fn version_is_read_from_the_engine() {
+    assert_eq!(engine::version(&engine("version")).unwrap(), "0.5.6");
+}
+
+#[test]
+fn limits_grow_with_the_audio() {
+    let short = Limits::for_duration(10.0, 2.0);
+    let long = Limits::for_duration(100.0, 2.0);
+    assert_eq!(long.timeout - short.timeout, Duration::from_secs(180));
+    assert_eq!(
+        Limits::for_duration(-1.0, 2.0).timeout,
+        Limits::for_duration(0.0, 2.0).timeout
+    );
+}
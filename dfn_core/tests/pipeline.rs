@@ -0,0 +1,337 @@
+//! The processing pipeline end to end, with stand-ins for the engine.
+
+use dfn_core::audio::{self, AudioBuffer};
+use dfn_core::cancel::{CANCELLED, CancelToken};
+use dfn_core::denoiser::Denoiser;
+use dfn_core::engine;
+use dfn_core::pipeline::{self, ChannelRouting, ChannelSplit, OutputFormat, ProcessingOptions};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Hands every file back unchanged, counting the calls.
+#[derive(Default)]
+struct Passthrough {
+    calls: AtomicUsize,
+}
+
+impl Denoiser for Passthrough {
+    fn process_file(
+        &self,
+        input: &Path,
+        output_dir: &Path,
+        _limits: &engine::Limits,
+        _cancel: &CancelToken,
+        on_progress: &mut dyn FnMut(f32),
+    ) -> Result<(), String> {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        std::fs::create_dir_all(output_dir).map_err(|e| e.to_string())?;
+        std::fs::copy(input, output_dir.join(input.file_name().unwrap()))
+            .map_err(|e| e.to_string())?;
+        on_progress(100.0);
+        Ok(())
+    }
+}
+
+struct Failing;
+
+impl Denoiser for Failing {
+    fn process_file(
+        &self,
+        _input: &Path,
+        _output_dir: &Path,
+        _limits: &engine::Limits,
+        _cancel: &CancelToken,
+        _on_progress: &mut dyn FnMut(f32),
+    ) -> Result<(), String> {
+        Err("The engine crashed".to_string())
+    }
+}
+
+/// Writes a second of a 440 Hz tone with `channels` channels into `dir`.
+fn tone(dir: &Path, channels: usize) -> PathBuf {
+    let rate = 48_000;
+    let samples: Vec<f32> = (0..rate)
+        .map(|i| 0.5 * (std::f32::consts::TAU * 440.0 * i as f32 / rate as f32).sin())
+        .collect();
+    let path = dir.join("tone.wav");
+    audio::write_wav(
+        &path,
+        audio::ENGINE_SPEC,
+        &AudioBuffer {
+            sample_rate: rate,
+            channels: vec![samples; channels],
+        },
+    )
+    .unwrap();
+    path
+}
+
+fn run(
+    input: &Path,
+    denoiser: Arc<dyn Denoiser>,
+    options: &ProcessingOptions,
+    cancel: bool,
+) -> Result<pipeline::RunReport, String> {
+    let mut progress = Vec::new();
+    let token = CancelToken::new();
+    if cancel {
+        token.cancel();
+    }
+    pipeline::run(input, denoiser, options, token, &mut |_, percent| {
+        progress.push(percent)
+    })
+    .inspect(|_| {
+        assert!(progress.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(
+            progress
+                .iter()
+                .all(|percent| (0.0..=100.0).contains(percent))
+        );
+    })
+}
+
+#[test]
+fn cleans_into_a_folder_next_to_the_input() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = tone(dir.path(), 1);
+
+    let report = run(
+        &input,
+        Arc::new(Passthrough::default()),
+        &ProcessingOptions::default(),
+        false,
+    )
+    .unwrap();
+
+    assert_eq!(report.output_path, dir.path().join("dnf_clean/tone.wav"));
+    assert_eq!(report.audio_duration.as_millis(), 1000);
+    let (spec, output) = audio::read_wav(&report.output_path).unwrap();
+    assert_eq!(spec.sample_rate, 48_000);
+    assert_eq!(output.channels.len(), 1);
+    assert_eq!(output.frames(), 48_000);
+}
+
+#[test]
+fn output_folder_and_suffix_are_applied() {
+    let options = ProcessingOptions {
+        output_dir: Some(PathBuf::from("/clean")),
+        output_suffix: "_dfn".to_string(),
+        ..ProcessingOptions::default()
+    };
+    assert_eq!(
+        pipeline::output_path_for(Path::new("/audio/take 1.mp3"), &options),
+        Path::new("/clean/take 1_dfn.wav")
+    );
+}
+
+#[test]
+fn wav_output_size_is_known_up_front() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = tone(dir.path(), 2);
+    let info = audio::probe(&input).unwrap();
+    let options = ProcessingOptions::default();
+    let estimate = pipeline::estimate_output(&input, &info, &options).unwrap();
+
+    let report = run(&input, Arc::new(Passthrough::default()), &options, false).unwrap();
+    assert_eq!(estimate.extension, "wav");
+    assert_eq!(
+        estimate.size,
+        std::fs::metadata(&report.output_path).unwrap().len()
+    );
+
+    let flac = ProcessingOptions {
+        output_format: OutputFormat::Flac,
+        ..options
+    };
+    let flac = pipeline::estimate_output(&input, &info, &flac).unwrap();
+    assert_eq!(flac.extension, "flac");
+    assert!(flac.size < estimate.size);
+}
+
+#[test]
+fn versioned_outputs_keep_earlier_ones() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = tone(dir.path(), 1);
+    let options = ProcessingOptions {
+        version_outputs: true,
+        ..ProcessingOptions::default()
+    };
+
+    let outputs: Vec<PathBuf> = (0..3)
+        .map(|_| {
+            run(&input, Arc::new(Passthrough::default()), &options, false)
+                .unwrap()
+                .output_path
+        })
+        .collect();
+    let clean = dir.path().join("dnf_clean");
+    assert_eq!(
+        outputs,
+        [
+            clean.join("tone.wav"),
+            clean.join("tone.v2.wav"),
+            clean.join("tone.v3.wav")
+        ]
+    );
+    assert!(outputs.iter().all(|output| output.exists()));
+    let manifest = std::fs::read_to_string(clean.join("tone.versions.jsonl")).unwrap();
+    assert_eq!(manifest.lines().count(), 3);
+}
+
+#[test]
+fn split_channels_share_a_version() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = tone(dir.path(), 2);
+    let clean = dir.path().join("dnf_clean");
+    std::fs::create_dir(&clean).unwrap();
+    // Only the second channel's first version is taken.
+    std::fs::write(clean.join("tone.ch2.wav"), b"").unwrap();
+    let options = ProcessingOptions {
+        split_channels: ChannelSplit::Files,
+        version_outputs: true,
+        ..ProcessingOptions::default()
+    };
+
+    let report = run(&input, Arc::new(Passthrough::default()), &options, false).unwrap();
+
+    assert_eq!(report.output_path, clean.join("tone.ch1.v2.wav"));
+    assert_eq!(report.other_outputs, [clean.join("tone.ch2.v2.wav")]);
+}
+
+#[test]
+fn inputs_are_never_written_over() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = tone(dir.path(), 1);
+    let original = std::fs::read(&input).unwrap();
+    let options = ProcessingOptions {
+        output_dir: Some(dir.path().to_path_buf()),
+        ..ProcessingOptions::default()
+    };
+    assert_eq!(
+        pipeline::planned_output(&input, &options),
+        dir.path().join("tone.v2.wav")
+    );
+
+    let report = run(&input, Arc::new(Passthrough::default()), &options, false).unwrap();
+    assert_eq!(report.output_path, dir.path().join("tone.v2.wav"));
+    assert_eq!(std::fs::read(&input).unwrap(), original);
+
+    // The same folder, named another way.
+    std::fs::remove_file(&report.output_path).unwrap();
+    std::fs::create_dir(dir.path().join("other")).unwrap();
+    let options = ProcessingOptions {
+        output_dir: Some(dir.path().join("other/..")),
+        ..options
+    };
+    let report = run(&input, Arc::new(Passthrough::default()), &options, false).unwrap();
+    assert_eq!(report.output_path.file_name().unwrap(), "tone.v2.wav");
+    assert_eq!(std::fs::read(&input).unwrap(), original);
+}
+
+#[test]
+fn channels_beyond_stereo_are_cleaned_one_at_a_time() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = tone(dir.path(), 4);
+    let denoiser = Arc::new(Passthrough::default());
+
+    let report = run(
+        &input,
+        denoiser.clone(),
+        &ProcessingOptions::default(),
+        false,
+    )
+    .unwrap();
+
+    assert_eq!(denoiser.calls.load(Ordering::Relaxed), 4);
+    let (_, output) = audio::read_wav(&report.output_path).unwrap();
+    assert_eq!(output.channels.len(), 4);
+}
+
+#[test]
+fn stereo_is_downmixed_or_narrowed_to_one_channel() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = tone(dir.path(), 2);
+
+    for channels in [ChannelRouting::Mono, ChannelRouting::Only(1)] {
+        let options = ProcessingOptions {
+            channels,
+            ..ProcessingOptions::default()
+        };
+        let report = run(&input, Arc::new(Passthrough::default()), &options, false).unwrap();
+        let (_, output) = audio::read_wav(&report.output_path).unwrap();
+        assert_eq!(output.channels.len(), 1, "{:?}", channels);
+        assert_eq!(output.frames(), 48_000);
+    }
+
+    let options = ProcessingOptions {
+        channels: ChannelRouting::Only(2),
+        ..ProcessingOptions::default()
+    };
+    let error = run(&input, Arc::new(Passthrough::default()), &options, false).unwrap_err();
+    assert_eq!(error, "Channel 3 was picked, but the input has 2");
+}
+
+#[test]
+fn split_channels_are_cleaned_apart() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = tone(dir.path(), 2);
+
+    let denoiser = Arc::new(Passthrough::default());
+    let options = ProcessingOptions {
+        split_channels: ChannelSplit::Recombined,
+        ..ProcessingOptions::default()
+    };
+    let report = run(&input, denoiser.clone(), &options, false).unwrap();
+    assert_eq!(denoiser.calls.load(Ordering::Relaxed), 2);
+    let (_, output) = audio::read_wav(&report.output_path).unwrap();
+    assert_eq!(output.channels.len(), 2);
+    assert!(report.other_outputs.is_empty());
+
+    let options = ProcessingOptions {
+        split_channels: ChannelSplit::Files,
+        ..ProcessingOptions::default()
+    };
+    let report = run(&input, Arc::new(Passthrough::default()), &options, false).unwrap();
+    let clean = dir.path().join("dnf_clean");
+    assert_eq!(report.output_path, clean.join("tone.ch1.wav"));
+    assert_eq!(report.other_outputs, [clean.join("tone.ch2.wav")]);
+    for path in std::iter::once(&report.output_path).chain(&report.other_outputs) {
+        let (_, output) = audio::read_wav(path).unwrap();
+        assert_eq!(output.channels.len(), 1);
+        assert_eq!(output.frames(), 48_000);
+    }
+}
+
+#[test]
+fn failed_run_leaves_no_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = tone(dir.path(), 1);
+
+    let result = run(
+        &input,
+        Arc::new(Failing),
+        &ProcessingOptions::default(),
+        false,
+    );
+
+    assert_eq!(result.err().as_deref(), Some("The engine crashed"));
+    assert!(!dir.path().join("dnf_clean/tone.wav").exists());
+}
+
+#[test]
+fn cancelled_run_stops_before_denoising() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = tone(dir.path(), 1);
+    let denoiser = Arc::new(Passthrough::default());
+
+    let result = run(
+        &input,
+        denoiser.clone(),
+        &ProcessingOptions::default(),
+        true,
+    );
+
+    assert_eq!(result.err().as_deref(), Some(CANCELLED));
+    assert_eq!(denoiser.calls.load(Ordering::Relaxed), 0);
+}
@@ -0,0 +1,222 @@
+//! Hosting the LADSPA plugin and cleaning files with it in-process, with a
+//! small C plugin standing in for DeepFilterNet's. It turns its input down by
+//! the attenuation limit and delays it by a few frames, which it reports like
+//! plugins with a delay do. It's compiled with the system's C compiler;
+//! without one these tests are skipped.
+#![cfg(unix)]
+
+use dfn_core::audio::{self, AudioBuffer};
+use dfn_core::cancel::{self, CancelToken};
+use dfn_core::denoiser::{Denoiser, InProcess};
+use dfn_core::engine::Limits;
+use dfn_core::plugin::Plugin;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, OnceLock};
+
+/// Frames the stand-in delays its input by.
+const DELAY: usize = 3;
+
+const SOURCE: &str = r#"
+#include <math.h>
+#include <stdlib.h>
+
+typedef struct {
+    float *ports[4];
+    float delayed[DELAY];
+} Instance;
+
+typedef struct {
+    int hint;
+    float lower, upper;
+} Range;
+
+typedef struct Descriptor {
+    unsigned long id;
+    const char *label;
+    int properties;
+    const char *name, *maker, *copyright;
+    unsigned long port_count;
+    const int *port_descriptors;
+    const char *const *port_names;
+    const Range *hints;
+    void *data;
+    void *(*instantiate)(const struct Descriptor *, unsigned long);
+    void (*connect_port)(void *, unsigned long, float *);
+    void (*activate)(void *);
+    void (*run)(void *, unsigned long);
+    void (*run_adding)(void *, unsigned long);
+    void (*set_run_adding_gain)(void *, float);
+    void (*deactivate)(void *);
+    void (*cleanup)(void *);
+} Descriptor;
+
+static void *instantiate(const Descriptor *d, unsigned long rate) {
+    return rate == 48000 ? calloc(1, sizeof(Instance)) : NULL;
+}
+
+static void connect_port(void *h, unsigned long port, float *data) {
+    ((Instance *)h)->ports[port] = data;
+}
+
+static void run(void *h, unsigned long frames) {
+    Instance *i = h;
+    float gain = powf(10.0f, -*i->ports[2] / 20.0f);
+    for (unsigned long f = 0; f < frames; f++) {
+        float in = i->ports[0][f];
+        i->ports[1][f] = i->delayed[0] * gain;
+        for (int d = 0; d < DELAY - 1; d++) i->delayed[d] = i->delayed[d + 1];
+        i->delayed[DELAY - 1] = in;
+    }
+    *i->ports[3] = DELAY;
+}
+
+static const int kinds[] = {0x9, 0xA, 0x5, 0x6};
+static const char *const names[] = {
+    "Audio In", "Audio Out", "Attenuation Limit (dB)", "latency"};
+static const Range hints[] = {{0, 0, 0}, {0, 0, 0}, {0x143, 0, 100}, {0, 0, 0}};
+
+static const Descriptor stereo = {1, "deep_filter_stereo"};
+static const Descriptor mono = {
+    2, "deep_filter_mono", 0, "Stand-in", "", "", 4, kinds, names, hints, NULL,
+    instantiate, connect_port, NULL, run, NULL, NULL, NULL, free};
+
+const Descriptor *ladspa_descriptor(unsigned long index) {
+    return index == 0 ? &stereo : index == 1 ? &mono : NULL;
+}
+"#;
+
+/// The stand-in plugin, compiled once, or `None` without a C compiler.
+fn plugin() -> Option<&'static Path> {
+    static PLUGIN: OnceLock<Option<(tempfile::TempDir, PathBuf)>> = OnceLock::new();
+    PLUGIN
+        .get_or_init(|| {
+            let dir = tempfile::tempdir().unwrap();
+            let source = dir.path().join("plugin.c");
+            let library = dir.path().join("plugin.so");
+            std::fs::write(&source, SOURCE).unwrap();
+            let compiled = Command::new("cc")
+                .args(["-shared", "-fPIC", "-o"])
+                .arg(&library)
+                .arg(format!("-DDELAY={}", DELAY))
+                .arg(&source)
+                .arg("-lm")
+                .status()
+                .is_ok_and(|status| status.success());
+            if !compiled {
+                eprintln!("No C compiler, skipping");
+            }
+            compiled.then_some((dir, library))
+        })
+        .as_ref()
+        .map(|(_, library)| library.as_path())
+}
+
+#[test]
+fn input_comes_out_turned_down_by_the_attenuation_limit() {
+    let Some(path) = plugin() else { return };
+    let plugin = Plugin::load(path).unwrap();
+    let mut instance = plugin.instantiate(48_000).unwrap();
+    let input: Vec<f32> = (1..=10).map(|i| i as f32 / 10.0).collect();
+    let mut output = vec![0.0; input.len()];
+
+    // Starts at the port's default, the upper bound: 100 dB.
+    instance.process(&input[..5], &mut output[..5]);
+    instance.set_attenuation(20.0);
+    instance.process(&input[5..], &mut output[5..]);
+
+    assert_eq!(instance.latency(), DELAY);
+    assert_eq!(&output[..DELAY], &[0.0; DELAY]);
+    assert!(output[DELAY] < 1e-5, "{}", output[DELAY]);
+    assert!((output[5 + DELAY] - input[5] / 10.0).abs() < 1e-6);
+}
+
+#[test]
+fn attenuation_is_kept_within_the_port_bounds() {
+    let Some(path) = plugin() else { return };
+    let plugin = Plugin::load(path).unwrap();
+    let mut instance = plugin.instantiate(48_000).unwrap();
+    let input = [1.0; 4];
+    let mut output = [0.0; 4];
+
+    instance.set_attenuation(-20.0);
+    instance.process(&input, &mut output);
+
+    assert_eq!(output[DELAY], 1.0);
+}
+
+#[test]
+fn unsupported_rates_and_other_libraries_are_refused() {
+    let Some(path) = plugin() else { return };
+    let plugin = Plugin::load(path).unwrap();
+    assert!(plugin.instantiate(44_100).is_err());
+
+    let dir = tempfile::tempdir().unwrap();
+    let not_a_plugin = dir.path().join("plugin.so");
+    std::fs::write(&not_a_plugin, b"not a library").unwrap();
+    assert!(Plugin::load(&not_a_plugin).is_err());
+}
+
+/// Cleans a stereo file of `frames` frames in-process, returning the input
+/// and what came out.
+fn clean_in_process(
+    frames: usize,
+    cancel: &CancelToken,
+) -> Result<(AudioBuffer, AudioBuffer), String> {
+    let plugin = Plugin::load(plugin().unwrap()).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("part_0.wav");
+    let output_dir = dir.path().join("out");
+    std::fs::create_dir(&output_dir).unwrap();
+    let buffer = AudioBuffer {
+        sample_rate: audio::ENGINE_SAMPLE_RATE,
+        channels: vec![
+            (0..frames).map(|i| (i % 100) as f32 / 100.0).collect(),
+            (0..frames).map(|i| -((i % 50) as f32) / 50.0).collect(),
+        ],
+    };
+    audio::write_wav(&input, audio::ENGINE_SPEC, &buffer).unwrap();
+    let limits = Limits::for_duration(1.0, 1.0);
+    let mut progress = Vec::new();
+    InProcess::new(Arc::new(plugin)).process_file(
+        &input,
+        &output_dir,
+        &limits,
+        cancel,
+        &mut |p| progress.push(p),
+    )?;
+    assert_eq!(progress.last(), Some(&100.0));
+    let (_, cleaned) = audio::read_wav(&output_dir.join("part_0.wav")).unwrap();
+    Ok((buffer, cleaned))
+}
+
+#[test]
+fn files_cleaned_in_process_line_up_with_their_input() {
+    if plugin().is_none() {
+        return;
+    }
+    // Longer than a block, so the delay carries across blocks.
+    let (input, cleaned) = clean_in_process(10_000, &CancelToken::new()).unwrap();
+
+    assert_eq!(cleaned.channels.len(), 2);
+    assert_eq!(cleaned.frames(), input.frames());
+    // Turned down by the default limit of 100 dB, with no delay left.
+    for (input, cleaned) in input.channels.iter().zip(&cleaned.channels) {
+        for (frame, (a, b)) in input.iter().zip(cleaned).enumerate() {
+            assert!((a * 1e-5 - b).abs() < 1e-9, "frame {}: {} {}", frame, a, b);
+        }
+    }
+}
+
+#[test]
+fn cleaning_in_process_stops_when_cancelled() {
+    if plugin().is_none() {
+        return;
+    }
+    let cancel = CancelToken::new();
+    cancel.cancel();
+    assert_eq!(
+        clean_in_process(100, &cancel).unwrap_err(),
+        cancel::CANCELLED
+    );
+}
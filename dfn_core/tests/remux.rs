@@ -0,0 +1,161 @@
+//! Putting cleaned audio back into videos, with a shell script standing in
+//! for ffmpeg. It copies its first input to its output and notes how it was
+//! called. It's put on `PATH`, so these tests run in a process of their own.
+#![cfg(unix)]
+
+use dfn_core::audio::{self, AudioBuffer};
+use dfn_core::cancel::CancelToken;
+use dfn_core::denoiser::Denoiser;
+use dfn_core::engine;
+use dfn_core::pipeline::{self, OutputFormat, ProcessingOptions};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+const FFMPEG: &str = r#"#!/bin/sh
+echo "$@" >> "$(dirname "$0")/calls"
+input=
+while [ $# -gt 1 ]; do
+    if [ "$1" = -i ] && [ -z "$input" ]; then input=$2; fi
+    shift
+done
+cp "$input" "$1"
+"#;
+
+/// Hands every file back unchanged.
+struct Passthrough;
+
+impl Denoiser for Passthrough {
+    fn process_file(
+        &self,
+        input: &Path,
+        output_dir: &Path,
+        _limits: &engine::Limits,
+        _cancel: &CancelToken,
+        on_progress: &mut dyn FnMut(f32),
+    ) -> Result<(), String> {
+        std::fs::create_dir_all(output_dir).map_err(|e| e.to_string())?;
+        std::fs::copy(input, output_dir.join(input.file_name().unwrap()))
+            .map_err(|e| e.to_string())?;
+        on_progress(100.0);
+        Ok(())
+    }
+}
+
+/// The folder of the fake ffmpeg, put first on `PATH`, with the calls made
+/// to it so far cleared. Tests take turns through the lock.
+fn ffmpeg() -> (std::sync::MutexGuard<'static, ()>, &'static Path) {
+    static DIR: OnceLock<tempfile::TempDir> = OnceLock::new();
+    static LOCK: Mutex<()> = Mutex::new(());
+    let dir = DIR.get_or_init(|| {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ffmpeg");
+        std::fs::write(&path, FFMPEG).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let paths = std::env::var_os("PATH").unwrap_or_default();
+        let paths = std::env::join_paths(
+            std::iter::once(dir.path().to_path_buf()).chain(std::env::split_paths(&paths)),
+        )
+        .unwrap();
+        // SAFETY: set once, before any test of this file runs ffmpeg.
+        unsafe { std::env::set_var("PATH", paths) };
+        dir
+    });
+    let guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let _ = std::fs::remove_file(dir.path().join("calls"));
+    (guard, dir.path())
+}
+
+/// The argument lists ffmpeg was called with.
+fn calls(dir: &Path) -> Vec<String> {
+    std::fs::read_to_string(dir.join("calls"))
+        .unwrap_or_default()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+/// A "video" in `dir` holding a second of a tone. It's really a WAV, which
+/// decodes like the audio track of a video would.
+fn video(dir: &Path) -> PathBuf {
+    let rate = 48_000;
+    let samples: Vec<f32> = (0..rate)
+        .map(|i| 0.5 * (std::f32::consts::TAU * 440.0 * i as f32 / rate as f32).sin())
+        .collect();
+    let path = dir.join("take.mp4");
+    audio::write_wav(
+        &path,
+        audio::ENGINE_SPEC,
+        &AudioBuffer {
+            sample_rate: rate,
+            channels: vec![samples],
+        },
+    )
+    .unwrap();
+    path
+}
+
+fn run(input: &Path, options: &ProcessingOptions) -> pipeline::RunReport {
+    pipeline::run(
+        input,
+        Arc::new(Passthrough),
+        options,
+        CancelToken::new(),
+        &mut |_, _| {},
+    )
+    .unwrap()
+}
+
+#[test]
+fn video_audio_is_encoded_once_from_the_cleaned_audio() {
+    let (_lock, ffmpeg) = ffmpeg();
+    let dir = tempfile::tempdir().unwrap();
+    let input = video(dir.path());
+    // Which is AAC in an M4A for videos.
+    let options = ProcessingOptions {
+        remux_video: true,
+        output_format: OutputFormat::SameAsInput,
+        ..ProcessingOptions::default()
+    };
+
+    let report = run(&input, &options);
+
+    assert_eq!(report.output_path, dir.path().join("dnf_clean/take.mp4"));
+    let remuxes: Vec<String> = calls(ffmpeg)
+        .into_iter()
+        .filter(|call| call.contains("-c:a aac"))
+        .collect();
+    assert_eq!(remuxes.len(), 1, "{:?}", remuxes);
+    let audio = remuxes[0]
+        .split(" -i ")
+        .nth(2)
+        .and_then(|rest| rest.split(' ').next())
+        .unwrap();
+    assert!(audio.ends_with(".wav"), "{}", remuxes[0]);
+    assert!(!dir.path().join("dnf_clean/take.m4a").exists());
+}
+
+#[test]
+fn remuxed_videos_are_named_and_versioned_like_audio_outputs() {
+    let (_lock, _) = ffmpeg();
+    let dir = tempfile::tempdir().unwrap();
+    let input = video(dir.path());
+    let options = ProcessingOptions {
+        remux_video: true,
+        output_suffix: String::from("_clean"),
+        version_outputs: true,
+        ..ProcessingOptions::default()
+    };
+    let clean = dir.path().join("dnf_clean");
+
+    let first = run(&input, &options).output_path;
+    assert_eq!(
+        pipeline::planned_output(&input, &options),
+        clean.join("take_clean.v2.mp4")
+    );
+    let second = run(&input, &options).output_path;
+
+    assert_eq!(first, clean.join("take_clean.mp4"));
+    assert_eq!(second, clean.join("take_clean.v2.mp4"));
+    assert!(first.exists() && second.exists());
+}
@@ -0,0 +1,483 @@
+# German texts of DeepFilterNet GUI.
+
+app-title = DeepFilterNet GUI
+app-heading = DeepFilterNet Rauschunterdrückung
+sleep-reason = Audio wird mit DeepFilterNet bereinigt
+
+## Tabs
+
+tab-clean = Bereinigen
+tab-live = Live
+tab-history = Verlauf
+tab-logs = Protokoll
+tab-settings = Einstellungen
+compact-enter = Kompakt
+new-window = Neues Fenster
+compact-leave = Vergrößern
+
+## Selecting files
+
+file-filter-supported = Alle unterstützten Dateien
+file-filter-audio = Alle unterstützten Audioformate
+file-filter-video = Video
+file-filter-text = Textliste
+file-drop-hint = Audio- oder Videodatei hierher ziehen oder zum Auswählen klicken
+drop-release = Loslassen, um die Datei zu öffnen
+drop-unsupported = Dieser Dateityp kann nicht geöffnet werden
+file-selected = Datei: { $name }
+file-select = Audiodatei auswählen
+file-unknown-length = unbekannte Länge
+file-sample-rate = { $khz } kHz
+file-bit-depth = { $bits } Bit
+file-mono = Mono
+file-stereo = Stereo
+file-channels = { $channels } Kanäle
+file-unknown-channels = unbekannte Kanalzahl
+file-size = { $megabytes } MB
+note-wav-conversion = WAV im Format { $encoding } ({ $bits } Bit), { $rate } Hz, { $channels } Kan.: wird in PCM umgewandelt
+note-misnamed-wav = Endet auf .wav, enthält aber { $kind }: wird entsprechend dekodiert
+note-music = Das klingt nach Musik. Der Filter ist für Sprache gemacht und kann sie ausdünnen.
+note-music-lower = Rauschminderung auf { $percent } % senken
+error-unsupported-file = Nicht unterstützter Dateityp. Unterstützte Formate: { $formats }
+
+## Engine download
+
+status-checking = Ressourcen werden geprüft...
+download-engine = Engine herunterladen
+engine-missing = Die Engine zum Bereinigen ist noch nicht installiert.
+engine-install-file = Aus Datei installieren…
+download-progress = Wird heruntergeladen... { $percent } %
+error-download = Download fehlgeschlagen: { $error }
+offline-title = Du scheinst offline zu sein
+offline-body = Die Engine konnte nicht heruntergeladen werden, weil das Internet nicht erreichbar ist. Prüfe deine Verbindung und versuche es erneut.
+offline-retry = Erneut versuchen
+offline-install-hint = Oder lade die deep-filter-Datei auf einem anderen Gerät aus den DeepFilterNet-Releases herunter und installiere sie von dort.
+
+## Cleaning options
+
+option-remove-dc-offset = Gleichspannungsanteil entfernen
+option-trim-silence = Stille am Anfang und Ende entfernen
+option-silence-threshold = Stille unter { $db } dBFS
+option-channels = Kanäle
+option-split-channels = Kanäle trennen
+option-noise-reduction = Rauschminderung { $percent } %
+option-normalize = Spitzenpegel auf { $db } dBFS normalisieren
+option-preserve-format = Abtastrate und Bittiefe der Quelle beibehalten
+option-save-as = Speichern als
+option-keep-metadata = Tags und Cover behalten
+option-keep-broadcast-chunks = BWF-Timecode und iXML behalten
+option-export-labels = Audacity-Marken für stark bereinigte Stellen exportieren
+option-write-provenance = Herkunft der Ausgabe speichern (.dfn.json)
+option-version-outputs = Frühere Ausgaben behalten (take.v2.wav, …)
+option-skip-duplicates = Doppelte Eingaben überspringen
+option-remux-video = Bereinigten Ton in eine Kopie von Videodateien einsetzen
+option-bitrate = Bitrate { $kbps } kbit/s
+option-needs-ffmpeg = Andere Formate als WAV und Videoausgabe benötigen ffmpeg im PATH
+
+format-wav = WAV
+format-flac = FLAC (verlustfrei)
+format-mp3 = MP3
+format-ogg = OGG Vorbis
+format-same-as-input = Wie Eingabe
+
+channels-keep = Wie aufgenommen
+channels-mono = Zu Mono mischen
+channels-left = Nur links
+channels-right = Nur rechts
+channels-only = Nur Kanal { $channel }
+split-off = Gemeinsam bereinigen
+split-recombined = Einzeln bereinigen, in einer Datei
+split-files = Einzeln bereinigen, je eine Datei
+
+## Processing
+
+status-ready = Bereit.
+status-clean = Audio bereinigen
+estimate-output = ≈{ $duration }, Ausgabe ≈{ $size } MB { $format }
+estimate-output-wav = ≈{ $duration }, Ausgabe ≈{ $size } MB WAV (oder ≈{ $flac } MB FLAC)
+status-processing = { $phase }... { $percent } %
+status-finished = Fertig!
+status-saved-to = Gespeichert unter: { $path }
+status-also-saved-to = Und: { $path }
+status-speed = { $factor }× Echtzeit, { $elapsed } für { $duration } Audio
+status-provider = Ausgeführt mit { $provider }
+status-session = Sitzung: { $factor }× Echtzeit über { $runs } Dateien ({ $duration } Audio)
+status-open-location = Speicherort öffnen
+status-cancel = Abbrechen
+status-adjust = Anpassen & erneut bereinigen
+status-rerun = Erneut bereinigen
+status-adjusting = Passe oben die Optionen an. Die neue Ausgabe wird neben { $name } gespeichert, das erhalten bleibt.
+
+phase-decoding = Audio wird gelesen
+phase-resampling = Umrechnung auf 48 kHz
+phase-preprocessing = Audio wird vorbereitet
+phase-denoising = Audio wird bereinigt
+phase-mixing = Mischen
+phase-measuring = Pegel werden gemessen
+phase-normalizing = Normalisieren
+phase-restoring = Quellformat wird wiederhergestellt
+phase-encoding = Ausgabe wird geschrieben
+phase-remuxing = Video wird geschrieben
+
+levels-noise-floor = Grundrauschen
+levels-rms = RMS
+levels-peak = Spitze
+levels-snr = Gesch. SNR
+levels-snr-improvement = SNR um etwa { $db } dB verbessert
+levels-quality = Geschätzte Qualität: { $score } / 5
+levels-quality-poor = Die Bereinigung ist wohl misslungen oder hat die Sprache schwerer verständlich gemacht. Vor der Verwendung anhören.
+
+spectrograms-show = Spektrogramme anzeigen
+spectrograms-hide = Spektrogramme ausblenden
+spectrograms-original = Original
+spectrograms-cleaned = Bereinigt
+spectrograms-computing = Spektrogramme werden berechnet...
+error-spectrograms = Spektrogramme konnten nicht berechnet werden: { $error }
+
+error-summary = Fehler: { $error }
+error-show-details = Protokoll anzeigen
+error-hide-details = Protokoll ausblenden
+error-copy = Fehler kopieren
+error-lines-hidden = { $count } frühere Zeilen ausgeblendet
+error-view-full-log = Vollständiges Protokoll anzeigen
+error-full-log = Vollständiges Protokoll
+error-save = Speichern…
+error-close-log = Schließen
+error-retry = Erneut versuchen
+error-retry-download = Download wiederholen
+error-use-mirror = Mirror verwenden
+error-retry-file = Datei erneut versuchen
+error-skip-file = Überspringen
+error-reinstall-engine = Engine neu installieren
+error-choose-file = Andere Datei wählen
+
+## Benchmark
+
+benchmark-start = Engine-Benchmark
+benchmark-progress = Benchmark läuft... Durchlauf { $run } von { $runs }
+benchmark-complete = Benchmark abgeschlossen
+benchmark-result = Durchschnitt { $average }× Echtzeit, langsamster Durchlauf { $slowest }× Echtzeit ({ $runs } Durchläufe)
+benchmark-threads-all = alle
+benchmark-setup-normal = { $threads } CPU-Threads, normale Priorität
+benchmark-setup-background = { $threads } CPU-Threads, Hintergrundpriorität
+error-benchmark = Benchmark fehlgeschlagen: { $error }
+
+## Preview and recording
+
+preview-play = Abspielen
+preview-pause = Pause
+preview-input = Eingabe
+preview-cleaned = Bereinigt
+preview-original = Original
+preview-close = Schließen
+preview-play-input = Eingabe abspielen
+preview-play-output = Ausgabe abspielen
+preview-device-failed = { $error }. Möglicherweise belegt eine andere App das Gerät exklusiv. Schließe sie oder wähle eine andere Ausgabe.
+preview-device-retry = Erneut versuchen
+
+recording-start = Aufnehmen
+recording-stop = Stoppen und bereinigen
+recording-elapsed = Aufnahme { $elapsed }
+recording-saved = Aufnahme gespeichert unter { $path }
+
+device-input = Eingang
+device-output = Ausgang
+device-default = Systemstandard
+
+## Live listening
+
+live-intro = Hören Sie Ihr Mikrofon bereinigt, während Sie sprechen, um die Engine vor einer Aufnahme auszuprobieren. Verwenden Sie Kopfhörer, sonst koppeln die Lautsprecher ins Mikrofon zurück.
+live-plugin-missing = Die Live-Bereinigung nutzt das LADSPA-Plugin von DeepFilterNet, das noch nicht installiert ist.
+live-plugin-download = Plugin herunterladen
+live-plugin-downloading = Plugin wird heruntergeladen… { $percent } %
+live-plugin-install = Plugin aus Datei installieren…
+live-attenuation = Rauschminderung: { $db } dB
+live-start = Mithören starten
+live-stop = Mithören beenden
+live-play = Über die Ausgabe abspielen
+live-virtual-mic = Anderen Apps als Mikrofon anbieten, „{ $name }“
+live-microphone = Mikrofon
+live-cleaned = Bereinigt
+error-plugin-download = Download des Plugins fehlgeschlagen: { $error }
+
+## Quitting while busy
+
+exit-stopping = Engine wird beendet...
+exit-title = Es wird noch gearbeitet.
+exit-warning = Jetzt beenden:
+exit-item-download = Bricht den Engine-Download ab ({ $percent }%) und entfernt die unfertige Datei
+exit-item-processing = Stoppt die Bereinigung von { $name } ({ $percent }%) und verwirft die unfertige Ausgabe
+exit-item-benchmark = Stoppt den Benchmark
+exit-item-recording = Stoppt die Aufnahme und behält das Aufgenommene
+exit-item-schedule = Sagt den Start der wartenden Dateien um { $time } ab
+exit-keep-working = Weiterarbeiten
+exit-confirm = Stoppen und beenden
+shutdown-countdown = Der Computer wird in { $seconds } s heruntergefahren
+shutdown-cancel = Nicht herunterfahren
+
+## Notices
+
+toast-file-added = { $name } hinzugefügt
+toast-file-queued = { $name } wird nach den Dateien davor bereinigt
+toast-duplicate-skipped = { $name } übersprungen, eine Kopie einer Datei im Stapel
+toast-folder-profile = Ordnereinstellungen aus { $path } werden verwendet
+toast-download-complete = Engine heruntergeladen
+toast-engine-installed = Engine installiert
+toast-engine-install-failed = Die Engine konnte nicht installiert werden: { $error }
+toast-plugin-installed = Plugin installiert
+toast-plugin-install-failed = Das Plugin konnte nicht installiert werden: { $error }
+toast-error-copied = Fehler in die Zwischenablage kopiert
+toast-status-bar-copied = Statusleiste in die Zwischenablage kopiert.
+toast-mirror-missing = Geben Sie unter Einstellungen eine Mirror-Adresse ein und versuchen Sie es erneut.
+toast-shell-menu-added = „Clean with DeepFilterNet“ wurde dem Kontextmenü von Audio- und Videodateien hinzugefügt.
+toast-shell-menu-removed = Aus dem Kontextmenü entfernt
+toast-webhook-failed = Der Webhook konnte nicht benachrichtigt werden: { $error }
+toast-update-failed = Aktualisierung fehlgeschlagen: { $error }
+
+## Desktop notifications
+
+notification-done = { $name } bereinigt
+notification-batch-done = Stapel fertig
+notification-batch-done-body = { $count } Dateien wurden bereinigt
+notification-failed = { $name } konnte nicht bereinigt werden
+
+## Tray icon
+
+tray-idle = Bereit
+tray-busy = Audio wird bereinigt... { $percent } %
+tray-show-window = Fenster anzeigen
+tray-quit = Beenden
+
+## First-run walkthrough
+
+onboarding-step = Schritt { $step } von { $steps }
+onboarding-welcome-title = Willkommen bei DeepFilterNet GUI
+onboarding-welcome-body = Diese App entfernt Hintergrundgeräusche aus Sprache in Audio- und Videodateien. Sie nutzt DeepFilterNet, ein neuronales Netz, das standardmäßig direkt auf diesem Computer läuft, sodass Aufnahmen hier bleiben.
+onboarding-engine-title = Engine herunterladen
+onboarding-engine-body = Die Bereinigung übernimmt das Programm deep-filter, das einmalig von den DeepFilterNet-Releases auf GitHub heruntergeladen wird.
+onboarding-engine-size = Downloadgröße: { $size } MB
+onboarding-engine-installed = Die Engine ist installiert.
+onboarding-engine-retry = Download wiederholen
+onboarding-output-title = Speicherort für bereinigte Dateien
+onboarding-output-body = Bereinigte Dateien landen in einem Ordner dnf_clean neben jeder Eingabe, außer Sie wählen einen Ordner für alle. Das lässt sich später unter Einstellungen ändern.
+onboarding-test-title = Ausprobieren
+onboarding-test-body = Bereinigen Sie ein kurzes erzeugtes Beispiel, um zu sehen, wie es funktioniert: ein summender, stimmähnlicher Ton mit Rauschen und Brummen.
+onboarding-test-run = Beispiel bereinigen
+onboarding-test-needs-engine = Das Beispiel kann bereinigt werden, sobald die Engine installiert ist.
+onboarding-back = Zurück
+onboarding-next = Weiter
+onboarding-skip = Einrichtung überspringen
+onboarding-finish = Fertig
+
+## Status bar
+
+status-bar-app = DeepFilterNet GUI { $version }
+status-bar-engine = deep-filter { $version }
+status-bar-engine-missing = deep-filter nicht installiert
+status-bar-model = Modell: { $model }
+status-bar-model-remote = Modell vom Server gewählt
+status-bar-counts = { $cleaned } bereinigt, { $failed } fehlgeschlagen
+status-bar-counts-running = { $counts }, 1 läuft
+status-bar-copy = Kopieren
+
+## Help on options
+
+help-remove-dc-offset = Entfernt einen konstanten Versatz, den manche Rekorder der Wellenform hinzufügen. Nur nötig, wenn die Wellenform nicht mittig liegt; sonst unschädlich.
+help-trim-silence = Schneidet leise Passagen am Anfang und Ende der Ausgabe ab. Bei Videos übersprungen, da es die Lippensynchronität zerstören würde.
+help-silence-threshold = Leiserer Ton gilt beim Kürzen als Stille. -60 dBFS passt für die meisten Aufnahmen; in lauten Räumen Richtung -40 anheben.
+help-channels = Welche Kanäle bereinigt werden. Zu Mono mischen oder einen Kanal wählen hilft, wenn eine Stimme auf nur einem Kanal einer Stereodatei aufgenommen wurde, und ergibt eine Mono-Ausgabe.
+help-split-channels = Bereinigt jeden Kanal für sich, für Interviews mit einer Stimme pro Kanal, bei denen der andere Kanal den Filter stören würde. Die Kanäle bleiben in einer Datei oder kommen je in eine Mono-Datei, benannt wie take.ch2.wav.
+help-noise-reduction = Anteil des bereinigten Signals in der Ausgabe; der Rest ist das Original. 100 % entfernt am meisten Rauschen; 70–90 % klingt natürlicher, wenn Stimmen dünn werden.
+help-normalize = Skaliert die Ausgabe so, dass ihre lauteste Spitze diesen Pegel erreicht. Hilfreich bei leisen Aufnahmen; die Dynamik bleibt erhalten.
+help-preserve-format = Die Engine arbeitet mit 48 kHz und 16 Bit. Ist dies aktiv, wird die Ausgabe zurück in Abtastrate und Bittiefe der Eingabe gewandelt.
+help-keep-metadata = Übernimmt Tags, Kapitel und Cover von der Eingabe in die Ausgabe.
+help-keep-broadcast-chunks = Übernimmt Broadcast-Wave-Timecode und iXML aus WAV-Eingaben, damit die Ausgabe in Videoschnitt und DAW richtig liegt.
+help-export-labels = Schreibt neben die Ausgabe eine Audacity-Labelspur, die markiert, wo am meisten Rauschen entfernt wurde, um diese Stellen anzuhören.
+help-write-provenance = Schreibt neben die Ausgabe eine .dfn.json-Datei mit Engine-Version, Modell, Optionen, Prüfsummen von Ein- und Ausgabe sowie Zeiten, für Archive und um den Durchlauf später zu wiederholen.
+help-version-outputs = Wird eine Datei erneut bereinigt, entstehen take.v2.wav, take.v3.wav usw., statt die vorige Ausgabe zu ersetzen. Jede Version wird mit den verwendeten Optionen in take.versions.jsonl aufgeführt.
+help-skip-duplicates = In Stapeln und Archiven werden Dateien übersprungen, die exakte Kopien einer früheren sind, und nach dem Stapel aufgeführt.
+help-remux-video = Schreibt bei Videos zusätzlich eine Kopie des Videos mit dem bereinigten Ton. Das Bild wird nicht neu kodiert.
+help-bitrate = Bitrate von MP3, Ogg und Videoton. 128 kbit/s genügen für Sprache; bei Musik 192 oder mehr verwenden.
+help-low-priority = Führt die Engine mit niedrigerer CPU-Priorität aus, damit der Computer reaktionsfähig bleibt. Läufe dauern länger, wenn andere Programme beschäftigt sind.
+help-threads = Begrenzt, wie viele CPU-Threads die Engine nutzt. Alle ist am schnellsten; weniger lässt Platz für andere Arbeit.
+help-concurrency = Wie viele Dateien eines Stapels gleichzeitig bereinigt werden. Jede läuft in einer eigenen Engine, mehrere helfen also vor allem bei vielen kurzen Dateien.
+help-timeout = Läufe, die länger als so viele Male die Audiolänge dauern, werden als hängend gestoppt. 10× reicht auf den meisten Rechnern; auf langsamen erhöhen.
+help-engine-env = Umgebungsvariablen, mit denen die Engine läuft, etwa OMP_NUM_THREADS, RUST_LOG oder ORT_*, um Tempo oder Protokollierung einzustellen. Sie haben Vorrang vor der Thread-Anzahl oben.
+help-settings-transfer = Speichert alle Einstellungen in einer JSON-Datei oder ersetzt sie durch die aus einer, um andere Rechner genauso einzurichten. Server-Token und Webhook-Geheimnis werden nicht exportiert.
+
+## Queue
+
+queue-title = Wartend ({ $count })
+queue-start = Bereinigen starten
+schedule-start-at = Starten um…
+schedule-keep-awake = Computer bis dahin wach halten
+schedule-wake = Computer dafür aufwecken
+schedule-waiting = Startet um { $time }
+schedule-cancel = Nicht warten
+queue-remove = Entfernen
+queue-export = Liste speichern…
+queue-import = Aus Liste einreihen…
+express-choose = Express-Datei…
+express-label = Express
+archive-open = Archiv bereinigen…
+archive-title = Archiv bereinigen
+archive-body = Bereinigt jede unterstützte Datei unterhalb der Quelle in dieselben Ordner unterhalb des Ziels. Ein erneuter Start mit denselben Ordnern überspringt bereits erledigte Dateien.
+archive-source = Quelle
+archive-destination = Ziel
+archive-none = Nicht gewählt
+archive-choose = Wählen…
+archive-copy-other = Andere Dateien unverändert kopieren
+archive-start = Starten
+archive-close = Schließen
+queue-settings = { $backend }, { $reduction } % Rauschunterdrückung, gespeichert { $folder }
+queue-next-to-input = neben der Datei
+queue-in-folder = in { $path }
+priority-high = Eilig
+priority-normal = Normal
+priority-low = Niedrig
+
+## History
+
+history-search = Nach Dateiname oder Pfad suchen
+history-outcome-all = Alle Durchläufe
+history-outcome-succeeded = Erfolgreich
+history-outcome-failed = Fehlgeschlagen
+history-empty = Noch keine Durchläufe.
+history-succeeded = { $duration } Audio in { $elapsed } bereinigt
+history-failed = Fehlgeschlagen: { $error }
+history-open-folder = Ordner öffnen
+history-rerun = Erneut ausführen
+history-compare = Versionen vergleichen
+history-export-list = Als Liste speichern…
+compare-back = Zurück
+compare-version-a = A
+compare-version-b = B
+compare-option = Option
+compare-same-options = Beide Versionen wurden mit denselben Optionen erstellt.
+compare-play = A/B abspielen
+compare-single-version = Von dieser Datei gibt es nur eine Ausgabe. Mit „Anpassen & erneut bereinigen“ entsteht eine weitere zum Vergleichen.
+
+## Problem reports
+
+report-problem = Problem melden:
+report-save = Bericht speichern...
+report-github = GitHub-Issue öffnen
+toast-report-saved = Bericht gespeichert unter { $path }
+
+## Logs
+
+logs-copy = Kopieren
+logs-save = In Datei speichern...
+logs-app = App-Protokoll
+logs-app-empty = Noch nichts protokolliert.
+logs-engine = Ausgabe der Engine für die gewählte Datei
+logs-engine-running = Ausgabe der Engine (läuft)
+logs-engine-empty = Die Engine ist noch nicht gelaufen.
+toast-logs-copied = Protokoll in die Zwischenablage kopiert
+toast-logs-saved = Protokoll gespeichert unter { $path }
+toast-logs-save-failed = Protokoll konnte nicht gespeichert werden: { $error }
+
+## App updates
+
+update-available = Version { $version } ist verfügbar
+update-available-prerelease = Vorabversion { $version } ist verfügbar
+update-back-to-stable = Zur stabilen Version { $version } zurückkehren
+update-install = Aktualisieren und neu starten
+update-installing = Wird aktualisiert...
+update-dismiss = Nicht jetzt
+accelerated-available = Für deine Grafikkarte gibt es einen schnelleren Engine-Build für { $provider }.
+accelerated-install = Herunterladen
+leftovers-found = Abgebrochene Durchläufe haben unvollständige Ergebnisse hinterlassen ({ $count }):
+leftovers-rerun = Erneut bereinigen
+leftovers-delete = Löschen
+duplicates-skipped = Der letzte Stapel hat Kopien von Dateien darin übersprungen ({ $count }):
+duplicates-copy-of = { $input }, eine Kopie von { $original }
+
+## Crash reports
+
+crash-title = Die App ist beim letzten Mal abgestürzt
+crash-explanation = Ein Bericht über den Absturz wurde gespeichert. Ihn einzusenden hilft, das Problem zu beheben.
+crash-report = Auf GitHub melden
+crash-open = Bericht öffnen
+crash-dismiss = Schließen
+toast-crash-report-unreadable = Absturzbericht konnte nicht gelesen werden: { $error }
+toast-settings-exported = Einstellungen exportiert nach { $path }
+toast-settings-export-failed = Einstellungen konnten nicht exportiert werden: { $error }
+toast-settings-imported = Einstellungen importiert aus { $path }
+toast-settings-import-failed = Einstellungen konnten nicht importiert werden: { $error }
+toast-list-exported = Liste gespeichert unter { $path }
+toast-list-imported = { $count } der { $total } Dateien der Liste eingereiht
+toast-archive-started = { $count } Dateien zum Bereinigen eingereiht, { $copied } kopiert, { $done } bereits erledigt, { $duplicates } Duplikate übersprungen
+toast-schedule-invalid = Gib eine Uhrzeit wie 2:00 oder 14:30 ein
+toast-schedule-set = Die wartenden Dateien starten um { $time }
+toast-schedule-no-wake = Der Computer kann nicht zum Aufwachen eingestellt werden, halte ihn stattdessen wach
+toast-leftovers-deleted = { $count } unvollständige Dateien gelöscht
+toast-archive-failed = Das Archiv konnte nicht gestartet werden: { $error }
+toast-express-done = Express-Lauf gespeichert unter { $path }
+toast-express-failed = Der Express-Lauf ist fehlgeschlagen: { $error }
+toast-worker-failed = { $name } konnte nicht bereinigt werden: { $error }
+
+## Settings
+
+settings-output-default = Ordner dnf_clean neben jeder Eingabe
+settings-save-to = Speichern in: { $folder }
+settings-choose = Auswählen...
+settings-reset = Zurücksetzen
+settings-suffix = An Dateinamen anhängen
+settings-suffix-placeholder = z. B. _clean
+settings-engine = Engine
+settings-engine-mirror = Mirror für den Engine-Download (optional)
+settings-server-url = Server-URL
+settings-server-token = Token (optional)
+settings-webhook-url = Webhook für fertige Durchläufe (optional)
+settings-webhook-secret = Webhook-Geheimnis (optional)
+settings-low-priority = Hintergrundpriorität
+settings-threads-all = CPU-Threads: alle
+settings-threads = CPU-Threads: { $threads }
+settings-concurrency = Gleichzeitig bereinigte Dateien: { $files }
+settings-timeout = Nach dem { $factor }-fachen der Audiolänge abbrechen
+settings-engine-env = Umgebung der Engine:
+settings-engine-env-name = Name
+settings-engine-env-value = Wert
+settings-engine-env-add = Variable hinzufügen
+settings-theme = Design
+settings-language = Sprache
+settings-log-verbosity = Protokollumfang
+settings-open-log-folder = Protokollordner öffnen
+settings-shell-menu-add = Zum Kontextmenü hinzufügen
+settings-shell-menu-remove = Aus dem Kontextmenü entfernen
+settings-scale = Oberflächenskalierung { $scale }×
+settings-text-size = Textgröße { $size } px (nach Neustart)
+settings-notify-when-done = Benachrichtigen, wenn die Bereinigung im Hintergrund fertig ist
+settings-notification-sound = Ton bei Benachrichtigungen abspielen
+settings-open-folder-when-done = Bereinigte Datei nach dem Abschluss im Dateimanager zeigen
+settings-play-when-done = Bereinigte Datei nach dem Abschluss abspielen
+settings-batch-action = Wenn ein Stapel fertig ist:
+settings-batch-command-placeholder = Auszuführender Befehl, z. B.: rsync -a "$DFN_GUI_OUTPUT" backup:
+settings-check-updates = Beim Start nach App-Updates suchen
+settings-channel = Release-Kanal:
+settings-reinstall-engine = Engine erneut herunterladen
+settings-engine-provider = Engine-Build:
+settings-channel-beta-note = Vorabversionen der App und der Engine können instabil sein. Zurück auf Stabil wird wieder die stabile App angeboten; für die stabile Engine diese erneut herunterladen.
+settings-tray-icon = Symbol im Infobereich anzeigen
+settings-minimize-to-tray = In den Infobereich minimieren
+settings-export = Einstellungen exportieren…
+settings-import = Einstellungen importieren…
+
+backend-external-binary = deep-filter-Programm
+backend-in-process = deep-filter-Plugin in der App
+backend-remote = Entfernter Server
+channel-stable = Stabil
+channel-beta = Beta (Vorabversionen)
+
+language-system = Systemsprache
+
+log-verbosity-errors = Nur Fehler
+log-verbosity-normal = Normal
+log-verbosity-detailed = Ausführlich
+completion-nothing = Nichts tun
+completion-sound = Einen Ton abspielen
+completion-notification = Eine Benachrichtigung zeigen
+completion-open-folder = Den Ausgabeordner öffnen
+completion-command = Einen Befehl ausführen
+completion-shut-down = Den Computer herunterfahren
@@ -0,0 +1,485 @@
+# English texts of DeepFilterNet GUI. This file is the reference for
+# translations: copy it to locales/<code>.ftl, translate the texts after
+# the `=` and leave keys and { $variables } as they are.
+
+app-title = DeepFilterNet GUI
+app-heading = DeepFilterNet Noise Cancellation
+sleep-reason = Cleaning audio with DeepFilterNet
+
+## Tabs
+
+tab-clean = Clean
+tab-live = Live
+tab-history = History
+tab-logs = Logs
+tab-settings = Settings
+compact-enter = Compact
+new-window = New window
+compact-leave = Expand
+
+## Selecting files
+
+file-filter-supported = All supported files
+file-filter-audio = All supported audio
+file-filter-video = Video
+file-filter-text = Text list
+file-drop-hint = Drag and drop an audio or video file here or click to select
+drop-release = Release to open the file
+drop-unsupported = This file type can't be opened
+file-selected = File: { $name }
+file-select = Select Audio File
+file-unknown-length = unknown length
+file-sample-rate = { $khz } kHz
+file-bit-depth = { $bits }-bit
+file-mono = mono
+file-stereo = stereo
+file-channels = { $channels } channels
+file-unknown-channels = unknown channels
+file-size = { $megabytes } MB
+note-wav-conversion = WAV encoded as { $encoding } ({ $bits }-bit), { $rate } Hz, { $channels } ch: it will be converted to PCM
+note-misnamed-wav = Named .wav but holds { $kind }: it will be decoded as such
+note-music = This sounds like music. The filter is made for speech and may thin it out.
+note-music-lower = Lower noise reduction to { $percent }%
+error-unsupported-file = Unsupported file type. Supported formats: { $formats }
+
+## Engine download
+
+status-checking = Checking resources...
+download-engine = Download engine
+engine-missing = The cleaning engine isn't installed yet.
+engine-install-file = Install from file…
+download-progress = Downloading... { $percent }%
+error-download = Download failed: { $error }
+offline-title = You appear to be offline
+offline-body = The engine couldn't be downloaded because the internet can't be reached. Check your connection and try again.
+offline-retry = Try again
+offline-install-hint = Or download the deep-filter file from the DeepFilterNet releases on another device and install it from there.
+
+## Cleaning options
+
+option-remove-dc-offset = Remove DC offset
+option-trim-silence = Trim leading/trailing silence
+option-silence-threshold = Silence below { $db } dBFS
+option-channels = Channels
+option-split-channels = Split channels
+option-noise-reduction = Noise reduction { $percent }%
+option-normalize = Normalize peak to { $db } dBFS
+option-preserve-format = Match source sample rate and bit depth
+option-save-as = Save as
+option-keep-metadata = Keep tags and cover art
+option-keep-broadcast-chunks = Keep BWF timecode and iXML
+option-export-labels = Export Audacity labels of heavily cleaned regions
+option-write-provenance = Save how the output was made (.dfn.json)
+option-version-outputs = Keep earlier outputs (take.v2.wav, …)
+option-skip-duplicates = Skip duplicate inputs
+option-remux-video = Put cleaned audio back into a copy of video files
+option-bitrate = Bitrate { $kbps } kbit/s
+option-needs-ffmpeg = Formats other than WAV and video output need ffmpeg on PATH
+
+format-wav = WAV
+format-flac = FLAC (lossless)
+format-mp3 = MP3
+format-ogg = OGG Vorbis
+format-same-as-input = Same as input
+
+channels-keep = As recorded
+channels-mono = Downmix to mono
+channels-left = Left only
+channels-right = Right only
+channels-only = Channel { $channel } only
+split-off = Clean together
+split-recombined = Clean apart, keep in one file
+split-files = Clean apart, one file each
+
+## Processing
+
+status-ready = Ready.
+status-clean = Clean Audio
+estimate-output = ≈{ $duration }, output ≈{ $size } MB { $format }
+estimate-output-wav = ≈{ $duration }, output ≈{ $size } MB WAV (or ≈{ $flac } MB FLAC)
+status-processing = { $phase }... { $percent }%
+status-finished = Finished!
+status-saved-to = Saved to: { $path }
+status-also-saved-to = And: { $path }
+status-speed = { $factor }× realtime, { $elapsed } for { $duration } of audio
+status-provider = Ran on { $provider }
+status-session = Session: { $factor }× realtime over { $runs } files ({ $duration } of audio)
+status-open-location = Open File Location
+status-cancel = Cancel
+status-adjust = Adjust & Re-run
+status-rerun = Clean Again
+status-adjusting = Adjust the options above. The new output is saved next to { $name }, which is kept.
+
+phase-decoding = Reading audio
+phase-resampling = Resampling to 48 kHz
+phase-preprocessing = Preparing audio
+phase-denoising = Cleaning audio
+phase-mixing = Mixing
+phase-measuring = Measuring levels
+phase-normalizing = Normalizing
+phase-restoring = Restoring source format
+phase-encoding = Writing output
+phase-remuxing = Writing video
+
+levels-noise-floor = Noise floor
+levels-rms = RMS
+levels-peak = Peak
+levels-snr = Est. SNR
+levels-snr-improvement = SNR improved by about { $db } dB
+levels-quality = Estimated quality: { $score } / 5
+levels-quality-poor = Denoising likely failed or made speech harder to follow. Listen before using it.
+
+spectrograms-show = Show spectrograms
+spectrograms-hide = Hide spectrograms
+spectrograms-original = Original
+spectrograms-cleaned = Cleaned
+spectrograms-computing = Computing spectrograms...
+error-spectrograms = Could not compute spectrograms: { $error }
+
+error-summary = Error: { $error }
+error-show-details = Show log
+error-hide-details = Hide log
+error-copy = Copy error
+error-lines-hidden = { $count } earlier lines not shown
+error-view-full-log = View full log
+error-full-log = Full log
+error-save = Save…
+error-close-log = Close
+error-retry = Retry
+error-retry-download = Retry download
+error-use-mirror = Use mirror
+error-retry-file = Retry file
+error-skip-file = Skip
+error-reinstall-engine = Reinstall engine
+error-choose-file = Choose another file
+
+## Benchmark
+
+benchmark-start = Benchmark engine
+benchmark-progress = Benchmarking... run { $run } of { $runs }
+benchmark-complete = Benchmark complete
+benchmark-result = Average { $average }× realtime, slowest run { $slowest }× realtime ({ $runs } runs)
+benchmark-threads-all = all
+benchmark-setup-normal = { $threads } CPU threads, normal priority
+benchmark-setup-background = { $threads } CPU threads, background priority
+error-benchmark = Benchmark failed: { $error }
+
+## Preview and recording
+
+preview-play = Play
+preview-pause = Pause
+preview-input = Input
+preview-cleaned = Cleaned
+preview-original = Original
+preview-close = Close
+preview-play-input = Play input
+preview-play-output = Play output
+preview-device-failed = { $error }. Another app may be using the device exclusively. Close it or pick another output.
+preview-device-retry = Try again
+
+recording-start = Record
+recording-stop = Stop and clean
+recording-elapsed = Recording { $elapsed }
+recording-saved = Recording saved to { $path }
+
+device-input = Input
+device-output = Output
+device-default = System default
+
+## Live listening
+
+live-intro = Hear your microphone cleaned as you speak, to try the engine before recording. Use headphones, or the speakers feed back into the microphone.
+live-plugin-missing = Live cleaning runs DeepFilterNet's LADSPA plugin, which isn't installed yet.
+live-plugin-download = Download plugin
+live-plugin-downloading = Downloading plugin… { $percent }%
+live-plugin-install = Install plugin from file…
+live-attenuation = Noise reduction: { $db } dB
+live-start = Start listening
+live-stop = Stop listening
+live-play = Play through the output
+live-virtual-mic = Offer to other apps as a microphone, "{ $name }"
+live-microphone = Microphone
+live-cleaned = Cleaned
+error-plugin-download = Plugin download failed: { $error }
+
+## Quitting while busy
+
+exit-stopping = Stopping the engine...
+exit-title = Work is still in progress.
+exit-warning = Quitting now:
+exit-item-download = Cancels the engine download ({ $percent }%) and removes the unfinished file
+exit-item-processing = Stops cleaning { $name } ({ $percent }%) and discards the unfinished output
+exit-item-benchmark = Stops the benchmark
+exit-item-recording = Stops the recording and keeps what was recorded
+exit-item-schedule = Calls off starting the files waiting at { $time }
+exit-keep-working = Keep working
+exit-confirm = Stop and quit
+shutdown-countdown = The computer shuts down in { $seconds } s
+shutdown-cancel = Don't shut down
+
+## Notices
+
+toast-file-added = Added { $name }
+toast-file-queued = { $name } will be cleaned after the files before it
+toast-duplicate-skipped = Skipped { $name }, a copy of a file already in the batch
+toast-folder-profile = Using the folder settings in { $path }
+toast-download-complete = Engine downloaded
+toast-engine-installed = Engine installed
+toast-engine-install-failed = Could not install the engine: { $error }
+toast-plugin-installed = Plugin installed
+toast-plugin-install-failed = Could not install the plugin: { $error }
+toast-error-copied = Error copied to the clipboard
+toast-status-bar-copied = Status bar copied to the clipboard.
+toast-mirror-missing = Enter a mirror address under Settings, then try again.
+toast-shell-menu-added = "Clean with DeepFilterNet" was added to the right-click menu of audio and video files.
+toast-shell-menu-removed = Removed from the right-click menu
+toast-webhook-failed = Could not notify the webhook: { $error }
+toast-update-failed = Could not update: { $error }
+
+## Desktop notifications
+
+notification-done = Cleaned { $name }
+notification-batch-done = Batch done
+notification-batch-done-body = { $count } files were cleaned
+notification-failed = Could not clean { $name }
+
+## Tray icon
+
+tray-idle = Idle
+tray-busy = Cleaning audio... { $percent }%
+tray-show-window = Show window
+tray-quit = Quit
+
+## First-run walkthrough
+
+onboarding-step = Step { $step } of { $steps }
+onboarding-welcome-title = Welcome to DeepFilterNet GUI
+onboarding-welcome-body = This app removes background noise from speech in audio and video files. It uses DeepFilterNet, a neural network that by default runs right on this computer, so recordings stay here.
+onboarding-engine-title = Download the engine
+onboarding-engine-body = The cleaning is done by the deep-filter program, which is downloaded once from the DeepFilterNet releases on GitHub.
+onboarding-engine-size = Download size: { $size } MB
+onboarding-engine-installed = The engine is installed.
+onboarding-engine-retry = Retry download
+onboarding-output-title = Where to save cleaned files
+onboarding-output-body = Cleaned files go to a dnf_clean folder next to each input, unless you pick one folder for all of them. This can be changed later under Settings.
+onboarding-test-title = Try it out
+onboarding-test-body = Clean a short generated sample to see how it works: a buzzing, voice-like tone mixed with hiss and hum.
+onboarding-test-run = Clean the sample
+onboarding-test-needs-engine = The sample can be cleaned once the engine is installed.
+onboarding-back = Back
+onboarding-next = Next
+onboarding-skip = Skip setup
+onboarding-finish = Finish
+
+## Status bar
+
+status-bar-app = DeepFilterNet GUI { $version }
+status-bar-engine = deep-filter { $version }
+status-bar-engine-missing = deep-filter not installed
+status-bar-model = Model: { $model }
+status-bar-model-remote = Model chosen by the server
+status-bar-counts = { $cleaned } cleaned, { $failed } failed
+status-bar-counts-running = { $counts }, 1 running
+status-bar-copy = Copy
+
+## Help on options
+
+help-remove-dc-offset = Removes a constant offset some recorders add to the waveform. Only needed when the waveform doesn't sit centered; harmless otherwise.
+help-trim-silence = Cuts quiet stretches from the start and end of the output. Skipped for video, where it would break lip sync.
+help-silence-threshold = Audio quieter than this counts as silence when trimming. -60 dBFS suits most recordings; raise it towards -40 for noisy rooms.
+help-channels = Which channels are cleaned. Downmixing or picking one channel helps when a voice was recorded on one channel of a stereo file, and writes a mono output.
+help-split-channels = Cleans each channel on its own, for interviews with one voice per channel, where the other channel would throw the filter off. The channels can stay in one file or go to a mono file each, named like take.ch2.wav.
+help-noise-reduction = How much of the cleaned signal is in the output; the rest is the original. 100% removes the most noise; 70–90% sounds more natural when voices turn thin.
+help-normalize = Scales the output so its loudest peak reaches this level. Useful for quiet recordings; the dynamics stay as they are.
+help-preserve-format = The engine works at 48 kHz and 16 bits. When on, the output is converted back to the input's sample rate and bit depth.
+help-keep-metadata = Copies tags, chapters and cover art from the input to the output.
+help-keep-broadcast-chunks = Copies Broadcast Wave timecode and iXML from WAV inputs, so the output lines up in video editors and DAWs.
+help-export-labels = Writes an Audacity label track next to the output marking where the most noise was removed, to check those places by ear.
+help-write-provenance = Writes a .dfn.json file next to the output with the engine version, model, options, checksums of the input and output, and timing, for archives and to repeat the run later.
+help-version-outputs = Cleaning a file again writes take.v2.wav, take.v3.wav and so on instead of replacing the previous output, and lists each version with the options it was made with in take.versions.jsonl.
+help-skip-duplicates = In batches and archives, files that are exact copies of an earlier one are skipped and listed once the batch is done.
+help-remux-video = For video inputs, also writes a copy of the video with the cleaned audio. The picture isn't re-encoded.
+help-bitrate = Bitrate of MP3, Ogg and video audio. 128 kbit/s is enough for speech; use 192 or more when there's music.
+help-low-priority = Runs the engine at a lower CPU priority so the computer stays responsive. Runs take longer while other programs are busy.
+help-threads = Caps how many CPU threads the engine uses. All is fastest; fewer leaves room for other work.
+help-concurrency = How many files of a batch are cleaned at the same time. Each runs its own engine, so more than one mostly helps with many short files.
+help-timeout = Runs taking longer than this many times the audio's length are stopped as hung. 10× is plenty on most machines; raise it on slow ones.
+help-engine-env = Environment variables the engine is run with, like OMP_NUM_THREADS, RUST_LOG or ORT_* ones, to tune its speed or logging. They win over the thread count above.
+help-settings-transfer = Saves all settings to a JSON file, or replaces them with those from one, to set up other machines the same way. The server token and webhook secret aren't exported.
+
+## Queue
+
+queue-title = Waiting ({ $count })
+queue-start = Start cleaning
+schedule-start-at = Start at…
+schedule-keep-awake = Keep the computer awake until then
+schedule-wake = Wake the computer for it
+schedule-waiting = Starts at { $time }
+schedule-cancel = Don't wait
+queue-remove = Remove
+queue-export = Save list…
+queue-import = Queue from list…
+express-choose = Express file…
+express-label = Express
+archive-open = Archive clean…
+archive-title = Archive clean
+archive-body = Cleans every supported file below the source into the same folders below the destination. Starting it again with the same folders skips the files already done.
+archive-source = Source
+archive-destination = Destination
+archive-none = Not chosen
+archive-choose = Choose…
+archive-copy-other = Copy other files as they are
+archive-start = Start
+archive-close = Close
+queue-settings = { $backend }, { $reduction }% noise reduction, saved { $folder }
+queue-next-to-input = next to the file
+queue-in-folder = in { $path }
+priority-high = Rush
+priority-normal = Normal
+priority-low = Low
+
+## History
+
+history-search = Search by file name or path
+history-outcome-all = All runs
+history-outcome-succeeded = Succeeded
+history-outcome-failed = Failed
+history-empty = No runs yet.
+history-succeeded = Cleaned { $duration } of audio in { $elapsed }
+history-failed = Failed: { $error }
+history-open-folder = Open folder
+history-rerun = Run again
+history-compare = Compare versions
+history-export-list = Save as list…
+compare-back = Back
+compare-version-a = A
+compare-version-b = B
+compare-option = Option
+compare-same-options = Both versions were made with the same options.
+compare-play = Play A/B
+compare-single-version = There's only one output of this file. Use Adjust & Re-run to make another one to compare.
+
+## Problem reports
+
+report-problem = Report a problem:
+report-save = Save report...
+report-github = Open a GitHub issue
+toast-report-saved = Report saved to { $path }
+
+## Logs
+
+logs-copy = Copy
+logs-save = Save to file...
+logs-app = App log
+logs-app-empty = Nothing logged yet.
+logs-engine = Engine output for the selected file
+logs-engine-running = Engine output (running)
+logs-engine-empty = The engine hasn't run yet.
+toast-logs-copied = Logs copied to the clipboard
+toast-logs-saved = Logs saved to { $path }
+toast-logs-save-failed = Could not save the logs: { $error }
+
+## App updates
+
+update-available = Version { $version } is available
+update-available-prerelease = Pre-release { $version } is available
+update-back-to-stable = Go back to the stable version { $version }
+update-install = Update and restart
+update-installing = Updating...
+update-dismiss = Not now
+accelerated-available = A faster engine build for { $provider } is available for your graphics card.
+accelerated-install = Download it
+leftovers-found = Runs that never finished left partial results behind ({ $count }):
+leftovers-rerun = Clean again
+leftovers-delete = Delete
+duplicates-skipped = The last batch skipped copies of files already in it ({ $count }):
+duplicates-copy-of = { $input }, a copy of { $original }
+
+## Crash reports
+
+crash-title = The app crashed last time
+crash-explanation = A report of the crash was saved. Sending it helps get the problem fixed.
+crash-report = Report on GitHub
+crash-open = Open report
+crash-dismiss = Dismiss
+toast-crash-report-unreadable = Could not read the crash report: { $error }
+toast-settings-exported = Settings exported to { $path }
+toast-settings-export-failed = Could not export the settings: { $error }
+toast-settings-imported = Settings imported from { $path }
+toast-settings-import-failed = Could not import the settings: { $error }
+toast-list-exported = List saved to { $path }
+toast-list-imported = Queued { $count } of the { $total } files in the list
+toast-archive-started = Queued { $count } files to clean, copied { $copied }, { $done } done before, { $duplicates } duplicates skipped
+toast-schedule-invalid = Enter a time of day like 2:00 or 14:30
+toast-schedule-set = The files waiting start at { $time }
+toast-schedule-no-wake = The computer can't be set to wake up, so keep it from sleeping instead
+toast-leftovers-deleted = Deleted { $count } partial files
+toast-archive-failed = Could not start the archive: { $error }
+toast-express-done = Express run saved to { $path }
+toast-express-failed = The express run failed: { $error }
+toast-worker-failed = Could not clean { $name }: { $error }
+
+## Settings
+
+settings-output-default = dnf_clean folder next to each input
+settings-save-to = Save to: { $folder }
+settings-choose = Choose...
+settings-reset = Reset
+settings-suffix = Append to file names
+settings-suffix-placeholder = e.g. _clean
+settings-engine = Engine
+settings-engine-mirror = Engine download mirror (optional)
+settings-server-url = Server URL
+settings-server-token = Token (optional)
+settings-webhook-url = Webhook for finished runs (optional)
+settings-webhook-secret = Webhook secret (optional)
+settings-low-priority = Background priority
+settings-threads-all = CPU threads: all
+settings-threads = CPU threads: { $threads }
+settings-concurrency = Files cleaned at once: { $files }
+settings-timeout = Give up after { $factor }× the audio length
+settings-engine-env = Engine environment:
+settings-engine-env-name = Name
+settings-engine-env-value = Value
+settings-engine-env-add = Add variable
+settings-theme = Theme
+settings-language = Language
+settings-log-verbosity = Log detail
+settings-open-log-folder = Open log folder
+settings-shell-menu-add = Add to right-click menu
+settings-shell-menu-remove = Remove from right-click menu
+settings-scale = Interface scale { $scale }×
+settings-text-size = Text size { $size } px (after restart)
+settings-notify-when-done = Notify when cleaning finishes in the background
+settings-notification-sound = Play a sound with notifications
+settings-open-folder-when-done = Show the cleaned file in the file manager when done
+settings-play-when-done = Play the cleaned file when done
+settings-batch-action = When a batch is done:
+settings-batch-command-placeholder = Command to run, like: rsync -a "$DFN_GUI_OUTPUT" backup:
+settings-check-updates = Check for app updates at startup
+settings-channel = Release channel:
+settings-reinstall-engine = Download the engine again
+settings-engine-provider = Engine build:
+settings-channel-beta-note = Pre-releases of the app and the engine may be unstable. Switch back to Stable to be offered the stable app again, and download the engine again to get its stable version.
+settings-tray-icon = Show an icon in the system tray
+settings-minimize-to-tray = Minimize to the tray
+settings-export = Export settings…
+settings-import = Import settings…
+
+backend-external-binary = deep-filter binary
+backend-in-process = deep-filter plugin, in the app
+backend-remote = Remote server
+channel-stable = Stable
+channel-beta = Beta (pre-releases)
+
+language-system = System language
+
+log-verbosity-errors = Errors only
+log-verbosity-normal = Normal
+log-verbosity-detailed = Detailed
+completion-nothing = Do nothing
+completion-sound = Play a sound
+completion-notification = Show a notification
+completion-open-folder = Open the output folder
+completion-command = Run a command
+completion-shut-down = Shut down the computer
@@ -0,0 +1,483 @@
+# French texts of DeepFilterNet GUI.
+
+app-title = DeepFilterNet GUI
+app-heading = Réduction de bruit DeepFilterNet
+sleep-reason = Nettoyage audio avec DeepFilterNet
+
+## Tabs
+
+tab-clean = Nettoyer
+tab-live = Direct
+tab-history = Historique
+tab-logs = Journaux
+tab-settings = Paramètres
+compact-enter = Compact
+new-window = Nouvelle fenêtre
+compact-leave = Agrandir
+
+## Selecting files
+
+file-filter-supported = Tous les fichiers pris en charge
+file-filter-audio = Tous les formats audio pris en charge
+file-filter-video = Vidéo
+file-filter-text = Liste texte
+file-drop-hint = Glissez-déposez un fichier audio ou vidéo ici ou cliquez pour choisir
+drop-release = Relâchez pour ouvrir le fichier
+drop-unsupported = Ce type de fichier ne peut pas être ouvert
+file-selected = Fichier : { $name }
+file-select = Choisir un fichier audio
+file-unknown-length = durée inconnue
+file-sample-rate = { $khz } kHz
+file-bit-depth = { $bits } bits
+file-mono = mono
+file-stereo = stéréo
+file-channels = { $channels } canaux
+file-unknown-channels = canaux inconnus
+file-size = { $megabytes } Mo
+note-wav-conversion = WAV encodé en { $encoding } ({ $bits } bits), { $rate } Hz, { $channels } can. : il sera converti en PCM
+note-misnamed-wav = Nommé .wav mais contient du { $kind } : il sera décodé comme tel
+note-music = Cela ressemble à de la musique. Le filtre est fait pour la voix et risque de l'appauvrir.
+note-music-lower = Baisser la réduction du bruit à { $percent } %
+error-unsupported-file = Type de fichier non pris en charge. Formats pris en charge : { $formats }
+
+## Engine download
+
+status-checking = Vérification des ressources...
+download-engine = Télécharger le moteur
+engine-missing = Le moteur de nettoyage n'est pas encore installé.
+engine-install-file = Installer depuis un fichier…
+download-progress = Téléchargement... { $percent } %
+error-download = Échec du téléchargement : { $error }
+offline-title = Vous semblez être hors ligne
+offline-body = Le moteur n'a pas pu être téléchargé car Internet est inaccessible. Vérifiez votre connexion et réessayez.
+offline-retry = Réessayer
+offline-install-hint = Ou téléchargez le fichier deep-filter depuis les versions de DeepFilterNet sur un autre appareil et installez-le à partir de là.
+
+## Cleaning options
+
+option-remove-dc-offset = Supprimer la composante continue
+option-trim-silence = Couper les silences de début et de fin
+option-silence-threshold = Silence sous { $db } dBFS
+option-channels = Canaux
+option-split-channels = Séparer les canaux
+option-noise-reduction = Réduction du bruit { $percent } %
+option-normalize = Normaliser la crête à { $db } dBFS
+option-preserve-format = Garder la fréquence et la résolution de la source
+option-save-as = Enregistrer en
+option-keep-metadata = Garder les tags et la pochette
+option-keep-broadcast-chunks = Garder le timecode BWF et l'iXML
+option-export-labels = Exporter des marqueurs Audacity des passages très nettoyés
+option-write-provenance = Enregistrer la provenance de la sortie (.dfn.json)
+option-version-outputs = Garder les sorties précédentes (take.v2.wav, …)
+option-skip-duplicates = Ignorer les entrées en double
+option-remux-video = Remettre l'audio nettoyé dans une copie des vidéos
+option-bitrate = Débit { $kbps } kbit/s
+option-needs-ffmpeg = Les formats autres que WAV et la sortie vidéo nécessitent ffmpeg dans le PATH
+
+format-wav = WAV
+format-flac = FLAC (sans perte)
+format-mp3 = MP3
+format-ogg = OGG Vorbis
+format-same-as-input = Comme l'entrée
+
+channels-keep = Comme enregistrés
+channels-mono = Mixer en mono
+channels-left = Gauche seulement
+channels-right = Droite seulement
+channels-only = Canal { $channel } seulement
+split-off = Nettoyer ensemble
+split-recombined = Nettoyer séparément, un seul fichier
+split-files = Nettoyer séparément, un fichier chacun
+
+## Processing
+
+status-ready = Prêt.
+status-clean = Nettoyer l'audio
+estimate-output = ≈{ $duration }, sortie ≈{ $size } Mo { $format }
+estimate-output-wav = ≈{ $duration }, sortie ≈{ $size } Mo WAV (ou ≈{ $flac } Mo FLAC)
+status-processing = { $phase }... { $percent } %
+status-finished = Terminé !
+status-saved-to = Enregistré dans : { $path }
+status-also-saved-to = Et : { $path }
+status-speed = { $factor }× temps réel, { $elapsed } pour { $duration } d'audio
+status-provider = Exécuté sur { $provider }
+status-session = Session : { $factor }× temps réel sur { $runs } fichiers ({ $duration } d'audio)
+status-open-location = Ouvrir l'emplacement
+status-cancel = Annuler
+status-adjust = Ajuster et relancer
+status-rerun = Nettoyer à nouveau
+status-adjusting = Ajustez les options ci-dessus. La nouvelle sortie est enregistrée à côté de { $name }, qui est conservé.
+
+phase-decoding = Lecture de l'audio
+phase-resampling = Rééchantillonnage à 48 kHz
+phase-preprocessing = Préparation de l'audio
+phase-denoising = Nettoyage de l'audio
+phase-mixing = Mixage
+phase-measuring = Mesure des niveaux
+phase-normalizing = Normalisation
+phase-restoring = Restauration du format source
+phase-encoding = Écriture du résultat
+phase-remuxing = Écriture de la vidéo
+
+levels-noise-floor = Bruit de fond
+levels-rms = RMS
+levels-peak = Crête
+levels-snr = SNR est.
+levels-snr-improvement = SNR amélioré d'environ { $db } dB
+levels-quality = Qualité estimée : { $score } / 5
+levels-quality-poor = Le nettoyage a sans doute échoué ou rendu la voix moins intelligible. Écoutez avant de l'utiliser.
+
+spectrograms-show = Afficher les spectrogrammes
+spectrograms-hide = Masquer les spectrogrammes
+spectrograms-original = Original
+spectrograms-cleaned = Nettoyé
+spectrograms-computing = Calcul des spectrogrammes...
+error-spectrograms = Impossible de calculer les spectrogrammes : { $error }
+
+error-summary = Erreur : { $error }
+error-show-details = Afficher le journal
+error-hide-details = Masquer le journal
+error-copy = Copier l'erreur
+error-lines-hidden = { $count } lignes précédentes masquées
+error-view-full-log = Voir le journal complet
+error-full-log = Journal complet
+error-save = Enregistrer…
+error-close-log = Fermer
+error-retry = Réessayer
+error-retry-download = Réessayer le téléchargement
+error-use-mirror = Utiliser un miroir
+error-retry-file = Réessayer le fichier
+error-skip-file = Passer
+error-reinstall-engine = Réinstaller le moteur
+error-choose-file = Choisir un autre fichier
+
+## Benchmark
+
+benchmark-start = Tester le moteur
+benchmark-progress = Test en cours... passe { $run } sur { $runs }
+benchmark-complete = Test terminé
+benchmark-result = Moyenne { $average }× temps réel, passe la plus lente { $slowest }× temps réel ({ $runs } passes)
+benchmark-threads-all = tous les
+benchmark-setup-normal = { $threads } threads CPU, priorité normale
+benchmark-setup-background = { $threads } threads CPU, priorité basse
+error-benchmark = Échec du test : { $error }
+
+## Preview and recording
+
+preview-play = Lire
+preview-pause = Pause
+preview-input = Entrée
+preview-cleaned = Nettoyé
+preview-original = Original
+preview-close = Fermer
+preview-play-input = Écouter l'entrée
+preview-play-output = Écouter le résultat
+preview-device-failed = { $error }. Une autre application utilise peut-être le périphérique en exclusivité. Fermez-la ou choisissez une autre sortie.
+preview-device-retry = Réessayer
+
+recording-start = Enregistrer
+recording-stop = Arrêter et nettoyer
+recording-elapsed = Enregistrement { $elapsed }
+recording-saved = Enregistrement sauvegardé dans { $path }
+
+device-input = Entrée
+device-output = Sortie
+device-default = Par défaut du système
+
+## Live listening
+
+live-intro = Écoutez votre microphone nettoyé pendant que vous parlez, pour essayer le moteur avant d'enregistrer. Utilisez un casque, sinon les haut-parleurs reviennent dans le microphone.
+live-plugin-missing = Le nettoyage en direct utilise le plugin LADSPA de DeepFilterNet, qui n'est pas encore installé.
+live-plugin-download = Télécharger le plugin
+live-plugin-downloading = Téléchargement du plugin… { $percent } %
+live-plugin-install = Installer le plugin depuis un fichier…
+live-attenuation = Réduction du bruit : { $db } dB
+live-start = Commencer l'écoute
+live-stop = Arrêter l'écoute
+live-play = Jouer sur la sortie
+live-virtual-mic = Proposer aux autres applications comme microphone, « { $name } »
+live-microphone = Microphone
+live-cleaned = Nettoyé
+error-plugin-download = Échec du téléchargement du plugin : { $error }
+
+## Quitting while busy
+
+exit-stopping = Arrêt du moteur...
+exit-title = Un travail est encore en cours.
+exit-warning = Quitter maintenant :
+exit-item-download = Annule le téléchargement du moteur ({ $percent } %) et supprime le fichier inachevé
+exit-item-processing = Arrête le nettoyage de { $name } ({ $percent } %) et abandonne le résultat inachevé
+exit-item-benchmark = Arrête le benchmark
+exit-item-recording = Arrête l'enregistrement et conserve ce qui a été enregistré
+exit-item-schedule = Annule le démarrage des fichiers en attente à { $time }
+exit-keep-working = Continuer
+exit-confirm = Arrêter et quitter
+shutdown-countdown = L'ordinateur s'éteint dans { $seconds } s
+shutdown-cancel = Ne pas éteindre
+
+## Notices
+
+toast-file-added = { $name } ajouté
+toast-file-queued = { $name } sera nettoyé après les fichiers qui le précèdent
+toast-duplicate-skipped = { $name } ignoré, copie d'un fichier déjà dans le lot
+toast-folder-profile = Réglages du dossier utilisés : { $path }
+toast-download-complete = Moteur téléchargé
+toast-engine-installed = Moteur installé
+toast-engine-install-failed = Impossible d'installer le moteur : { $error }
+toast-plugin-installed = Plugin installé
+toast-plugin-install-failed = Impossible d'installer le plugin : { $error }
+toast-error-copied = Erreur copiée dans le presse-papiers
+toast-status-bar-copied = Barre d'état copiée dans le presse-papiers.
+toast-mirror-missing = Saisissez l'adresse d'un miroir dans les Paramètres, puis réessayez.
+toast-shell-menu-added = « Clean with DeepFilterNet » a été ajouté au menu contextuel des fichiers audio et vidéo.
+toast-shell-menu-removed = Retiré du menu contextuel
+toast-webhook-failed = Impossible de notifier le webhook : { $error }
+toast-update-failed = Impossible de mettre à jour : { $error }
+
+## Desktop notifications
+
+notification-done = { $name } nettoyé
+notification-batch-done = Lot terminé
+notification-batch-done-body = { $count } fichiers ont été nettoyés
+notification-failed = Impossible de nettoyer { $name }
+
+## Tray icon
+
+tray-idle = Inactif
+tray-busy = Nettoyage de l'audio... { $percent } %
+tray-show-window = Afficher la fenêtre
+tray-quit = Quitter
+
+## First-run walkthrough
+
+onboarding-step = Étape { $step } sur { $steps }
+onboarding-welcome-title = Bienvenue dans DeepFilterNet GUI
+onboarding-welcome-body = Cette application retire le bruit de fond de la parole dans les fichiers audio et vidéo. Elle utilise DeepFilterNet, un réseau de neurones qui tourne par défaut sur cet ordinateur : les enregistrements restent ici.
+onboarding-engine-title = Télécharger le moteur
+onboarding-engine-body = Le nettoyage est fait par le programme deep-filter, téléchargé une seule fois depuis les versions de DeepFilterNet sur GitHub.
+onboarding-engine-size = Taille du téléchargement : { $size } Mo
+onboarding-engine-installed = Le moteur est installé.
+onboarding-engine-retry = Réessayer le téléchargement
+onboarding-output-title = Où enregistrer les fichiers nettoyés
+onboarding-output-body = Les fichiers nettoyés vont dans un dossier dnf_clean à côté de chaque entrée, sauf si vous choisissez un dossier commun. Ce choix peut être changé plus tard dans les Paramètres.
+onboarding-test-title = Essayer
+onboarding-test-body = Nettoyez un court échantillon généré pour voir le résultat : un son bourdonnant proche d'une voix, mêlé de souffle et de ronflement.
+onboarding-test-run = Nettoyer l'échantillon
+onboarding-test-needs-engine = L'échantillon pourra être nettoyé une fois le moteur installé.
+onboarding-back = Retour
+onboarding-next = Suivant
+onboarding-skip = Passer la configuration
+onboarding-finish = Terminer
+
+## Status bar
+
+status-bar-app = DeepFilterNet GUI { $version }
+status-bar-engine = deep-filter { $version }
+status-bar-engine-missing = deep-filter non installé
+status-bar-model = Modèle : { $model }
+status-bar-model-remote = Modèle choisi par le serveur
+status-bar-counts = { $cleaned } nettoyé(s), { $failed } échec(s)
+status-bar-counts-running = { $counts }, 1 en cours
+status-bar-copy = Copier
+
+## Help on options
+
+help-remove-dc-offset = Retire un décalage constant que certains enregistreurs ajoutent à la forme d'onde. Utile seulement si la forme d'onde n'est pas centrée ; sans effet néfaste sinon.
+help-trim-silence = Coupe les passages silencieux au début et à la fin du résultat. Ignoré pour les vidéos, où cela casserait la synchronisation labiale.
+help-silence-threshold = Un son plus faible compte comme silence lors de la coupe. -60 dBFS convient à la plupart des enregistrements ; montez vers -40 dans les pièces bruyantes.
+help-channels = Les canaux à nettoyer. Mixer en mono ou choisir un canal aide quand une voix a été enregistrée sur un seul canal d'un fichier stéréo, et donne une sortie mono.
+help-split-channels = Nettoie chaque canal séparément, pour les interviews avec une voix par canal, où l'autre canal perturberait le filtre. Les canaux restent dans un fichier ou vont chacun dans un fichier mono, nommé comme take.ch2.wav.
+help-noise-reduction = Part du signal nettoyé dans le résultat ; le reste est l'original. 100 % retire le plus de bruit ; 70 à 90 % sonne plus naturel quand les voix s'amincissent.
+help-normalize = Ajuste le résultat pour que son pic le plus fort atteigne ce niveau. Utile pour les enregistrements faibles ; la dynamique est conservée.
+help-preserve-format = Le moteur travaille en 48 kHz et 16 bits. Activé, le résultat est reconverti à la fréquence et à la résolution de l'entrée.
+help-keep-metadata = Copie les tags, chapitres et pochettes de l'entrée vers le résultat.
+help-keep-broadcast-chunks = Copie le timecode Broadcast Wave et l'iXML des entrées WAV, pour que le résultat se cale dans les logiciels de montage et les DAW.
+help-export-labels = Écrit à côté du résultat une piste de labels Audacity marquant où le plus de bruit a été retiré, pour vérifier ces passages à l'oreille.
+help-write-provenance = Écrit à côté de la sortie un fichier .dfn.json avec la version du moteur, le modèle, les options, les sommes de contrôle de l'entrée et de la sortie, et les durées, pour l'archivage et pour refaire le traitement plus tard.
+help-version-outputs = Nettoyer à nouveau un fichier écrit take.v2.wav, take.v3.wav et ainsi de suite au lieu de remplacer la sortie précédente, et liste chaque version avec les options utilisées dans take.versions.jsonl.
+help-skip-duplicates = Dans les lots et les archives, les fichiers identiques à un fichier précédent sont ignorés et listés à la fin du lot.
+help-remux-video = Pour les vidéos, écrit aussi une copie de la vidéo avec le son nettoyé. L'image n'est pas réencodée.
+help-bitrate = Débit du MP3, de l'Ogg et du son des vidéos. 128 kbit/s suffit pour la parole ; 192 ou plus s'il y a de la musique.
+help-low-priority = Exécute le moteur avec une priorité CPU réduite pour que l'ordinateur reste réactif. Les traitements sont plus longs quand d'autres programmes travaillent.
+help-threads = Limite le nombre de threads CPU utilisés par le moteur. Tous est le plus rapide ; moins laisse de la place aux autres tâches.
+help-concurrency = Combien de fichiers d'un lot sont traités en même temps. Chacun utilise son propre moteur, plusieurs aident donc surtout avec beaucoup de fichiers courts.
+help-timeout = Les traitements qui durent plus que ce multiple de la durée de l'audio sont arrêtés comme bloqués. 10× suffit sur la plupart des machines ; augmentez sur les plus lentes.
+help-engine-env = Variables d'environnement avec lesquelles le moteur est lancé, comme OMP_NUM_THREADS, RUST_LOG ou ORT_*, pour régler sa vitesse ou sa journalisation. Elles priment sur le nombre de threads ci-dessus.
+help-settings-transfer = Enregistre tous les réglages dans un fichier JSON, ou les remplace par ceux d'un fichier, pour configurer d'autres machines de la même façon. Le jeton du serveur et le secret du webhook ne sont pas exportés.
+
+## Queue
+
+queue-title = En attente ({ $count })
+queue-start = Lancer le nettoyage
+schedule-start-at = Démarrer à…
+schedule-keep-awake = Empêcher la mise en veille d'ici là
+schedule-wake = Sortir l'ordinateur de veille pour cela
+schedule-waiting = Démarre à { $time }
+schedule-cancel = Ne pas attendre
+queue-remove = Retirer
+queue-export = Enregistrer la liste…
+queue-import = Mettre en file depuis une liste…
+express-choose = Fichier express…
+express-label = Express
+archive-open = Nettoyer une archive…
+archive-title = Nettoyer une archive
+archive-body = Nettoie chaque fichier pris en charge sous la source dans les mêmes dossiers sous la destination. Le relancer avec les mêmes dossiers ignore les fichiers déjà traités.
+archive-source = Source
+archive-destination = Destination
+archive-none = Non choisi
+archive-choose = Choisir…
+archive-copy-other = Copier les autres fichiers tels quels
+archive-start = Démarrer
+archive-close = Fermer
+queue-settings = { $backend }, réduction du bruit de { $reduction } %, enregistré { $folder }
+queue-next-to-input = à côté du fichier
+queue-in-folder = dans { $path }
+priority-high = Urgent
+priority-normal = Normale
+priority-low = Basse
+
+## History
+
+history-search = Rechercher par nom de fichier ou chemin
+history-outcome-all = Toutes les exécutions
+history-outcome-succeeded = Réussies
+history-outcome-failed = Échouées
+history-empty = Aucune exécution pour l'instant.
+history-succeeded = { $duration } d'audio nettoyés en { $elapsed }
+history-failed = Échec : { $error }
+history-open-folder = Ouvrir le dossier
+history-rerun = Relancer
+history-compare = Comparer les versions
+history-export-list = Enregistrer comme liste…
+compare-back = Retour
+compare-version-a = A
+compare-version-b = B
+compare-option = Option
+compare-same-options = Les deux versions ont été faites avec les mêmes options.
+compare-play = Écouter A/B
+compare-single-version = Ce fichier n'a qu'une sortie. Utilisez « Ajuster et relancer » pour en faire une autre à comparer.
+
+## Problem reports
+
+report-problem = Signaler un problème :
+report-save = Enregistrer le rapport...
+report-github = Ouvrir un ticket GitHub
+toast-report-saved = Rapport enregistré dans { $path }
+
+## Logs
+
+logs-copy = Copier
+logs-save = Enregistrer dans un fichier...
+logs-app = Journal de l'application
+logs-app-empty = Rien n'a encore été journalisé.
+logs-engine = Sortie du moteur pour le fichier sélectionné
+logs-engine-running = Sortie du moteur (en cours)
+logs-engine-empty = Le moteur n'a pas encore été lancé.
+toast-logs-copied = Journaux copiés dans le presse-papiers
+toast-logs-saved = Journaux enregistrés dans { $path }
+toast-logs-save-failed = Impossible d'enregistrer les journaux : { $error }
+
+## App updates
+
+update-available = La version { $version } est disponible
+update-available-prerelease = La préversion { $version } est disponible
+update-back-to-stable = Revenir à la version stable { $version }
+update-install = Mettre à jour et redémarrer
+update-installing = Mise à jour...
+update-dismiss = Plus tard
+accelerated-available = Une version plus rapide du moteur pour { $provider } est disponible pour votre carte graphique.
+accelerated-install = La télécharger
+leftovers-found = Des traitements interrompus ont laissé des résultats incomplets ({ $count }) :
+leftovers-rerun = Traiter à nouveau
+leftovers-delete = Supprimer
+duplicates-skipped = Le dernier lot a ignoré des copies de fichiers qu'il contenait ({ $count }) :
+duplicates-copy-of = { $input }, copie de { $original }
+
+## Crash reports
+
+crash-title = L'application a planté la dernière fois
+crash-explanation = Un rapport du plantage a été enregistré. L'envoyer aide à corriger le problème.
+crash-report = Signaler sur GitHub
+crash-open = Ouvrir le rapport
+crash-dismiss = Ignorer
+toast-crash-report-unreadable = Impossible de lire le rapport de plantage : { $error }
+toast-settings-exported = Réglages exportés vers { $path }
+toast-settings-export-failed = Impossible d'exporter les réglages : { $error }
+toast-settings-imported = Réglages importés depuis { $path }
+toast-settings-import-failed = Impossible d'importer les réglages : { $error }
+toast-list-exported = Liste enregistrée dans { $path }
+toast-list-imported = { $count } des { $total } fichiers de la liste mis en file
+toast-archive-started = { $count } fichiers à nettoyer mis en file, { $copied } copiés, { $done } déjà traités, { $duplicates } doublons ignorés
+toast-schedule-invalid = Saisissez une heure comme 2:00 ou 14:30
+toast-schedule-set = Les fichiers en attente démarrent à { $time }
+toast-schedule-no-wake = L'ordinateur ne peut pas être réglé pour sortir de veille, empêchez plutôt sa mise en veille
+toast-leftovers-deleted = { $count } fichiers incomplets supprimés
+toast-archive-failed = Impossible de démarrer l'archive : { $error }
+toast-express-done = Traitement express enregistré dans { $path }
+toast-express-failed = Le traitement express a échoué : { $error }
+toast-worker-failed = Impossible de traiter { $name } : { $error }
+
+## Settings
+
+settings-output-default = Dossier dnf_clean à côté de chaque entrée
+settings-save-to = Enregistrer dans : { $folder }
+settings-choose = Choisir...
+settings-reset = Réinitialiser
+settings-suffix = Ajouter aux noms de fichiers
+settings-suffix-placeholder = p. ex. _clean
+settings-engine = Moteur
+settings-engine-mirror = Miroir de téléchargement du moteur (facultatif)
+settings-server-url = URL du serveur
+settings-server-token = Jeton (facultatif)
+settings-webhook-url = Webhook pour les traitements terminés (facultatif)
+settings-webhook-secret = Secret du webhook (facultatif)
+settings-low-priority = Priorité basse
+settings-threads-all = Threads CPU : tous
+settings-threads = Threads CPU : { $threads }
+settings-concurrency = Fichiers traités en même temps : { $files }
+settings-timeout = Abandonner après { $factor }× la durée de l'audio
+settings-engine-env = Environnement du moteur :
+settings-engine-env-name = Nom
+settings-engine-env-value = Valeur
+settings-engine-env-add = Ajouter une variable
+settings-theme = Thème
+settings-language = Langue
+settings-log-verbosity = Niveau du journal
+settings-open-log-folder = Ouvrir le dossier des journaux
+settings-shell-menu-add = Ajouter au menu contextuel
+settings-shell-menu-remove = Retirer du menu contextuel
+settings-scale = Échelle de l'interface { $scale }×
+settings-text-size = Taille du texte { $size } px (après redémarrage)
+settings-notify-when-done = Notifier quand le nettoyage se termine en arrière-plan
+settings-notification-sound = Jouer un son avec les notifications
+settings-open-folder-when-done = Afficher le fichier nettoyé dans le gestionnaire de fichiers une fois terminé
+settings-play-when-done = Lire le fichier nettoyé une fois terminé
+settings-batch-action = Quand un lot est terminé :
+settings-batch-command-placeholder = Commande à lancer, par exemple : rsync -a "$DFN_GUI_OUTPUT" backup:
+settings-check-updates = Rechercher les mises à jour de l'application au démarrage
+settings-channel = Canal de publication :
+settings-reinstall-engine = Télécharger à nouveau le moteur
+settings-engine-provider = Version du moteur :
+settings-channel-beta-note = Les préversions de l'application et du moteur peuvent être instables. Revenez sur Stable pour que l'application stable soit de nouveau proposée, et téléchargez à nouveau le moteur pour obtenir sa version stable.
+settings-tray-icon = Afficher une icône dans la zone de notification
+settings-minimize-to-tray = Réduire dans la zone de notification
+settings-export = Exporter les réglages…
+settings-import = Importer des réglages…
+
+backend-external-binary = Programme deep-filter
+backend-in-process = Plugin deep-filter dans l'app
+backend-remote = Serveur distant
+channel-stable = Stable
+channel-beta = Bêta (préversions)
+
+language-system = Langue du système
+
+log-verbosity-errors = Erreurs seulement
+log-verbosity-normal = Normal
+log-verbosity-detailed = Détaillé
+completion-nothing = Ne rien faire
+completion-sound = Jouer un son
+completion-notification = Afficher une notification
+completion-open-folder = Ouvrir le dossier de sortie
+completion-command = Lancer une commande
+completion-shut-down = Éteindre l'ordinateur
@@ -0,0 +1,220 @@
+//! Cleaning a folder tree into another with the same layout, set up in a
+//! form on the clean screen and cleaned from the queue.
+
+use crate::{DfnGui, Message, blocking_stream, toast};
+use dfn_core::i18n::tr;
+use dfn_core::pipeline::ProcessingOptions;
+use dfn_core::{archive, queue};
+use futures_util::Stream;
+use iced::widget::{button, checkbox, column, container, row, text};
+use iced::{Alignment, Element, Length, Task};
+use rfd::FileDialog;
+use std::path::PathBuf;
+
+/// The folders and choices of an archive to start.
+#[derive(Debug, Default)]
+pub struct ArchiveForm {
+    pub source: Option<PathBuf>,
+    pub destination: Option<PathBuf>,
+    /// Copy the files that aren't cleaned over as they are.
+    pub copy_other: bool,
+}
+
+/// Cleaning a folder tree into another with the same layout.
+#[derive(Debug, Clone)]
+pub enum ArchiveMessage {
+    Open,
+    Close,
+    ChooseSource,
+    SourceChosen(Option<PathBuf>),
+    ChooseDestination,
+    DestinationChosen(Option<PathBuf>),
+    CopyOtherToggled(bool),
+    Start,
+    /// The files left to clean were found, and the others copied.
+    Planned(Result<(archive::Archive, archive::Plan), String>),
+}
+
+impl DfnGui {
+    pub fn update_archive(&mut self, message: ArchiveMessage) -> Task<Message> {
+        match message {
+            ArchiveMessage::Open => self.archive_form = Some(ArchiveForm::default()),
+            ArchiveMessage::Close => self.archive_form = None,
+            ArchiveMessage::ChooseSource => {
+                return Task::perform(async { FileDialog::new().pick_folder() }, |folder| {
+                    Message::Archive(ArchiveMessage::SourceChosen(folder))
+                });
+            }
+            ArchiveMessage::ChooseDestination => {
+                return Task::perform(async { FileDialog::new().pick_folder() }, |folder| {
+                    Message::Archive(ArchiveMessage::DestinationChosen(folder))
+                });
+            }
+            ArchiveMessage::SourceChosen(folder) => {
+                if let Some(form) = &mut self.archive_form
+                    && folder.is_some()
+                {
+                    form.source = folder;
+                }
+            }
+            ArchiveMessage::DestinationChosen(folder) => {
+                if let Some(form) = &mut self.archive_form
+                    && folder.is_some()
+                {
+                    form.destination = folder;
+                }
+            }
+            ArchiveMessage::CopyOtherToggled(enabled) => {
+                if let Some(form) = &mut self.archive_form {
+                    form.copy_other = enabled;
+                }
+            }
+            ArchiveMessage::Start => {
+                let Some(ArchiveForm {
+                    source: Some(source),
+                    destination: Some(destination),
+                    copy_other,
+                }) = &self.archive_form
+                else {
+                    return Task::none();
+                };
+                match archive::Archive::new(source.clone(), destination.clone()) {
+                    Ok(archive) => {
+                        return Task::run(
+                            archive_process(archive, *copy_other, self.options.skip_duplicates),
+                            |message| message,
+                        );
+                    }
+                    Err(e) => self.toasts.push(toast::Severity::Error, e),
+                }
+            }
+            ArchiveMessage::Planned(Ok((archive, plan))) => {
+                tracing::info!(
+                    "Archiving {} into {}: {} files to clean, {} copied, {} done before, {} duplicates",
+                    archive.source.display(),
+                    archive.destination.display(),
+                    plan.clean.len(),
+                    plan.copy.len(),
+                    plan.done,
+                    plan.duplicates.len()
+                );
+                for (input, original) in &plan.duplicates {
+                    tracing::info!(
+                        "Skipped {}, a copy of {}",
+                        input.display(),
+                        original.display()
+                    );
+                }
+                self.batch_duplicates
+                    .extend(plan.duplicates.iter().cloned());
+                for (input, folder) in plan.clean.iter().cloned() {
+                    let options = ProcessingOptions {
+                        output_dir: Some(folder),
+                        ..self.options.clone()
+                    };
+                    self.queue.push(input, options, queue::Priority::default());
+                }
+                self.toasts.push(
+                    toast::Severity::Success,
+                    tr!(
+                        "toast-archive-started",
+                        count = plan.clean.len(),
+                        copied = plan.copy.len(),
+                        done = plan.done,
+                        duplicates = plan.duplicates.len()
+                    ),
+                );
+                self.archive = Some(archive);
+                self.archive_form = None;
+                if self.is_idle() && self.schedule.is_none() {
+                    return self.start_queued();
+                }
+            }
+            ArchiveMessage::Planned(Err(e)) => {
+                tracing::error!("Could not start the archive: {}", e);
+                self.toasts.push(
+                    toast::Severity::Error,
+                    tr!("toast-archive-failed", error = e),
+                );
+            }
+        }
+        Task::none()
+    }
+
+    pub fn view_archive_form<'a>(&self, form: &'a ArchiveForm) -> Element<'a, Message> {
+        let folder = |label: String, path: &'a Option<PathBuf>, choose: ArchiveMessage| {
+            row![
+                text(label).size(12).width(Length::Fixed(100.0)),
+                text(
+                    path.as_ref()
+                        .map(|path| path.display().to_string())
+                        .unwrap_or_else(|| tr!("archive-none"))
+                )
+                .size(12)
+                .width(Length::Fill),
+                button(text(tr!("archive-choose")).size(12))
+                    .on_press(Message::Archive(choose))
+                    .style(button::secondary),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center)
+        };
+        let ready = form.source.is_some() && form.destination.is_some();
+        container(
+            column![
+                text(tr!("archive-title")).size(14),
+                text(tr!("archive-body")).size(12),
+                folder(
+                    tr!("archive-source"),
+                    &form.source,
+                    ArchiveMessage::ChooseSource
+                ),
+                folder(
+                    tr!("archive-destination"),
+                    &form.destination,
+                    ArchiveMessage::ChooseDestination
+                ),
+                checkbox(form.copy_other)
+                    .label(tr!("archive-copy-other"))
+                    .on_toggle(|enabled| Message::Archive(ArchiveMessage::CopyOtherToggled(
+                        enabled
+                    )))
+                    .text_size(12),
+                row![
+                    button(text(tr!("archive-start")).size(12))
+                        .on_press_maybe(ready.then_some(Message::Archive(ArchiveMessage::Start))),
+                    button(text(tr!("archive-close")).size(12))
+                        .on_press(Message::Archive(ArchiveMessage::Close))
+                        .style(button::secondary),
+                ]
+                .spacing(10),
+            ]
+            .spacing(8),
+        )
+        .padding(10)
+        .width(Length::Fill)
+        .style(container::bordered_box)
+        .into()
+    }
+}
+
+/// Finds what's left of `archive` to clean and copies the other files when
+/// `copy_other` is set, off the UI thread.
+fn archive_process(
+    archive: archive::Archive,
+    copy_other: bool,
+    skip_duplicates: bool,
+) -> impl Stream<Item = Message> {
+    blocking_stream(
+        move |_| {
+            let result = archive.plan(copy_other, skip_duplicates).and_then(|plan| {
+                for (file, to) in &plan.copy {
+                    archive.copy(file, to)?;
+                }
+                Ok((archive, plan))
+            });
+            Message::Archive(ArchiveMessage::Planned(result))
+        },
+        |e| Message::Archive(ArchiveMessage::Planned(Err(e))),
+    )
+}
@@ -0,0 +1,501 @@
+//! The clean screen: the file to clean, picked or dropped, with what's
+//! known of it, its waveform and, once cleaned, its spectrograms, above the
+//! panels of the status, the queue, the preview and the recording.
+
+use crate::downloader::{DownloadMessage, install_from_file_button};
+use crate::player::{PlayerMessage, Preview};
+use crate::processing::Failure;
+use crate::queue_panel::QueueMessage;
+use crate::status_panel::StatusMessage;
+use crate::{
+    ArchiveMessage, DfnGui, EngineState, JobState, Message, Status, blocking_stream, cancel_button,
+    format_duration, playback, recording, spectrogram, style, toast, waveform,
+};
+use dfn_core::i18n::tr;
+use dfn_core::{audio, content, formats};
+use futures_util::Stream;
+use iced::widget::{button, canvas, column, container, image, progress_bar, row, text};
+use iced::{Alignment, Element, Length, Task, Theme};
+use rfd::FileDialog;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone)]
+pub enum CleanMessage {
+    SelectFile,
+    FileSelected(Option<PathBuf>),
+    /// Waveform peaks of the file at the path, for the input or the output.
+    PeaksLoaded(Result<(Preview, PathBuf, waveform::Peaks), String>),
+    ContentGuessed(Result<(PathBuf, Option<content::Content>), String>),
+    ToggleSpectrograms,
+    /// Input and output spectrograms for the output at the path.
+    SpectrogramsLoaded(Result<(PathBuf, image::Handle, image::Handle), String>),
+}
+
+/// Whether files dragged over the window can be opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DragHover {
+    Supported,
+    /// At least one of the files has a type that isn't supported.
+    Unsupported,
+}
+
+impl DfnGui {
+    pub fn update_clean(&mut self, message: CleanMessage) -> Task<Message> {
+        match message {
+            CleanMessage::SelectFile => {
+                return Task::perform(pick_input(), |file| {
+                    Message::Clean(CleanMessage::FileSelected(file))
+                });
+            }
+            CleanMessage::FileSelected(file) => {
+                if let Some(path) = file {
+                    return self.take_file(path);
+                }
+            }
+            CleanMessage::PeaksLoaded(Ok((preview, path, peaks))) => match preview {
+                Preview::Input if self.status.selected_file() == Some(path.as_path()) => {
+                    self.input_peaks = Some(peaks);
+                }
+                Preview::Output if matches!(&self.status, Status::Job(JobState::Done { report, .. }) if report.output_path == path) =>
+                {
+                    self.output_peaks = Some(peaks);
+                }
+                // A result for a file that has since been replaced.
+                _ => {}
+            },
+            // The waveform is only an aid, so it is simply left out.
+            CleanMessage::PeaksLoaded(Err(_)) => {}
+            CleanMessage::ContentGuessed(Ok((path, content))) => {
+                if self.status.selected_file() == Some(path.as_path()) {
+                    self.input_content = content;
+                }
+            }
+            // Like the waveform, the warning is only an aid.
+            CleanMessage::ContentGuessed(Err(_)) => {}
+            CleanMessage::ToggleSpectrograms => {
+                self.show_spectrograms = !self.show_spectrograms;
+                if self.show_spectrograms && !matches!(self.spectrograms, Some(Ok(_))) {
+                    self.spectrograms = None;
+                    return self.load_spectrograms();
+                }
+            }
+            CleanMessage::SpectrogramsLoaded(Ok((path, input, output))) => {
+                if matches!(&self.status, Status::Job(JobState::Done { report, .. }) if report.output_path == path)
+                {
+                    self.spectrograms = Some(Ok((input, output)));
+                }
+            }
+            CleanMessage::SpectrogramsLoaded(Err(e)) => {
+                self.spectrograms = Some(Err(e));
+            }
+        }
+        Task::none()
+    }
+
+    /// Selects `path`, or queues it with the current options while another
+    /// file is cleaned.
+    pub fn take_file(&mut self, path: PathBuf) -> Task<Message> {
+        if matches!(self.status, Status::Job(JobState::Processing(_))) {
+            self.queue_file(path, self.options.clone());
+            return Task::none();
+        }
+        self.select_file(path)
+    }
+
+    /// Selects `path` as input after checking that it can be read. WAV files
+    /// are inspected up front so mislabeled or unusual encodings are reported
+    /// before any processing starts.
+    pub fn select_file(&mut self, path: PathBuf) -> Task<Message> {
+        self.forget_file();
+        if audio::is_wav(&path) {
+            match audio::inspect_wav(&path) {
+                Ok(audio::WavContent::Wav(header)) if header.is_pcm() => {}
+                Ok(audio::WavContent::Wav(header)) => {
+                    self.input_note = Some(tr!(
+                        "note-wav-conversion",
+                        encoding = header.encoding(),
+                        bits = header.bits_per_sample,
+                        rate = header.sample_rate,
+                        channels = header.channels
+                    ));
+                }
+                Ok(audio::WavContent::Other(kind)) => {
+                    self.input_note = Some(tr!("note-misnamed-wav", kind = kind));
+                }
+                Err(e) => {
+                    tracing::warn!("Could not open {}: {}", path.display(), e);
+                    self.fail_input(e);
+                    return Task::none();
+                }
+            }
+        }
+        self.input_info = audio::probe(&path).ok();
+        if let Some(name) = path.file_name() {
+            self.toasts.push(
+                toast::Severity::Info,
+                tr!("toast-file-added", name = name.to_string_lossy()),
+            );
+        }
+        let engine_ready = self.engine_ready();
+        match &mut self.status {
+            // A failed download may have left the engine as it was.
+            Status::Engine {
+                state: EngineState::Failed(_) | EngineState::Offline,
+                ..
+            } if engine_ready => self.status = Status::Job(JobState::Ready(path.clone())),
+            Status::Engine { waiting, .. } => *waiting = Some(path.clone()),
+            Status::Job(_) => self.status = Status::Job(JobState::Ready(path.clone())),
+        }
+        Task::batch([
+            Task::run(peaks_process(Preview::Input, path.clone()), Message::Clean),
+            Task::run(content_process(path), Message::Clean),
+        ])
+    }
+
+    /// Drops what was shown about the selected file and its output.
+    pub fn forget_file(&mut self) {
+        self.input_info = None;
+        self.input_note = None;
+        self.input_content = None;
+        self.player = None;
+        self.preview_error = None;
+        self.device_error = None;
+        self.input_peaks = None;
+        self.output_peaks = None;
+        self.spectrograms = None;
+    }
+
+    /// Reports that the file to clean couldn't be read.
+    pub fn fail_input(&mut self, e: String) {
+        match &mut self.status {
+            // The engine still has to be put in place, which the error
+            // mustn't hide.
+            Status::Engine { waiting, .. } => {
+                *waiting = None;
+                self.toasts.push(toast::Severity::Error, e);
+            }
+            Status::Job(_) => {
+                self.status = Status::Job(JobState::Failed {
+                    failure: Failure::Input,
+                    error: e,
+                    input: None,
+                });
+            }
+        }
+    }
+
+    /// Starts computing spectrograms of the finished run, if they are shown.
+    pub fn load_spectrograms(&self) -> Task<Message> {
+        match &self.status {
+            Status::Job(JobState::Done { input, report, .. }) if self.show_spectrograms => {
+                Task::run(
+                    spectrogram_process(input.clone(), report.output_path.clone()),
+                    Message::Clean,
+                )
+            }
+            _ => Task::none(),
+        }
+    }
+
+    pub fn view_clean(&self) -> Element<'_, Message> {
+        let mut content = column![self.view_main_area()];
+        if self.input_peaks.is_some() {
+            content = content.push(self.view_waveform());
+        }
+        if let Status::Job(JobState::Done { .. }) = self.status {
+            content = content.push(self.view_spectrograms());
+        }
+        content = content.push(self.view_status());
+        if !self.queue.is_empty() || self.express.is_some() || !self.workers.is_empty() {
+            content = content.push(self.view_queue());
+        }
+        if let Some(form) = &self.archive_form {
+            content = content.push(self.view_archive_form(form));
+        }
+        if playback::AVAILABLE {
+            content = content.push(self.view_preview());
+        }
+        if recording::AVAILABLE {
+            content = content.push(self.view_recording());
+        }
+        if playback::AVAILABLE || recording::AVAILABLE {
+            content = content.push(self.view_devices());
+        }
+        if self.is_idle() && self.express.is_none() {
+            content = content.push(
+                button(text(tr!("benchmark-start")).size(12))
+                    .on_press(Message::Status(StatusMessage::StartBenchmark))
+                    .style(button::text),
+            );
+        }
+        content.spacing(20).align_x(Alignment::Center).into()
+    }
+
+    fn view_main_area(&self) -> Element<'_, Message> {
+        match &self.status {
+            Status::Engine {
+                state: EngineState::Checking,
+                ..
+            } => text(tr!("status-checking")).into(),
+            Status::Engine {
+                state: EngineState::Missing,
+                ..
+            } => column![
+                text(tr!("engine-missing")),
+                button(text(tr!("download-engine")))
+                    .on_press(Message::Download(DownloadMessage::Start))
+                    .padding(20),
+                install_from_file_button(),
+            ]
+            .spacing(10)
+            .align_x(Alignment::Center)
+            .into(),
+            Status::Engine {
+                state: EngineState::Downloading { progress, .. },
+                ..
+            } => column![
+                text(tr!(
+                    "download-progress",
+                    percent = format!("{:.0}", progress)
+                )),
+                progress_bar(0.0..=100.0, *progress),
+                cancel_button(),
+            ]
+            .spacing(10)
+            .align_x(Alignment::Center)
+            .into(),
+            _ => {
+                let hover = self.drag_hover.filter(|_| self.accepts_drops());
+                container(
+                    column![
+                        text(self.drop_zone_label(hover)),
+                        text(
+                            self.input_info
+                                .as_ref()
+                                .map(describe_file)
+                                .unwrap_or_default()
+                        )
+                        .size(12),
+                        text(self.input_note.as_deref().unwrap_or_default()).size(12),
+                        self.view_music_warning()
+                            .map(|warning| warning.map(Message::Options)),
+                        row![
+                            button(text(tr!("file-select"))).on_press_maybe(
+                                self.accepts_drops()
+                                    .then_some(Message::Clean(CleanMessage::SelectFile))
+                            ),
+                            button(text(tr!("queue-import")))
+                                .on_press_maybe(
+                                    self.accepts_drops()
+                                        .then_some(Message::Queue(QueueMessage::Import))
+                                )
+                                .style(button::secondary),
+                            button(text(tr!("archive-open")))
+                                .on_press_maybe(
+                                    (self.accepts_drops() && self.archive_form.is_none())
+                                        .then_some(Message::Archive(ArchiveMessage::Open))
+                                )
+                                .style(button::secondary),
+                        ]
+                        .spacing(10),
+                        self.view_options().map(Message::Options),
+                    ]
+                    .spacing(10)
+                    .align_x(Alignment::Center),
+                )
+                .padding(40)
+                .width(Length::Fill)
+                .style(move |theme: &Theme| drop_zone_style(theme, hover))
+                .into()
+            }
+        }
+    }
+
+    pub fn drop_zone_label(&self, hover: Option<DragHover>) -> String {
+        match (hover, self.status.selected_file()) {
+            (Some(DragHover::Supported), _) => tr!("drop-release"),
+            (Some(DragHover::Unsupported), _) => tr!("drop-unsupported"),
+            (None, Some(path)) => tr!(
+                "file-selected",
+                name = path.file_name().unwrap().to_string_lossy()
+            ),
+            (None, None) => tr!("file-drop-hint"),
+        }
+    }
+
+    fn view_waveform(&self) -> Element<'_, Message> {
+        let position = self.player.as_ref().map(|(_, player)| {
+            player.position().as_secs_f32() / player.duration().as_secs_f32().max(f32::EPSILON)
+        });
+        canvas(waveform::Waveform {
+            input: self.input_peaks.as_ref(),
+            output: self.output_peaks.as_ref(),
+            position,
+            on_seek: self.player.is_some().then_some(
+                (|fraction| Message::Player(PlayerMessage::SeekFraction(fraction)))
+                    as fn(f32) -> Message,
+            ),
+        })
+        .width(Length::Fill)
+        .height(if self.output_peaks.is_some() { 120 } else { 60 })
+        .into()
+    }
+
+    fn view_spectrograms(&self) -> Element<'_, Message> {
+        let toggle = button(
+            text(if self.show_spectrograms {
+                tr!("spectrograms-hide")
+            } else {
+                tr!("spectrograms-show")
+            })
+            .size(12),
+        )
+        .on_press(Message::Clean(CleanMessage::ToggleSpectrograms))
+        .style(button::text);
+        if !self.show_spectrograms {
+            return toggle.into();
+        }
+
+        let images: Element<'_, Message> = match &self.spectrograms {
+            Some(Ok((input, output))) => row![
+                column![
+                    text(tr!("spectrograms-original")).size(12),
+                    image(input.clone())
+                ]
+                .spacing(4),
+                column![
+                    text(tr!("spectrograms-cleaned")).size(12),
+                    image(output.clone())
+                ]
+                .spacing(4),
+            ]
+            .spacing(10)
+            .into(),
+            Some(Err(e)) => text(tr!("error-spectrograms", error = e))
+                .size(12)
+                .style(text::danger)
+                .into(),
+            None => text(tr!("spectrograms-computing")).size(12).into(),
+        };
+        column![toggle, images]
+            .spacing(5)
+            .align_x(Alignment::Center)
+            .into()
+    }
+}
+
+/// Border of the drop zone, lit up while files are dragged over it.
+pub fn drop_zone_style(theme: &Theme, hover: Option<DragHover>) -> container::Style {
+    container::Style {
+        border: iced::Border {
+            color: match hover {
+                Some(DragHover::Supported) => theme.extended_palette().primary.base.color,
+                Some(DragHover::Unsupported) => theme.extended_palette().danger.base.color,
+                None => theme.extended_palette().background.strong.color,
+            },
+            width: if style::is_high_contrast(theme) {
+                3.0
+            } else {
+                2.0
+            },
+            radius: 10.0.into(),
+        },
+        ..Default::default()
+    }
+}
+
+/// One-line summary such as "3:24 · 44.1 kHz · 24-bit · stereo · pcm_s24le · 35.2 MB".
+fn describe_file(info: &audio::FileInfo) -> String {
+    let mut parts = vec![
+        info.duration
+            .map(format_duration)
+            .unwrap_or_else(|| tr!("file-unknown-length")),
+    ];
+    if let Some(rate) = info.sample_rate {
+        parts.push(tr!("file-sample-rate", khz = rate as f32 / 1000.0));
+    }
+    if let Some(bits) = info.bits_per_sample {
+        parts.push(tr!("file-bit-depth", bits = bits));
+    }
+    parts.push(match info.channels {
+        Some(1) => tr!("file-mono"),
+        Some(2) => tr!("file-stereo"),
+        Some(channels) => tr!("file-channels", channels = channels),
+        None => tr!("file-unknown-channels"),
+    });
+    parts.push(info.codec.clone());
+    parts.push(tr!(
+        "file-size",
+        megabytes = format!("{:.1}", info.size as f64 / 1_000_000.0)
+    ));
+    parts.join(" · ")
+}
+
+/// Asks for a file to clean, showing the supported ones.
+pub async fn pick_input() -> Option<PathBuf> {
+    FileDialog::new()
+        .add_filter(
+            tr!("file-filter-supported"),
+            &formats::supported_extensions(),
+        )
+        .add_filter(
+            tr!("file-filter-audio"),
+            &formats::extensions(formats::Kind::Audio),
+        )
+        .add_filter(
+            tr!("file-filter-video"),
+            &formats::extensions(formats::Kind::Video),
+        )
+        .pick_file()
+}
+
+/// Decodes `path` and reduces it to waveform peaks off the UI thread.
+pub fn peaks_process(preview: Preview, path: PathBuf) -> impl Stream<Item = CleanMessage> {
+    blocking_stream(
+        move |_| {
+            let peaks = audio::decode(&path)
+                .map(|(_, buffer)| waveform::Peaks::compute(&buffer, waveform::COLUMNS));
+            CleanMessage::PeaksLoaded(peaks.map(|peaks| (preview, path, peaks)))
+        },
+        |e| CleanMessage::PeaksLoaded(Err(e)),
+    )
+}
+
+/// Guesses what the file at `path` holds off the UI thread.
+fn content_process(path: PathBuf) -> impl Stream<Item = CleanMessage> {
+    blocking_stream(
+        move |_| {
+            let content = audio::decode(&path).map(|(_, buffer)| content::classify(&buffer));
+            CleanMessage::ContentGuessed(content.map(|content| (path, content)))
+        },
+        |e| CleanMessage::ContentGuessed(Err(e)),
+    )
+}
+
+/// Computes input and output spectrograms off the UI thread.
+fn spectrogram_process(
+    input_path: PathBuf,
+    output_path: PathBuf,
+) -> impl Stream<Item = CleanMessage> {
+    const WIDTH: usize = 280;
+    const HEIGHT: usize = 128;
+
+    blocking_stream(
+        move |_| {
+            let render = |path: &PathBuf| {
+                audio::decode(path).map(|(_, buffer)| {
+                    let spectrogram = spectrogram::Spectrogram::compute(&buffer, WIDTH, HEIGHT);
+                    image::Handle::from_rgba(
+                        spectrogram.width,
+                        spectrogram.height,
+                        spectrogram.pixels,
+                    )
+                })
+            };
+            let result = render(&input_path)
+                .and_then(|input| render(&output_path).map(|output| (output_path, input, output)));
+            CleanMessage::SpectrogramsLoaded(result)
+        },
+        |e| CleanMessage::SpectrogramsLoaded(Err(e)),
+    )
+}
@@ -0,0 +1,168 @@
+//! What the app does by itself once the last file of a batch is cleaned,
+//! picked under Settings: play a sound, show a notification, open the
+//! output folder, run a command of the user's, or shut the machine down
+//! after a countdown that can be called off.
+
+use dfn_core::i18n::tr;
+use std::fmt;
+use std::path::Path;
+use std::process::Command;
+
+/// Seconds given to call off shutting down.
+pub const SHUTDOWN_DELAY: u32 = 60;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Action {
+    #[default]
+    Nothing,
+    Sound,
+    Notification,
+    OpenFolder,
+    /// Runs the command from the settings.
+    Command,
+    ShutDown,
+}
+
+impl Action {
+    pub const ALL: [Action; 6] = [
+        Action::Nothing,
+        Action::Sound,
+        Action::Notification,
+        Action::OpenFolder,
+        Action::Command,
+        Action::ShutDown,
+    ];
+
+    /// Name the action is saved under.
+    pub fn key(self) -> &'static str {
+        match self {
+            Action::Nothing => "nothing",
+            Action::Sound => "sound",
+            Action::Notification => "notification",
+            Action::OpenFolder => "open_folder",
+            Action::Command => "command",
+            Action::ShutDown => "shut_down",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|action| action.key() == key)
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Nothing => write!(f, "{}", tr!("completion-nothing")),
+            Action::Sound => write!(f, "{}", tr!("completion-sound")),
+            Action::Notification => write!(f, "{}", tr!("completion-notification")),
+            Action::OpenFolder => write!(f, "{}", tr!("completion-open-folder")),
+            Action::Command => write!(f, "{}", tr!("completion-command")),
+            Action::ShutDown => write!(f, "{}", tr!("completion-shut-down")),
+        }
+    }
+}
+
+/// Plays the system's completion sound without waiting for it.
+pub fn play_sound() {
+    #[cfg(target_os = "linux")]
+    {
+        // libcanberra's player knows the theme's sounds; PulseAudio's and
+        // PipeWire's need the file.
+        let played = Command::new("canberra-gtk-play")
+            .arg("--id=complete")
+            .spawn()
+            .or_else(|_| {
+                Command::new("paplay")
+                    .arg("/usr/share/sounds/freedesktop/stereo/complete.oga")
+                    .spawn()
+            });
+        if let Ok(child) = played {
+            reap(child);
+        }
+    }
+    #[cfg(target_os = "macos")]
+    if let Ok(child) = Command::new("afplay")
+        .arg("/System/Library/Sounds/Glass.aiff")
+        .spawn()
+    {
+        reap(child);
+    }
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::UI::WindowsAndMessaging::{MB_ICONASTERISK, MessageBeep};
+
+        // SAFETY: only asks the system to play one of its sounds.
+        unsafe { MessageBeep(MB_ICONASTERISK) };
+    }
+}
+
+/// Starts `command` through the shell without waiting for it. It gets the
+/// number of files in the batch in `DFN_GUI_FILES` and the last output in
+/// `DFN_GUI_OUTPUT`, empty if the last file failed.
+pub fn run_command(command: &str, files: usize, output: Option<&Path>) -> Result<(), String> {
+    #[cfg(windows)]
+    let mut shell = {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    };
+    #[cfg(not(windows))]
+    let mut shell = {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    };
+    let child = shell
+        .env("DFN_GUI_FILES", files.to_string())
+        .env("DFN_GUI_OUTPUT", output.unwrap_or(Path::new("")))
+        .spawn()
+        .map_err(|e| format!("Could not run the command: {}", e))?;
+    reap(child);
+    Ok(())
+}
+
+/// Asks the system to shut down now.
+pub fn shut_down() -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    let mut command = {
+        let mut command = Command::new("systemctl");
+        command.arg("poweroff");
+        command
+    };
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let mut command = Command::new("osascript");
+        command
+            .arg("-e")
+            .arg("tell application \"System Events\" to shut down");
+        command
+    };
+    #[cfg(windows)]
+    let mut command = {
+        let mut command = Command::new("shutdown");
+        command.args(["/s", "/t", "0"]);
+        command
+    };
+    #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+    return Err("Shutting down isn't supported here".to_string());
+
+    #[cfg(any(target_os = "linux", target_os = "macos", windows))]
+    match command.status() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("Could not shut down: {}", status)),
+        Err(e) => Err(format!("Could not shut down: {}", e)),
+    }
+}
+
+/// Waits for `child` on a thread of its own, so it doesn't linger once it
+/// exits.
+fn reap(mut child: std::process::Child) {
+    std::thread::spawn(move || {
+        if let Ok(status) = child.wait()
+            && !status.success()
+        {
+            tracing::warn!("A completion action exited with {}", status);
+        }
+    });
+}
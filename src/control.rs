@@ -0,0 +1,428 @@
+//! Control of the running app by scripts, Stream Deck buttons and other
+//! programs. On Linux it's a D-Bus service, `org.deepfilternet.Gui` on the
+//! session bus with the `org.deepfilternet.Gui1` interface at
+//! `/org/deepfilternet/Gui`. Elsewhere it's a local socket taking one
+//! command per line (a named pipe on Windows, `control.sock` in the app's
+//! data folder otherwise):
+//!
+//! - `Enqueue(path)` / `enqueue PATH`: cleans the file, or holds it while
+//!   paused. While another file is cleaned, it waits in the queue. Fails
+//!   while the engine isn't ready.
+//! - `Pause()` / `pause`: files enqueued from now on wait for `Resume`. A run
+//!   already under way finishes.
+//! - `Resume()` / `resume`: starts the file waiting, if any.
+//! - `GetStatus()` / `status`: the state (`idle`, `paused`, `processing` or
+//!   `unavailable` while the engine is missing or busy otherwise), the
+//!   selected file and the progress in percent. The socket answers with a
+//!   JSON object, D-Bus with the three values.
+//!
+//! The socket answers other commands with `ok` or `error: ` and why.
+//!
+//! The right-click menu goes through it too: [`hand_over`] passes the
+//! clicked file to the app if it's running, rather than starting another.
+
+use dfn_core::formats;
+use futures_util::{Stream, StreamExt};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+/// What the app is doing, as reported by `GetStatus`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum State {
+    #[default]
+    Idle,
+    /// Files are held until `Resume`.
+    Paused,
+    Processing,
+    /// The engine is missing, downloading or benchmarking.
+    Unavailable,
+}
+
+impl State {
+    fn key(self) -> &'static str {
+        match self {
+            State::Idle => "idle",
+            State::Paused => "paused",
+            State::Processing => "processing",
+            State::Unavailable => "unavailable",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Status {
+    pub state: State,
+    pub file: Option<PathBuf>,
+    /// Progress of the current run in percent.
+    pub progress: f32,
+}
+
+#[derive(Debug, Clone)]
+pub enum Command {
+    Enqueue(PathBuf),
+    Pause,
+    Resume,
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// The interface is up; the app keeps it posted through the [`Control`].
+    Started(Control),
+    Failed(String),
+    Command(Command),
+}
+
+/// The running interface. It stops once the last clone is dropped.
+#[derive(Clone)]
+pub struct Control {
+    shared: Arc<Shared>,
+    _service: Arc<imp::Service>,
+}
+
+impl Control {
+    /// Updates what `GetStatus` reports.
+    pub fn set_status(&self, status: Status) {
+        *self.shared.status.lock().unwrap() = status;
+    }
+}
+
+impl std::fmt::Debug for Control {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Control")
+    }
+}
+
+/// What the transports share: the way to the app and its latest status.
+struct Shared {
+    sender: mpsc::UnboundedSender<Event>,
+    status: Mutex<Status>,
+}
+
+impl Shared {
+    fn enqueue(&self, path: PathBuf) -> Result<(), String> {
+        if self.status.lock().unwrap().state == State::Unavailable {
+            return Err("The engine isn't ready".to_string());
+        }
+        if !path.is_file() {
+            return Err(format!("No such file: {}", path.display()));
+        }
+        if !formats::is_supported(&path) {
+            return Err(format!("Unsupported file: {}", path.display()));
+        }
+        self.send(Command::Enqueue(path));
+        Ok(())
+    }
+
+    fn send(&self, command: Command) {
+        let _ = self.sender.send(Event::Command(command));
+    }
+
+    fn status(&self) -> Status {
+        self.status.lock().unwrap().clone()
+    }
+}
+
+/// Serves the interface for as long as the stream and the [`Control`] it
+/// starts with are kept.
+pub fn events() -> impl Stream<Item = Event> {
+    futures_util::stream::once(async {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let shared = Arc::new(Shared {
+            sender,
+            status: Mutex::default(),
+        });
+        let started = match imp::Service::start(shared.clone()).await {
+            Ok(service) => Event::Started(Control {
+                shared,
+                _service: Arc::new(service),
+            }),
+            Err(e) => Event::Failed(format!("Could not start the control interface: {}", e)),
+        };
+        let commands = futures_util::stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|event| (event, receiver))
+        });
+        futures_util::stream::once(async { started }).chain(commands)
+    })
+    .flatten()
+}
+
+/// How a running app took a file passed to [`hand_over`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Handover {
+    Taken,
+    /// It answered, but couldn't take the file, and why.
+    Refused(String),
+    /// No app is running, or it doesn't serve the interface.
+    NoAnswer,
+}
+
+/// Enqueues `path` in the app already running, if there's one.
+pub fn hand_over(path: &Path) -> Handover {
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            tracing::warn!("Could not look for a running app: {}", e);
+            return Handover::NoAnswer;
+        }
+    };
+    runtime.block_on(async {
+        // An app that hangs is as good as none.
+        tokio::time::timeout(std::time::Duration::from_secs(5), imp::enqueue(path))
+            .await
+            .unwrap_or(Handover::NoAnswer)
+    })
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use super::Shared;
+    use std::sync::Arc;
+
+    /// Keeps the service on the bus.
+    pub struct Service(#[allow(dead_code)] zbus::Connection);
+
+    impl Service {
+        pub async fn start(shared: Arc<Shared>) -> Result<Self, String> {
+            zbus::connection::Builder::session()
+                .and_then(|builder| builder.name("org.deepfilternet.Gui"))
+                .and_then(|builder| builder.serve_at("/org/deepfilternet/Gui", Interface(shared)))
+                .map_err(|e| e.to_string())?
+                .build()
+                .await
+                .map(Service)
+                .map_err(|e| e.to_string())
+        }
+    }
+
+    pub async fn enqueue(path: &std::path::Path) -> super::Handover {
+        let Ok(connection) = zbus::Connection::session().await else {
+            return super::Handover::NoAnswer;
+        };
+        let reply = connection
+            .call_method(
+                Some("org.deepfilternet.Gui"),
+                "/org/deepfilternet/Gui",
+                Some("org.deepfilternet.Gui1"),
+                "Enqueue",
+                &(path.to_string_lossy().as_ref(),),
+            )
+            .await;
+        match reply {
+            Ok(_) => super::Handover::Taken,
+            // What `Interface::enqueue` answers with; anything else means
+            // no one took the call.
+            Err(zbus::Error::MethodError(name, detail, _))
+                if name.as_str() == "org.freedesktop.DBus.Error.Failed" =>
+            {
+                super::Handover::Refused(detail.unwrap_or_default())
+            }
+            Err(_) => super::Handover::NoAnswer,
+        }
+    }
+
+    struct Interface(Arc<Shared>);
+
+    #[zbus::interface(name = "org.deepfilternet.Gui1")]
+    impl Interface {
+        fn enqueue(&self, path: String) -> zbus::fdo::Result<()> {
+            self.0
+                .enqueue(path.into())
+                .map_err(zbus::fdo::Error::Failed)
+        }
+
+        fn pause(&self) {
+            self.0.send(super::Command::Pause);
+        }
+
+        fn resume(&self) {
+            self.0.send(super::Command::Resume);
+        }
+
+        /// State, selected file (empty for none) and progress in percent.
+        fn get_status(&self) -> (String, String, f64) {
+            let status = self.0.status();
+            (
+                status.state.key().to_string(),
+                status
+                    .file
+                    .map(|file| file.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                status.progress.into(),
+            )
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod imp {
+    use super::{Command, Handover, Shared};
+    use std::sync::Arc;
+    use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+    #[cfg(windows)]
+    const PIPE: &str = r"\\.\pipe\dfn_gui";
+
+    #[cfg(unix)]
+    fn socket_path() -> Result<std::path::PathBuf, String> {
+        Ok(dfn_core::data_dir()
+            .ok_or("Could not find project directories")?
+            .join("control.sock"))
+    }
+
+    /// Accepts connections until dropped.
+    pub struct Service(tokio::task::AbortHandle);
+
+    impl Drop for Service {
+        fn drop(&mut self) {
+            self.0.abort();
+        }
+    }
+
+    impl Service {
+        #[cfg(unix)]
+        pub async fn start(shared: Arc<Shared>) -> Result<Self, String> {
+            use tokio::net::{UnixListener, UnixStream};
+
+            let path = socket_path()?;
+            if UnixStream::connect(&path).await.is_ok() {
+                return Err("Another instance of the app is using it".to_string());
+            }
+            // Left behind by an instance that didn't shut down cleanly.
+            let _ = std::fs::remove_file(&path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let listener = UnixListener::bind(&path).map_err(|e| e.to_string())?;
+            let task = tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    tokio::spawn(serve(stream, shared.clone()));
+                }
+            });
+            Ok(Service(task.abort_handle()))
+        }
+
+        #[cfg(windows)]
+        pub async fn start(shared: Arc<Shared>) -> Result<Self, String> {
+            use tokio::net::windows::named_pipe::ServerOptions;
+
+            let mut server = ServerOptions::new()
+                .first_pipe_instance(true)
+                .create(PIPE)
+                .map_err(|e| e.to_string())?;
+            let task = tokio::spawn(async move {
+                while server.connect().await.is_ok() {
+                    let Ok(next) = ServerOptions::new().create(PIPE) else {
+                        break;
+                    };
+                    let client = std::mem::replace(&mut server, next);
+                    tokio::spawn(serve(client, shared.clone()));
+                }
+            });
+            Ok(Service(task.abort_handle()))
+        }
+    }
+
+    pub async fn enqueue(path: &std::path::Path) -> Handover {
+        #[cfg(unix)]
+        let stream = match socket_path() {
+            Ok(socket) => tokio::net::UnixStream::connect(socket).await,
+            Err(_) => return Handover::NoAnswer,
+        };
+        #[cfg(windows)]
+        let stream = tokio::net::windows::named_pipe::ClientOptions::new().open(PIPE);
+        let Ok(stream) = stream else {
+            return Handover::NoAnswer;
+        };
+        let (reader, mut writer) = tokio::io::split(stream);
+        let command = format!("enqueue {}\n", path.display());
+        if writer.write_all(command.as_bytes()).await.is_err() {
+            return Handover::NoAnswer;
+        }
+        match BufReader::new(reader).lines().next_line().await {
+            Ok(Some(reply)) if reply == "ok" => Handover::Taken,
+            Ok(Some(reply)) => {
+                Handover::Refused(reply.strip_prefix("error: ").unwrap_or(&reply).to_string())
+            }
+            _ => Handover::NoAnswer,
+        }
+    }
+
+    /// Answers the commands of one client, one line each.
+    async fn serve(stream: impl AsyncRead + AsyncWrite, shared: Arc<Shared>) {
+        let (reader, mut writer) = tokio::io::split(stream);
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let reply = answer(line.trim(), &shared);
+            if writer
+                .write_all(format!("{}\n", reply).as_bytes())
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+    }
+
+    fn answer(line: &str, shared: &Shared) -> String {
+        let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+        let result = match command {
+            "enqueue" if !argument.is_empty() => shared.enqueue(argument.into()),
+            "pause" => {
+                shared.send(Command::Pause);
+                Ok(())
+            }
+            "resume" => {
+                shared.send(Command::Resume);
+                Ok(())
+            }
+            "status" => {
+                let status = shared.status();
+                return serde_json::json!({
+                    "state": status.state.key(),
+                    "file": status.file.map(|file| file.to_string_lossy().into_owned()),
+                    "progress": status.progress.round(),
+                })
+                .to_string();
+            }
+            _ => Err(format!("Unknown command: {}", line)),
+        };
+        match result {
+            Ok(()) => "ok".to_string(),
+            Err(e) => format!("error: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_enqueued_during_a_run_are_passed_on() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("take.wav");
+        std::fs::write(&file, b"").unwrap();
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let shared = Shared {
+            sender,
+            status: Mutex::new(Status {
+                state: State::Processing,
+                ..Status::default()
+            }),
+        };
+
+        assert_eq!(shared.enqueue(file.clone()), Ok(()));
+        assert!(matches!(
+            receiver.try_recv(),
+            Ok(Event::Command(Command::Enqueue(path))) if path == file
+        ));
+
+        shared.status.lock().unwrap().state = State::Unavailable;
+        assert!(shared.enqueue(file).is_err());
+        assert!(shared.enqueue(dir.path().join("missing.wav")).is_err());
+    }
+}
@@ -0,0 +1,65 @@
+//! Crash reports. A panic writes what's known about it to the app's data
+//! folder, and the next launch offers to open or submit the report instead
+//! of the app having just vanished.
+
+use std::backtrace::Backtrace;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Number of log lines put into a report.
+const LOG_LINES: usize = 50;
+
+/// Name of the file holding the path of a report not yet seen.
+const PENDING: &str = "pending";
+
+fn dir() -> Option<PathBuf> {
+    dfn_core::data_dir().map(|dir| dir.join("crashes"))
+}
+
+/// Writes a report on every panic, before the default handling.
+pub fn install() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        tracing::error!("{}", info);
+        // There's nothing left to tell about a failed write.
+        let _ = write_report(&info.to_string());
+        default_hook(info);
+    }));
+}
+
+fn write_report(panic: &str) -> std::io::Result<()> {
+    let dir = dir().ok_or(std::io::ErrorKind::NotFound)?;
+    std::fs::create_dir_all(&dir)?;
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let path = dir.join(format!("crash-{}.txt", seconds));
+
+    let log = crate::logging::recent();
+    let log = &log[log.len().saturating_sub(LOG_LINES)..];
+    let report = format!(
+        "{} {} crashed on {} ({})\n\n{}\n\nBacktrace:\n{}\n\nLast log lines:\n{}\n",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        panic,
+        Backtrace::force_capture(),
+        log.join("\n"),
+    );
+    std::fs::write(&path, report)?;
+    std::fs::write(dir.join(PENDING), path.to_string_lossy().as_bytes())
+}
+
+/// Report of a crash in an earlier session that hasn't been dismissed yet.
+pub fn pending() -> Option<PathBuf> {
+    let path = PathBuf::from(std::fs::read_to_string(dir()?.join(PENDING)).ok()?);
+    path.exists().then_some(path)
+}
+
+/// Stops offering the pending report. The report itself is kept.
+pub fn dismiss() {
+    if let Some(dir) = dir() {
+        let _ = std::fs::remove_file(dir.join(PENDING));
+    }
+}
@@ -0,0 +1,26 @@
+//! Choice of audio device for recording and playback.
+
+use dfn_core::i18n::tr;
+use std::fmt;
+
+/// An input or output device, by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Device {
+    /// Whatever the system currently uses as its default.
+    #[default]
+    Default,
+    #[cfg_attr(
+        not(any(feature = "playback", feature = "recording")),
+        allow(dead_code)
+    )]
+    Named(String),
+}
+
+impl fmt::Display for Device {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Device::Default => write!(f, "{}", tr!("device-default")),
+            Device::Named(name) => write!(f, "{}", name),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! The devices recordings are made with and previews played on, listed
+//! again as they're plugged in and out.
+
+use crate::{DfnGui, Message, blocking_stream, devices, playback, recording};
+use dfn_core::i18n::tr;
+use futures_util::Stream;
+use iced::widget::{pick_list, row, text};
+use iced::{Alignment, Element, Task};
+
+#[derive(Debug, Clone)]
+pub enum DevicesMessage {
+    Refresh,
+    /// Available input and output devices.
+    Listed(Result<(Vec<devices::Device>, Vec<devices::Device>), String>),
+    InputSelected(devices::Device),
+    OutputSelected(devices::Device),
+}
+
+impl DfnGui {
+    pub fn update_devices(&mut self, message: DevicesMessage) -> Task<Message> {
+        match message {
+            DevicesMessage::Refresh => {
+                if playback::AVAILABLE || recording::AVAILABLE {
+                    return Task::run(devices_process(), Message::Devices);
+                }
+            }
+            DevicesMessage::Listed(Ok((inputs, outputs))) => {
+                // Fall back to the default when the chosen device is unplugged.
+                if !inputs.contains(&self.input_device) {
+                    self.input_device = devices::Device::Default;
+                }
+                if !outputs.contains(&self.output_device) {
+                    self.output_device = devices::Device::Default;
+                }
+                self.input_devices = inputs;
+                self.output_devices = outputs;
+            }
+            // Leaves the previous lists in place.
+            DevicesMessage::Listed(Err(_)) => {}
+            DevicesMessage::InputSelected(device) => {
+                self.input_device = device;
+            }
+            DevicesMessage::OutputSelected(device) => {
+                self.output_device = device;
+            }
+        }
+        Task::none()
+    }
+
+    /// Pickers for the devices used to record and to play previews.
+    pub fn view_devices(&self) -> Element<'_, Message> {
+        let mut pickers = row![].spacing(20);
+        if recording::AVAILABLE {
+            pickers = pickers.push(view_device_picker(
+                tr!("device-input"),
+                &self.input_devices,
+                &self.input_device,
+                |device| Message::Devices(DevicesMessage::InputSelected(device)),
+            ));
+        }
+        if playback::AVAILABLE {
+            pickers = pickers.push(view_device_picker(
+                tr!("device-output"),
+                &self.output_devices,
+                &self.output_device,
+                |device| Message::Devices(DevicesMessage::OutputSelected(device)),
+            ));
+        }
+        pickers.into()
+    }
+}
+
+pub fn view_device_picker<'a>(
+    label: String,
+    devices: &'a [devices::Device],
+    selected: &'a devices::Device,
+    on_select: fn(devices::Device) -> Message,
+) -> Element<'a, Message> {
+    row![
+        text(label).size(12),
+        pick_list(devices, Some(selected), on_select).text_size(12),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center)
+    .into()
+}
+
+/// Lists input and output devices off the UI thread.
+pub fn devices_process() -> impl Stream<Item = DevicesMessage> {
+    blocking_stream(
+        |_| DevicesMessage::Listed(Ok((recording::input_devices(), playback::output_devices()))),
+        |e| DevicesMessage::Listed(Err(e)),
+    )
+}
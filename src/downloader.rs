@@ -0,0 +1,310 @@
+//! Getting the engine in place: downloading it from GitHub or a mirror, or
+//! installing a copy downloaded elsewhere, with a screen of its own for
+//! when there's no connection.
+
+use crate::{DfnGui, EngineState, JobState, Message, Screen, Status, read_engine_version, toast};
+use dfn_core::i18n::tr;
+use dfn_core::{accel, download, update};
+use iced::widget::{button, column, text};
+use iced::{Alignment, Element, Task};
+use rfd::FileDialog;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Where the engine is downloaded from.
+#[derive(Debug, Clone)]
+enum DownloadSource {
+    Official,
+    /// The newest release, pre-releases included.
+    Prerelease,
+    /// Base URL of a mirror of the release files.
+    Mirror(String),
+}
+
+/// Downloading the engine.
+#[derive(Debug, Clone)]
+pub enum DownloadMessage {
+    Start,
+    /// Download the engine from the mirror in the settings.
+    UseMirror,
+    Event(download::Event),
+    /// A failed download was followed by a check whether the internet can
+    /// be reached.
+    ConnectivityChecked {
+        error: String,
+        online: bool,
+    },
+    /// Install an engine downloaded elsewhere.
+    InstallFromFile,
+    FileChosen(Option<PathBuf>),
+}
+
+impl DfnGui {
+    /// Getting the engine in place.
+    pub fn update_download(&mut self, message: DownloadMessage) -> Task<Message> {
+        match message {
+            DownloadMessage::Start => {
+                return self.download_engine(match self.updates.channel {
+                    update::Channel::Stable => DownloadSource::Official,
+                    update::Channel::Beta => DownloadSource::Prerelease,
+                });
+            }
+            DownloadMessage::UseMirror => {
+                let mirror = self.engine_mirror.trim();
+                if mirror.is_empty() {
+                    self.screen = Screen::Settings;
+                    self.toasts
+                        .push(toast::Severity::Info, tr!("toast-mirror-missing"));
+                } else {
+                    return self.download_engine(DownloadSource::Mirror(mirror.to_string()));
+                }
+            }
+            DownloadMessage::Event(event) => {
+                // Only the engine download in progress is shown.
+                let Status::Engine {
+                    state:
+                        EngineState::Downloading {
+                            id: current,
+                            progress: shown,
+                        },
+                    ..
+                } = &mut self.status
+                else {
+                    return Task::none();
+                };
+                match event {
+                    download::Event::Progress(id, progress) if id == *current => {
+                        *shown = progress;
+                    }
+                    download::Event::Finished(id, result) if id == *current => {
+                        return self.engine_downloaded(result);
+                    }
+                    _ => {}
+                }
+            }
+            DownloadMessage::ConnectivityChecked { error, online } => {
+                if let Status::Engine { state, .. } = &mut self.status {
+                    *state = if online {
+                        EngineState::Failed(tr!("error-download", error = error))
+                    } else {
+                        EngineState::Offline
+                    };
+                }
+            }
+            DownloadMessage::InstallFromFile => {
+                return Task::perform(async { FileDialog::new().pick_file() }, |file| {
+                    Message::Download(DownloadMessage::FileChosen(file))
+                });
+            }
+            DownloadMessage::FileChosen(Some(file)) => {
+                let result = dfn_core::data_dir()
+                    .ok_or_else(|| "Could not find project directories".to_string())
+                    .and_then(|dir| download::install_from(&file, &download::binary_path(&dir)));
+                match result {
+                    Ok(bin_path) => {
+                        tracing::info!("Engine installed from {}", file.display());
+                        self.engine_in_place();
+                        self.toasts
+                            .push(toast::Severity::Success, tr!("toast-engine-installed"));
+                        return read_engine_version(bin_path);
+                    }
+                    Err(e) => {
+                        tracing::error!("Could not install the engine: {}", e);
+                        self.toasts.push(
+                            toast::Severity::Error,
+                            tr!("toast-engine-install-failed", error = e),
+                        );
+                    }
+                }
+            }
+            DownloadMessage::FileChosen(None) => {}
+        }
+        Task::none()
+    }
+
+    /// Moves on once the engine is downloaded, or tells why it wasn't.
+    fn engine_downloaded(&mut self, result: Result<PathBuf, String>) -> Task<Message> {
+        match result {
+            Ok(bin_path) => {
+                tracing::info!("Engine downloaded to {}", bin_path.display());
+                self.engine_in_place();
+                self.toasts
+                    .push(toast::Severity::Success, tr!("toast-download-complete"));
+                read_engine_version(bin_path)
+            }
+            Err(e) if e == download::CANCELLED => {
+                tracing::info!("Engine download was cancelled");
+                if let Status::Engine { state, .. } = &mut self.status {
+                    *state = EngineState::Failed(tr!("error-download", error = e));
+                }
+                Task::none()
+            }
+            Err(e) => {
+                tracing::error!("Engine download failed: {}", e);
+                // Being offline gets its own screen rather than the error.
+                Task::perform(download::is_online(), move |online| {
+                    Message::Download(DownloadMessage::ConnectivityChecked { error: e, online })
+                })
+            }
+        }
+    }
+
+    /// Starts downloading the engine from `source`. A selected file waits
+    /// for it.
+    fn download_engine(&mut self, source: DownloadSource) -> Task<Message> {
+        let waiting = self.status.selected_file().map(Path::to_path_buf);
+        let Some(dir) = dfn_core::data_dir() else {
+            self.status = Status::Engine {
+                state: EngineState::Failed(tr!(
+                    "error-download",
+                    error = "Could not find project directories"
+                )),
+                waiting,
+            };
+            return Task::none();
+        };
+        let (id, events) = self.downloads.download(
+            engine_url(self.downloads.network(), source, self.engine_provider),
+            download::binary_path(&dir),
+        );
+        self.status = Status::Engine {
+            state: EngineState::Downloading { id, progress: 0.0 },
+            waiting,
+        };
+        Task::run(events, |event| {
+            Message::Download(DownloadMessage::Event(event))
+        })
+    }
+
+    /// Moves on once the engine is in place, to the file that waited for it.
+    pub fn engine_in_place(&mut self) {
+        self.engine_installed = true;
+        if let Status::Engine { waiting, .. } = &mut self.status {
+            self.status = Status::Job(waiting.take().map_or(JobState::Idle, JobState::Ready));
+        }
+    }
+}
+
+/// Where the engine build for `provider` is downloaded from, for `source`.
+async fn engine_url(
+    network: Arc<dyn download::Network>,
+    source: DownloadSource,
+    provider: accel::Provider,
+) -> Result<String, String> {
+    match source {
+        DownloadSource::Official => {
+            update::engine_url(network.as_ref(), update::Channel::Stable, provider).await
+        }
+        DownloadSource::Prerelease => {
+            update::engine_url(network.as_ref(), update::Channel::Beta, provider).await
+        }
+        // Mirrors are for where GitHub can't be reached, so the plain build
+        // of the release the app was made for is the one expected there.
+        DownloadSource::Mirror(mirror) => {
+            download::release_url().map(|url| download::mirror_url(&mirror, &url))
+        }
+    }
+}
+
+/// What's shown when the engine can't be downloaded for lack of a
+/// connection.
+pub fn view_offline<'a>() -> Element<'a, Message> {
+    column![
+        text(tr!("offline-title")).size(20),
+        text(tr!("offline-body")),
+        button(text(tr!("offline-retry"))).on_press(Message::Download(DownloadMessage::Start)),
+        text(tr!("offline-install-hint")).size(12),
+        install_from_file_button(),
+    ]
+    .spacing(10)
+    .align_x(Alignment::Center)
+    .into()
+}
+
+pub fn install_from_file_button<'a>() -> Element<'a, Message> {
+    button(text(tr!("engine-install-file")))
+        .on_press(Message::Download(DownloadMessage::InstallFromFile))
+        .style(button::secondary)
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Downloading the engine as download `id`, with a file waiting for it.
+    fn downloading(id: download::DownloadId) -> DfnGui {
+        DfnGui {
+            status: Status::Engine {
+                state: EngineState::Downloading { id, progress: 0.0 },
+                waiting: Some(PathBuf::from("/audio/talk.wav")),
+            },
+            ..DfnGui::default()
+        }
+    }
+
+    #[test]
+    fn only_the_download_shown_is_followed() {
+        let mut gui = downloading(2);
+        let _ = gui.update_download(DownloadMessage::Event(download::Event::Progress(1, 50.0)));
+        let _ = gui.update_download(DownloadMessage::Event(download::Event::Progress(2, 20.0)));
+        assert!(matches!(
+            gui.status,
+            Status::Engine {
+                state: EngineState::Downloading { progress, .. },
+                ..
+            } if progress == 20.0
+        ));
+        let _ = gui.update_download(DownloadMessage::Event(download::Event::Finished(
+            1,
+            Ok(PathBuf::from("/engine/deep-filter")),
+        )));
+        assert!(matches!(
+            gui.status,
+            Status::Engine {
+                state: EngineState::Downloading { .. },
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn the_file_waiting_is_selected_once_the_engine_is_in() {
+        let mut gui = downloading(1);
+        let _ = gui.update_download(DownloadMessage::Event(download::Event::Finished(
+            1,
+            Ok(PathBuf::from("/engine/deep-filter")),
+        )));
+        assert!(matches!(
+            &gui.status,
+            Status::Job(JobState::Ready(path)) if path == Path::new("/audio/talk.wav")
+        ));
+    }
+
+    #[test]
+    fn being_offline_is_told_apart_from_other_failures() {
+        let mut gui = downloading(1);
+        let _ = gui.update_download(DownloadMessage::ConnectivityChecked {
+            error: "timed out".to_string(),
+            online: false,
+        });
+        assert!(matches!(
+            gui.status,
+            Status::Engine {
+                state: EngineState::Offline,
+                ..
+            }
+        ));
+        let _ = gui.update_download(DownloadMessage::ConnectivityChecked {
+            error: "timed out".to_string(),
+            online: true,
+        });
+        assert!(matches!(
+            gui.status,
+            Status::Engine {
+                state: EngineState::Failed(_),
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,24 @@
+//! The file manager, asked over D-Bus (`org.freedesktop.FileManager1`) to
+//! show a file selected. Nautilus, Dolphin, Nemo, Caja and Thunar answer it.
+
+use std::path::PathBuf;
+
+/// Opens the folder holding `path` with the file selected.
+pub async fn show_item(path: PathBuf) -> Result<(), String> {
+    let uri = reqwest::Url::from_file_path(&path)
+        .map_err(|_| format!("Not an absolute path: {}", path.display()))?;
+    let connection = zbus::Connection::session()
+        .await
+        .map_err(|e| e.to_string())?;
+    connection
+        .call_method(
+            Some("org.freedesktop.FileManager1"),
+            "/org/freedesktop/FileManager1",
+            Some("org.freedesktop.FileManager1"),
+            "ShowItems",
+            &(vec![uri.as_str()], ""),
+        )
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,111 @@
+//! Cleaning files from the command line without opening the window. Each
+//! file is run with the saved settings and the folder profile that applies
+//! to it (see [`dfn_core::config`]), and what happens is printed to
+//! stdout as [`dfn_core::events`] for wrapping tools to follow. Once all
+//! are done, a summary goes to the webhook if one is set. With
+//! `skip_duplicates` set, inputs that are copies of an earlier one are
+//! skipped and listed in the summary.
+
+use dfn_core::cancel::CancelToken;
+use dfn_core::events::Event;
+use dfn_core::pipeline::{self, ProcessingOptions};
+use dfn_core::webhook::{self, Webhook};
+use dfn_core::{config, dedup, denoiser, download};
+use std::path::PathBuf;
+
+/// Cleans `inputs` one after another, returning the exit code: 0 when all
+/// of them were cleaned, 1 otherwise.
+pub fn run(inputs: &[PathBuf], options: &ProcessingOptions, webhook: &Webhook) -> i32 {
+    for input in inputs {
+        emit(Event::Queued { input });
+    }
+    let bin_path = download::installed_binary().ok();
+
+    let mut results = Vec::new();
+    let mut duplicates = Vec::new();
+    let mut seen = dedup::Seen::default();
+    for input in inputs {
+        if options.skip_duplicates {
+            match seen.original_of(input) {
+                Ok(Some(original)) => {
+                    tracing::info!(
+                        "Skipped {}, a copy of {}",
+                        input.display(),
+                        original.display()
+                    );
+                    emit(Event::Duplicate {
+                        input,
+                        original: &original,
+                    });
+                    duplicates.push((input.clone(), original));
+                    continue;
+                }
+                Ok(None) => {}
+                // Cleaning reports the file if it really can't be read.
+                Err(e) => tracing::warn!("{}", e),
+            }
+        }
+        let mut options = options.clone();
+        let denoiser = config::apply_folder_profile(input, &mut options).and_then(|_| {
+            if options.backend == denoiser::Backend::ExternalBinary && bin_path.is_none() {
+                Err("The engine isn't installed. Start the app once to download it.".to_string())
+            } else {
+                options.denoiser(bin_path.as_deref())
+            }
+        });
+        let result = denoiser.and_then(|denoiser| {
+            let mut last = None;
+            pipeline::run(
+                input,
+                denoiser,
+                &options,
+                CancelToken::new(),
+                &mut |phase, percent| {
+                    // Whole percents are plenty for following along.
+                    let step = (phase, percent.floor() as u8);
+                    if last != Some(step) {
+                        last = Some(step);
+                        emit(Event::Progress {
+                            input,
+                            phase,
+                            percent,
+                        });
+                    }
+                },
+            )
+        });
+        match &result {
+            Ok(report) => {
+                tracing::info!(
+                    "Cleaned {} into {}",
+                    input.display(),
+                    report.output_path.display()
+                );
+                emit(Event::Done { input, report });
+            }
+            Err(message) => {
+                tracing::error!("Cleaning {} failed: {}", input.display(), message);
+                emit(Event::Error { input, message });
+            }
+        }
+        results.push((input.clone(), result));
+    }
+
+    if webhook.is_set() {
+        let summary = webhook::summary(&results, &duplicates);
+        let sent = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| format!("Could not start the network runtime: {}", e))
+            .and_then(|runtime| runtime.block_on(webhook::post(webhook, summary)));
+        if let Err(e) = sent {
+            tracing::warn!("{}", e);
+            eprintln!("{}", e);
+        }
+    }
+    i32::from(results.iter().any(|(_, result)| result.is_err()))
+}
+
+fn emit(event: Event) {
+    println!("{}", event.to_json());
+}
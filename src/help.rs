@@ -0,0 +1,51 @@
+//! Controls with a short explanation behind a help mark, for options whose
+//! effect isn't obvious from their label alone.
+
+use iced::widget::{container, row, text, tooltip};
+use iced::{Alignment, Element, Theme};
+
+/// `control`, e.g. a checkbox, followed by a help mark showing `help` when
+/// hovered.
+pub fn with_help<'a, Message: 'a>(
+    control: impl Into<Element<'a, Message>>,
+    help: String,
+) -> Element<'a, Message> {
+    row![control.into(), mark(help)]
+        .spacing(6)
+        .align_y(Alignment::Center)
+        .into()
+}
+
+/// `label` with a help mark, then `control`, e.g. a slider.
+pub fn labeled<'a, Message: 'a>(
+    label: String,
+    help: String,
+    control: impl Into<Element<'a, Message>>,
+) -> Element<'a, Message> {
+    row![text(label).size(12), mark(help), control.into()]
+        .spacing(10)
+        .align_y(Alignment::Center)
+        .into()
+}
+
+fn mark<'a, Message: 'a>(help: String) -> Element<'a, Message> {
+    tooltip(
+        container(text("?").size(11))
+            .padding([0, 5])
+            .style(|theme: &Theme| {
+                let palette = theme.extended_palette();
+                container::Style {
+                    text_color: Some(palette.background.strong.text),
+                    background: Some(palette.background.strong.color.into()),
+                    border: iced::border::rounded(8),
+                    ..Default::default()
+                }
+            }),
+        container(text(help).size(12))
+            .max_width(280)
+            .padding(8)
+            .style(container::rounded_box),
+        tooltip::Position::Bottom,
+    )
+    .into()
+}
@@ -0,0 +1,433 @@
+//! Past runs, searched, cleaned again or compared two outputs at a time.
+
+use crate::player::{PlayerMessage, Preview};
+use crate::{
+    DfnGui, JobState, Message, Screen, Status, export_list, format_duration, playback, style, toast,
+};
+use dfn_core::i18n::tr;
+use dfn_core::pipeline::ProcessingOptions;
+use dfn_core::{config, history};
+use iced::widget::{button, column, container, pick_list, row, scrollable, text, text_input};
+use iced::{Alignment, Element, Length, Task};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default)]
+pub struct HistoryScreen {
+    pub query: String,
+    pub outcome: history::Outcome,
+    /// Runs matching the filters, newest first.
+    pub entries: Vec<history::Entry>,
+    /// Why the history couldn't be opened or read.
+    pub error: Option<String>,
+    /// Two outputs of one input being compared, shown instead of the list.
+    pub comparison: Option<Comparison>,
+}
+
+/// Outputs of the same input made with different options, two of which are
+/// played and listed side by side.
+#[derive(Debug)]
+pub struct Comparison {
+    input: PathBuf,
+    /// Newest first.
+    versions: Vec<Version>,
+    pub a: Version,
+    pub b: Version,
+}
+
+/// An output as listed in a comparison.
+#[derive(Debug, Clone)]
+pub struct Version {
+    pub entry: history::Entry,
+    options: ProcessingOptions,
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.entry.id == other.entry.id
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = self
+            .entry
+            .output_path
+            .as_deref()
+            .and_then(Path::file_name)
+            .unwrap_or_default();
+        write!(f, "{} · {}", name.to_string_lossy(), self.entry.started)
+    }
+}
+
+/// Filtering and reusing past runs on the history screen.
+#[derive(Debug, Clone)]
+pub enum HistoryMessage {
+    QueryChanged(String),
+    OutcomeSelected(history::Outcome),
+    /// Clean the input of a past run again with the options it used.
+    Rerun(i64),
+    /// Compare the outputs made from the input of a past run.
+    Compare(i64),
+    PickA(Version),
+    PickB(Version),
+    CloseComparison,
+    /// Save the inputs of the runs listed, to queue them again later.
+    ExportList,
+}
+
+impl DfnGui {
+    pub fn update_history(&mut self, message: HistoryMessage) -> Task<Message> {
+        match message {
+            HistoryMessage::QueryChanged(query) => {
+                self.history_screen.query = query;
+                self.refresh_history();
+            }
+            HistoryMessage::OutcomeSelected(outcome) => {
+                self.history_screen.outcome = outcome;
+                self.refresh_history();
+            }
+            HistoryMessage::Compare(id) => {
+                let Some(history) = &self.history else {
+                    return Task::none();
+                };
+                let Some(input) = self
+                    .history_screen
+                    .entries
+                    .iter()
+                    .find(|entry| entry.id == id)
+                    .map(|entry| entry.input_path.clone())
+                else {
+                    return Task::none();
+                };
+                let history = history.lock().unwrap();
+                let versions = history.versions(&input).and_then(|entries| {
+                    entries
+                        .into_iter()
+                        .filter(|entry| {
+                            entry.output_path.as_ref().is_some_and(|path| path.exists())
+                        })
+                        .map(|entry| {
+                            history
+                                .options(entry.id)
+                                .map(|options| Version { entry, options })
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                });
+                drop(history);
+                match versions {
+                    Ok(versions) if versions.len() > 1 => {
+                        self.close_comparison_preview();
+                        self.history_screen.comparison = Some(Comparison {
+                            input,
+                            a: versions[0].clone(),
+                            b: versions[1].clone(),
+                            versions,
+                        });
+                    }
+                    Ok(_) => self
+                        .toasts
+                        .push(toast::Severity::Info, tr!("compare-single-version")),
+                    Err(e) => self.toasts.push(toast::Severity::Error, e),
+                }
+            }
+            HistoryMessage::PickA(version) => {
+                self.close_comparison_preview();
+                if let Some(comparison) = &mut self.history_screen.comparison {
+                    comparison.a = version;
+                }
+            }
+            HistoryMessage::PickB(version) => {
+                self.close_comparison_preview();
+                if let Some(comparison) = &mut self.history_screen.comparison {
+                    comparison.b = version;
+                }
+            }
+            HistoryMessage::CloseComparison => {
+                self.close_comparison_preview();
+                self.history_screen.comparison = None;
+            }
+            HistoryMessage::ExportList => {
+                // Oldest first, each input once, like the files were queued.
+                let mut inputs: Vec<PathBuf> = Vec::new();
+                for entry in self.history_screen.entries.iter().rev() {
+                    if !inputs.contains(&entry.input_path) {
+                        inputs.push(entry.input_path.clone());
+                    }
+                }
+                return export_list(inputs.into_iter());
+            }
+            HistoryMessage::Rerun(id) => {
+                let Some(history) = &self.history else {
+                    return Task::none();
+                };
+                let Some(entry) = self
+                    .history_screen
+                    .entries
+                    .iter()
+                    .find(|entry| entry.id == id)
+                else {
+                    return Task::none();
+                };
+                let options = history.lock().unwrap().options(id);
+                match options {
+                    Ok(mut options) => {
+                        // The token isn't kept in the history.
+                        options.server = self.options.server.clone();
+                        let input = entry.input_path.clone();
+                        if matches!(self.status, Status::Job(JobState::Processing(_))) {
+                            self.queue_file(input, options);
+                            return Task::none();
+                        }
+                        // The options of the run are for this file only;
+                        // the panel keeps its own.
+                        self.screen = Screen::Clean;
+                        let select = self.select_file(input.clone());
+                        if matches!(self.status, Status::Job(JobState::Ready(_))) {
+                            return Task::batch([select, self.start_run(input, options)]);
+                        }
+                        return select;
+                    }
+                    Err(e) => self.toasts.push(toast::Severity::Error, e),
+                }
+            }
+        }
+        Task::none()
+    }
+
+    /// Stops playing the versions compared, which are about to change.
+    fn close_comparison_preview(&mut self) {
+        if let Some((Preview::Versions, _)) = self.player {
+            self.player = None;
+        }
+    }
+
+    /// Lists the runs matching the history filters again.
+    pub fn refresh_history(&mut self) {
+        let Some(history) = &self.history else {
+            return;
+        };
+        let screen = &mut self.history_screen;
+        let found = history
+            .lock()
+            .unwrap()
+            .search(&screen.query, screen.outcome);
+        match found {
+            Ok(entries) => {
+                screen.entries = entries;
+                screen.error = None;
+            }
+            Err(e) => screen.error = Some(e),
+        }
+    }
+
+    pub fn view_history(&self) -> Element<'_, Message> {
+        let screen = &self.history_screen;
+        if let Some(comparison) = &screen.comparison {
+            return self.view_comparison(comparison);
+        }
+        let filters = row![
+            text_input(&tr!("history-search"), &screen.query)
+                .on_input(|query| Message::History(HistoryMessage::QueryChanged(query)))
+                .style(style::text_input)
+                .size(12),
+            pick_list(history::Outcome::ALL, Some(screen.outcome), |outcome| {
+                Message::History(HistoryMessage::OutcomeSelected(outcome))
+            })
+            .text_size(12),
+            button(text(tr!("history-export-list")).size(12))
+                .on_press_maybe(
+                    (!screen.entries.is_empty())
+                        .then_some(Message::History(HistoryMessage::ExportList))
+                )
+                .style(button::secondary),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let entries: Element<'_, Message> = if let Some(e) = &screen.error {
+            text(e).size(12).style(text::danger).into()
+        } else if screen.entries.is_empty() {
+            text(tr!("history-empty")).size(12).into()
+        } else {
+            let can_rerun = self.accepts_drops();
+            scrollable(
+                screen
+                    .entries
+                    .iter()
+                    .fold(column![].spacing(8), |entries, entry| {
+                        entries.push(view_history_entry(entry, can_rerun))
+                    }),
+            )
+            .height(Length::Fixed(380.0))
+            .into()
+        };
+
+        column![filters, entries].spacing(15).into()
+    }
+
+    /// The two versions picked, with the options they differ in and a way
+    /// to switch between them while playing.
+    fn view_comparison<'a>(&'a self, comparison: &'a Comparison) -> Element<'a, Message> {
+        let header = row![
+            button(text(tr!("compare-back")).size(12))
+                .on_press(Message::History(HistoryMessage::CloseComparison))
+                .style(button::secondary),
+            text(
+                comparison
+                    .input
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+            )
+            .size(14),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+        let pickers = row![
+            text(tr!("compare-version-a")).size(12),
+            pick_list(
+                comparison.versions.as_slice(),
+                Some(&comparison.a),
+                |version| Message::History(HistoryMessage::PickA(version))
+            )
+            .text_size(12)
+            .width(Length::Fill),
+            text(tr!("compare-version-b")).size(12),
+            pick_list(
+                comparison.versions.as_slice(),
+                Some(&comparison.b),
+                |version| Message::History(HistoryMessage::PickB(version))
+            )
+            .text_size(12)
+            .width(Length::Fill),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let document = |options| {
+            let mut document = toml_edit::DocumentMut::new();
+            config::write_options(options, &mut document);
+            document
+        };
+        let a = document(&comparison.a.options);
+        let b = document(&comparison.b.options);
+        let value = |document: &toml_edit::DocumentMut, key: &str| {
+            document
+                .get(key)
+                .map(|item| item.to_string().trim().to_string())
+                .unwrap_or_else(|| "—".to_string())
+        };
+        let mut keys: Vec<&str> = Vec::new();
+        for (key, _) in a.iter().chain(b.iter()) {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        let differences: Vec<(String, String, String)> = keys
+            .into_iter()
+            .map(|key| (key.to_string(), value(&a, key), value(&b, key)))
+            .filter(|(_, a, b)| a != b)
+            .collect();
+        let settings: Element<'a, Message> = if differences.is_empty() {
+            text(tr!("compare-same-options")).size(12).into()
+        } else {
+            differences
+                .into_iter()
+                .fold(
+                    column![row![
+                        text(tr!("compare-option")).size(12).width(Length::Fill),
+                        text(tr!("compare-version-a")).size(12).width(Length::Fill),
+                        text(tr!("compare-version-b")).size(12).width(Length::Fill),
+                    ]]
+                    .spacing(4),
+                    |rows, (key, a, b)| {
+                        rows.push(row![
+                            text(key).size(11).width(Length::Fill),
+                            text(a).size(11).width(Length::Fill),
+                            text(b).size(11).width(Length::Fill),
+                        ])
+                    },
+                )
+                .into()
+        };
+
+        let mut content = column![header, pickers, settings].spacing(15);
+        if playback::AVAILABLE {
+            content = content.push(match &self.player {
+                Some((Preview::Versions, _)) => self.view_preview(),
+                _ => row![
+                    button(text(tr!("compare-play")).size(12))
+                        .on_press(Message::Player(PlayerMessage::Load(Preview::Versions))),
+                ]
+                .push(
+                    self.preview_error
+                        .as_ref()
+                        .map(|e| text(e).size(12).style(text::danger)),
+                )
+                .spacing(10)
+                .align_y(Alignment::Center)
+                .into(),
+            });
+            content = content.push(self.view_device_error(true));
+        }
+        content.into()
+    }
+}
+
+fn view_history_entry(entry: &history::Entry, can_rerun: bool) -> Element<'_, Message> {
+    let name = entry
+        .input_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let outcome = match (&entry.error, entry.audio_duration, entry.elapsed) {
+        (Some(e), _, _) => text(tr!(
+            "history-failed",
+            error = e.lines().next().unwrap_or_default()
+        ))
+        .style(text::danger),
+        (None, Some(audio), Some(elapsed)) => text(tr!(
+            "history-succeeded",
+            duration = format_duration(audio),
+            elapsed = format_duration(elapsed)
+        )),
+        (None, _, _) => text(tr!("history-outcome-succeeded")),
+    };
+    let show_output = entry
+        .output_path
+        .as_ref()
+        .filter(|path| path.exists())
+        .map(|path| Message::RevealFile(path.clone()));
+    let can_compare = show_output.is_some();
+
+    container(
+        column![
+            row![
+                text(name).width(Length::Fill),
+                text(&entry.started).size(11),
+            ]
+            .spacing(10),
+            text(entry.input_path.display().to_string()).size(11),
+            outcome.size(11),
+            row![
+                button(text(tr!("history-open-folder")).size(11))
+                    .on_press_maybe(show_output)
+                    .style(button::secondary),
+                button(text(tr!("history-rerun")).size(11)).on_press_maybe(
+                    can_rerun.then_some(Message::History(HistoryMessage::Rerun(entry.id)))
+                ),
+                button(text(tr!("history-compare")).size(11))
+                    .on_press_maybe(
+                        can_compare.then_some(Message::History(HistoryMessage::Compare(entry.id)))
+                    )
+                    .style(button::secondary),
+            ]
+            .spacing(10),
+        ]
+        .spacing(4),
+    )
+    .padding(10)
+    .width(Length::Fill)
+    .style(container::bordered_box)
+    .into()
+}
@@ -0,0 +1,366 @@
+//! Hearing the microphone cleaned as it's captured, to try the engine before
+//! recording. Captured audio is cleaned by the engine's LADSPA plugin on a
+//! thread of its own and played on the output device, and can be handed on
+//! to a [`crate::virtual_mic`] for other apps. Only compiled in with
+//! the `live` feature; without it [`Monitor::start`] always fails and the
+//! Live tab is hidden.
+
+use crate::devices::Device;
+use crate::meter::Meter;
+use std::io::Write;
+use std::path::Path;
+
+/// Whether this build can clean live audio.
+pub const AVAILABLE: bool = cfg!(feature = "live");
+
+/// Attenuation limit listening starts with, in dB: noise removed whole.
+pub const DEFAULT_ATTENUATION_DB: f32 = 100.0;
+
+#[cfg(feature = "live")]
+pub use imp::Monitor;
+#[cfg(not(feature = "live"))]
+pub use stub::Monitor;
+
+#[cfg(feature = "live")]
+mod imp {
+    use super::*;
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+    use cpal::{
+        FromSample, SampleFormat, SampleRate, SizedSample, Stream, StreamConfig,
+        SupportedStreamConfig, SupportedStreamConfigRange,
+    };
+    use dfn_core::audio::ENGINE_SAMPLE_RATE;
+    use dfn_core::plugin::Plugin;
+    use std::collections::VecDeque;
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+    use std::sync::{Arc, Mutex, mpsc};
+
+    /// Cleaned audio waiting to be played, in frames. Kept short so what's
+    /// heard follows what's said; what comes in beyond it is dropped.
+    const MAX_QUEUED: usize = ENGINE_SAMPLE_RATE as usize / 5;
+
+    /// Silence played ahead of the first cleaned audio, so the capture
+    /// running a little late doesn't make playback run dry.
+    const HEADROOM: usize = ENGINE_SAMPLE_RATE as usize / 50;
+
+    /// Cleaned audio on its way from the cleaning thread to the output.
+    type Queue = Arc<Mutex<VecDeque<f32>>>;
+
+    /// Where the cleaned audio is also written, as raw samples.
+    type Tap = Arc<Mutex<Option<Box<dyn Write + Send>>>>;
+
+    /// Listening under way. The input is downmixed to mono, cleaned and
+    /// played on every output channel.
+    pub struct Monitor {
+        // Dropping the input stream ends the cleaning thread, and dropping
+        // the output stream stops playback.
+        _input: Stream,
+        _output: Stream,
+        /// Bits of the attenuation limit the cleaning thread applies.
+        attenuation: Arc<AtomicU32>,
+        /// Whether the cleaned audio is heard on the output.
+        playing: Arc<AtomicBool>,
+        tap: Tap,
+        levels: mpsc::Receiver<[Meter; 2]>,
+        errors: mpsc::Receiver<String>,
+        meters: [Meter; 2],
+    }
+
+    impl Monitor {
+        /// Starts cleaning `input` into `output` with the plugin at `plugin`.
+        pub fn start(
+            input: &Device,
+            output: &Device,
+            plugin: &Path,
+            attenuation_db: f32,
+        ) -> Result<Self, String> {
+            let mut instance = Plugin::load(plugin)?.instantiate(ENGINE_SAMPLE_RATE)?;
+            instance.set_attenuation(attenuation_db);
+
+            let host = cpal::default_host();
+            let input_device = match input {
+                Device::Default => host.default_input_device(),
+                Device::Named(name) => host.input_devices().ok().and_then(|mut devices| {
+                    devices.find(|device| device.name().ok().as_ref() == Some(name))
+                }),
+            }
+            .ok_or("The input device is not available")?;
+            let output_device = match output {
+                Device::Default => host.default_output_device(),
+                Device::Named(name) => host.output_devices().ok().and_then(|mut devices| {
+                    devices.find(|device| device.name().ok().as_ref() == Some(name))
+                }),
+            }
+            .ok_or("The output device is not available")?;
+            let input_config = input_device
+                .supported_input_configs()
+                .ok()
+                .and_then(at_engine_rate)
+                .ok_or("The input device can't record at 48 kHz, which the engine needs")?;
+            let output_config = output_device
+                .supported_output_configs()
+                .ok()
+                .and_then(at_engine_rate)
+                .ok_or("The output device can't play at 48 kHz, which the engine needs")?;
+
+            let (sender, receiver) = mpsc::channel::<Vec<f32>>();
+            let (level_sender, levels) = mpsc::channel();
+            let (error_sender, errors) = mpsc::channel();
+            let queue: Queue = Arc::new(Mutex::new(VecDeque::from(vec![0.0; HEADROOM])));
+            let attenuation = Arc::new(AtomicU32::new(attenuation_db.to_bits()));
+            let playing = Arc::new(AtomicBool::new(true));
+            let tap: Tap = Arc::default();
+
+            let cleaned_queue = queue.clone();
+            let wanted = attenuation.clone();
+            let cleaned_tap = tap.clone();
+            let tap_errors = error_sender.clone();
+            std::thread::spawn(move || {
+                let mut applied = attenuation_db;
+                let mut cleaned = Vec::new();
+                for captured in receiver {
+                    let attenuation_db = f32::from_bits(wanted.load(Ordering::Relaxed));
+                    if attenuation_db != applied {
+                        instance.set_attenuation(attenuation_db);
+                        applied = attenuation_db;
+                    }
+                    cleaned.resize(captured.len(), 0.0);
+                    instance.process(&captured, &mut cleaned);
+                    let _ = level_sender.send([measure(&captured), measure(&cleaned)]);
+
+                    let mut tap = cleaned_tap.lock().unwrap();
+                    if let Some(writer) = tap.as_mut() {
+                        let bytes: Vec<u8> = cleaned
+                            .iter()
+                            .flat_map(|sample| sample.to_le_bytes())
+                            .collect();
+                        if let Err(e) = writer.write_all(&bytes) {
+                            *tap = None;
+                            let _ = tap_errors
+                                .send(format!("The cleaned audio could not be handed on: {}", e));
+                        }
+                    }
+                    drop(tap);
+
+                    let mut queue = cleaned_queue.lock().unwrap();
+                    queue.extend(&cleaned);
+                    let excess = queue.len().saturating_sub(MAX_QUEUED);
+                    queue.drain(..excess);
+                }
+            });
+
+            let config = input_config.config();
+            let capture_errors = error_sender.clone();
+            let input_stream = match input_config.sample_format() {
+                SampleFormat::F32 => capture::<f32>(&input_device, &config, sender, capture_errors),
+                SampleFormat::I16 => capture::<i16>(&input_device, &config, sender, capture_errors),
+                SampleFormat::U16 => capture::<u16>(&input_device, &config, sender, capture_errors),
+                SampleFormat::I32 => capture::<i32>(&input_device, &config, sender, capture_errors),
+                SampleFormat::U8 => capture::<u8>(&input_device, &config, sender, capture_errors),
+                format => Err(format!("Unsupported input sample format {}", format)),
+            }?;
+            let config = output_config.config();
+            let heard = playing.clone();
+            let output_stream = match output_config.sample_format() {
+                SampleFormat::F32 => {
+                    play::<f32>(&output_device, &config, queue, heard, error_sender)
+                }
+                SampleFormat::I16 => {
+                    play::<i16>(&output_device, &config, queue, heard, error_sender)
+                }
+                SampleFormat::U16 => {
+                    play::<u16>(&output_device, &config, queue, heard, error_sender)
+                }
+                SampleFormat::I32 => {
+                    play::<i32>(&output_device, &config, queue, heard, error_sender)
+                }
+                SampleFormat::U8 => play::<u8>(&output_device, &config, queue, heard, error_sender),
+                format => Err(format!("Unsupported output sample format {}", format)),
+            }?;
+            for stream in [&input_stream, &output_stream] {
+                stream
+                    .play()
+                    .map_err(|e| format!("Could not start listening: {}", e))?;
+            }
+
+            Ok(Self {
+                _input: input_stream,
+                _output: output_stream,
+                attenuation,
+                playing,
+                tap,
+                levels,
+                errors,
+                meters: [Meter::default(); 2],
+            })
+        }
+
+        /// Changes the attenuation limit, in dB, as listening goes on.
+        pub fn set_attenuation(&self, db: f32) {
+            self.attenuation.store(db.to_bits(), Ordering::Relaxed);
+        }
+
+        /// Plays the cleaned audio on the output, or only hands it on.
+        pub fn set_playing(&self, playing: bool) {
+            self.playing.store(playing, Ordering::Relaxed);
+        }
+
+        /// Also writes the cleaned audio to `tap`, as 32-bit float samples,
+        /// or stops writing it anywhere else than the output.
+        pub fn set_tap(&self, tap: Option<Box<dyn Write + Send>>) {
+            *self.tap.lock().unwrap() = tap;
+        }
+
+        /// Takes in the meter readings sent since the last call, failing if
+        /// a device stopped working.
+        pub fn update(&mut self) -> Result<(), String> {
+            if let Some(meters) = self.levels.try_iter().last() {
+                self.meters = meters;
+            }
+            match self.errors.try_recv() {
+                Ok(e) => Err(e),
+                Err(_) => Ok(()),
+            }
+        }
+
+        /// Levels of the microphone and of the cleaned audio.
+        pub fn meters(&self) -> [Meter; 2] {
+            self.meters
+        }
+    }
+
+    /// A configuration of the device at the engine's sample rate, in float
+    /// samples and with as few channels as it allows where it can.
+    fn at_engine_rate(
+        configs: impl Iterator<Item = SupportedStreamConfigRange>,
+    ) -> Option<SupportedStreamConfig> {
+        let rate = SampleRate(ENGINE_SAMPLE_RATE);
+        configs
+            .filter(|config| config.min_sample_rate() <= rate && rate <= config.max_sample_rate())
+            .min_by_key(|config| {
+                (
+                    config.sample_format() != SampleFormat::F32,
+                    config.channels(),
+                )
+            })
+            .map(|config| config.with_sample_rate(rate))
+    }
+
+    /// Levels of a block of mono samples, shown on both sides.
+    fn measure(samples: &[f32]) -> Meter {
+        let peak = samples
+            .iter()
+            .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+        let squares: f32 = samples.iter().map(|sample| sample * sample).sum();
+        let rms = (squares / samples.len().max(1) as f32).sqrt();
+        Meter {
+            peak: [peak; 2],
+            rms: [rms; 2],
+        }
+    }
+
+    fn capture<T>(
+        device: &cpal::Device,
+        config: &StreamConfig,
+        sender: mpsc::Sender<Vec<f32>>,
+        errors: mpsc::Sender<String>,
+    ) -> Result<Stream, String>
+    where
+        T: SizedSample,
+        f32: FromSample<T>,
+    {
+        let channels = config.channels.max(1) as usize;
+        device
+            .build_input_stream(
+                config,
+                move |data: &[T], _| {
+                    let _ = sender.send(
+                        data.chunks(channels)
+                            .map(|frame| {
+                                frame
+                                    .iter()
+                                    .map(|sample| sample.to_sample::<f32>())
+                                    .sum::<f32>()
+                                    / channels as f32
+                            })
+                            .collect(),
+                    );
+                },
+                move |e| {
+                    let _ = errors.send(format!("Listening failed: {}", e));
+                },
+                None,
+            )
+            .map_err(|e| format!("Could not open the input device: {}", e))
+    }
+
+    fn play<T>(
+        device: &cpal::Device,
+        config: &StreamConfig,
+        queue: Queue,
+        playing: Arc<AtomicBool>,
+        errors: mpsc::Sender<String>,
+    ) -> Result<Stream, String>
+    where
+        T: SizedSample + FromSample<f32>,
+    {
+        let channels = config.channels.max(1) as usize;
+        device
+            .build_output_stream(
+                config,
+                move |data: &mut [T], _| {
+                    let playing = playing.load(Ordering::Relaxed);
+                    let mut queue = queue.lock().unwrap();
+                    for frame in data.chunks_mut(channels) {
+                        let sample = queue.pop_front().filter(|_| playing).unwrap_or(0.0);
+                        frame.fill(T::from_sample(sample));
+                    }
+                },
+                move |e| {
+                    let _ = errors.send(format!("Listening failed: {}", e));
+                },
+                None,
+            )
+            .map_err(|e| format!("Could not open the output device: {}", e))
+    }
+}
+
+#[cfg(not(feature = "live"))]
+mod stub {
+    use super::*;
+
+    /// Stands in for the real monitor in builds without live cleaning. It
+    /// can't be constructed.
+    pub enum Monitor {}
+
+    impl Monitor {
+        pub fn start(
+            _input: &Device,
+            _output: &Device,
+            _plugin: &Path,
+            _attenuation_db: f32,
+        ) -> Result<Self, String> {
+            Err("This build was made without live cleaning".to_string())
+        }
+
+        pub fn set_attenuation(&self, _db: f32) {
+            match *self {}
+        }
+
+        pub fn set_playing(&self, _playing: bool) {
+            match *self {}
+        }
+
+        pub fn set_tap(&self, _tap: Option<Box<dyn Write + Send>>) {
+            match *self {}
+        }
+
+        pub fn update(&mut self) -> Result<(), String> {
+            match *self {}
+        }
+
+        pub fn meters(&self) -> [Meter; 2] {
+            match *self {}
+        }
+    }
+}
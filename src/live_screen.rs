@@ -0,0 +1,320 @@
+//! The Live tab: the microphone cleaned as it's captured and played back or
+//! offered to other apps, and getting the engine's plugin in place for it.
+
+use crate::{DfnGui, Message, live, meter, toast, virtual_mic};
+use dfn_core::i18n::tr;
+use dfn_core::{denoiser, download, plugin};
+use iced::widget::{button, canvas, checkbox, column, row, slider, text};
+use iced::{Alignment, Element, Length, Task};
+use rfd::FileDialog;
+use std::path::PathBuf;
+
+/// What the Live tab holds between frames.
+pub struct LiveScreen {
+    /// The installed plugin, looked up when the tab is opened.
+    plugin: Option<PathBuf>,
+    monitor: Option<live::Monitor>,
+    attenuation_db: f32,
+    /// Whether the cleaned audio is heard on the output.
+    playing: bool,
+    /// The microphone the cleaned audio is offered to other apps as, while
+    /// listening.
+    virtual_mic: Option<virtual_mic::VirtualMic>,
+    /// The plugin download under way and how far it is, in percent.
+    download: Option<(download::DownloadId, f32)>,
+    /// Why listening stopped, or the plugin couldn't be got.
+    error: Option<String>,
+}
+
+impl Default for LiveScreen {
+    fn default() -> Self {
+        Self {
+            plugin: None,
+            monitor: None,
+            attenuation_db: live::DEFAULT_ATTENUATION_DB,
+            playing: true,
+            virtual_mic: None,
+            download: None,
+            error: None,
+        }
+    }
+}
+
+impl LiveScreen {
+    /// Whether audio is being captured, so devices aren't probed meanwhile.
+    pub fn is_listening(&self) -> bool {
+        self.monitor.is_some()
+    }
+
+    /// Stops listening, taking the virtual microphone away with it.
+    fn stop(&mut self) {
+        self.remove_virtual_mic();
+        self.monitor = None;
+    }
+
+    /// Takes the virtual microphone away, once nothing's written to it.
+    fn remove_virtual_mic(&mut self) {
+        if let Some(monitor) = &self.monitor {
+            monitor.set_tap(None);
+        }
+        self.virtual_mic = None;
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum LiveMessage {
+    /// The tab was opened.
+    Opened,
+    Start,
+    Stop,
+    AttenuationChanged(f32),
+    PlayingToggled(bool),
+    VirtualMicToggled(bool),
+    Tick,
+    DownloadPlugin,
+    Download(download::Event),
+    /// Install a plugin downloaded or built elsewhere.
+    InstallPlugin,
+    PluginChosen(Option<PathBuf>),
+}
+
+impl DfnGui {
+    pub fn update_live(&mut self, message: LiveMessage) -> Task<Message> {
+        let screen = &mut self.live_screen;
+        match message {
+            LiveMessage::Opened => screen.plugin = plugin::installed_plugin(),
+            LiveMessage::Start => {
+                screen.error = None;
+                let Some(plugin) = &screen.plugin else {
+                    return Task::none();
+                };
+                match live::Monitor::start(
+                    &self.input_device,
+                    &self.output_device,
+                    plugin,
+                    screen.attenuation_db,
+                ) {
+                    Ok(monitor) => {
+                        tracing::info!("Listening through the plugin at {}", plugin.display());
+                        monitor.set_playing(screen.playing);
+                        screen.monitor = Some(monitor);
+                    }
+                    Err(e) => {
+                        tracing::error!("Could not start listening: {}", e);
+                        screen.error = Some(e);
+                    }
+                }
+            }
+            LiveMessage::Stop => screen.stop(),
+            LiveMessage::AttenuationChanged(db) => {
+                screen.attenuation_db = db;
+                if let Some(monitor) = &screen.monitor {
+                    monitor.set_attenuation(db);
+                }
+            }
+            LiveMessage::PlayingToggled(playing) => {
+                screen.playing = playing;
+                if let Some(monitor) = &screen.monitor {
+                    monitor.set_playing(playing);
+                }
+            }
+            LiveMessage::VirtualMicToggled(true) => {
+                let Some(monitor) = &screen.monitor else {
+                    return Task::none();
+                };
+                screen.error = None;
+                match virtual_mic::VirtualMic::create() {
+                    Ok(mut mic) => {
+                        monitor.set_tap(mic.take_input());
+                        screen.virtual_mic = Some(mic);
+                    }
+                    Err(e) => {
+                        tracing::error!("Could not offer the virtual microphone: {}", e);
+                        screen.error = Some(e);
+                    }
+                }
+            }
+            LiveMessage::VirtualMicToggled(false) => screen.remove_virtual_mic(),
+            LiveMessage::Tick => {
+                if let Some(monitor) = &mut screen.monitor
+                    && let Err(e) = monitor.update()
+                {
+                    tracing::error!("{}", e);
+                    screen.stop();
+                    screen.error = Some(e);
+                }
+            }
+            LiveMessage::DownloadPlugin => {
+                let Some(dir) = dfn_core::data_dir() else {
+                    screen.error = Some("Could not find project directories".to_string());
+                    return Task::none();
+                };
+                screen.error = None;
+                let (id, events) = self
+                    .downloads
+                    .download(async { plugin::release_url() }, plugin::plugin_path(&dir));
+                self.live_screen.download = Some((id, 0.0));
+                return Task::run(events, |event| Message::Live(LiveMessage::Download(event)));
+            }
+            LiveMessage::Download(event) => match (event, &mut screen.download) {
+                (download::Event::Progress(id, progress), Some((current, shown)))
+                    if id == *current =>
+                {
+                    *shown = progress;
+                }
+                (download::Event::Finished(id, result), Some((current, _))) if id == *current => {
+                    screen.download = None;
+                    match result {
+                        Ok(path) => {
+                            tracing::info!("Plugin downloaded to {}", path.display());
+                            screen.plugin = Some(path);
+                            self.plugin_installed = denoiser::InProcess::is_installed();
+                        }
+                        Err(e) => {
+                            tracing::error!("Plugin download failed: {}", e);
+                            screen.error = Some(tr!("error-plugin-download", error = e));
+                        }
+                    }
+                }
+                _ => {}
+            },
+            LiveMessage::InstallPlugin => {
+                return Task::perform(async { FileDialog::new().pick_file() }, |file| {
+                    Message::Live(LiveMessage::PluginChosen(file))
+                });
+            }
+            LiveMessage::PluginChosen(Some(file)) => {
+                // Loaded first, so a file that isn't the plugin is turned down
+                // rather than kept.
+                let result = plugin::Plugin::load(&file).and_then(|_| {
+                    dfn_core::data_dir()
+                        .ok_or_else(|| "Could not find project directories".to_string())
+                        .and_then(|dir| download::install_from(&file, &plugin::plugin_path(&dir)))
+                });
+                match result {
+                    Ok(path) => {
+                        tracing::info!("Plugin installed from {}", file.display());
+                        screen.plugin = Some(path);
+                        self.plugin_installed = denoiser::InProcess::is_installed();
+                        self.toasts
+                            .push(toast::Severity::Success, tr!("toast-plugin-installed"));
+                    }
+                    Err(e) => {
+                        tracing::error!("Could not install the plugin: {}", e);
+                        self.toasts.push(
+                            toast::Severity::Error,
+                            tr!("toast-plugin-install-failed", error = e),
+                        );
+                    }
+                }
+            }
+            LiveMessage::PluginChosen(None) => {}
+        }
+        Task::none()
+    }
+
+    pub fn view_live(&self) -> Element<'_, Message> {
+        let screen = &self.live_screen;
+        let error = screen
+            .error
+            .as_ref()
+            .map(|e| text(e).size(12).style(text::danger));
+        if screen.plugin.is_none() {
+            let download: Element<'_, Message> = match screen.download {
+                Some((_, progress)) => text(tr!(
+                    "live-plugin-downloading",
+                    percent = format!("{:.0}", progress)
+                ))
+                .size(12)
+                .into(),
+                None => button(text(tr!("live-plugin-download")).size(12))
+                    .on_press_maybe(
+                        plugin::release_url()
+                            .is_ok()
+                            .then_some(Message::Live(LiveMessage::DownloadPlugin)),
+                    )
+                    .into(),
+            };
+            return column![
+                text(tr!("live-intro")).size(12),
+                text(tr!("live-plugin-missing")).size(12),
+                row![
+                    download,
+                    button(text(tr!("live-plugin-install")).size(12))
+                        .on_press(Message::Live(LiveMessage::InstallPlugin))
+                        .style(button::secondary),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            ]
+            .push(error)
+            .spacing(10)
+            .into();
+        }
+
+        let attenuation = row![
+            text(tr!(
+                "live-attenuation",
+                db = format!("{:.0}", screen.attenuation_db)
+            ))
+            .size(12)
+            .width(Length::Fixed(170.0)),
+            slider(0.0..=100.0, screen.attenuation_db, |db| {
+                Message::Live(LiveMessage::AttenuationChanged(db))
+            })
+            .step(1.0),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+        let toggle = match &screen.monitor {
+            Some(_) => button(text(tr!("live-stop")))
+                .on_press(Message::Live(LiveMessage::Stop))
+                .style(button::danger),
+            None => button(text(tr!("live-start"))).on_press(Message::Live(LiveMessage::Start)),
+        };
+        let levels = screen.monitor.as_ref().map(|monitor| {
+            let [captured, cleaned] = monitor.meters();
+            let offer = virtual_mic::AVAILABLE.then(|| {
+                checkbox(screen.virtual_mic.is_some())
+                    .label(tr!("live-virtual-mic", name = virtual_mic::MIC_NAME))
+                    .on_toggle(|offered| Message::Live(LiveMessage::VirtualMicToggled(offered)))
+            });
+            column![
+                checkbox(screen.playing)
+                    .label(tr!("live-play"))
+                    .on_toggle(|playing| Message::Live(LiveMessage::PlayingToggled(playing))),
+            ]
+            .push(offer)
+            .push(
+                column![
+                    text(tr!("live-microphone")).size(12),
+                    view_meter(captured),
+                    text(tr!("live-cleaned")).size(12),
+                    view_meter(cleaned),
+                ]
+                .spacing(5),
+            )
+            .spacing(10)
+        });
+        column![
+            text(tr!("live-intro")).size(12),
+            self.view_devices(),
+            attenuation,
+            toggle,
+        ]
+        .push(levels)
+        .push(error)
+        .spacing(10)
+        .into()
+    }
+}
+
+fn view_meter(meter: meter::Meter) -> Element<'static, Message> {
+    canvas(meter::LevelMeter {
+        meter,
+        clipped: false,
+    })
+    .width(Length::Fill)
+    .height(12)
+    .into()
+}
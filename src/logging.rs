@@ -0,0 +1,150 @@
+//! Diagnostic log written to daily files in the app's data folder, so
+//! failures can be looked into after the fact. Only the last week of files
+//! is kept. The latest lines are also kept in memory for the Logs tab.
+
+use dfn_core::i18n::tr;
+use std::collections::VecDeque;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{Registry, reload};
+
+/// Number of daily log files kept.
+const KEPT_FILES: usize = 7;
+
+/// Number of lines kept in memory.
+const RECENT_LINES: usize = 1000;
+
+/// Changes the filter of the running logger.
+static FILTER: OnceLock<reload::Handle<Targets, Registry>> = OnceLock::new();
+
+/// The latest lines logged, oldest first.
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// How much goes into the log.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Verbosity {
+    /// Errors and warnings only.
+    Errors,
+    /// Also downloads, engine runs and their outcome.
+    #[default]
+    Normal,
+    /// Also each step of a run, for tracking down a problem.
+    Detailed,
+}
+
+impl Verbosity {
+    pub const ALL: [Verbosity; 3] = [Verbosity::Errors, Verbosity::Normal, Verbosity::Detailed];
+
+    /// Name the verbosity is saved under.
+    pub fn key(self) -> &'static str {
+        match self {
+            Verbosity::Errors => "errors",
+            Verbosity::Normal => "normal",
+            Verbosity::Detailed => "detailed",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Verbosity> {
+        Verbosity::ALL
+            .into_iter()
+            .find(|verbosity| verbosity.key() == key)
+    }
+
+    /// Events kept at this verbosity. Other libraries only get to log their
+    /// warnings and errors, since their details would drown out the app's.
+    fn filter(self) -> Targets {
+        let level = match self {
+            Verbosity::Errors => LevelFilter::WARN,
+            Verbosity::Normal => LevelFilter::INFO,
+            Verbosity::Detailed => LevelFilter::DEBUG,
+        };
+        Targets::new()
+            .with_target(env!("CARGO_CRATE_NAME"), level)
+            .with_target("dfn_core", level)
+            .with_default(level.min(LevelFilter::WARN))
+    }
+}
+
+impl fmt::Display for Verbosity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verbosity::Errors => write!(f, "{}", tr!("log-verbosity-errors")),
+            Verbosity::Normal => write!(f, "{}", tr!("log-verbosity-normal")),
+            Verbosity::Detailed => write!(f, "{}", tr!("log-verbosity-detailed")),
+        }
+    }
+}
+
+/// Folder the log files are written to.
+pub fn dir() -> Option<PathBuf> {
+    dfn_core::data_dir().map(|dir| dir.join("logs"))
+}
+
+/// Starts logging to files. Without a log, the app works as before.
+pub fn init(verbosity: Verbosity) -> Result<(), String> {
+    let dir = dir().ok_or("Could not find project directories")?;
+    // The appender looks for old files to remove before it creates the
+    // folder, and complains on stderr if it isn't there yet.
+    let _ = std::fs::create_dir_all(&dir);
+    let appender = tracing_appender::rolling::Builder::new()
+        .rotation(tracing_appender::rolling::Rotation::DAILY)
+        .filename_prefix(env!("CARGO_PKG_NAME"))
+        .filename_suffix("log")
+        .max_log_files(KEPT_FILES)
+        .build(dir)
+        .map_err(|e| format!("Could not open the log: {}", e))?;
+    let (filter, handle) = reload::Layer::new(verbosity.filter());
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(appender)
+                .with_ansi(false),
+        )
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(|| Recent)
+                .with_ansi(false),
+        )
+        .try_init()
+        .map_err(|e| format!("Could not start the log: {}", e))?;
+    let _ = FILTER.set(handle);
+    Ok(())
+}
+
+/// Applies `verbosity` to the running logger.
+pub fn set_verbosity(verbosity: Verbosity) {
+    if let Some(handle) = FILTER.get() {
+        let _ = handle.reload(verbosity.filter());
+    }
+}
+
+/// The latest lines logged this session, oldest first.
+pub fn recent() -> Vec<String> {
+    // Also read while panicking, when the lock may be poisoned.
+    let recent = RECENT.lock().unwrap_or_else(|e| e.into_inner());
+    recent.iter().cloned().collect()
+}
+
+/// Keeps what's logged in [`RECENT`].
+struct Recent;
+
+impl std::io::Write for Recent {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut recent = RECENT.lock().unwrap();
+        for line in String::from_utf8_lossy(buf).lines() {
+            if recent.len() == RECENT_LINES {
+                recent.pop_front();
+            }
+            recent.push_back(line.to_string());
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
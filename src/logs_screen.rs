@@ -0,0 +1,118 @@
+//! The Logs tab: the app's recent log and the engine output of the
+//! selected file.
+
+use crate::{DfnGui, JobState, Message, Status, logging, toast};
+use dfn_core::i18n::tr;
+use dfn_core::{denoiser, engine};
+use iced::widget::{button, column, container, row, scrollable, text};
+use iced::{Element, Font, Length, Task};
+use rfd::FileDialog;
+use std::path::PathBuf;
+
+/// What the Logs tab shows, refreshed while it's open.
+#[derive(Debug, Default)]
+pub struct LogsScreen {
+    app: Vec<String>,
+    engine: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum LogsMessage {
+    Refresh,
+    Copy,
+    Save,
+    SaveTo(Option<PathBuf>),
+}
+
+impl DfnGui {
+    pub fn update_logs(&mut self, message: LogsMessage) -> Task<Message> {
+        match message {
+            LogsMessage::Refresh => {
+                self.logs_screen.app = logging::recent();
+                self.logs_screen.engine = self
+                    .engine_transcript
+                    .as_ref()
+                    .map(engine::Transcript::lines)
+                    .unwrap_or_default();
+            }
+            LogsMessage::Copy => {
+                self.toasts
+                    .push(toast::Severity::Info, tr!("toast-logs-copied"));
+                return iced::clipboard::write(self.logs_text());
+            }
+            LogsMessage::Save => {
+                let name = format!("{}.log", env!("CARGO_PKG_NAME"));
+                return Task::perform(
+                    async move { FileDialog::new().set_file_name(name).save_file() },
+                    |path| Message::Logs(LogsMessage::SaveTo(path)),
+                );
+            }
+            LogsMessage::SaveTo(Some(path)) => match std::fs::write(&path, self.logs_text()) {
+                Ok(()) => self.toasts.push(
+                    toast::Severity::Success,
+                    tr!("toast-logs-saved", path = path.display()),
+                ),
+                Err(e) => self.toasts.push(
+                    toast::Severity::Error,
+                    tr!("toast-logs-save-failed", error = e),
+                ),
+            },
+            LogsMessage::SaveTo(None) => {}
+        }
+        Task::none()
+    }
+
+    /// Both logs on the Logs tab, as one text.
+    fn logs_text(&self) -> String {
+        format!(
+            "{}\n{}\n\n{}\n{}\n",
+            tr!("logs-app"),
+            self.logs_screen.app.join("\n"),
+            tr!("logs-engine"),
+            self.logs_screen.engine.join("\n")
+        )
+    }
+
+    pub fn view_logs(&self) -> Element<'_, Message> {
+        let screen = &self.logs_screen;
+        let engine_running = matches!(self.status, Status::Job(JobState::Processing(_)))
+            && self.options.backend == denoiser::Backend::ExternalBinary;
+        column![
+            row![
+                button(text(tr!("logs-copy")).size(12))
+                    .on_press(Message::Logs(LogsMessage::Copy))
+                    .style(button::secondary),
+                button(text(tr!("logs-save")).size(12))
+                    .on_press(Message::Logs(LogsMessage::Save))
+                    .style(button::secondary),
+            ]
+            .spacing(10),
+            text(tr!("logs-app")).size(14),
+            view_log_lines(&screen.app, tr!("logs-app-empty")),
+            text(if engine_running {
+                tr!("logs-engine-running")
+            } else {
+                tr!("logs-engine")
+            })
+            .size(14),
+            view_log_lines(&screen.engine, tr!("logs-engine-empty")),
+        ]
+        .spacing(10)
+        .into()
+    }
+}
+
+/// `lines` in a box that follows new lines as they come in.
+fn view_log_lines(lines: &[String], empty: String) -> Element<'_, Message> {
+    let content = if lines.is_empty() {
+        text(empty).size(12)
+    } else {
+        text(lines.join("\n")).size(11).font(Font::MONOSPACE)
+    };
+    container(scrollable(content).anchor_bottom().width(Length::Fill))
+        .height(Length::Fixed(170.0))
+        .padding(10)
+        .width(Length::Fill)
+        .style(container::bordered_box)
+        .into()
+}
@@ -1,471 +1,2021 @@
+mod archive_panel;
+mod clean_screen;
+mod completion;
+mod control;
+mod crash;
+mod devices;
+mod devices_panel;
+mod downloader;
+#[cfg(target_os = "linux")]
+mod file_manager;
+mod headless;
+mod help;
+mod history_screen;
+mod live;
+mod live_screen;
+mod logging;
+mod logs_screen;
+mod meter;
+mod notification;
+mod onboarding;
+mod options_panel;
+mod playback;
+mod player;
+#[cfg(target_os = "linux")]
+mod portal;
+mod power;
+mod processing;
+mod queue_panel;
+mod recording;
+mod recording_panel;
+mod report;
+mod sample;
+mod settings;
+mod settings_screen;
+mod shell_menu;
+mod spectrogram;
+mod status_panel;
+mod style;
+mod taskbar;
+mod toast;
+mod tray;
+mod virtual_mic;
+mod waveform;
+mod windows;
+
+use archive_panel::{ArchiveForm, ArchiveMessage};
+use clean_screen::{CleanMessage, DragHover, drop_zone_style};
+use devices_panel::DevicesMessage;
+use dfn_core::i18n::{self, tr};
+use dfn_core::pipeline::{ProcessingOptions, RunReport};
+use dfn_core::{
+    accel, archive, audio, benchmark, cancel, content, dedup, denoiser, download, engine, formats,
+    history, partial, queue, temp, update, webhook,
+};
+use downloader::DownloadMessage;
 use futures_util::{Stream, StreamExt};
-use iced::widget::{button, column, container, progress_bar, text};
-use iced::{Alignment, Element, Length, Task, Theme, window};
+use history_screen::{HistoryMessage, HistoryScreen};
+use iced::widget::{
+    button, column, container, image, opaque, progress_bar, row, scrollable, stack, text,
+};
+use iced::{Alignment, Element, Length, Size, Task, Theme, window};
+use live_screen::{LiveMessage, LiveScreen};
+use logs_screen::{LogsMessage, LogsScreen};
+use onboarding::{Onboarding, OnboardingMessage};
+use options_panel::OptionsMessage;
+use player::{PlayerMessage, Preview};
+use processing::{ExpressMessage, Failure, PerformanceStats, ProcessingMessage, Run};
+use queue_panel::{QueueMessage, Schedule, ScheduleForm};
+use recording_panel::RecordingMessage;
 use rfd::FileDialog;
-use std::io::Write;
+use settings_screen::{SettingsMessage, SettingsScreen};
+use status_panel::{StatusMessage, view_full_log, view_report_actions};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
-use std::process::Command as StdCommand;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use windows::Windows;
+
+/// Start the app again once it has exited, after an update.
+static RELAUNCH: AtomicBool = AtomicBool::new(false);
 
 pub fn main() -> iced::Result {
-    iced::application(DfnGui::init, DfnGui::update, DfnGui::view)
-        .title(DfnGui::title)
-        .subscription(DfnGui::subscription)
-        .theme(DfnGui::theme)
-        .window(window::Settings {
-            size: (600.0, 450.0).into(),
-            ..Default::default()
-        })
-        .run()
+    let mut args: Vec<std::ffi::OsString> = std::env::args_os().skip(1).collect();
+    // Decides where everything is kept, so it comes before anything is read.
+    let portable = args.iter().any(|arg| arg == "--portable");
+    args.retain(|arg| arg != "--portable");
+    dfn_core::set_portable(portable);
+    let settings = settings::Settings::load();
+    if let Err(e) = logging::init(settings.log_verbosity) {
+        eprintln!("{}", e);
+    }
+    crash::install();
+    tracing::info!(
+        "Starting {} {} on {} ({})",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    if let Some(dir) = dfn_core::portable_dir() {
+        tracing::info!("Running portable from {}", dir.display());
+    }
+    // Looked up before an update replaces the executable, after which Linux
+    // reports the old file as deleted.
+    let exe = std::env::current_exe().ok();
+    if let Some(exe) = &exe {
+        update::remove_replaced(exe);
+    }
+    // Scratch folders of runs cut short by a crash or a kill.
+    let removed = temp::TempStore::open().remove_stale();
+    if removed > 0 {
+        tracing::info!("Removed {} leftover temporary folders", removed);
+    }
+    let appearance = settings.appearance;
+    i18n::apply(appearance.language);
+    let mut args = args.into_iter().peekable();
+    if args.next_if(|arg| arg == "--json").is_some() {
+        let inputs: Vec<PathBuf> = args.map(PathBuf::from).collect();
+        if inputs.is_empty() {
+            eprintln!("Usage: {} --json FILE...", env!("CARGO_PKG_NAME"));
+            std::process::exit(2);
+        }
+        std::process::exit(headless::run(&inputs, &settings.options, &settings.webhook));
+    }
+    // Files passed on the command line, as by the right-click menu: the
+    // first is selected right away and the others wait in the queue. File
+    // managers may pass them relative to the folder shown.
+    let files: Vec<PathBuf> = args
+        .map(|arg| std::path::absolute(&arg).unwrap_or_else(|_| PathBuf::from(arg)))
+        .collect();
+    // They go to the app if it's already running, rather than to a second
+    // one.
+    let mut running = false;
+    for file in &files {
+        match control::hand_over(file) {
+            control::Handover::Taken => {
+                tracing::info!("Passed {} to the running app", file.display());
+            }
+            control::Handover::Refused(e) => {
+                tracing::warn!("The running app could not take {}: {}", file.display(), e);
+                notification::show_now(
+                    &tr!(
+                        "notification-failed",
+                        name = file.file_name().unwrap_or_default().to_string_lossy()
+                    ),
+                    &e,
+                    false,
+                );
+            }
+            control::Handover::NoAnswer => break,
+        }
+        running = true;
+    }
+    if running {
+        return Ok(());
+    }
+    let result = iced::daemon(
+        move || Windows::boot(files.clone()),
+        Windows::update,
+        Windows::view,
+    )
+    .title(Windows::title)
+    .subscription(Windows::subscription)
+    .theme(Windows::theme)
+    .scale_factor(Windows::scale_factor)
+    .settings(iced::Settings {
+        default_text_size: appearance.text_size.into(),
+        ..Default::default()
+    })
+    .run();
+    if RELAUNCH.load(Ordering::Relaxed)
+        && let Some(exe) = exe
+        && let Err(e) = std::process::Command::new(&exe)
+            .args(std::env::args_os().skip(1))
+            .spawn()
+    {
+        tracing::error!("Could not restart after the update: {}", e);
+    }
+    result
 }
 
+/// Initial size of the window.
+const WINDOW_SIZE: Size = Size::new(600.0, 600.0);
+
+/// Size the window is shrunk to for the compact layout.
+const COMPACT_WINDOW_SIZE: Size = Size::new(320.0, 240.0);
+
+/// The compact layout is used when the window is narrower or lower than this,
+/// in interface units.
+const COMPACT_BREAKPOINT: Size = Size::new(420.0, 360.0);
+
 #[derive(Default)]
 struct DfnGui {
-    selected_file: Option<PathBuf>,
     status: Status,
-    download_progress: f32,
+    /// Format summary of the selected file.
+    input_info: Option<audio::FileInfo>,
+    /// Remark about how the selected file will be read, e.g. a conversion.
+    input_note: Option<String>,
+    /// What the selected file sounds like, once it has been listened to.
+    input_content: Option<content::Content>,
+    options: ProcessingOptions,
+    show_error_details: bool,
+    /// The whole error, with everything the engine printed, laid over the
+    /// window.
+    show_full_log: bool,
+    /// Base URL of a mirror of the engine releases, empty for none.
+    engine_mirror: String,
+    /// Which build of the engine is downloaded.
+    engine_provider: accel::Provider,
+    /// What the engine can run on here, found at startup.
+    providers: Vec<accel::Provider>,
+    /// A faster engine build for this machine, offered until it's taken or
+    /// turned down.
+    accelerated_offer: Option<accel::Provider>,
+    accelerated_declined: bool,
+    webhook: webhook::Webhook,
+    log_verbosity: logging::Verbosity,
+    sleep_inhibitor: Option<power::SleepInhibitor>,
+    /// Downloads of the engine and app updates, which share their connections
+    /// across all windows.
+    downloads: Arc<download::DownloadManager>,
+    /// Files added while another was cleaned, each with its own options.
+    queue: queue::Queue,
+    /// The archive being set up, while its form is open.
+    archive_form: Option<ArchiveForm>,
+    /// The archive whose files are cleaned from the queue, noted as
+    /// finished one by one.
+    archive: Option<archive::Archive>,
+    /// When the files waiting are set to start, until they do.
+    schedule: Option<Schedule>,
+    schedule_form: ScheduleForm,
+    /// An urgent file cleaned next to the current run, ahead of the queue.
+    express: Option<Run>,
+    /// Files of the batch cleaned next to the selected one, up to the
+    /// concurrency set, by when they were started.
+    workers: BTreeMap<u64, Run>,
+    /// Id of the next file started next to the selected one.
+    next_worker: u64,
+    /// A close was requested while processing and awaits confirmation.
+    confirm_exit: bool,
+    /// Seconds left before the machine is shut down, once a batch is done
+    /// with shutting down picked.
+    shutdown_countdown: Option<u32>,
+    /// Report of a crash in an earlier session, offered until dismissed.
+    crash_report: Option<PathBuf>,
+    /// Runs of an earlier session that never finished and left partial
+    /// outputs, offered to delete or clean again until dismissed.
+    leftovers: Vec<history::Unfinished>,
+    /// Opened with New Window. The first window keeps the tray icon, the
+    /// control socket, update checks and first-run prompts, and hands the
+    /// tray icon and the control socket on to the next when it's closed.
+    secondary: bool,
+    /// The window this is the state of. Only missing in tests.
+    window: Option<window::Id>,
+    /// Whether "Clean with DeepFilterNet" is in the file manager's menu.
+    shell_menu_installed: bool,
+    /// Exit as soon as the cancelled run has shut down its engine.
+    exit_when_idle: bool,
+    stats: PerformanceStats,
+    /// Whether the engine was found installed, as of the last check or
+    /// install.
+    engine_installed: bool,
+    /// Whether files can be cleaned in-process, as of startup or the
+    /// plugin's install.
+    plugin_installed: bool,
+    /// Version of the installed engine, once it's known.
+    engine_version: Option<String>,
+    /// The engine's command line and output in the run of the selected file
+    /// or the benchmark, for the Logs tab and problem reports. An express
+    /// run keeps its own.
+    engine_transcript: Option<engine::Transcript>,
+    /// The clip being previewed and what it was loaded from.
+    player: Option<(Preview, playback::Player)>,
+    /// Why the last preview couldn't be played.
+    preview_error: Option<String>,
+    /// The preview the output device failed to play, and its error.
+    device_error: Option<(Preview, String)>,
+    input_peaks: Option<waveform::Peaks>,
+    output_peaks: Option<waveform::Peaks>,
+    show_spectrograms: bool,
+    /// Input and output spectrograms of the finished run, once computed.
+    spectrograms: Option<Result<(image::Handle, image::Handle), String>>,
+    input_devices: Vec<devices::Device>,
+    input_device: devices::Device,
+    output_devices: Vec<devices::Device>,
+    output_device: devices::Device,
+    recorder: Option<recording::Recorder>,
+    /// Why the last recording failed.
+    recording_error: Option<String>,
+    appearance: settings::Appearance,
+    notifications: settings::Notifications,
+    after_cleaning: settings::AfterCleaning,
+    batches: settings::Batches,
+    updates: settings::Updates,
+    /// A newer version found at startup, until it's installed or dismissed.
+    app_update: Option<AppUpdate>,
+    tray_icon: settings::TrayIcon,
+    tray: Option<tray::Tray>,
+    /// The interface other programs control the app through, once it's up.
+    control: Option<control::Control>,
+    /// Files enqueued by other programs wait to be started.
+    control_paused: bool,
+    /// A file enqueued while paused is selected and waits to be started.
+    control_waiting: bool,
+    /// What the tray icon was last told to show.
+    tray_state: tray::State,
+    /// Files of the current batch finished so far.
+    batch_done: usize,
+    /// Inputs of the current batch, to spot copies among the files queued.
+    batch_seen: dedup::Seen,
+    /// Inputs left out of the current batch as copies, each with the file
+    /// it copies.
+    batch_duplicates: Vec<(PathBuf, PathBuf)>,
+    /// Copies left out of the last batch, listed once it's done until
+    /// dismissed.
+    skipped_duplicates: Vec<(PathBuf, PathBuf)>,
+    /// Batch progress the taskbar button was last told to show, in tenths
+    /// of a percent.
+    taskbar_progress: Option<u16>,
+    /// Files being dragged over the window.
+    drag_hover: Option<DragHover>,
+    /// Whether the window has focus, so finished runs needn't be notified.
+    window_focused: bool,
+    window_size: Size,
+    /// Size to restore when leaving the compact layout.
+    expanded_size: Option<Size>,
+    screen: Screen,
+    settings_screen: SettingsScreen,
+    /// Shared by all windows.
+    history: Option<Arc<Mutex<history::History>>>,
+    history_screen: HistoryScreen,
+    live_screen: LiveScreen,
+    logs_screen: LogsScreen,
+    /// The first-run walkthrough, while it's shown.
+    onboarding: Option<Onboarding>,
+    toasts: toast::Toasts,
+}
+
+/// Top-level screens, picked with the tab bar.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Screen {
+    #[default]
+    Clean,
+    /// Only offered in builds that can clean live audio.
+    Live,
+    History,
+    Logs,
+    Settings,
+}
+
+impl Screen {
+    const ALL: [Screen; 5] = [
+        Screen::Clean,
+        Screen::Live,
+        Screen::History,
+        Screen::Logs,
+        Screen::Settings,
+    ];
+
+    fn label(self) -> String {
+        match self {
+            Screen::Clean => tr!("tab-clean"),
+            Screen::Live => tr!("tab-live"),
+            Screen::History => tr!("tab-history"),
+            Screen::Logs => tr!("tab-logs"),
+            Screen::Settings => tr!("tab-settings"),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Default)]
+/// A newer version of the app, offered with its release notes.
+#[derive(Debug)]
+struct AppUpdate {
+    release: update::Release,
+    installing: bool,
+}
+
+/// What the app is doing. Each state carries the data only it has, so there
+/// is no run without its input and no progress without a download or run.
+#[derive(Debug)]
 enum Status {
+    /// Cleaning waits for the engine, and so does a file selected meanwhile.
+    Engine {
+        state: EngineState,
+        waiting: Option<PathBuf>,
+    },
+    /// The engine is in place, or the backend doesn't need it.
+    Job(JobState),
+}
+
+impl Default for Status {
+    fn default() -> Self {
+        Status::Engine {
+            state: EngineState::Checking,
+            waiting: None,
+        }
+    }
+}
+
+impl Status {
+    /// The file selected for cleaning, if any.
+    fn selected_file(&self) -> Option<&Path> {
+        match self {
+            Status::Engine { waiting, .. } => waiting.as_deref(),
+            Status::Job(
+                JobState::Ready(input)
+                | JobState::Processing(Run { input, .. })
+                | JobState::Done { input, .. }
+                | JobState::Adjusting { input, .. },
+            ) => Some(input),
+            Status::Job(JobState::Failed { input, .. }) => input.as_deref(),
+            Status::Job(JobState::Idle | JobState::Benchmarking(_) | JobState::Benchmarked(_)) => {
+                None
+            }
+        }
+    }
+
+    /// The error shown, if something failed.
+    fn error(&self) -> Option<&str> {
+        match self {
+            Status::Engine {
+                state: EngineState::Failed(error),
+                ..
+            }
+            | Status::Job(JobState::Failed { error, .. }) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// Stops the engine run or benchmark under way when set.
+    fn cancel(&self) -> Option<&cancel::CancelToken> {
+        match self {
+            Status::Job(JobState::Processing(Run { cancel, .. }))
+            | Status::Job(JobState::Benchmarking(Benchmark { cancel, .. })) => Some(cancel),
+            _ => None,
+        }
+    }
+}
+
+/// Getting the engine in place.
+#[derive(Debug, Default)]
+enum EngineState {
     #[default]
     Checking,
-    MissingBinary,
-    Downloading,
+    Missing,
+    Downloading {
+        id: download::DownloadId,
+        progress: f32,
+    },
+    /// The download failed with the error.
+    Failed(String),
+    /// The download failed and the internet can't be reached.
+    Offline,
+}
+
+/// What's done with the engine once it's in place.
+#[derive(Debug)]
+enum JobState {
+    /// No file is selected.
     Idle,
-    Ready,
-    Processing,
-    Done(PathBuf),
-    Error(String),
+    /// The file is selected and can be cleaned.
+    Ready(PathBuf),
+    Processing(Run),
+    Done {
+        input: PathBuf,
+        report: RunReport,
+        /// The options it was cleaned with.
+        options: Box<ProcessingOptions>,
+    },
+    /// The cleaned file waits to be cleaned again with adjusted options,
+    /// next to its earlier output at `previous`.
+    Adjusting {
+        input: PathBuf,
+        previous: PathBuf,
+    },
+    /// What failed, with the file it failed on if it can be retried.
+    Failed {
+        failure: Failure,
+        error: String,
+        input: Option<PathBuf>,
+    },
+    Benchmarking(Benchmark),
+    Benchmarked(benchmark::Summary),
+}
+
+/// A benchmark in progress.
+#[derive(Debug, Default)]
+struct Benchmark {
+    runs_done: usize,
+    /// Stops the benchmark when set.
+    cancel: cancel::CancelToken,
 }
 
 #[derive(Debug, Clone)]
 enum Message {
     BinaryCheckCompleted(Result<PathBuf, ()>),
-    StartDownload,
-    DownloadProgress(f32),
-    DownloadFinished(Result<PathBuf, String>),
-    SelectFile,
-    FileSelected(Option<PathBuf>),
+    EngineVersionRead(Result<String, String>),
+    Download(DownloadMessage),
+    Clean(CleanMessage),
     EventOccurred(iced::Event),
-    StartProcessing,
-    ProcessingFinished(Result<PathBuf, String>),
+    Processing(ProcessingMessage),
+    Queue(QueueMessage),
+    Archive(ArchiveMessage),
+    Express(ExpressMessage),
+    Options(OptionsMessage),
     OpenLocation(PathBuf),
+    /// Shows the file selected in the file manager.
+    RevealFile(PathBuf),
+    ScreenSelected(Screen),
+    /// Shrink the window to the compact layout, or restore it.
+    ToggleCompact,
+    /// Open another window with a selection of its own, e.g. to clean a
+    /// file while a batch runs here.
+    NewWindow,
+    /// The window was closed.
+    WindowClosed,
+    Settings(SettingsMessage),
+    Onboarding(OnboardingMessage),
+    History(HistoryMessage),
+    Live(LiveMessage),
+    Logs(LogsMessage),
+    Status(StatusMessage),
+    DismissToast(u64),
+    Tray(tray::Event),
+    Control(control::Event),
+    ExpireToasts(Instant),
+    /// Write the settings changed since the last save.
+    SaveSettings,
+    CloseRequested,
+    ConfirmExit,
+    CancelExit,
+    /// Stop the run, benchmark or engine download under way.
+    Cancel,
+    /// The latest release, if it's newer than this version.
+    UpdateChecked(Result<Option<update::Release>, String>),
+    /// Download the new version, put it in place and restart.
+    InstallUpdate,
+    UpdateInstalled(Result<(), String>),
+    DismissUpdate,
+    /// The release has an engine build for the graphics card found here.
+    AcceleratedEngineFound(Option<accel::Provider>),
+    /// Download the engine build on offer in place of the current one.
+    InstallAcceleratedEngine,
+    DeclineAcceleratedEngine,
+    OpenCrashReport,
+    /// Open a new GitHub issue with the crash report filled in.
+    ReportCrash,
+    DismissCrash,
+    ShutdownTick,
+    CancelShutdown,
+    ShutdownAsked(Result<(), String>),
+    /// Delete the partial outputs left by runs that never finished.
+    DeleteLeftovers,
+    /// Delete the partial outputs and queue their inputs again.
+    RerunLeftovers,
+    /// Keep the partial outputs as they are.
+    DismissLeftovers,
+    DismissDuplicates,
+    Player(PlayerMessage),
+    Devices(DevicesMessage),
+    Recording(RecordingMessage),
 }
 
 impl DfnGui {
-    fn init() -> (Self, Task<Message>) {
-        (
-            Self::default(),
+    /// State of `window`. The first window selects the first of `files`,
+    /// passed on the command line, and queues the others; the others are
+    /// opened from `opener`, and share its settings, history and downloads.
+    fn init(
+        window: window::Id,
+        files: Vec<PathBuf>,
+        opener: Option<&DfnGui>,
+    ) -> (Self, Task<Message>) {
+        let secondary = opener.is_some();
+        let settings = opener.map_or_else(settings::Settings::load, DfnGui::settings);
+        let (history, history_error) = match opener {
+            Some(opener) => (opener.history.clone(), opener.history_screen.error.clone()),
+            None => match history::History::open() {
+                Ok(history) => (Some(Arc::new(Mutex::new(history))), None),
+                Err(e) => (None, Some(e)),
+            },
+        };
+        let leftovers = match &history {
+            Some(history) if !secondary => {
+                partial::find(&history.lock().unwrap()).unwrap_or_else(|e| {
+                    tracing::warn!("{}", e);
+                    Vec::new()
+                })
+            }
+            _ => Vec::new(),
+        };
+        let onboarding = (!settings.onboarding_done && !secondary).then(Onboarding::default);
+        let downloads = opener.map_or_else(Arc::default, |opener| opener.downloads.clone());
+        let check_for_update = if settings.updates.check && updates_supported() && !secondary {
+            check_for_update(downloads.network(), settings.updates.channel)
+        } else {
+            Task::none()
+        };
+        let fetch_download_size = if onboarding.is_some() {
             Task::perform(
-                async { check_binary_exists().ok().ok_or(()) },
-                Message::BinaryCheckCompleted,
-            ),
+                engine_download_size(
+                    downloads.network(),
+                    settings.updates.channel,
+                    settings.engine_provider,
+                ),
+                |size| Message::Onboarding(OnboardingMessage::DownloadSizeFetched(size)),
+            )
+        } else {
+            Task::none()
+        };
+        let providers = accel::available();
+        let find_accelerated = match providers.first() {
+            Some(&provider)
+                if provider != accel::Provider::Cpu
+                    && settings.engine_provider == accel::Provider::Cpu
+                    && !settings.accelerated_declined
+                    && !secondary =>
+            {
+                Task::perform(
+                    find_accelerated_engine(
+                        downloads.network(),
+                        settings.updates.channel,
+                        provider,
+                    ),
+                    Message::AcceleratedEngineFound,
+                )
+            }
+            _ => Task::none(),
+        };
+        let mut gui = Self {
+            onboarding,
+            downloads,
+            options: settings.options.clone(),
+            appearance: settings.appearance.clone(),
+            notifications: settings.notifications.clone(),
+            after_cleaning: settings.after_cleaning.clone(),
+            batches: settings.batches.clone(),
+            updates: settings.updates.clone(),
+            tray_icon: settings.tray_icon.clone(),
+            engine_mirror: settings.engine_mirror.clone(),
+            engine_provider: settings.engine_provider,
+            providers,
+            accelerated_declined: settings.accelerated_declined,
+            webhook: settings.webhook.clone(),
+            log_verbosity: settings.log_verbosity,
+            crash_report: crash::pending().filter(|_| !secondary),
+            leftovers,
+            secondary,
+            window: Some(window),
+            shell_menu_installed: shell_menu::is_installed(),
+            plugin_installed: denoiser::InProcess::is_installed(),
+            window_focused: true,
+            window_size: WINDOW_SIZE,
+            settings_screen: SettingsScreen {
+                saved: settings,
+                ..SettingsScreen::default()
+            },
+            history,
+            history_screen: HistoryScreen {
+                error: history_error,
+                ..HistoryScreen::default()
+            },
+            ..Self::default()
+        };
+        let mut files = files.into_iter();
+        let selected = files.next();
+        for file in files {
+            gui.queue_file(file, gui.options.clone());
+        }
+        (
+            gui,
+            Task::batch([
+                Task::perform(
+                    async { download::installed_binary().ok().ok_or(()) },
+                    Message::BinaryCheckCompleted,
+                ),
+                Task::done(Message::Devices(DevicesMessage::Refresh)),
+                fetch_download_size,
+                check_for_update,
+                find_accelerated,
+                selected.map_or(Task::none(), |file| {
+                    Task::done(Message::Clean(CleanMessage::FileSelected(Some(file))))
+                }),
+            ]),
         )
     }
 
     fn title(&self) -> String {
-        String::from("DeepFilterNet GUI")
+        tr!("app-title")
     }
 
     fn update(&mut self, message: Message) -> Task<Message> {
-        match message {
-            Message::BinaryCheckCompleted(Ok(_)) => {
-                self.status = Status::Idle;
+        let task = self.handle_message(message);
+        self.sync_sleep_inhibitor();
+        self.sync_control();
+        Task::batch([
+            task,
+            self.schedule_settings_save(),
+            self.sync_tray(),
+            self.sync_taskbar(),
+        ])
+    }
+
+    /// Shows the current progress in the tray icon.
+    fn sync_tray(&mut self) -> Task<Message> {
+        let state = match &self.status {
+            Status::Job(JobState::Processing(run)) => tray::State::Busy(run.progress.round()),
+            _ => tray::State::Idle,
+        };
+        match &self.tray {
+            Some(tray) if state != self.tray_state => {
+                self.tray_state = state;
+                Task::future(tray.set_state(state)).discard()
             }
-            Message::BinaryCheckCompleted(Err(_)) => {
-                self.status = Status::MissingBinary;
+            _ => Task::none(),
+        }
+    }
+
+    /// Shows the batch progress on the taskbar button, and draws attention
+    /// to the window when a batch finishes in the background.
+    fn sync_taskbar(&mut self) -> Task<Message> {
+        let progress = match &self.status {
+            Status::Job(JobState::Processing(run)) => {
+                let total = self.batch_done + 1 + self.workers.len() + self.queue.len();
+                let running: f32 = std::iter::once(run)
+                    .chain(self.workers.values())
+                    .map(|run| run.progress / 100.0)
+                    .sum();
+                Some((self.batch_done as f32 + running) / total as f32)
+            }
+            _ => None,
+        };
+        let shown = progress.map(|progress| (progress * 1000.0).round() as u16);
+        let Some(window) = self.window.filter(|_| !self.secondary) else {
+            return Task::none();
+        };
+        if shown == self.taskbar_progress {
+            return Task::none();
+        }
+        let finished = self.taskbar_progress.is_some() && shown.is_none();
+        self.taskbar_progress = shown;
+        if finished && !self.window_focused {
+            return Task::batch([taskbar::show(window, progress), taskbar::finished(window)]);
+        }
+        taskbar::show(window, progress)
+    }
+
+    /// Keeps what other programs see through the control interface current.
+    fn sync_control(&self) {
+        let Some(control) = &self.control else {
+            return;
+        };
+        let progress = match &self.status {
+            Status::Job(JobState::Processing(run)) => Some(run.progress),
+            _ => None,
+        };
+        let state = if progress.is_some() {
+            control::State::Processing
+        } else if !self.accepts_drops() {
+            control::State::Unavailable
+        } else if self.control_paused {
+            control::State::Paused
+        } else {
+            control::State::Idle
+        };
+        control.set_status(control::Status {
+            state,
+            file: self.status.selected_file().map(Path::to_path_buf),
+            progress: progress.unwrap_or_default(),
+        });
+    }
+
+    /// Closes the window, writing settings whose save is still due. The app
+    /// exits with its last window.
+    fn exit(&mut self) -> Task<Message> {
+        self.save_settings();
+        self.window.map_or_else(iced::exit, window::close)
+    }
+
+    /// Does what was picked for when a batch is done. `output` is the last
+    /// file written, if the last run succeeded.
+    fn finish_batch(&mut self, output: Option<&Path>) {
+        let files = self.batch_done;
+        self.skipped_duplicates = std::mem::take(&mut self.batch_duplicates);
+        match self.after_cleaning.batch_action {
+            completion::Action::Nothing => {}
+            completion::Action::Sound => completion::play_sound(),
+            completion::Action::Notification => notification::show(
+                tr!("notification-batch-done"),
+                tr!("notification-batch-done-body", count = files),
+                self.notifications.sound,
+            ),
+            completion::Action::OpenFolder => {
+                if let Some(output) = output {
+                    reveal(output);
+                }
             }
-            Message::StartDownload => {
-                self.status = Status::Downloading;
-                self.download_progress = 0.0;
+            completion::Action::Command => {
+                let command = self.after_cleaning.batch_command.trim();
+                if !command.is_empty()
+                    && let Err(e) = completion::run_command(command, files, output)
+                {
+                    tracing::error!("{}", e);
+                    self.toasts.push(toast::Severity::Error, e);
+                }
             }
-            Message::DownloadProgress(progress) => {
-                self.download_progress = progress;
+            completion::Action::ShutDown => {
+                self.shutdown_countdown = Some(completion::SHUTDOWN_DELAY);
             }
-            Message::DownloadFinished(Ok(_)) => {
-                self.status = Status::Idle;
+        }
+        self.reset_batch();
+    }
+
+    /// Counts the files cleaned next afresh, as the batch is over.
+    fn reset_batch(&mut self) {
+        self.batch_done = 0;
+        self.batch_seen = dedup::Seen::default();
+        self.batch_duplicates.clear();
+    }
+
+    /// Whether files can be cleaned with the selected backend.
+    fn engine_ready(&self) -> bool {
+        match self.options.backend {
+            denoiser::Backend::ExternalBinary => self.engine_installed,
+            denoiser::Backend::InProcess => self.plugin_installed,
+            denoiser::Backend::Remote => true,
+        }
+    }
+
+    /// Whether dropped files are opened, or queued while another is
+    /// cleaned. They aren't while the binary is missing or busy with
+    /// something else.
+    fn accepts_drops(&self) -> bool {
+        match &self.status {
+            Status::Engine { state, .. } => {
+                matches!(state, EngineState::Failed(_) | EngineState::Offline)
             }
-            Message::DownloadFinished(Err(e)) => {
-                self.status = Status::Error(format!("Download failed: {}", e));
+            Status::Job(JobState::Processing(_)) => true,
+            Status::Job(_) => !self.is_running_engine(),
+        }
+    }
+
+    /// Whether an engine child process may currently be running.
+    fn is_running_engine(&self) -> bool {
+        self.status.cancel().is_some()
+    }
+
+    /// Whether a run, a benchmark or a recording can be started.
+    fn is_idle(&self) -> bool {
+        matches!(self.status, Status::Job(_)) && !self.is_running_engine()
+    }
+
+    /// Whether the engine can be downloaded again: it isn't running, nor
+    /// being checked or downloaded already.
+    fn engine_replaceable(&self) -> bool {
+        !self.is_running_engine()
+            && self.express.is_none()
+            && self.workers.is_empty()
+            && !matches!(
+                self.status,
+                Status::Engine {
+                    state: EngineState::Checking | EngineState::Downloading { .. },
+                    ..
+                }
+            )
+    }
+
+    /// Whether a download or processing run is in progress.
+    fn is_busy(&self) -> bool {
+        self.express.is_some()
+            || !self.workers.is_empty()
+            || matches!(
+                self.status,
+                Status::Engine {
+                    state: EngineState::Downloading { .. },
+                    ..
+                } | Status::Job(JobState::Processing(_))
+            )
+    }
+
+    /// What quitting now would interrupt, as shown when asking to confirm.
+    fn pending_work(&self) -> Vec<String> {
+        let mut work = Vec::new();
+        match &self.status {
+            Status::Engine {
+                state: EngineState::Downloading { progress, .. },
+                ..
+            } => work.push(tr!(
+                "exit-item-download",
+                percent = format!("{:.0}", progress)
+            )),
+            Status::Job(JobState::Processing(run)) => {
+                let name = run.input.file_name().unwrap_or_default().to_string_lossy();
+                work.push(tr!(
+                    "exit-item-processing",
+                    name = name,
+                    percent = format!("{:.0}", run.progress)
+                ));
             }
-            Message::SelectFile => {
-                return Task::perform(
-                    async {
-                        FileDialog::new()
-                            .add_filter("WAV audio", &["wav"])
-                            .pick_file()
-                    },
-                    Message::FileSelected,
-                );
+            Status::Job(JobState::Benchmarking(_)) => work.push(tr!("exit-item-benchmark")),
+            _ => {}
+        }
+        for run in self.express.iter().chain(self.workers.values()) {
+            let name = run.input.file_name().unwrap_or_default().to_string_lossy();
+            work.push(tr!(
+                "exit-item-processing",
+                name = name,
+                percent = format!("{:.0}", run.progress)
+            ));
+        }
+        if self.recorder.is_some() {
+            work.push(tr!("exit-item-recording"));
+        }
+        if let Some(schedule) = &self.schedule {
+            work.push(tr!("exit-item-schedule", time = schedule.time.as_str()));
+        }
+        work
+    }
+
+    /// Keeps the machine awake exactly while a download or processing run is
+    /// in progress, or until a scheduled start that asked for it.
+    fn sync_sleep_inhibitor(&mut self) {
+        let waiting = self
+            .schedule
+            .as_ref()
+            .is_some_and(|schedule| schedule.keep_awake);
+        if !self.is_busy() && !waiting {
+            self.sleep_inhibitor = None;
+        } else if self.sleep_inhibitor.is_none() {
+            self.sleep_inhibitor = power::SleepInhibitor::acquire(&tr!("sleep-reason"));
+        }
+    }
+
+    fn handle_message(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::BinaryCheckCompleted(Ok(bin_path)) => {
+                self.engine_in_place();
+                return read_engine_version(bin_path);
             }
-            Message::FileSelected(file) => {
-                if let Some(path) = file {
-                    self.selected_file = Some(path);
-                    self.status = Status::Ready;
+            Message::BinaryCheckCompleted(Err(_)) => {
+                self.engine_installed = false;
+                let ready = self.engine_ready();
+                match &mut self.status {
+                    // The plugin cleans files without the binary.
+                    Status::Engine { waiting, .. } if ready => {
+                        self.status =
+                            Status::Job(waiting.take().map_or(JobState::Idle, JobState::Ready));
+                    }
+                    Status::Engine { state, .. } => *state = EngineState::Missing,
+                    Status::Job(_) => {}
                 }
             }
+            Message::EngineVersionRead(version) => {
+                self.engine_version = version.ok();
+            }
             Message::EventOccurred(event) => {
-                // Prevent drag-and-drop if binary is missing
-                if matches!(
-                    self.status,
-                    Status::Checking | Status::MissingBinary | Status::Downloading
-                ) {
+                match &event {
+                    iced::Event::Window(window::Event::FileHovered(path)) => {
+                        if !formats::is_supported(path) {
+                            self.drag_hover = Some(DragHover::Unsupported);
+                        } else if self.drag_hover.is_none() {
+                            self.drag_hover = Some(DragHover::Supported);
+                        }
+                    }
+                    iced::Event::Window(
+                        window::Event::FilesHoveredLeft | window::Event::FileDropped(_),
+                    ) => self.drag_hover = None,
+                    iced::Event::Window(
+                        window::Event::Opened { size, .. } | window::Event::Resized(size),
+                    ) => self.window_size = *size,
+                    iced::Event::Window(window::Event::Focused) => self.window_focused = true,
+                    iced::Event::Window(window::Event::Unfocused) => {
+                        self.window_focused = false;
+                        if self.tray.is_some() && self.tray_icon.minimize_to_tray {
+                            return self.window.map_or_else(Task::none, hide_if_minimized);
+                        }
+                    }
+                    _ => {}
+                }
+                if let iced::Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                    key: iced::keyboard::Key::Character(key),
+                    modifiers,
+                    ..
+                }) = &event
+                    && modifiers.command()
+                    && key.as_str() == "n"
+                {
+                    return Task::done(Message::NewWindow);
+                }
+                if let iced::Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                    key: iced::keyboard::Key::Character(key),
+                    ..
+                }) = &event
+                    && key.as_str() == "a"
+                {
+                    return self.update_player(PlayerMessage::SwitchComparison);
+                }
+
+                if !self.accepts_drops() {
                     return Task::none();
                 }
 
                 if let iced::Event::Window(window::Event::FileDropped(path)) = event {
-                    if path.extension().map(|s| s == "wav").unwrap_or(false) {
-                        self.selected_file = Some(path);
-                        self.status = Status::Ready;
+                    if formats::is_supported(&path) {
+                        return self.take_file(path);
                     } else {
-                        self.status = Status::Error("Only .wav files are supported".to_string());
+                        self.toasts.push(
+                            toast::Severity::Warning,
+                            tr!(
+                                "error-unsupported-file",
+                                formats = formats::supported_extensions().join(", ")
+                            ),
+                        );
                     }
                 }
             }
-            Message::StartProcessing => {
-                if let Some(input_path) = &self.selected_file {
-                    if let Ok(bin_path) = check_binary_exists() {
-                        self.status = Status::Processing;
-                        let path = input_path.clone();
-                        return Task::perform(
-                            async move {
-                                tokio::task::spawn_blocking(move || {
-                                    run_deep_filter(&path, &bin_path)
-                                })
-                                .await
-                                .unwrap_or_else(|e| Err(format!("Task join error: {}", e)))
-                            },
-                            Message::ProcessingFinished,
-                        );
-                    } else {
-                        self.status = Status::Error("Binary missing during processing".to_string());
+            Message::OpenLocation(path) => open_externally(path.as_os_str()),
+            Message::RevealFile(path) => reveal(&path),
+            Message::ScreenSelected(screen) => {
+                self.screen = screen;
+                match screen {
+                    Screen::Live => return self.update_live(LiveMessage::Opened),
+                    Screen::History => self.refresh_history(),
+                    Screen::Logs => return self.update_logs(LogsMessage::Refresh),
+                    _ => {}
+                }
+            }
+            // Handled by the app, which holds the windows.
+            Message::NewWindow | Message::WindowClosed => {}
+            Message::ToggleCompact => {
+                let size = if self.is_compact() {
+                    self.expanded_size.take().unwrap_or(WINDOW_SIZE)
+                } else {
+                    self.expanded_size = Some(self.window_size);
+                    COMPACT_WINDOW_SIZE * self.appearance.scale
+                };
+                return self
+                    .window
+                    .map_or_else(Task::none, |id| window::resize(id, size));
+            }
+            Message::Download(message) => return self.update_download(message),
+            Message::Processing(message) => return self.update_processing(message),
+            Message::Queue(message) => return self.update_queue(message),
+            Message::Archive(message) => return self.update_archive(message),
+            Message::Express(message) => return self.update_express(message),
+            Message::Options(message) => self.update_options(message),
+            Message::Player(message) => return self.update_player(message),
+            Message::Settings(message) => return self.update_settings(message),
+            Message::Onboarding(message) => return self.update_onboarding(message),
+            Message::History(message) => return self.update_history(message),
+            Message::Live(message) => return self.update_live(message),
+            Message::Clean(message) => return self.update_clean(message),
+            Message::Status(message) => return self.update_status(message),
+            Message::Recording(message) => return self.update_recording(message),
+            Message::Devices(message) => return self.update_devices(message),
+            Message::Logs(message) => return self.update_logs(message),
+            Message::UpdateChecked(Ok(release)) => {
+                if let Some(release) = &release {
+                    tracing::info!("Version {} is available", release.version);
+                }
+                self.app_update = release.map(|release| AppUpdate {
+                    release,
+                    installing: false,
+                });
+            }
+            Message::UpdateChecked(Err(e)) => {
+                tracing::warn!("Could not check for updates: {}", e);
+            }
+            Message::InstallUpdate => {
+                if let Some(app_update) = &mut self.app_update {
+                    app_update.installing = true;
+                    tracing::info!("Updating to {}", app_update.release.version);
+                    return Task::perform(
+                        install_update(self.downloads.network(), app_update.release.clone()),
+                        Message::UpdateInstalled,
+                    );
+                }
+            }
+            Message::UpdateInstalled(Ok(())) => {
+                tracing::info!("Updated, restarting");
+                RELAUNCH.store(true, Ordering::Relaxed);
+                // Every window goes, to start again on the new version.
+                self.save_settings();
+                return iced::exit();
+            }
+            Message::UpdateInstalled(Err(e)) => {
+                tracing::error!("Could not update: {}", e);
+                if let Some(app_update) = &mut self.app_update {
+                    app_update.installing = false;
+                }
+                self.toasts.push(
+                    toast::Severity::Error,
+                    tr!("toast-update-failed", error = e),
+                );
+            }
+            Message::DismissUpdate => {
+                self.app_update = None;
+            }
+            Message::AcceleratedEngineFound(provider) => {
+                if let Some(provider) = provider {
+                    tracing::info!("An engine build for {} is available", provider);
+                }
+                self.accelerated_offer = provider;
+            }
+            Message::InstallAcceleratedEngine => {
+                if let Some(provider) = self.accelerated_offer.take() {
+                    self.engine_provider = provider;
+                    return Task::done(Message::Download(DownloadMessage::Start));
+                }
+            }
+            Message::DeclineAcceleratedEngine => {
+                self.accelerated_offer = None;
+                self.accelerated_declined = true;
+            }
+            Message::OpenCrashReport => {
+                if let Some(path) = &self.crash_report {
+                    open_externally(path.as_os_str());
+                }
+            }
+            Message::ReportCrash => {
+                if let Some(path) = &self.crash_report {
+                    match std::fs::read_to_string(path) {
+                        Ok(report) => {
+                            open_externally(report::crash_issue_url(&report).as_ref());
+                        }
+                        Err(e) => self.toasts.push(
+                            toast::Severity::Error,
+                            tr!("toast-crash-report-unreadable", error = e),
+                        ),
                     }
                 }
             }
-            Message::ProcessingFinished(result) => match result {
-                Ok(path) => {
-                    self.status = Status::Done(path);
+            Message::DismissCrash => {
+                crash::dismiss();
+                self.crash_report = None;
+            }
+            Message::ShutdownTick => match self.shutdown_countdown {
+                Some(0) => {
+                    self.shutdown_countdown = None;
+                    tracing::info!("Shutting down now that the batch is done");
+                    return Task::perform(
+                        async { completion::shut_down() },
+                        Message::ShutdownAsked,
+                    );
                 }
-                Err(e) => self.status = Status::Error(e),
+                Some(seconds) => self.shutdown_countdown = Some(seconds - 1),
+                None => {}
             },
-            Message::OpenLocation(path) => {
-                let folder = path.as_path();
-                #[cfg(target_os = "linux")]
-                let _ = std::process::Command::new("xdg-open").arg(folder).spawn();
-                #[cfg(target_os = "windows")]
-                let _ = std::process::Command::new("explorer").arg(folder).spawn();
-                #[cfg(target_os = "macos")]
-                let _ = std::process::Command::new("open").arg(folder).spawn();
+            Message::CancelShutdown => {
+                self.shutdown_countdown = None;
+            }
+            Message::ShutdownAsked(Err(e)) => {
+                tracing::error!("{}", e);
+                self.toasts.push(toast::Severity::Error, e);
+            }
+            Message::ShutdownAsked(Ok(())) => {}
+            Message::DeleteLeftovers | Message::RerunLeftovers => {
+                let rerun = matches!(message, Message::RerunLeftovers);
+                let mut deleted = 0;
+                for run in std::mem::take(&mut self.leftovers) {
+                    match std::fs::remove_file(&run.output_path) {
+                        Ok(()) => deleted += 1,
+                        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                            tracing::warn!("Could not delete {}: {}", run.output_path.display(), e);
+                        }
+                        Err(_) => {}
+                    }
+                    self.forget_unfinished(run.id);
+                    if rerun {
+                        let mut options = run.options;
+                        // The token isn't kept in the history.
+                        options.server = self.options.server.clone();
+                        self.queue
+                            .push(run.input_path, options, queue::Priority::default());
+                    }
+                }
+                self.toasts.push(
+                    toast::Severity::Success,
+                    tr!("toast-leftovers-deleted", count = deleted),
+                );
+                if rerun && self.is_idle() {
+                    return self.start_queued();
+                }
+            }
+            Message::DismissLeftovers => {
+                for run in std::mem::take(&mut self.leftovers) {
+                    self.forget_unfinished(run.id);
+                }
+            }
+            Message::DismissDuplicates => self.skipped_duplicates.clear(),
+            Message::Tray(tray::Event::Started(tray)) => {
+                self.tray = Some(tray);
+                self.tray_state = tray::State::Idle;
+            }
+            Message::Tray(tray::Event::Failed(e)) => {
+                self.toasts.push(toast::Severity::Warning, e);
+            }
+            Message::Tray(tray::Event::ShowWindow) => {
+                return self.window.map_or_else(Task::none, show_window);
+            }
+            Message::Tray(tray::Event::Quit) => {
+                return Task::batch([
+                    self.window.map_or_else(Task::none, show_window),
+                    self.handle_message(Message::CloseRequested),
+                ]);
+            }
+            Message::Control(control::Event::Started(control)) => {
+                self.control = Some(control);
+            }
+            Message::Control(control::Event::Failed(e)) => {
+                tracing::warn!("{}", e);
+            }
+            Message::Control(control::Event::Command(command)) => {
+                return self.handle_control(command);
+            }
+            Message::DismissToast(id) => {
+                self.toasts.dismiss(id);
+            }
+            Message::ExpireToasts(now) => {
+                self.toasts.expire(now);
+            }
+            Message::SaveSettings => self.save_settings(),
+            Message::CloseRequested => {
+                if self.pending_work().is_empty() {
+                    return self.exit();
+                }
+                self.confirm_exit = true;
+            }
+            Message::ConfirmExit => {
+                self.confirm_exit = false;
+                if let Some(recorder) = self.recorder.take() {
+                    let _ = recorder.stop();
+                }
+                if let Status::Engine {
+                    state: EngineState::Downloading { .. },
+                    ..
+                } = self.status
+                {
+                    download::discard_partial();
+                }
+                if let Some(cancel) = self.status.cancel() {
+                    for run in self.workers.values() {
+                        run.cancel.cancel();
+                    }
+                    // Exit once the run reports back, so the engine is
+                    // killed rather than left running orphaned.
+                    cancel.cancel();
+                    self.exit_when_idle = true;
+                } else {
+                    return self.exit();
+                }
+            }
+            Message::CancelExit => {
+                self.confirm_exit = false;
+            }
+            Message::Cancel => {
+                if let Some(cancel) = self.status.cancel() {
+                    // The files cleaned next to it are part of the batch.
+                    for run in self.workers.values() {
+                        run.cancel.cancel();
+                    }
+                    cancel.cancel();
+                } else if let Status::Engine {
+                    state: EngineState::Downloading { id, .. },
+                    ..
+                } = &self.status
+                {
+                    self.downloads.cancel(*id);
+                }
             }
         }
         Task::none()
     }
 
+    /// Acts on a command from another program. An enqueued file waits for
+    /// the one being cleaned, or is selected and started like a dropped one.
+    fn handle_control(&mut self, command: control::Command) -> Task<Message> {
+        match command {
+            control::Command::Enqueue(path) => {
+                // The app may have got busy since the command was accepted.
+                if !self.accepts_drops() {
+                    tracing::warn!("Could not take {}, the app is busy", path.display());
+                    return Task::none();
+                }
+                tracing::info!("{} was enqueued by another program", path.display());
+                if matches!(self.status, Status::Job(JobState::Processing(_))) {
+                    self.queue_file(path, self.options.clone());
+                    return Task::none();
+                }
+                let select = self.select_file(path);
+                if self.control_paused {
+                    self.control_waiting = true;
+                    return select;
+                }
+                self.control_waiting = false;
+                Task::batch([
+                    select,
+                    Task::done(Message::Processing(ProcessingMessage::Start)),
+                ])
+            }
+            control::Command::Pause => {
+                self.control_paused = true;
+                Task::none()
+            }
+            control::Command::Resume => {
+                self.control_paused = false;
+                if std::mem::take(&mut self.control_waiting)
+                    && matches!(self.status, Status::Job(JobState::Ready(_)))
+                {
+                    return self.update_processing(ProcessingMessage::Start);
+                }
+                self.update_queue(QueueMessage::Start)
+            }
+        }
+    }
+
     fn view(&self) -> Element<'_, Message> {
-        let content = column![
-            text("DeepFilterNet Noise Cancellation").size(30),
-            self.view_main_area(),
-            self.view_status(),
-        ]
-        .spacing(20)
-        .max_width(600)
-        .align_x(Alignment::Center);
+        if self.confirm_exit || self.exit_when_idle {
+            return container(self.view_exit_prompt())
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .center_x(Length::Fill)
+                .center_y(Length::Fill)
+                .into();
+        }
 
-        container(content)
+        if let Some(seconds) = self.shutdown_countdown {
+            return container(
+                column![
+                    text(tr!("shutdown-countdown", seconds = seconds)).size(20),
+                    button(text(tr!("shutdown-cancel"))).on_press(Message::CancelShutdown),
+                ]
+                .spacing(15)
+                .align_x(Alignment::Center),
+            )
             .width(Length::Fill)
             .height(Length::Fill)
             .center_x(Length::Fill)
             .center_y(Length::Fill)
-            .into()
-    }
+            .into();
+        }
 
-    fn view_main_area(&self) -> Element<'_, Message> {
-        match self.status {
-            Status::Checking => text("Checking resources...").into(),
-            Status::MissingBinary => button("Download Engine (Required)")
-                .on_press(Message::StartDownload)
-                .padding(20)
-                .into(),
-            Status::Downloading => column![
-                text(format!("Downloading... {:.0}%", self.download_progress)),
-                progress_bar(0.0..=100.0, self.download_progress),
+        if let Some(path) = &self.crash_report {
+            return self.toasts.view(
+                container(view_crash_prompt(path))
+                    .padding(20)
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .center_x(Length::Fill)
+                    .center_y(Length::Fill)
+                    .into(),
+                Message::DismissToast,
+            );
+        }
+
+        if let Some(onboarding) = &self.onboarding {
+            return self.toasts.view(
+                container(self.view_onboarding(onboarding))
+                    .padding(20)
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .center_x(Length::Fill)
+                    .center_y(Length::Fill)
+                    .into(),
+                Message::DismissToast,
+            );
+        }
+
+        if self.is_compact() {
+            return self.toasts.view(
+                container(self.view_compact())
+                    .padding(10)
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .into(),
+                Message::DismissToast,
+            );
+        }
+
+        let tabs = Screen::ALL
+            .into_iter()
+            .filter(|&screen| screen != Screen::Live || live::AVAILABLE)
+            .fold(row![].spacing(5), |tabs, screen| {
+                tabs.push(
+                    button(text(screen.label()).size(14))
+                        .on_press(Message::ScreenSelected(screen))
+                        .style(if screen == self.screen {
+                            button::primary
+                        } else {
+                            button::text
+                        }),
+                )
+            })
+            .push(iced::widget::space::horizontal())
+            .push(
+                button(text(tr!("new-window")).size(14))
+                    .on_press(Message::NewWindow)
+                    .style(button::text),
+            )
+            .push(
+                button(text(tr!("compact-enter")).size(14))
+                    .on_press(Message::ToggleCompact)
+                    .style(button::text),
+            );
+        let screen = match self.screen {
+            Screen::Clean => self.view_clean(),
+            Screen::Live => self.view_live(),
+            Screen::History => self.view_history(),
+            Screen::Logs => self.view_logs(),
+            Screen::Settings => column![
+                self.view_settings().map(Message::Settings),
+                view_report_actions()
             ]
             .spacing(10)
-            .align_x(Alignment::Center)
             .into(),
-            _ => container(
-                column![
-                    text(match &self.selected_file {
-                        Some(path) =>
-                            format!("File: {}", path.file_name().unwrap().to_string_lossy()),
-                        None => String::from("Drag and drop a .wav file here or click to select"),
-                    }),
-                    button("Select WAV File").on_press(Message::SelectFile),
+        };
+        let content = column![text(tr!("app-heading")).size(30)]
+            .push(
+                self.app_update
+                    .as_ref()
+                    .map(|app_update| self.view_app_update(app_update)),
+            )
+            .push(
+                self.accelerated_offer
+                    .map(|provider| self.view_accelerated_offer(provider)),
+            )
+            .push((!self.leftovers.is_empty()).then(|| self.view_leftovers()))
+            .push((!self.skipped_duplicates.is_empty()).then(|| self.view_skipped_duplicates()))
+            .push(tabs)
+            .push(screen)
+            .spacing(20)
+            .max_width(600)
+            .align_x(Alignment::Center);
+
+        let window = column![
+            container(content)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .center_x(Length::Fill)
+                .center_y(Length::Fill),
+            self.view_status_bar(),
+        ];
+        let log = self.status.error().filter(|_| self.show_full_log);
+        self.toasts.view(
+            match log {
+                Some(e) => stack![window, opaque(view_full_log(e))].into(),
+                None => window.into(),
+            },
+            Message::DismissToast,
+        )
+    }
+
+    /// The new version on offer, with its release notes.
+    fn view_app_update<'a>(&'a self, app_update: &'a AppUpdate) -> Element<'a, Message> {
+        let busy = self.is_busy();
+        let install = if app_update.installing {
+            button(text(tr!("update-installing")).size(12))
+        } else {
+            button(text(tr!("update-install")).size(12))
+                .on_press_maybe((!busy).then_some(Message::InstallUpdate))
+        };
+        let release = &app_update.release;
+        let version = release.version.as_str();
+        let heading = if release.prerelease {
+            tr!("update-available-prerelease", version = version)
+        } else if update::is_newer(version, env!("CARGO_PKG_VERSION")) {
+            tr!("update-available", version = version)
+        } else {
+            tr!("update-back-to-stable", version = version)
+        };
+        let notes = release.notes.trim();
+        column![text(heading)]
+            .push((!notes.is_empty()).then(|| {
+                container(scrollable(text(notes).size(11)).width(Length::Fill))
+                    .max_height(120)
+                    .padding(5)
+                    .style(container::rounded_box)
+            }))
+            .push(
+                row![
+                    install,
+                    button(text(tr!("update-dismiss")).size(12))
+                        .on_press_maybe((!app_update.installing).then_some(Message::DismissUpdate))
+                        .style(button::text),
                 ]
-                .spacing(10)
-                .align_x(Alignment::Center),
+                .spacing(10),
             )
-            .padding(40)
-            .width(Length::Fill)
-            .style(|_theme: &Theme| container::Style {
-                border: iced::Border {
-                    color: iced::Color::from_rgb(0.3, 0.3, 0.3),
-                    width: 2.0,
-                    radius: 10.0.into(),
-                },
-                ..Default::default()
-            })
-            .into(),
-        }
+            .spacing(8)
+            .into()
     }
 
-    fn view_status(&self) -> Element<'_, Message> {
-        match &self.status {
-            Status::Checking | Status::MissingBinary | Status::Downloading => text("").into(),
-            Status::Idle => text("Ready.").into(),
-            Status::Ready => button("Clean Audio")
-                .on_press(Message::StartProcessing)
-                .padding(10)
-                .into(),
-            Status::Processing => {
-                column![text("Cleaning audio..."), progress_bar(0.0..=100.0, 50.0),]
-                    .spacing(10)
-                    .align_x(Alignment::Center)
-                    .into()
-            }
-            Status::Done(path) => column![
-                text("Finished!").color(iced::Color::from_rgb(0.0, 1.0, 0.0)),
-                text(format!("Saved to: {}", path.display())).size(12),
-                button("Open File Location")
-                    .on_press(Message::OpenLocation(path.parent().unwrap().to_path_buf())),
+    /// The engine build for the graphics card found here, on offer.
+    fn view_accelerated_offer(&self, provider: accel::Provider) -> Element<'_, Message> {
+        column![
+            text(tr!("accelerated-available", provider = provider)),
+            row![
+                button(text(tr!("accelerated-install")).size(12)).on_press_maybe(
+                    self.engine_replaceable()
+                        .then_some(Message::InstallAcceleratedEngine)
+                ),
+                button(text(tr!("update-dismiss")).size(12))
+                    .on_press(Message::DeclineAcceleratedEngine)
+                    .style(button::text),
             ]
-            .spacing(10)
-            .align_x(Alignment::Center)
+            .spacing(10),
+        ]
+        .spacing(8)
+        .into()
+    }
+
+    fn view_leftovers(&self) -> Element<'_, Message> {
+        let files = self
+            .leftovers
+            .iter()
+            .map(|run| text(run.output_path.display().to_string()).size(12).into());
+        column![
+            text(tr!("leftovers-found", count = self.leftovers.len())),
+            column(files).spacing(2),
+            row![
+                button(text(tr!("leftovers-rerun")).size(12)).on_press(Message::RerunLeftovers),
+                button(text(tr!("leftovers-delete")).size(12)).on_press(Message::DeleteLeftovers),
+                button(text(tr!("update-dismiss")).size(12))
+                    .on_press(Message::DismissLeftovers)
+                    .style(button::text),
+            ]
+            .spacing(10),
+        ]
+        .spacing(8)
+        .into()
+    }
+
+    /// The copies the last batch left out, each with the file it copies.
+    fn view_skipped_duplicates(&self) -> Element<'_, Message> {
+        let files = self.skipped_duplicates.iter().map(|(input, original)| {
+            text(tr!(
+                "duplicates-copy-of",
+                input = input.display(),
+                original = original.display()
+            ))
+            .size(12)
+            .into()
+        });
+        column![
+            text(tr!(
+                "duplicates-skipped",
+                count = self.skipped_duplicates.len()
+            )),
+            column(files).spacing(2),
+            button(text(tr!("update-dismiss")).size(12))
+                .on_press(Message::DismissDuplicates)
+                .style(button::text),
+        ]
+        .spacing(8)
+        .into()
+    }
+
+    /// Just a drop zone, the progress of the current download or run and
+    /// the session's counts, for a small window.
+    fn view_compact(&self) -> Element<'_, Message> {
+        let hover = self.drag_hover.filter(|_| self.accepts_drops());
+        let drop_zone = container(
+            column![
+                text(self.drop_zone_label(hover)).size(12),
+                button(text(tr!("file-select")).size(12)).on_press_maybe(
+                    self.accepts_drops()
+                        .then_some(Message::Clean(CleanMessage::SelectFile))
+                ),
+            ]
+            .spacing(8)
+            .align_x(Alignment::Center),
+        )
+        .padding(10)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x(Length::Fill)
+        .center_y(Length::Fill)
+        .style(move |theme: &Theme| drop_zone_style(theme, hover));
+
+        let progress: Element<'_, Message> = match &self.status {
+            Status::Engine {
+                state: EngineState::Downloading { progress, .. },
+                ..
+            } => column![
+                text(tr!(
+                    "download-progress",
+                    percent = format!("{:.0}", progress)
+                ))
+                .size(12),
+                progress_bar(0.0..=100.0, *progress).girth(8),
+            ]
+            .spacing(4)
             .into(),
-            Status::Error(e) => column![
-                text(format!("Error: {}", e)).color(iced::Color::from_rgb(1.0, 0.0, 0.0)),
-                button("Retry").on_press(Message::SelectFile),
+            Status::Job(JobState::Processing(run)) => column![
+                text(tr!(
+                    "status-processing",
+                    phase = run.phase.label(),
+                    percent = format!("{:.0}", run.progress)
+                ))
+                .size(12),
+                progress_bar(0.0..=100.0, run.progress).girth(8),
             ]
-            .spacing(10)
-            .align_x(Alignment::Center)
+            .spacing(4)
             .into(),
+            Status::Job(JobState::Ready(_)) => button(text(tr!("status-clean")).size(12))
+                .on_press(Message::Processing(ProcessingMessage::Start))
+                .into(),
+            Status::Job(JobState::Adjusting { .. }) => button(text(tr!("status-rerun")).size(12))
+                .on_press(Message::Processing(ProcessingMessage::Start))
+                .into(),
+            Status::Job(JobState::Done { .. }) => text(tr!("status-finished")).size(12).into(),
+            status => match status.error() {
+                Some(e) => text(tr!(
+                    "error-summary",
+                    error = e.lines().next().unwrap_or_default()
+                ))
+                .size(12)
+                .style(text::danger)
+                .into(),
+                None => text("").size(12).into(),
+            },
+        };
+
+        let counts = tr!(
+            "status-bar-counts",
+            cleaned = self.stats.runs,
+            failed = self.stats.failed
+        );
+        column![
+            drop_zone,
+            progress,
+            row![
+                text(counts).size(11).width(Length::Fill),
+                button(text(tr!("compact-leave")).size(11))
+                    .on_press(Message::ToggleCompact)
+                    .padding([0, 4])
+                    .style(button::text),
+            ]
+            .align_y(Alignment::Center),
+        ]
+        .spacing(8)
+        .into()
+    }
+
+    /// Whether the window is small enough for the compact layout.
+    fn is_compact(&self) -> bool {
+        let size = self.window_size * (1.0 / self.appearance.scale);
+        size.width < COMPACT_BREAKPOINT.width || size.height < COMPACT_BREAKPOINT.height
+    }
+
+    fn view_exit_prompt(&self) -> Element<'_, Message> {
+        if self.exit_when_idle {
+            return text(tr!("exit-stopping")).into();
         }
+
+        let work = self
+            .pending_work()
+            .into_iter()
+            .fold(column![].spacing(5), |work, item| {
+                work.push(text(format!("• {}", item)))
+            });
+        column![
+            text(tr!("exit-title")).size(20),
+            text(tr!("exit-warning")),
+            work,
+            row![
+                button(text(tr!("exit-keep-working"))).on_press(Message::CancelExit),
+                button(text(tr!("exit-confirm")))
+                    .on_press(Message::ConfirmExit)
+                    .style(button::danger),
+            ]
+            .spacing(10),
+        ]
+        .spacing(15)
+        .align_x(Alignment::Center)
+        .into()
     }
 
+    /// Subscriptions of this window. Events and close requests come
+    /// through [`Windows::subscription`], which tells the windows apart.
     fn subscription(&self) -> iced::Subscription<Message> {
-        let events = iced::event::listen().map(Message::EventOccurred);
-
-        if let Status::Downloading = self.status {
-            iced::Subscription::batch(vec![events, iced::Subscription::run(download_process)])
-        } else {
-            events
+        let mut subscriptions = Vec::new();
+        if self
+            .player
+            .as_ref()
+            .is_some_and(|(_, player)| player.is_playing())
+        {
+            subscriptions.push(
+                iced::time::every(Duration::from_millis(50))
+                    .map(|_| Message::Player(PlayerMessage::Tick)),
+            );
+        }
+        if self.recorder.is_some() {
+            subscriptions.push(
+                iced::time::every(Duration::from_millis(50))
+                    .map(|_| Message::Recording(RecordingMessage::Tick)),
+            );
         }
+        if self.live_screen.is_listening() {
+            subscriptions.push(
+                iced::time::every(Duration::from_millis(50))
+                    .map(|_| Message::Live(LiveMessage::Tick)),
+            );
+        }
+        if !self.secondary {
+            if tray::AVAILABLE && self.tray_icon.enabled {
+                subscriptions.push(iced::Subscription::run(tray::events).map(Message::Tray));
+            }
+            subscriptions.push(iced::Subscription::run(control::events).map(Message::Control));
+        }
+        if self.screen == Screen::Logs {
+            subscriptions.push(
+                iced::time::every(Duration::from_millis(500))
+                    .map(|_| Message::Logs(LogsMessage::Refresh)),
+            );
+        }
+        if self.schedule.is_some() {
+            subscriptions.push(
+                iced::time::every(Duration::from_secs(10))
+                    .map(|_| Message::Queue(QueueMessage::ScheduleTick)),
+            );
+        }
+        if self.shutdown_countdown.is_some() {
+            subscriptions
+                .push(iced::time::every(Duration::from_secs(1)).map(|_| Message::ShutdownTick));
+        }
+        if !self.toasts.is_empty() {
+            subscriptions
+                .push(iced::time::every(Duration::from_millis(250)).map(Message::ExpireToasts));
+        }
+        // Picks up plugged and unplugged devices. Devices aren't listed while
+        // audio is running, as probing them can disturb the stream.
+        if (playback::AVAILABLE || recording::AVAILABLE)
+            && self.recorder.is_none()
+            && !self.live_screen.is_listening()
+            && !self
+                .player
+                .as_ref()
+                .is_some_and(|(_, player)| player.is_playing())
+        {
+            subscriptions.push(
+                iced::time::every(Duration::from_secs(3))
+                    .map(|_| Message::Devices(DevicesMessage::Refresh)),
+            );
+        }
+        iced::Subscription::batch(subscriptions)
     }
 
     fn theme(&self) -> Theme {
-        Theme::Dark
+        self.appearance.theme.clone()
+    }
+
+    fn scale_factor(&self) -> f32 {
+        self.appearance.scale
     }
 }
 
-fn check_binary_exists() -> Result<PathBuf, String> {
-    let dirs = directories::ProjectDirs::from("com", "deepfilternet", "deepfilternet-gui")
-        .ok_or("Could not find project directories")?;
-    let data_dir = dirs.data_local_dir();
+/// Brings the window back from the tray.
+fn show_window(id: window::Id) -> Task<Message> {
+    Task::batch([
+        window::set_mode(id, window::Mode::Windowed),
+        window::gain_focus(id),
+    ])
+}
 
-    #[cfg(windows)]
-    let bin_name = "deep-filter.exe";
-    #[cfg(not(windows))]
-    let bin_name = "deep-filter";
+/// Hides the window if it was minimized, leaving it to the tray icon.
+fn hide_if_minimized(id: window::Id) -> Task<Message> {
+    window::is_minimized(id).and_then(move |minimized| {
+        if minimized {
+            window::set_mode(id, window::Mode::Hidden)
+        } else {
+            Task::none()
+        }
+    })
+}
 
-    let bin_path = data_dir.join(bin_name);
-    if bin_path.exists() {
-        Ok(bin_path)
-    } else {
-        Err("Binary not found".to_string())
-    }
-}
-
-fn download_process() -> impl Stream<Item = Message> {
-    futures_util::stream::unfold(State::Start, |state| async move {
-        match state {
-            State::Start => {
-                let dirs = match directories::ProjectDirs::from(
-                    "com",
-                    "deepfilternet",
-                    "deepfilternet-gui",
-                ) {
-                    Some(d) => d,
-                    None => {
-                        return Some((
-                            Message::DownloadFinished(Err("No download dir".into())),
-                            State::Finished,
-                        ));
-                    }
-                };
-                let data_dir = dirs.data_local_dir();
-                if let Err(e) = std::fs::create_dir_all(data_dir) {
-                    return Some((
-                        Message::DownloadFinished(Err(e.to_string())),
-                        State::Finished,
-                    ));
-                }
+fn view_crash_prompt(path: &Path) -> Element<'_, Message> {
+    column![
+        text(tr!("crash-title")).size(20),
+        text(tr!("crash-explanation")),
+        text(path.display().to_string()).size(12),
+        row![
+            button(text(tr!("crash-report"))).on_press(Message::ReportCrash),
+            button(text(tr!("crash-open")))
+                .on_press(Message::OpenCrashReport)
+                .style(button::secondary),
+            button(text(tr!("crash-dismiss")))
+                .on_press(Message::DismissCrash)
+                .style(button::text),
+        ]
+        .spacing(10),
+    ]
+    .spacing(15)
+    .align_x(Alignment::Center)
+    .into()
+}
 
-                let (url, bin_name) = match get_binary_url_and_name() {
-                    Ok(val) => val,
-                    Err(e) => return Some((Message::DownloadFinished(Err(e)), State::Finished)),
-                };
+/// Opens a folder or link with the system's default handler.
+fn open_externally(target: &std::ffi::OsStr) {
+    #[cfg(target_os = "linux")]
+    if dfn_core::sandboxed() {
+        let target = target.to_os_string();
+        call_dbus(portal::open(target.clone()), move || {
+            let _ = std::process::Command::new("xdg-open").arg(&target).spawn();
+        });
+        return;
+    }
+    #[cfg(target_os = "linux")]
+    let _ = std::process::Command::new("xdg-open").arg(target).spawn();
+    #[cfg(target_os = "windows")]
+    let _ = std::process::Command::new("explorer").arg(target).spawn();
+    #[cfg(target_os = "macos")]
+    let _ = std::process::Command::new("open").arg(target).spawn();
+}
 
-                let bin_path = data_dir.join(bin_name);
-
-                match reqwest::get(url).await {
-                    Ok(response) => {
-                        let total_size = response.content_length().unwrap_or(0);
-                        let stream = response.bytes_stream().boxed();
-                        let file = match std::fs::File::create(&bin_path) {
-                            Ok(f) => f,
-                            Err(e) => {
-                                return Some((
-                                    Message::DownloadFinished(Err(e.to_string())),
-                                    State::Finished,
-                                ));
-                            }
-                        };
-                        Some((
-                            Message::DownloadProgress(0.0),
-                            State::Downloading {
-                                stream,
-                                file,
-                                total: total_size,
-                                downloaded: 0,
-                                path: bin_path,
-                            },
-                        ))
-                    }
-                    Err(e) => Some((
-                        Message::DownloadFinished(Err(e.to_string())),
-                        State::Finished,
-                    )),
-                }
-            }
-            State::Downloading {
-                mut stream,
-                mut file,
-                total,
-                mut downloaded,
-                path,
-            } => {
-                match stream.next().await {
-                    Some(Ok(chunk)) => {
-                        if let Err(e) = file.write_all(&chunk) {
-                            return Some((
-                                Message::DownloadFinished(Err(e.to_string())),
-                                State::Finished,
-                            ));
-                        }
-                        downloaded += chunk.len() as u64;
-                        let percentage = if total > 0 {
-                            (downloaded as f32 / total as f32) * 100.0
-                        } else {
-                            0.0
-                        };
-                        Some((
-                            Message::DownloadProgress(percentage),
-                            State::Downloading {
-                                stream,
-                                file,
-                                total,
-                                downloaded,
-                                path,
-                            },
-                        ))
-                    }
-                    Some(Err(e)) => Some((
-                        Message::DownloadFinished(Err(e.to_string())),
-                        State::Finished,
-                    )),
-                    None => {
-                        // Done
-                        #[cfg(unix)]
-                        {
-                            use std::os::unix::fs::PermissionsExt;
-                            if let Ok(meta) = file.metadata() {
-                                let mut perms = meta.permissions();
-                                perms.set_mode(0o755);
-                                let _ = file.set_permissions(perms);
-                            }
-                        }
-                        Some((Message::DownloadFinished(Ok(path)), State::Finished))
-                    }
-                }
-            }
-            State::Finished => None,
+/// Shows `path` selected in the file manager, or opens the folder holding
+/// it where that isn't possible.
+fn reveal(path: &Path) {
+    #[cfg(target_os = "linux")]
+    {
+        let folder = path.parent().unwrap_or(path).to_path_buf();
+        let fallback = move || open_externally(folder.as_os_str());
+        if dfn_core::sandboxed() {
+            call_dbus(portal::open_directory(path.to_path_buf()), fallback);
+        } else {
+            call_dbus(file_manager::show_item(path.to_path_buf()), fallback);
         }
-    })
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        // Explorer reads its command line itself and only takes the path
+        // quoted after the comma.
+        let _ = std::process::Command::new("explorer")
+            .raw_arg(format!("/select,\"{}\"", path.display()))
+            .spawn();
+    }
+    #[cfg(target_os = "macos")]
+    let _ = std::process::Command::new("open")
+        .arg("-R")
+        .arg(path)
+        .spawn();
 }
 
-enum State {
-    Start,
-    Downloading {
-        stream: futures_util::stream::BoxStream<'static, reqwest::Result<bytes::Bytes>>,
-        file: std::fs::File,
-        total: u64,
-        downloaded: u64,
-        path: PathBuf,
-    },
-    Finished,
-}
-
-// Rewriting download_process to use BoxStream to handle the type
-fn get_binary_url_and_name() -> Result<(&'static str, &'static str), String> {
-    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
-    return Ok((
-        "https://github.com/Rikorose/DeepFilterNet/releases/download/v0.5.6/deep-filter-0.5.6-x86_64-unknown-linux-musl",
-        "deep-filter",
-    ));
-
-    #[cfg(all(target_os = "linux", target_arch = "aarch64"))]
-    return Ok((
-        "https://github.com/Rikorose/DeepFilterNet/releases/download/v0.5.6/deep-filter-0.5.6-aarch64-unknown-linux-gnu",
-        "deep-filter",
-    ));
-
-    #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
-    return Ok((
-        "https://github.com/Rikorose/DeepFilterNet/releases/download/v0.5.6/deep-filter-0.5.6-aarch64-apple-darwin",
-        "deep-filter",
-    ));
-
-    #[cfg(all(target_os = "windows", target_arch = "x86_64"))]
-    return Ok((
-        "https://github.com/Rikorose/DeepFilterNet/releases/download/v0.5.6/deep-filter-0.5.6-x86_64-pc-windows-msvc.exe",
-        "deep-filter.exe",
-    ));
-
-    #[allow(unreachable_code)]
-    Err("Unsupported OS/Architecture".to_string())
-}
-
-fn run_deep_filter(input_path: &Path, bin_path: &Path) -> Result<PathBuf, String> {
-    // Prepare output path
-    let file_name = input_path
-        .file_name()
-        .unwrap()
-        .to_string_lossy()
-        .to_string();
-    let input_dir = input_path.parent().unwrap();
-    let output_dir = input_dir.join("dnf_clean");
-    let output_path = output_dir.join(file_name);
-
-    let status = StdCommand::new(bin_path)
-        .arg(input_path)
-        .arg("-o")
-        .arg(output_dir.clone())
-        .status()
-        .map_err(|e| format!("Failed to run AI engine: {}", e))?;
-
-    if status.success() {
-        Ok(output_path)
+/// Makes the D-Bus call `call` on a thread of its own, since `update` can't
+/// wait for it, and runs `fallback` if it fails.
+#[cfg(target_os = "linux")]
+fn call_dbus(
+    call: impl std::future::Future<Output = Result<(), String>> + Send + 'static,
+    fallback: impl FnOnce() + Send + 'static,
+) {
+    std::thread::spawn(move || {
+        let result = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| e.to_string())
+            .and_then(|runtime| runtime.block_on(call));
+        if let Err(e) = result {
+            tracing::warn!("D-Bus call failed: {}", e);
+            fallback();
+        }
+    });
+}
+
+/// Formats audio lengths as `m:ss`, or `h:mm:ss` from an hour up.
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds >= 3600 {
+        format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
     } else {
-        Err("DeepFilterNet failed to process the file".to_string())
+        format!("{}:{:02}", seconds / 60, seconds % 60)
     }
 }
+
+/// Formats processing times as seconds, switching to `m:ss` from a minute up.
+fn format_elapsed(duration: Duration) -> String {
+    if duration.as_secs() < 60 {
+        format!("{:.0} s", duration.as_secs_f32())
+    } else {
+        format_duration(duration)
+    }
+}
+
+/// `bytes` in megabytes, to two significant digits for small files.
+fn format_megabytes(bytes: u64) -> String {
+    let megabytes = bytes as f64 / 1_000_000.0;
+    if megabytes < 10.0 {
+        format!("{:.1}", megabytes)
+    } else {
+        format!("{:.0}", megabytes)
+    }
+}
+
+fn available_cores() -> u16 {
+    std::thread::available_parallelism()
+        .map(|cores| cores.get() as u16)
+        .unwrap_or(1)
+}
+
+/// Reads the engine's version off the UI thread.
+fn read_engine_version(bin_path: PathBuf) -> Task<Message> {
+    Task::perform(
+        async move {
+            tokio::task::spawn_blocking(move || engine::version(&bin_path))
+                .await
+                .unwrap_or_else(|e| Err(format!("Task join error: {}", e)))
+        },
+        Message::EngineVersionRead,
+    )
+}
+
+/// Looks for a newer version of the app on `channel`.
+fn check_for_update(
+    network: Arc<dyn download::Network>,
+    channel: update::Channel,
+) -> Task<Message> {
+    Task::perform(
+        async move { update::check(network.as_ref(), env!("CARGO_PKG_VERSION"), channel).await },
+        Message::UpdateChecked,
+    )
+}
+
+/// Whether the app can update itself. Flatpak updates it instead.
+fn updates_supported() -> bool {
+    update::build_name().is_ok() && !dfn_core::sandboxed()
+}
+
+/// Downloads `release` and puts it in place of the running executable.
+async fn install_update(
+    network: Arc<dyn download::Network>,
+    release: update::Release,
+) -> Result<(), String> {
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("Could not find the app's executable: {}", e))?;
+    let staged = update::download(network.as_ref(), &release, &exe).await?;
+    update::install(&staged, &exe)
+}
+
+/// Size of the engine download on `channel` in bytes, if the server tells.
+async fn engine_download_size(
+    network: Arc<dyn download::Network>,
+    channel: update::Channel,
+    provider: accel::Provider,
+) -> Option<u64> {
+    let url = update::engine_url(network.as_ref(), channel, provider)
+        .await
+        .ok()?;
+    network.size(&url).await
+}
+
+/// `provider`, if the release on `channel` has an engine build for it.
+async fn find_accelerated_engine(
+    network: Arc<dyn download::Network>,
+    channel: update::Channel,
+    provider: accel::Provider,
+) -> Option<accel::Provider> {
+    update::engine_url(network.as_ref(), channel, provider)
+        .await
+        .ok()
+        .map(|_| provider)
+}
+
+/// Asks where to save `inputs` as a list, M3U or plain text after the name
+/// picked, and saves them there.
+fn export_list(inputs: impl Iterator<Item = PathBuf>) -> Task<Message> {
+    let inputs: Vec<PathBuf> = inputs.collect();
+    let name = format!("{}-queue.m3u", env!("CARGO_PKG_NAME"));
+    let text_filter = tr!("file-filter-text");
+    Task::perform(
+        async move {
+            let path = FileDialog::new()
+                .add_filter("M3U", &["m3u", "m3u8"])
+                .add_filter(text_filter, &["txt"])
+                .set_file_name(name)
+                .save_file()?;
+            Some(queue::write_list(&path, inputs.iter().map(PathBuf::as_path)).map(|()| path))
+        },
+        |result| Message::Queue(QueueMessage::Exported(result)),
+    )
+}
+
+/// Stops what the progress bar above it follows.
+fn cancel_button<'a>() -> Element<'a, Message> {
+    button(text(tr!("status-cancel")))
+        .on_press(Message::Cancel)
+        .style(button::secondary)
+        .into()
+}
+
+/// Runs `work` on the blocking thread pool, streaming every message it sends
+/// followed by the message it returns. A panic in `work` is reported through
+/// `on_panic`.
+fn blocking_stream<M: Send + 'static>(
+    work: impl FnOnce(&dyn Fn(M)) -> M + Send + 'static,
+    on_panic: fn(String) -> M,
+) -> impl Stream<Item = M> {
+    futures_util::stream::once(async move {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let progress_sender = sender.clone();
+        let worker = tokio::task::spawn_blocking(move || {
+            work(&|message| {
+                let _ = progress_sender.send(message);
+            })
+        });
+        tokio::spawn(async move {
+            let message = worker
+                .await
+                .unwrap_or_else(|e| on_panic(format!("Task join error: {}", e)));
+            let _ = sender.send(message);
+        });
+
+        futures_util::stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|message| (message, receiver))
+        })
+    })
+    .flatten()
+}
@@ -0,0 +1,88 @@
+//! Peak/RMS level meters for the preview player.
+
+use iced::widget::canvas::{self, Frame, Geometry};
+use iced::{Point, Rectangle, Renderer, Size, Theme, mouse};
+
+/// Lowest level shown; quieter signals leave the bar empty.
+const FLOOR_DB: f32 = -60.0;
+
+/// Levels of a short stretch of playback, left and right. Mono audio shows
+/// the same level on both sides.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Meter {
+    pub peak: [f32; 2],
+    pub rms: [f32; 2],
+}
+
+impl Meter {
+    pub fn is_clipping(&self) -> bool {
+        self.peak.iter().any(|&peak| peak >= 1.0)
+    }
+}
+
+/// Two horizontal bars, RMS filled and peak as a tick, with a clip light on
+/// the right.
+pub struct LevelMeter {
+    pub meter: Meter,
+    /// Whether the audio clipped at any point since playback started.
+    pub clipped: bool,
+}
+
+impl<Message> canvas::Program<Message> for LevelMeter {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let palette = theme.extended_palette();
+        let light_size = bounds.height;
+        let bar_width = (bounds.width - light_size - 4.0).max(0.0);
+        let bar_height = (bounds.height - 2.0) / 2.0;
+
+        for side in 0..2 {
+            let y = side as f32 * (bar_height + 2.0);
+            frame.fill_rectangle(
+                Point::new(0.0, y),
+                Size::new(bar_width, bar_height),
+                palette.background.strong.color,
+            );
+            frame.fill_rectangle(
+                Point::new(0.0, y),
+                Size::new(bar_width * fill(self.meter.rms[side]), bar_height),
+                palette.success.base.color,
+            );
+            let peak_x = bar_width * fill(self.meter.peak[side]);
+            frame.fill_rectangle(
+                Point::new((peak_x - 2.0).max(0.0), y),
+                Size::new(2.0, bar_height),
+                palette.background.base.text,
+            );
+        }
+
+        frame.fill_rectangle(
+            Point::new(bounds.width - light_size, 0.0),
+            Size::new(light_size, light_size),
+            if self.clipped || self.meter.is_clipping() {
+                palette.danger.base.color
+            } else {
+                palette.background.strong.color
+            },
+        );
+
+        vec![frame.into_geometry()]
+    }
+}
+
+/// Share of the bar covered by `amplitude`, on a dB scale.
+fn fill(amplitude: f32) -> f32 {
+    if amplitude <= 0.0 {
+        return 0.0;
+    }
+    (1.0 - 20.0 * amplitude.log10() / FLOOR_DB).clamp(0.0, 1.0)
+}
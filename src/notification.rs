@@ -0,0 +1,30 @@
+//! Desktop notifications, used to report finished work while the window is
+//! in the background.
+
+/// System sound played along with a notification.
+#[cfg(all(unix, not(target_os = "macos")))]
+const SOUND: &str = "complete";
+#[cfg(target_os = "macos")]
+const SOUND: &str = "Glass";
+#[cfg(windows)]
+const SOUND: &str = "Default";
+
+/// Shows a notification without waiting for it. Failures are ignored, as
+/// not every desktop has a notification service running.
+pub fn show(summary: String, body: String, sound: bool) {
+    std::thread::spawn(move || show_now(&summary, &body, sound));
+}
+
+/// Shows a notification and waits until it's sent, for a process about to
+/// exit.
+pub fn show_now(summary: &str, body: &str, sound: bool) {
+    let mut notification = notify_rust::Notification::new();
+    notification
+        .appname("DeepFilterNet GUI")
+        .summary(summary)
+        .body(body);
+    if sound {
+        notification.sound_name(SOUND);
+    }
+    let _ = notification.show();
+}
@@ -0,0 +1,263 @@
+//! The first-run walkthrough, from getting the engine to cleaning a
+//! sample recording.
+
+use crate::downloader::{DownloadMessage, view_offline};
+use crate::processing::ProcessingMessage;
+use crate::{DfnGui, EngineState, JobState, Message, Screen, Status, cancel_button, sample};
+use dfn_core::i18n::tr;
+use iced::widget::{button, column, progress_bar, row, text};
+use iced::{Alignment, Element, Task};
+
+/// Steps of the first-run walkthrough.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum OnboardingStep {
+    #[default]
+    Welcome,
+    Engine,
+    OutputFolder,
+    TestRun,
+}
+
+impl OnboardingStep {
+    const ALL: [OnboardingStep; 4] = [
+        OnboardingStep::Welcome,
+        OnboardingStep::Engine,
+        OnboardingStep::OutputFolder,
+        OnboardingStep::TestRun,
+    ];
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|step| *step == self).unwrap_or(0)
+    }
+
+    fn next(self) -> Option<Self> {
+        Self::ALL.get(self.index() + 1).copied()
+    }
+
+    fn previous(self) -> Option<Self> {
+        self.index().checked_sub(1).map(|index| Self::ALL[index])
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Onboarding {
+    step: OnboardingStep,
+    /// Size of the engine download in bytes, once known.
+    download_size: Option<u64>,
+}
+
+/// Steps taken in the first-run walkthrough.
+#[derive(Debug, Clone)]
+pub enum OnboardingMessage {
+    DownloadSizeFetched(Option<u64>),
+    Back,
+    Next,
+    /// Close the walkthrough, whether it was gone through or skipped.
+    Finish,
+    /// Close the walkthrough and clean the sample recording.
+    CleanSample,
+}
+
+impl DfnGui {
+    pub fn update_onboarding(&mut self, message: OnboardingMessage) -> Task<Message> {
+        let Some(onboarding) = &mut self.onboarding else {
+            return Task::none();
+        };
+        match message {
+            OnboardingMessage::DownloadSizeFetched(size) => {
+                onboarding.download_size = size;
+            }
+            OnboardingMessage::Back => {
+                if let Some(step) = onboarding.step.previous() {
+                    onboarding.step = step;
+                }
+            }
+            OnboardingMessage::Next => {
+                if let Some(step) = onboarding.step.next() {
+                    onboarding.step = step;
+                }
+            }
+            OnboardingMessage::Finish => {
+                self.onboarding = None;
+            }
+            OnboardingMessage::CleanSample => {
+                self.onboarding = None;
+                self.screen = Screen::Clean;
+                match sample::write() {
+                    Ok(path) => {
+                        let select = self.select_file(path);
+                        if matches!(self.status, Status::Job(JobState::Ready(_))) {
+                            return Task::batch([
+                                select,
+                                self.update_processing(ProcessingMessage::Start),
+                            ]);
+                        }
+                        return select;
+                    }
+                    Err(e) => {
+                        self.show_error_details = false;
+                        self.show_full_log = false;
+                        self.fail_input(e);
+                    }
+                }
+            }
+        }
+        Task::none()
+    }
+
+    pub fn view_onboarding(&self, onboarding: &Onboarding) -> Element<'_, Message> {
+        let step = onboarding.step;
+        let (title, content): (String, Element<'_, Message>) = match step {
+            OnboardingStep::Welcome => (
+                tr!("onboarding-welcome-title"),
+                text(tr!("onboarding-welcome-body")).into(),
+            ),
+            OnboardingStep::Engine => (
+                tr!("onboarding-engine-title"),
+                self.view_onboarding_engine(onboarding.download_size),
+            ),
+            OnboardingStep::OutputFolder => (
+                tr!("onboarding-output-title"),
+                column![
+                    text(tr!("onboarding-output-body")),
+                    self.view_output_dir().map(Message::Settings),
+                ]
+                .spacing(15)
+                .into(),
+            ),
+            OnboardingStep::TestRun => {
+                let ready = self.engine_ready();
+                let mut content = column![
+                    text(tr!("onboarding-test-body")),
+                    button(text(tr!("onboarding-test-run"))).on_press_maybe(
+                        ready.then_some(Message::Onboarding(OnboardingMessage::CleanSample))
+                    ),
+                ]
+                .spacing(15);
+                if !ready {
+                    content = content.push(text(tr!("onboarding-test-needs-engine")).size(12));
+                }
+                (tr!("onboarding-test-title"), content.into())
+            }
+        };
+
+        let forward = match step.next() {
+            Some(_) => button(text(tr!("onboarding-next")))
+                .on_press(Message::Onboarding(OnboardingMessage::Next)),
+            None => button(text(tr!("onboarding-finish")))
+                .on_press(Message::Onboarding(OnboardingMessage::Finish)),
+        };
+        let navigation = row![
+            button(text(tr!("onboarding-skip")))
+                .on_press(Message::Onboarding(OnboardingMessage::Finish))
+                .style(button::text),
+            iced::widget::space::horizontal(),
+            button(text(tr!("onboarding-back")))
+                .on_press_maybe(
+                    step.previous()
+                        .map(|_| Message::Onboarding(OnboardingMessage::Back))
+                )
+                .style(button::secondary),
+            forward,
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        column![
+            text(tr!(
+                "onboarding-step",
+                step = step.index() + 1,
+                steps = OnboardingStep::ALL.len()
+            ))
+            .size(12),
+            text(title).size(24),
+            content,
+            navigation,
+        ]
+        .spacing(20)
+        .max_width(500)
+        .into()
+    }
+
+    fn view_onboarding_engine(&self, download_size: Option<u64>) -> Element<'_, Message> {
+        if let Status::Engine {
+            state: EngineState::Downloading { progress, .. },
+            ..
+        } = &self.status
+        {
+            return column![
+                text(tr!(
+                    "download-progress",
+                    percent = format!("{:.0}", progress)
+                )),
+                progress_bar(0.0..=100.0, *progress),
+                cancel_button(),
+            ]
+            .spacing(10)
+            .into();
+        }
+        if self.engine_installed {
+            return text(tr!("onboarding-engine-installed"))
+                .style(text::success)
+                .into();
+        }
+
+        let mut content = column![text(tr!("onboarding-engine-body"))].spacing(15);
+        if let Some(size) = download_size {
+            content = content.push(
+                text(tr!(
+                    "onboarding-engine-size",
+                    size = format!("{:.1}", size as f64 / 1_000_000.0)
+                ))
+                .size(12),
+            );
+        }
+        let error = match &self.status {
+            Status::Engine {
+                state: EngineState::Failed(e),
+                ..
+            } => Some(e),
+            Status::Engine {
+                state: EngineState::Offline,
+                ..
+            } => return view_offline(),
+            _ => None,
+        };
+        content = content.push(
+            button(text(if error.is_some() {
+                tr!("onboarding-engine-retry")
+            } else {
+                tr!("download-engine")
+            }))
+            .on_press(Message::Download(DownloadMessage::Start)),
+        );
+        if let Some(e) = error {
+            content = content.push(text(e).size(12).style(text::danger));
+        }
+        content.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_walkthrough_stays_within_its_steps() {
+        let mut gui = DfnGui {
+            onboarding: Some(Onboarding::default()),
+            ..DfnGui::default()
+        };
+        let step = |gui: &DfnGui| gui.onboarding.as_ref().map(|onboarding| onboarding.step);
+
+        let _ = gui.update_onboarding(OnboardingMessage::Back);
+        assert_eq!(step(&gui), Some(OnboardingStep::Welcome));
+        for _ in 0..OnboardingStep::ALL.len() + 1 {
+            let _ = gui.update_onboarding(OnboardingMessage::Next);
+        }
+        assert_eq!(step(&gui), Some(OnboardingStep::TestRun));
+
+        let _ = gui.update_onboarding(OnboardingMessage::Finish);
+        assert!(gui.onboarding.is_none());
+    }
+}
@@ -0,0 +1,282 @@
+//! The options a file is cleaned with, set on the clean screen before a
+//! run.
+
+use crate::{DfnGui, help};
+use dfn_core::i18n::tr;
+use dfn_core::pipeline::{self, OutputFormat};
+use dfn_core::{content, transcode};
+use iced::widget::{button, checkbox, column, pick_list, row, slider, text};
+use iced::{Alignment, Element};
+
+/// Noise reduction suggested for files that sound like music, which the
+/// engine would otherwise thin out.
+const MUSIC_NOISE_REDUCTION: f32 = 50.0;
+
+/// Changes to the options of the next run.
+#[derive(Debug, Clone)]
+pub enum OptionsMessage {
+    PreserveFormatToggled(bool),
+    RemoveDcOffsetToggled(bool),
+    TrimSilenceToggled(bool),
+    SilenceThresholdChanged(f32),
+    NoiseReductionChanged(f32),
+    NormalizeToggled(bool),
+    OutputFormatSelected(OutputFormat),
+    ChannelRoutingSelected(pipeline::ChannelRouting),
+    ChannelSplitSelected(pipeline::ChannelSplit),
+    BitrateChanged(u16),
+    RemuxVideoToggled(bool),
+    KeepMetadataToggled(bool),
+    KeepBroadcastChunksToggled(bool),
+    ExportLabelsToggled(bool),
+    WriteProvenanceToggled(bool),
+    VersionOutputsToggled(bool),
+    SkipDuplicatesToggled(bool),
+}
+
+impl DfnGui {
+    pub fn update_options(&mut self, message: OptionsMessage) {
+        match message {
+            OptionsMessage::PreserveFormatToggled(enabled) => {
+                self.options.preserve_format = enabled;
+            }
+            OptionsMessage::RemoveDcOffsetToggled(enabled) => {
+                self.options.remove_dc_offset = enabled;
+            }
+            OptionsMessage::TrimSilenceToggled(enabled) => {
+                self.options.trim_silence = enabled;
+            }
+            OptionsMessage::SilenceThresholdChanged(threshold) => {
+                self.options.silence_threshold_db = threshold;
+            }
+            OptionsMessage::NoiseReductionChanged(amount) => {
+                self.options.noise_reduction = amount;
+            }
+            OptionsMessage::NormalizeToggled(enabled) => {
+                self.options.normalize = enabled;
+            }
+            OptionsMessage::OutputFormatSelected(format) => {
+                self.options.output_format = format;
+            }
+            OptionsMessage::ChannelRoutingSelected(channels) => {
+                self.options.channels = channels;
+            }
+            OptionsMessage::ChannelSplitSelected(split) => {
+                self.options.split_channels = split;
+            }
+            OptionsMessage::BitrateChanged(bitrate) => {
+                self.options.bitrate_kbps = bitrate as u32;
+            }
+            OptionsMessage::KeepMetadataToggled(enabled) => {
+                self.options.keep_metadata = enabled;
+            }
+            OptionsMessage::KeepBroadcastChunksToggled(enabled) => {
+                self.options.keep_broadcast_chunks = enabled;
+            }
+            OptionsMessage::ExportLabelsToggled(enabled) => {
+                self.options.export_labels = enabled;
+            }
+            OptionsMessage::WriteProvenanceToggled(enabled) => {
+                self.options.write_provenance = enabled;
+            }
+            OptionsMessage::VersionOutputsToggled(enabled) => {
+                self.options.version_outputs = enabled;
+            }
+            OptionsMessage::SkipDuplicatesToggled(enabled) => {
+                self.options.skip_duplicates = enabled;
+            }
+            OptionsMessage::RemuxVideoToggled(enabled) => {
+                self.options.remux_video = enabled;
+            }
+        }
+    }
+
+    /// Warns that the selected file sounds like music, offering a gentler
+    /// noise reduction.
+    pub fn view_music_warning(&self) -> Option<Element<'_, OptionsMessage>> {
+        if self.input_content != Some(content::Content::Music) {
+            return None;
+        }
+        let mut warning = row![text(tr!("note-music")).size(12).style(text::warning)]
+            .spacing(10)
+            .align_y(Alignment::Center);
+        if self.options.noise_reduction > MUSIC_NOISE_REDUCTION {
+            warning = warning.push(
+                button(
+                    text(tr!(
+                        "note-music-lower",
+                        percent = format!("{:.0}", MUSIC_NOISE_REDUCTION)
+                    ))
+                    .size(12),
+                )
+                .on_press(OptionsMessage::NoiseReductionChanged(MUSIC_NOISE_REDUCTION)),
+            );
+        }
+        Some(warning.into())
+    }
+
+    pub fn view_options(&self) -> Element<'_, OptionsMessage> {
+        let mut options = column![
+            help::with_help(
+                checkbox(self.options.remove_dc_offset)
+                    .label(tr!("option-remove-dc-offset"))
+                    .on_toggle(OptionsMessage::RemoveDcOffsetToggled),
+                tr!("help-remove-dc-offset"),
+            ),
+            help::with_help(
+                checkbox(self.options.trim_silence)
+                    .label(tr!("option-trim-silence"))
+                    .on_toggle(OptionsMessage::TrimSilenceToggled),
+                tr!("help-trim-silence"),
+            ),
+        ]
+        .spacing(8);
+
+        if self.options.trim_silence {
+            options = options.push(help::labeled(
+                tr!(
+                    "option-silence-threshold",
+                    db = format!("{:.0}", self.options.silence_threshold_db)
+                ),
+                tr!("help-silence-threshold"),
+                slider(
+                    -90.0..=-20.0,
+                    self.options.silence_threshold_db,
+                    OptionsMessage::SilenceThresholdChanged,
+                )
+                .step(1.0),
+            ));
+        }
+
+        options = options.push(help::labeled(
+            tr!("option-channels"),
+            tr!("help-channels"),
+            pick_list(
+                pipeline::ChannelRouting::CHOICES,
+                Some(self.options.channels),
+                OptionsMessage::ChannelRoutingSelected,
+            )
+            .text_size(12),
+        ));
+        // Picking one channel leaves nothing to split.
+        if self.options.channels == pipeline::ChannelRouting::Keep {
+            options = options.push(help::labeled(
+                tr!("option-split-channels"),
+                tr!("help-split-channels"),
+                pick_list(
+                    pipeline::ChannelSplit::ALL,
+                    Some(self.options.split_channels),
+                    OptionsMessage::ChannelSplitSelected,
+                )
+                .text_size(12),
+            ));
+        }
+        options = options
+            .push(help::labeled(
+                tr!(
+                    "option-noise-reduction",
+                    percent = format!("{:.0}", self.options.noise_reduction)
+                ),
+                tr!("help-noise-reduction"),
+                slider(
+                    0.0..=100.0,
+                    self.options.noise_reduction,
+                    OptionsMessage::NoiseReductionChanged,
+                )
+                .step(5.0),
+            ))
+            .push(help::with_help(
+                checkbox(self.options.normalize)
+                    .label(tr!(
+                        "option-normalize",
+                        db = format!("{:.0}", self.options.normalize_peak_db)
+                    ))
+                    .on_toggle(OptionsMessage::NormalizeToggled),
+                tr!("help-normalize"),
+            ))
+            .push(help::with_help(
+                checkbox(self.options.preserve_format)
+                    .label(tr!("option-preserve-format"))
+                    .on_toggle(OptionsMessage::PreserveFormatToggled),
+                tr!("help-preserve-format"),
+            ))
+            .push(
+                row![
+                    text(tr!("option-save-as")).size(12),
+                    pick_list(
+                        OutputFormat::ALL,
+                        Some(self.options.output_format),
+                        OptionsMessage::OutputFormatSelected,
+                    )
+                    .text_size(12),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            );
+
+        options = options.push(help::with_help(
+            checkbox(self.options.keep_metadata)
+                .label(tr!("option-keep-metadata"))
+                .on_toggle(OptionsMessage::KeepMetadataToggled),
+            tr!("help-keep-metadata"),
+        ));
+        options = options.push(help::with_help(
+            checkbox(self.options.keep_broadcast_chunks)
+                .label(tr!("option-keep-broadcast-chunks"))
+                .on_toggle(OptionsMessage::KeepBroadcastChunksToggled),
+            tr!("help-keep-broadcast-chunks"),
+        ));
+        options = options.push(help::with_help(
+            checkbox(self.options.export_labels)
+                .label(tr!("option-export-labels"))
+                .on_toggle(OptionsMessage::ExportLabelsToggled),
+            tr!("help-export-labels"),
+        ));
+        options = options.push(help::with_help(
+            checkbox(self.options.write_provenance)
+                .label(tr!("option-write-provenance"))
+                .on_toggle(OptionsMessage::WriteProvenanceToggled),
+            tr!("help-write-provenance"),
+        ));
+        options = options.push(help::with_help(
+            checkbox(self.options.version_outputs)
+                .label(tr!("option-version-outputs"))
+                .on_toggle(OptionsMessage::VersionOutputsToggled),
+            tr!("help-version-outputs"),
+        ));
+        options = options.push(help::with_help(
+            checkbox(self.options.skip_duplicates)
+                .label(tr!("option-skip-duplicates"))
+                .on_toggle(OptionsMessage::SkipDuplicatesToggled),
+            tr!("help-skip-duplicates"),
+        ));
+        options = options.push(help::with_help(
+            checkbox(self.options.remux_video)
+                .label(tr!("option-remux-video"))
+                .on_toggle(OptionsMessage::RemuxVideoToggled),
+            tr!("help-remux-video"),
+        ));
+        if self.options.output_format.is_lossy() || self.options.remux_video {
+            options = options.push(help::labeled(
+                tr!("option-bitrate", kbps = self.options.bitrate_kbps),
+                tr!("help-bitrate"),
+                slider(
+                    64..=320,
+                    self.options.bitrate_kbps as u16,
+                    OptionsMessage::BitrateChanged,
+                )
+                .step(32u16),
+            ));
+        }
+        if (self.options.output_format != OutputFormat::Wav || self.options.remux_video)
+            && transcode::ffmpeg_path().is_none()
+        {
+            options = options.push(
+                text(tr!("option-needs-ffmpeg"))
+                    .size(12)
+                    .style(text::warning),
+            );
+        }
+        options.into()
+    }
+}
@@ -0,0 +1,421 @@
+//! Previewing audio inside the app. Playback is only compiled in with the
+//! `playback` feature; without it [`Player::new`] always fails and the
+//! preview controls are hidden.
+//!
+//! Errors opening the output device name it, and errors the device reports
+//! later, like when another app takes it over for exclusive use, are kept
+//! for [`Player::take_error`] rather than only logged.
+
+use crate::devices::Device;
+use crate::meter::Meter;
+use dfn_core::audio::AudioBuffer;
+use std::time::Duration;
+
+/// Whether this build can play audio.
+pub const AVAILABLE: bool = cfg!(feature = "playback");
+
+#[cfg(feature = "playback")]
+pub use imp::{Player, output_devices};
+#[cfg(not(feature = "playback"))]
+pub use stub::{Player, output_devices};
+
+#[cfg(feature = "playback")]
+mod imp {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+    use rodio::cpal::traits::{DeviceTrait, HostTrait};
+    use rodio::source::SeekError;
+    use rodio::{OutputStream, OutputStreamBuilder, Sink, Source};
+    use std::sync::mpsc;
+
+    /// How many meter readings are sent per second of audio.
+    const METER_RATE: u32 = 30;
+
+    /// Output devices of the default host, starting with the system default.
+    pub fn output_devices() -> Vec<Device> {
+        let mut devices = vec![Device::Default];
+        if let Ok(found) = rodio::cpal::default_host().output_devices() {
+            devices.extend(
+                found
+                    .filter_map(|device| device.name().ok())
+                    .map(Device::Named),
+            );
+        }
+        devices
+    }
+
+    /// A clip with its own sink, so several can run in step.
+    struct Track {
+        sink: Sink,
+        channels: u16,
+        sample_rate: u32,
+        /// Interleaved samples, re-queued when playing again after the end.
+        samples: Vec<f32>,
+        /// Where the clip's meter readings go, tagged with its index.
+        meter_sender: mpsc::Sender<(usize, Meter)>,
+        index: usize,
+    }
+
+    impl Track {
+        fn queue(&self) {
+            if self.sink.empty() {
+                self.sink.append(Metered::new(
+                    SamplesBuffer::new(self.channels, self.sample_rate, self.samples.clone()),
+                    self.index,
+                    self.meter_sender.clone(),
+                ));
+            }
+        }
+    }
+
+    /// Passes a source through unchanged while measuring it, sending a
+    /// [`Meter`] reading for every block of samples the output pulls.
+    struct Metered<S> {
+        inner: S,
+        index: usize,
+        sender: mpsc::Sender<(usize, Meter)>,
+        /// Frames per reading.
+        block: usize,
+        /// Samples consumed in the current block.
+        position: usize,
+        peak: [f32; 2],
+        squares: [f32; 2],
+    }
+
+    impl<S: Source> Metered<S> {
+        fn new(inner: S, index: usize, sender: mpsc::Sender<(usize, Meter)>) -> Self {
+            let block = (inner.sample_rate() / METER_RATE).max(1) as usize;
+            Self {
+                inner,
+                index,
+                sender,
+                block,
+                position: 0,
+                peak: [0.0; 2],
+                squares: [0.0; 2],
+            }
+        }
+    }
+
+    impl<S: Source> Iterator for Metered<S> {
+        type Item = f32;
+
+        fn next(&mut self) -> Option<f32> {
+            let sample = self.inner.next()?;
+            let channels = self.inner.channels().max(1) as usize;
+            let channel = self.position % channels;
+            // Mono feeds both sides; channels beyond the second are ignored.
+            let sides: &[usize] = match (channels, channel) {
+                (1, _) => &[0, 1],
+                (_, 0) => &[0],
+                (_, 1) => &[1],
+                _ => &[],
+            };
+            for &side in sides {
+                self.peak[side] = self.peak[side].max(sample.abs());
+                self.squares[side] += sample * sample;
+            }
+
+            self.position += 1;
+            if self.position == self.block * channels {
+                let rms = self
+                    .squares
+                    .map(|squares| (squares / self.block as f32).sqrt());
+                let _ = self.sender.send((
+                    self.index,
+                    Meter {
+                        peak: self.peak,
+                        rms,
+                    },
+                ));
+                self.position = 0;
+                self.peak = [0.0; 2];
+                self.squares = [0.0; 2];
+            }
+            Some(sample)
+        }
+    }
+
+    impl<S: Source> Source for Metered<S> {
+        fn current_span_len(&self) -> Option<usize> {
+            self.inner.current_span_len()
+        }
+
+        fn channels(&self) -> u16 {
+            self.inner.channels()
+        }
+
+        fn sample_rate(&self) -> u32 {
+            self.inner.sample_rate()
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            self.inner.total_duration()
+        }
+
+        fn try_seek(&mut self, position: Duration) -> Result<(), SeekError> {
+            self.inner.try_seek(position)
+        }
+    }
+
+    /// Opens `device`, sending the errors it reports once playing to
+    /// `errors`.
+    fn open(device: &Device, errors: mpsc::Sender<String>) -> Result<OutputStream, String> {
+        let builder = match device {
+            Device::Default => OutputStreamBuilder::from_default_device(),
+            Device::Named(name) => rodio::cpal::default_host()
+                .output_devices()
+                .ok()
+                .and_then(|mut devices| {
+                    devices.find(|device| device.name().ok().as_ref() == Some(name))
+                })
+                .ok_or_else(|| format!("The output device {} is not available", name))
+                .map(OutputStreamBuilder::from_device)?,
+        };
+        builder
+            .and_then(|builder| {
+                builder
+                    .with_error_callback(move |e| {
+                        let _ = errors.send(e.to_string());
+                    })
+                    .open_stream_or_fallback()
+            })
+            .map_err(|e| format!("Could not open the audio output {}: {}", device, e))
+    }
+
+    /// Decoded clips on an output device, starting paused. All
+    /// clips play simultaneously and stay at the same position, with only
+    /// the selected one audible.
+    pub struct Player {
+        // Dropping the stream ends playback, so it lives as long as the sinks.
+        _stream: OutputStream,
+        tracks: Vec<Track>,
+        selected: usize,
+        duration: Duration,
+        meter_receiver: mpsc::Receiver<(usize, Meter)>,
+        meter: Meter,
+        clipped: bool,
+        errors: mpsc::Receiver<String>,
+    }
+
+    impl Player {
+        /// Plays `buffers` on `device`, of which the first is audible.
+        pub fn new(buffers: &[AudioBuffer], device: &Device) -> Result<Self, String> {
+            let (error_sender, errors) = mpsc::channel();
+            let mut stream = open(device, error_sender)?;
+            stream.log_on_drop(false);
+
+            let (meter_sender, meter_receiver) = mpsc::channel();
+            let tracks = buffers
+                .iter()
+                .enumerate()
+                .map(|(index, buffer)| {
+                    let sink = Sink::connect_new(stream.mixer());
+                    sink.pause();
+                    sink.set_volume(if index == 0 { 1.0 } else { 0.0 });
+
+                    let mut samples = Vec::with_capacity(buffer.frames() * buffer.channels.len());
+                    for frame in 0..buffer.frames() {
+                        samples.extend(buffer.channels.iter().map(|channel| channel[frame]));
+                    }
+                    Track {
+                        sink,
+                        channels: buffer.channels.len() as u16,
+                        sample_rate: buffer.sample_rate,
+                        samples,
+                        meter_sender: meter_sender.clone(),
+                        index,
+                    }
+                })
+                .collect();
+            let duration = buffers
+                .iter()
+                .map(|buffer| {
+                    Duration::from_secs_f64(buffer.frames() as f64 / buffer.sample_rate as f64)
+                })
+                .max()
+                .unwrap_or_default();
+
+            let player = Self {
+                _stream: stream,
+                tracks,
+                selected: 0,
+                duration,
+                meter_receiver,
+                meter: Meter::default(),
+                clipped: false,
+                errors,
+            };
+            player.queue();
+            Ok(player)
+        }
+
+        /// Re-queues the clips once all of them have played to the end.
+        fn queue(&self) {
+            if self.tracks.iter().all(|track| track.sink.empty()) {
+                for track in &self.tracks {
+                    track.queue();
+                }
+            }
+        }
+
+        pub fn play(&self) {
+            // Playing again after the end starts over.
+            self.queue();
+            for track in &self.tracks {
+                track.sink.play();
+            }
+        }
+
+        pub fn pause(&self) {
+            for track in &self.tracks {
+                track.sink.pause();
+            }
+        }
+
+        /// Whether audio is currently coming out.
+        pub fn is_playing(&self) -> bool {
+            self.tracks
+                .iter()
+                .any(|track| !track.sink.is_paused() && !track.sink.empty())
+        }
+
+        pub fn seek(&self, position: Duration) {
+            for track in &self.tracks {
+                track.queue();
+                let _ = track.sink.try_seek(position.min(self.duration));
+            }
+        }
+
+        pub fn position(&self) -> Duration {
+            self.tracks
+                .iter()
+                .filter(|track| !track.sink.empty())
+                .map(|track| track.sink.get_pos())
+                .max()
+                .unwrap_or(self.duration)
+        }
+
+        pub fn duration(&self) -> Duration {
+            self.duration
+        }
+
+        /// Makes clip `index` the audible one, without interrupting playback.
+        pub fn select(&mut self, index: usize) {
+            if index < self.tracks.len() {
+                self.tracks[self.selected].sink.set_volume(0.0);
+                self.tracks[index].sink.set_volume(1.0);
+                self.selected = index;
+                self.clipped = false;
+            }
+        }
+
+        pub fn selected(&self) -> usize {
+            self.selected
+        }
+
+        pub fn track_count(&self) -> usize {
+            self.tracks.len()
+        }
+
+        /// Takes in the meter readings sent since the last call.
+        pub fn update_meter(&mut self) {
+            for (index, meter) in self.meter_receiver.try_iter() {
+                if index == self.selected {
+                    self.clipped |= meter.is_clipping();
+                    self.meter = meter;
+                }
+            }
+            if !self.is_playing() {
+                self.meter = Meter::default();
+            }
+        }
+
+        /// Latest levels of the audible clip.
+        pub fn meter(&self) -> Meter {
+            self.meter
+        }
+
+        /// Whether the audible clip clipped since it was loaded or selected.
+        pub fn clipped(&self) -> bool {
+            self.clipped
+        }
+
+        /// An error the output device reported since the last call, after
+        /// which it usually stays silent.
+        pub fn take_error(&self) -> Option<String> {
+            self.errors.try_iter().next()
+        }
+    }
+}
+
+#[cfg(not(feature = "playback"))]
+mod stub {
+    use super::*;
+
+    pub fn output_devices() -> Vec<Device> {
+        vec![Device::Default]
+    }
+
+    /// Stands in for the real player in builds without playback. It can't
+    /// be constructed.
+    pub enum Player {}
+
+    impl Player {
+        pub fn new(_buffers: &[AudioBuffer], _device: &Device) -> Result<Self, String> {
+            Err("This build was made without audio playback".to_string())
+        }
+
+        pub fn play(&self) {
+            match *self {}
+        }
+
+        pub fn pause(&self) {
+            match *self {}
+        }
+
+        pub fn is_playing(&self) -> bool {
+            match *self {}
+        }
+
+        pub fn seek(&self, _position: Duration) {
+            match *self {}
+        }
+
+        pub fn position(&self) -> Duration {
+            match *self {}
+        }
+
+        pub fn duration(&self) -> Duration {
+            match *self {}
+        }
+
+        pub fn select(&mut self, _index: usize) {
+            match *self {}
+        }
+
+        pub fn selected(&self) -> usize {
+            match *self {}
+        }
+
+        pub fn track_count(&self) -> usize {
+            match *self {}
+        }
+
+        pub fn update_meter(&mut self) {
+            match *self {}
+        }
+
+        pub fn meter(&self) -> Meter {
+            match *self {}
+        }
+
+        pub fn clipped(&self) -> bool {
+            match *self {}
+        }
+
+        pub fn take_error(&self) -> Option<String> {
+            match *self {}
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! Previews of the input, the cleaned output with the input alongside for
+//! A/B comparison, and two outputs compared on the history screen.
+
+use crate::devices_panel::{DevicesMessage, devices_process, view_device_picker};
+use crate::{DfnGui, JobState, Message, Status, blocking_stream, format_duration, meter, playback};
+use dfn_core::audio;
+use dfn_core::i18n::tr;
+use futures_util::Stream;
+use iced::widget::{button, canvas, column, row, slider, text};
+use iced::{Alignment, Element, Length, Task};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// File a preview plays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preview {
+    Input,
+    /// The cleaned output, with the input running alongside for A/B
+    /// comparison.
+    Output,
+    /// The two outputs being compared on the history screen.
+    Versions,
+}
+
+/// Playing a preview of the input or the output.
+#[derive(Debug, Clone)]
+pub enum PlayerMessage {
+    Load(Preview),
+    Loaded(Result<(Preview, Vec<audio::AudioBuffer>), String>),
+    Toggle,
+    /// Switch between the cleaned and the original audio of an output preview.
+    SwitchComparison,
+    Seek(f32),
+    /// Seek to a fraction of the length.
+    SeekFraction(f32),
+    Tick,
+    Close,
+}
+
+impl DfnGui {
+    /// Previewing the input or the cleaned output.
+    pub fn update_player(&mut self, message: PlayerMessage) -> Task<Message> {
+        match message {
+            PlayerMessage::Load(preview) => {
+                let input_path = self.status.selected_file().map(Path::to_path_buf);
+                let paths = match (preview, input_path, &self.status) {
+                    (Preview::Versions, _, _) => match &self.history_screen.comparison {
+                        Some(comparison) => [&comparison.a, &comparison.b]
+                            .into_iter()
+                            .filter_map(|version| version.entry.output_path.clone())
+                            .collect(),
+                        None => return Task::none(),
+                    },
+                    (
+                        Preview::Output,
+                        Some(input_path),
+                        Status::Job(JobState::Done { report, .. }),
+                    ) => vec![report.output_path.clone(), input_path],
+                    (Preview::Input, Some(input_path), _) => vec![input_path],
+                    _ => return Task::none(),
+                };
+                self.player = None;
+                self.preview_error = None;
+                self.device_error = None;
+                return Task::run(preview_process(preview, paths), Message::Player);
+            }
+            PlayerMessage::Loaded(Ok((preview, buffers))) => {
+                match playback::Player::new(&buffers, &self.output_device) {
+                    Ok(player) => {
+                        player.play();
+                        self.player = Some((preview, player));
+                    }
+                    Err(e) => return self.fail_device(preview, e),
+                }
+            }
+            PlayerMessage::Loaded(Err(e)) => self.preview_error = Some(e),
+            PlayerMessage::Toggle => {
+                if let Some((_, player)) = &self.player {
+                    if player.is_playing() {
+                        player.pause();
+                    } else {
+                        player.play();
+                    }
+                }
+            }
+            PlayerMessage::SwitchComparison => {
+                if let Some((_, player)) = &mut self.player {
+                    let next = (player.selected() + 1) % player.track_count();
+                    player.select(next);
+                }
+            }
+            PlayerMessage::Seek(seconds) => {
+                if let Some((_, player)) = &self.player {
+                    player.seek(Duration::from_secs_f32(seconds));
+                }
+            }
+            PlayerMessage::Tick => {
+                if let Some((preview, player)) = &mut self.player {
+                    player.update_meter();
+                    if let Some(e) = player.take_error() {
+                        let preview = *preview;
+                        self.player = None;
+                        return self.fail_device(preview, e);
+                    }
+                }
+            }
+            PlayerMessage::Close => {
+                self.player = None;
+            }
+            PlayerMessage::SeekFraction(fraction) => {
+                if let Some((_, player)) = &self.player {
+                    player.seek(player.duration().mul_f32(fraction));
+                }
+            }
+        }
+        Task::none()
+    }
+
+    /// Reports that the output device couldn't play `preview`, refreshing
+    /// the devices to pick another from.
+    fn fail_device(&mut self, preview: Preview, e: String) -> Task<Message> {
+        tracing::warn!("Preview failed on the output device: {}", e);
+        self.device_error = Some((preview, e));
+        Task::run(devices_process(), Message::Devices)
+    }
+
+    pub fn view_preview(&self) -> Element<'_, Message> {
+        if let Some((preview, player)) = &self.player {
+            let duration = player.duration();
+            let position = player.position().min(duration);
+            let controls = row![
+                button(text(if player.is_playing() {
+                    tr!("preview-pause")
+                } else {
+                    tr!("preview-play")
+                }))
+                .on_press(Message::Player(PlayerMessage::Toggle)),
+                text(match (preview, player.selected()) {
+                    (Preview::Input, _) => tr!("preview-input"),
+                    (Preview::Output, 0) => tr!("preview-cleaned"),
+                    (Preview::Output, _) => tr!("preview-original"),
+                    (Preview::Versions, 0) => tr!("compare-version-a"),
+                    (Preview::Versions, _) => tr!("compare-version-b"),
+                })
+                .size(12),
+                button(text("A/B").size(12)).on_press_maybe(
+                    (player.track_count() > 1)
+                        .then_some(Message::Player(PlayerMessage::SwitchComparison))
+                ),
+                slider(
+                    0.0..=duration.as_secs_f32(),
+                    position.as_secs_f32(),
+                    |seconds| Message::Player(PlayerMessage::Seek(seconds)),
+                )
+                .step(0.1),
+                text(format!(
+                    "{} / {}",
+                    format_duration(position),
+                    format_duration(duration)
+                ))
+                .size(12),
+                button(text(tr!("preview-close")).size(12))
+                    .on_press(Message::Player(PlayerMessage::Close))
+                    .style(button::text),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center);
+            let levels = canvas(meter::LevelMeter {
+                meter: player.meter(),
+                clipped: player.clipped(),
+            })
+            .width(Length::Fill)
+            .height(12);
+            return column![controls, levels].spacing(5).into();
+        }
+
+        let mut controls = row![].spacing(10).align_y(Alignment::Center);
+        if self.status.selected_file().is_some()
+            && !matches!(self.status, Status::Job(JobState::Processing(_)))
+        {
+            controls = controls.push(
+                button(text(tr!("preview-play-input")).size(12))
+                    .on_press(Message::Player(PlayerMessage::Load(Preview::Input))),
+            );
+        }
+        if let Status::Job(JobState::Done { .. }) = self.status {
+            controls = controls.push(
+                button(text(tr!("preview-play-output")).size(12))
+                    .on_press(Message::Player(PlayerMessage::Load(Preview::Output))),
+            );
+        }
+        if let Some(e) = &self.preview_error {
+            controls = controls.push(text(e).size(12).style(text::danger));
+        }
+        column![controls]
+            .push(self.view_device_error(false))
+            .spacing(5)
+            .into()
+    }
+
+    /// Why the output device couldn't play the preview, with a picker to
+    /// try it again on another. Only for the versions' preview when
+    /// `versions` is set, and only for the others when not.
+    pub fn view_device_error(&self, versions: bool) -> Option<Element<'_, Message>> {
+        let (preview, e) = self
+            .device_error
+            .as_ref()
+            .filter(|(preview, _)| (*preview == Preview::Versions) == versions)?;
+        Some(
+            column![
+                text(tr!("preview-device-failed", error = e))
+                    .size(12)
+                    .style(text::danger),
+                row![
+                    view_device_picker(
+                        tr!("device-output"),
+                        &self.output_devices,
+                        &self.output_device,
+                        |device| Message::Devices(DevicesMessage::OutputSelected(device)),
+                    ),
+                    button(text(tr!("preview-device-retry")).size(12))
+                        .on_press(Message::Player(PlayerMessage::Load(*preview))),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            ]
+            .spacing(5)
+            .into(),
+        )
+    }
+}
+
+/// Decodes the files for a preview off the UI thread.
+fn preview_process(preview: Preview, paths: Vec<PathBuf>) -> impl Stream<Item = PlayerMessage> {
+    blocking_stream(
+        move |_| {
+            let buffers = paths
+                .iter()
+                .map(|path| audio::decode(path).map(|(_, buffer)| buffer))
+                .collect::<Result<Vec<_>, _>>();
+            PlayerMessage::Loaded(buffers.map(|buffers| (preview, buffers)))
+        },
+        |e| PlayerMessage::Loaded(Err(e)),
+    )
+}
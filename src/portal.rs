@@ -0,0 +1,56 @@
+//! The desktop portal, through which the app reaches the rest of the desktop
+//! from a Flatpak sandbox, where `xdg-open` can't see the user's apps.
+
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use zbus::zvariant::{Fd, Value};
+
+async fn open_uri() -> Result<zbus::Proxy<'static>, String> {
+    let connection = zbus::Connection::session()
+        .await
+        .map_err(|e| e.to_string())?;
+    zbus::Proxy::new(
+        &connection,
+        "org.freedesktop.portal.Desktop",
+        "/org/freedesktop/portal/desktop",
+        "org.freedesktop.portal.OpenURI",
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Opens `target`, a link or a local file or folder, in the app the user
+/// picked for it.
+pub async fn open(target: OsString) -> Result<(), String> {
+    let proxy = open_uri().await?;
+    let options: HashMap<&str, Value> = HashMap::new();
+    let target_str = target.to_string_lossy();
+    if target_str.starts_with("http://") || target_str.starts_with("https://") {
+        proxy
+            .call_method("OpenURI", &("", target_str.as_ref(), options))
+            .await
+    } else {
+        // Paths inside the sandbox mean nothing outside it, so the portal
+        // takes the open file instead.
+        let file = std::fs::File::open(Path::new(&target)).map_err(|e| e.to_string())?;
+        proxy
+            .call_method("OpenFile", &("", Fd::from(&file), options))
+            .await
+    }
+    .map(|_| ())
+    .map_err(|e| e.to_string())
+}
+
+/// Opens the folder holding `path` in the file manager, with the file
+/// selected where the file manager supports it.
+pub async fn open_directory(path: PathBuf) -> Result<(), String> {
+    let proxy = open_uri().await?;
+    let options: HashMap<&str, Value> = HashMap::new();
+    let file = std::fs::File::open(&path).map_err(|e| e.to_string())?;
+    proxy
+        .call_method("OpenDirectory", &("", Fd::from(&file), options))
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,126 @@
+//! Keeping the machine awake while long-running work is in progress, and
+//! waking it for work set to start later.
+
+/// Holds a platform sleep inhibitor for as long as it is alive.
+pub struct SleepInhibitor {
+    #[cfg(not(windows))]
+    child: std::process::Child,
+}
+
+impl SleepInhibitor {
+    /// Asks the OS not to sleep, giving `reason` where the platform shows one.
+    /// Returns `None` if no inhibitor is available.
+    #[cfg(target_os = "linux")]
+    pub fn acquire(reason: &str) -> Option<Self> {
+        // `cat` holds the lock until its stdin is closed, so the lock cannot
+        // outlive this process.
+        std::process::Command::new("systemd-inhibit")
+            .arg("--what=sleep:idle")
+            .arg("--who=DeepFilterNet GUI")
+            .arg(format!("--why={}", reason))
+            .arg("--mode=block")
+            .arg("cat")
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .ok()
+            .map(|child| Self { child })
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn acquire(_reason: &str) -> Option<Self> {
+        std::process::Command::new("caffeinate")
+            .arg("-i")
+            .arg("-w")
+            .arg(std::process::id().to_string())
+            .spawn()
+            .ok()
+            .map(|child| Self { child })
+    }
+
+    #[cfg(windows)]
+    pub fn acquire(_reason: &str) -> Option<Self> {
+        use windows_sys::Win32::System::Power::{
+            ES_CONTINUOUS, ES_SYSTEM_REQUIRED, SetThreadExecutionState,
+        };
+
+        // SAFETY: only updates the calling thread's execution state.
+        let previous = unsafe { SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED) };
+        (previous != 0).then_some(Self {})
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+    pub fn acquire(_reason: &str) -> Option<Self> {
+        None
+    }
+}
+
+impl Drop for SleepInhibitor {
+    #[cfg(not(windows))]
+    fn drop(&mut self) {
+        drop(self.child.stdin.take());
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+
+    #[cfg(windows)]
+    fn drop(&mut self) {
+        use windows_sys::Win32::System::Power::{ES_CONTINUOUS, SetThreadExecutionState};
+
+        // SAFETY: only updates the calling thread's execution state.
+        unsafe { SetThreadExecutionState(ES_CONTINUOUS) };
+    }
+}
+
+/// Whether [`WakeTimer`] can wake the machine here.
+pub const WAKE_AVAILABLE: bool = cfg!(windows);
+
+/// Wakes the machine from sleep at a set time for as long as it is alive,
+/// where the system allows wake timers.
+pub struct WakeTimer {
+    /// The timer's handle, kept as a number so the timer can be sent
+    /// between threads.
+    #[cfg(windows)]
+    handle: isize,
+}
+
+impl WakeTimer {
+    /// Sets a timer waking the machine at `at`. Returns `None` if no wake
+    /// timer is available.
+    #[cfg(windows)]
+    pub fn set(at: std::time::SystemTime) -> Option<Self> {
+        use windows_sys::Win32::System::Threading::{CreateWaitableTimerW, SetWaitableTimer};
+
+        let delay = at.duration_since(std::time::SystemTime::now()).ok()?;
+        // SAFETY: creates an unnamed timer the returned value owns.
+        let handle = unsafe { CreateWaitableTimerW(std::ptr::null(), 0, std::ptr::null()) };
+        if handle.is_null() {
+            return None;
+        }
+        let timer = Self {
+            handle: handle as isize,
+        };
+        // Negative due times are relative, in steps of 100 ns.
+        let due = -((delay.as_nanos() / 100).min(i64::MAX as u128) as i64);
+        // SAFETY: the timer is open, and no completion routine is given.
+        let set =
+            unsafe { SetWaitableTimer(timer.handle as _, &due, 0, None, std::ptr::null(), 1) };
+        (set != 0).then_some(timer)
+    }
+
+    #[cfg(not(windows))]
+    pub fn set(_at: std::time::SystemTime) -> Option<Self> {
+        None
+    }
+}
+
+#[cfg(windows)]
+impl Drop for WakeTimer {
+    fn drop(&mut self) {
+        use windows_sys::Win32::Foundation::CloseHandle;
+
+        // SAFETY: the handle is open and owned by this timer.
+        unsafe { CloseHandle(self.handle as _) };
+    }
+}
@@ -0,0 +1,546 @@
+//! Cleaning the selected file and urgent files in the express lane, and
+//! what every run shares wherever it's started: the history it's noted in,
+//! the webhook told about it and the counts of the session.
+
+use crate::clean_screen::{peaks_process, pick_input};
+use crate::player::{PlayerMessage, Preview};
+use crate::{
+    DfnGui, JobState, Message, Screen, Status, blocking_stream, notification, playback, reveal,
+    toast,
+};
+use dfn_core::i18n::tr;
+use dfn_core::pipeline::{self, Phase, ProcessingOptions, RunReport};
+use dfn_core::{cancel, config, denoiser, download, history, webhook};
+use futures_util::Stream;
+use iced::widget::{button, column, container, progress_bar, row, text};
+use iced::{Alignment, Element, Length, Task};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+/// Processing speed accumulated over the runs of this session.
+#[derive(Debug, Default)]
+pub struct PerformanceStats {
+    pub runs: u32,
+    /// Runs that failed, not counting cancelled ones.
+    pub failed: u32,
+    pub audio: Duration,
+    pub elapsed: Duration,
+}
+
+impl PerformanceStats {
+    pub fn record(&mut self, report: &RunReport) {
+        self.runs += 1;
+        self.audio += report.audio_duration;
+        self.elapsed += report.elapsed;
+    }
+
+    pub fn realtime_factor(&self) -> f32 {
+        self.audio.as_secs_f32() / self.elapsed.as_secs_f32().max(f32::EPSILON)
+    }
+}
+
+/// A run in progress.
+#[derive(Debug)]
+pub struct Run {
+    pub input: PathBuf,
+    /// The options it was started with, kept for the history.
+    pub options: ProcessingOptions,
+    pub started: SystemTime,
+    pub phase: Phase,
+    pub progress: f32,
+    /// Stops the run when set.
+    pub cancel: cancel::CancelToken,
+}
+
+/// What a run or benchmark failure interrupted, which decides how it can be
+/// recovered from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// Cleaning the selected file failed.
+    Processing,
+    /// The engine went missing, so it has to be downloaded again.
+    MissingEngine,
+    /// The selected file couldn't be read.
+    Input,
+    Benchmark,
+}
+
+/// A run cleaning the selected file.
+#[derive(Debug, Clone)]
+pub enum ProcessingMessage {
+    Start,
+    Progress(Phase, f32),
+    Finished(Result<RunReport, String>),
+    /// Go back to the options of the finished run, to clean its input again.
+    Adjust,
+    /// The webhook got the summary of a run, or couldn't be reached.
+    SummarySent(Result<(), String>),
+}
+
+/// An urgent file cleaned right away, next to the run in progress.
+#[derive(Debug, Clone)]
+pub enum ExpressMessage {
+    Choose,
+    Chosen(Option<PathBuf>),
+    Cancel,
+    /// Progress and the result of the express run, as for the main one.
+    Run(ProcessingMessage),
+}
+
+impl DfnGui {
+    /// Tells about a finished run with a desktop notification if the window
+    /// is in the background.
+    pub fn notify_finished(&self, input: &Path, result: &Result<RunReport, String>) {
+        if self.window_focused || !self.notifications.when_done {
+            return;
+        }
+        let name = input
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let (summary, body) = match result {
+            Ok(report) => (
+                tr!("notification-done", name = name),
+                tr!("status-saved-to", path = report.output_path.display()),
+            ),
+            Err(e) => (
+                tr!("notification-failed", name = name),
+                e.lines().next().unwrap_or_default().to_string(),
+            ),
+        };
+        notification::show(summary, body, self.notifications.sound);
+    }
+
+    /// How a failed run can be recovered from: by downloading the engine
+    /// again if it's missing, or otherwise by retrying the file.
+    fn processing_failure(options: &ProcessingOptions) -> Failure {
+        if options.backend == denoiser::Backend::ExternalBinary
+            && download::installed_binary().is_err()
+        {
+            Failure::MissingEngine
+        } else {
+            Failure::Processing
+        }
+    }
+
+    /// Cleaning the selected file.
+    pub fn update_processing(&mut self, message: ProcessingMessage) -> Task<Message> {
+        match message {
+            ProcessingMessage::Start => {
+                let (input, options) = match &self.status {
+                    Status::Job(
+                        JobState::Ready(input)
+                        | JobState::Failed {
+                            input: Some(input), ..
+                        },
+                    ) => (input.clone(), self.options.clone()),
+                    // Cleaned again next to the earlier output.
+                    Status::Job(JobState::Adjusting { input, .. }) => (
+                        input.clone(),
+                        ProcessingOptions {
+                            version_outputs: true,
+                            ..self.options.clone()
+                        },
+                    ),
+                    _ => return Task::none(),
+                };
+                return self.start_run(input, options);
+            }
+            ProcessingMessage::Adjust => {
+                let Status::Job(JobState::Done {
+                    input,
+                    report,
+                    options,
+                }) = std::mem::take(&mut self.status)
+                else {
+                    return Task::none();
+                };
+                self.options = *options;
+                self.player = None;
+                self.output_peaks = None;
+                self.spectrograms = None;
+                self.status = Status::Job(JobState::Adjusting {
+                    input,
+                    previous: report.output_path,
+                });
+            }
+            ProcessingMessage::Progress(phase, progress) => {
+                if let Status::Job(JobState::Processing(run)) = &mut self.status {
+                    run.phase = phase;
+                    run.progress = progress;
+                }
+            }
+            ProcessingMessage::Finished(_) if self.exit_when_idle => {
+                return self.exit();
+            }
+            ProcessingMessage::Finished(result) => {
+                let run = match std::mem::take(&mut self.status) {
+                    Status::Job(JobState::Processing(run)) => run,
+                    status => {
+                        self.status = status;
+                        return Task::none();
+                    }
+                };
+                self.notify_finished(&run.input, &result);
+                self.record_run(&run, &result);
+                self.batch_done += 1;
+                let send_summary = self.send_summary(&run.input, &result);
+                match result {
+                    Ok(report) => {
+                        tracing::info!(
+                            "Saved {} after {:.1} s",
+                            report.output_path.display(),
+                            report.elapsed.as_secs_f32()
+                        );
+                        self.stats.record(&report);
+                        if let Some(archive) = &self.archive
+                            && archive.contains(&run.input)
+                            && let Err(e) = archive.finish(&run.input)
+                        {
+                            tracing::warn!("{}", e);
+                        }
+                        let path = report.output_path.clone();
+                        if self.after_cleaning.open_folder {
+                            reveal(&path);
+                        }
+                        self.status = Status::Job(JobState::Done {
+                            input: run.input,
+                            report,
+                            options: Box::new(run.options),
+                        });
+                        self.spectrograms = None;
+                        // The next file takes over the screen.
+                        if !self.queue.is_empty() && !self.control_paused && self.schedule.is_none()
+                        {
+                            return Task::batch([send_summary, self.start_queued()]);
+                        }
+                        if self.queue.is_empty() && self.workers.is_empty() {
+                            self.finish_batch(Some(&path));
+                        }
+                        let play = if playback::AVAILABLE && self.after_cleaning.play {
+                            Task::done(Message::Player(PlayerMessage::Load(Preview::Output)))
+                        } else {
+                            Task::none()
+                        };
+                        return Task::batch([
+                            Task::run(peaks_process(Preview::Output, path), Message::Clean),
+                            self.load_spectrograms(),
+                            send_summary,
+                            play,
+                        ]);
+                    }
+                    Err(e) => {
+                        let cancelled = e == cancel::CANCELLED;
+                        if cancelled {
+                            tracing::info!("Cleaning was cancelled");
+                        } else {
+                            tracing::error!("Cleaning failed: {}", e);
+                            self.stats.failed += 1;
+                        }
+                        self.show_error_details = false;
+                        self.show_full_log = false;
+                        self.status = Status::Job(JobState::Failed {
+                            failure: Self::processing_failure(&run.options),
+                            error: e,
+                            input: Some(run.input),
+                        });
+                        // A cancel stops the files waiting too.
+                        if cancelled || self.control_paused || self.schedule.is_some() {
+                            if self.queue.is_empty() && self.workers.is_empty() {
+                                self.reset_batch();
+                            }
+                            return send_summary;
+                        }
+                        if self.queue.is_empty() && self.workers.is_empty() {
+                            self.finish_batch(None);
+                        }
+                        return Task::batch([send_summary, self.start_queued()]);
+                    }
+                }
+            }
+            ProcessingMessage::SummarySent(Err(e)) => {
+                tracing::warn!("{}", e);
+                self.toasts.push(
+                    toast::Severity::Warning,
+                    tr!("toast-webhook-failed", error = e),
+                );
+            }
+            ProcessingMessage::SummarySent(Ok(())) => {}
+        }
+        Task::none()
+    }
+
+    /// Notes a run of `input` with `options` as started in the history, so
+    /// the next start can tell if it never finished.
+    pub fn begin_run(&mut self, input: &Path, options: &ProcessingOptions, started: SystemTime) {
+        let Some(history) = &self.history else {
+            return;
+        };
+        let output = pipeline::planned_output(input, options);
+        let begun = history
+            .lock()
+            .unwrap()
+            .begin(input, &output, options, started);
+        if let Err(e) = begun {
+            tracing::warn!("{}", e);
+        }
+    }
+
+    /// Stops offering the unfinished run `id`.
+    pub fn forget_unfinished(&mut self, id: i64) {
+        if let Some(Err(e)) = self
+            .history
+            .as_ref()
+            .map(|history| history.lock().unwrap().forget(id))
+        {
+            tracing::warn!("{}", e);
+        }
+    }
+
+    /// Posts the outcome of a run to the webhook, if one is set. Each run is
+    /// a batch of one file. Cancelled runs aren't reported.
+    pub fn send_summary(
+        &self,
+        input_path: &Path,
+        result: &Result<RunReport, String>,
+    ) -> Task<Message> {
+        if !self.webhook.is_set()
+            || result
+                .as_ref()
+                .err()
+                .is_some_and(|e| e == cancel::CANCELLED)
+        {
+            return Task::none();
+        }
+        let summary = webhook::summary(&[(input_path.to_path_buf(), result.clone())], &[]);
+        let webhook = self.webhook.clone();
+        Task::perform(
+            async move { webhook::post(&webhook, summary).await },
+            |result| Message::Processing(ProcessingMessage::SummarySent(result)),
+        )
+    }
+
+    /// Adds the finished run to the history.
+    pub fn record_run(&mut self, run: &Run, result: &Result<RunReport, String>) {
+        let Some(history) = &self.history else {
+            return;
+        };
+        let report = result.as_ref().ok();
+        let run = history::Run {
+            input_path: run.input.clone(),
+            output_path: report.map(|report| report.output_path.clone()),
+            audio_duration: report.map(|report| report.audio_duration),
+            elapsed: report.map(|report| report.elapsed),
+            options: run.options.clone(),
+            error: result.as_ref().err().cloned(),
+            started: run.started,
+            finished: SystemTime::now(),
+        };
+        let recorded = history.lock().unwrap().record(&run);
+        if let Err(e) = recorded {
+            self.toasts.push(toast::Severity::Warning, e);
+        } else if self.screen == Screen::History {
+            self.refresh_history();
+        }
+    }
+
+    /// Starts cleaning `input` with `options`.
+    pub fn start_run(&mut self, input: PathBuf, mut options: ProcessingOptions) -> Task<Message> {
+        self.player = None;
+        let denoiser = config::apply_folder_profile(&input, &mut options).and_then(|profile| {
+            if let Some(profile) = profile {
+                tracing::info!("Using the folder profile {}", profile.display());
+                self.toasts.push(
+                    toast::Severity::Info,
+                    tr!("toast-folder-profile", path = profile.display()),
+                );
+            }
+            options.denoiser(download::installed_binary().ok().as_deref())
+        });
+        match denoiser {
+            Ok(denoiser) => {
+                tracing::info!("Cleaning {} with {}", input.display(), options.backend);
+                self.engine_transcript = denoiser.transcript();
+                // Copies of it queued later are then spotted.
+                if options.skip_duplicates
+                    && let Err(e) = self.batch_seen.original_of(&input)
+                {
+                    tracing::warn!("{}", e);
+                }
+                let cancel = cancel::CancelToken::new();
+                let started = SystemTime::now();
+                self.begin_run(&input, &options, started);
+                self.status = Status::Job(JobState::Processing(Run {
+                    input: input.clone(),
+                    options: options.clone(),
+                    started,
+                    phase: Phase::default(),
+                    progress: 0.0,
+                    cancel: cancel.clone(),
+                }));
+                Task::run(
+                    processing_process(input, denoiser, options, cancel),
+                    Message::Processing,
+                )
+            }
+            Err(e) => {
+                tracing::error!("Could not start cleaning: {}", e);
+                self.status = Status::Job(JobState::Failed {
+                    failure: Self::processing_failure(&options),
+                    error: e,
+                    input: Some(input),
+                });
+                Task::none()
+            }
+        }
+    }
+
+    /// Cleaning an urgent file next to the run in progress.
+    pub fn update_express(&mut self, message: ExpressMessage) -> Task<Message> {
+        match message {
+            ExpressMessage::Choose => {
+                return Task::perform(pick_input(), |path| {
+                    Message::Express(ExpressMessage::Chosen(path))
+                });
+            }
+            ExpressMessage::Chosen(Some(input)) if self.express.is_none() => {
+                return self.start_express(input);
+            }
+            ExpressMessage::Chosen(_) => {}
+            ExpressMessage::Cancel => {
+                if let Some(run) = &self.express {
+                    run.cancel.cancel();
+                }
+            }
+            ExpressMessage::Run(ProcessingMessage::Progress(phase, progress)) => {
+                if let Some(run) = &mut self.express {
+                    run.phase = phase;
+                    run.progress = progress;
+                }
+            }
+            ExpressMessage::Run(ProcessingMessage::Finished(result)) => {
+                let Some(run) = self.express.take() else {
+                    return Task::none();
+                };
+                self.notify_finished(&run.input, &result);
+                self.record_run(&run, &result);
+                let send_summary = self.send_summary(&run.input, &result);
+                match &result {
+                    Ok(report) => {
+                        tracing::info!("Saved the express run {}", report.output_path.display());
+                        self.stats.record(report);
+                        if self.after_cleaning.open_folder {
+                            reveal(&report.output_path);
+                        }
+                        self.toasts.push(
+                            toast::Severity::Success,
+                            tr!("toast-express-done", path = report.output_path.display()),
+                        );
+                    }
+                    Err(e) if e == cancel::CANCELLED => {
+                        tracing::info!("The express run was cancelled");
+                    }
+                    Err(e) => {
+                        tracing::error!("The express run failed: {}", e);
+                        self.stats.failed += 1;
+                        self.toasts.push(
+                            toast::Severity::Error,
+                            tr!("toast-express-failed", error = e),
+                        );
+                    }
+                }
+                return send_summary;
+            }
+            ExpressMessage::Run(_) => {}
+        }
+        Task::none()
+    }
+
+    /// Starts cleaning `input` in the express lane with the current options,
+    /// leaving the run in progress and the queue as they are.
+    fn start_express(&mut self, input: PathBuf) -> Task<Message> {
+        let mut options = self.options.clone();
+        let denoiser = config::apply_folder_profile(&input, &mut options)
+            .and_then(|_| options.denoiser(download::installed_binary().ok().as_deref()));
+        match denoiser {
+            Ok(denoiser) => {
+                tracing::info!("Cleaning {} in the express lane", input.display());
+                let cancel = cancel::CancelToken::new();
+                let started = SystemTime::now();
+                self.begin_run(&input, &options, started);
+                self.express = Some(Run {
+                    input: input.clone(),
+                    options: options.clone(),
+                    started,
+                    phase: Phase::default(),
+                    progress: 0.0,
+                    cancel: cancel.clone(),
+                });
+                Task::run(
+                    processing_process(input, denoiser, options, cancel),
+                    |message| Message::Express(ExpressMessage::Run(message)),
+                )
+            }
+            Err(e) => {
+                tracing::error!("Could not start the express run: {}", e);
+                self.toasts.push(
+                    toast::Severity::Error,
+                    tr!("toast-express-failed", error = e),
+                );
+                Task::none()
+            }
+        }
+    }
+}
+
+/// The express run, set apart from the files waiting.
+pub fn view_express(run: &Run) -> Element<'_, Message> {
+    container(
+        row![
+            column![
+                text(tr!("express-label")).size(11).style(text::primary),
+                text(run.input.file_name().unwrap_or_default().to_string_lossy()),
+                text(tr!(
+                    "status-processing",
+                    phase = run.phase.label(),
+                    percent = format!("{:.0}", run.progress)
+                ))
+                .size(11),
+                progress_bar(0.0..=100.0, run.progress).girth(6),
+            ]
+            .spacing(4)
+            .width(Length::Fill),
+            button(text(tr!("status-cancel")).size(11))
+                .on_press(Message::Express(ExpressMessage::Cancel))
+                .style(button::secondary),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+    )
+    .padding(10)
+    .width(Length::Fill)
+    .style(container::bordered_box)
+    .into()
+}
+
+pub fn processing_process(
+    input_path: PathBuf,
+    denoiser: Arc<dyn denoiser::Denoiser>,
+    options: ProcessingOptions,
+    cancel: cancel::CancelToken,
+) -> impl Stream<Item = ProcessingMessage> {
+    blocking_stream(
+        move |send| {
+            let result = pipeline::run(
+                &input_path,
+                denoiser,
+                &options,
+                cancel,
+                &mut |phase, progress| send(ProcessingMessage::Progress(phase, progress)),
+            );
+            ProcessingMessage::Finished(result)
+        },
+        |e| ProcessingMessage::Finished(Err(e)),
+    )
+}
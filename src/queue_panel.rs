@@ -0,0 +1,715 @@
+//! The files waiting to be cleaned, each with its own options and
+//! priority, and the time of day they're set to start at.
+
+use crate::processing::{ProcessingMessage, Run, processing_process, view_express};
+use crate::{DfnGui, JobState, Message, Status, export_list, power, reveal, toast};
+use dfn_core::i18n::tr;
+use dfn_core::pipeline::{Phase, ProcessingOptions, RunReport};
+use dfn_core::{cancel, config, download, formats, queue, schedule};
+use iced::widget::{
+    button, checkbox, column, container, pick_list, progress_bar, row, text, text_input,
+};
+use iced::{Alignment, Element, Length, Task};
+use rfd::FileDialog;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// The files waiting, set to start later.
+pub struct Schedule {
+    at: SystemTime,
+    /// The time of day they start at, as shown.
+    pub time: String,
+    /// Keep the machine from sleeping until then.
+    pub keep_awake: bool,
+    /// Wakes the machine for the start, where the system allows it.
+    _wake: Option<power::WakeTimer>,
+}
+
+/// The time and choices of a start to set.
+#[derive(Debug, Default)]
+pub struct ScheduleForm {
+    time: String,
+    keep_awake: bool,
+    wake: bool,
+}
+
+/// Arranging the files waiting to be cleaned.
+#[derive(Debug, Clone)]
+pub enum QueueMessage {
+    /// Clean the next file waiting, once nothing else is.
+    Start,
+    SetPriority(queue::ItemId, queue::Priority),
+    Remove(queue::ItemId),
+    /// Save the files waiting as a list.
+    Export,
+    /// A list was saved where the user chose, or couldn't be.
+    Exported(Option<Result<PathBuf, String>>),
+    /// Queue the files of a saved list.
+    Import,
+    ImportFileChosen(Option<PathBuf>),
+    ScheduleTimeChanged(String),
+    KeepAwakeToggled(bool),
+    WakeToggled(bool),
+    /// Start the files waiting at the time entered.
+    Schedule,
+    CancelSchedule,
+    /// Checks whether it's time to start.
+    ScheduleTick,
+    /// Progress and the result of a file cleaned next to the selected one.
+    Worker(u64, ProcessingMessage),
+    CancelWorker(u64),
+}
+
+impl DfnGui {
+    pub fn update_queue(&mut self, message: QueueMessage) -> Task<Message> {
+        match message {
+            QueueMessage::Start => {
+                if self.is_idle() {
+                    self.schedule = None;
+                    return self.start_queued();
+                }
+            }
+            QueueMessage::ScheduleTimeChanged(time) => self.schedule_form.time = time,
+            QueueMessage::KeepAwakeToggled(enabled) => self.schedule_form.keep_awake = enabled,
+            QueueMessage::WakeToggled(enabled) => self.schedule_form.wake = enabled,
+            QueueMessage::Schedule => {
+                let form = &self.schedule_form;
+                let Some((hour, minute)) = schedule::parse_time(&form.time) else {
+                    self.toasts
+                        .push(toast::Severity::Error, tr!("toast-schedule-invalid"));
+                    return Task::none();
+                };
+                let at = match schedule::next(hour, minute) {
+                    Ok(at) => at,
+                    Err(e) => {
+                        self.toasts.push(toast::Severity::Error, e);
+                        return Task::none();
+                    }
+                };
+                let wake = if form.wake {
+                    let timer = power::WakeTimer::set(at);
+                    if timer.is_none() {
+                        self.toasts
+                            .push(toast::Severity::Warning, tr!("toast-schedule-no-wake"));
+                    }
+                    timer
+                } else {
+                    None
+                };
+                let time = format!("{}:{:02}", hour, minute);
+                tracing::info!("The files waiting start at {}", time);
+                self.toasts.push(
+                    toast::Severity::Info,
+                    tr!("toast-schedule-set", time = time.as_str()),
+                );
+                self.schedule = Some(Schedule {
+                    at,
+                    time,
+                    keep_awake: form.keep_awake,
+                    _wake: wake,
+                });
+            }
+            QueueMessage::CancelSchedule => self.schedule = None,
+            QueueMessage::ScheduleTick => {
+                if self
+                    .schedule
+                    .as_ref()
+                    .is_some_and(|schedule| schedule.at <= SystemTime::now())
+                {
+                    self.schedule = None;
+                    tracing::info!("Starting the files waiting, as scheduled");
+                    if self.is_idle() {
+                        return self.start_queued();
+                    }
+                }
+            }
+            QueueMessage::Worker(id, ProcessingMessage::Progress(phase, progress)) => {
+                if let Some(run) = self.workers.get_mut(&id) {
+                    run.phase = phase;
+                    run.progress = progress;
+                }
+            }
+            QueueMessage::Worker(id, ProcessingMessage::Finished(result)) => {
+                return self.finish_worker(id, result);
+            }
+            QueueMessage::Worker(..) => {}
+            QueueMessage::CancelWorker(id) => {
+                if let Some(run) = self.workers.get(&id) {
+                    run.cancel.cancel();
+                }
+            }
+            QueueMessage::SetPriority(id, priority) => self.queue.set_priority(id, priority),
+            QueueMessage::Remove(id) => {
+                self.queue.remove(id);
+                if self.queue.is_empty() {
+                    self.schedule = None;
+                    // Removing the last file waiting ends a stopped batch.
+                    if self.is_idle() && self.workers.is_empty() {
+                        self.reset_batch();
+                    }
+                }
+            }
+            QueueMessage::Export => {
+                return export_list(self.queue.ordered().map(|item| item.input.clone()));
+            }
+            QueueMessage::Exported(Some(Ok(path))) => {
+                self.toasts.push(
+                    toast::Severity::Success,
+                    tr!("toast-list-exported", path = path.display()),
+                );
+            }
+            QueueMessage::Exported(Some(Err(e))) => {
+                tracing::warn!("{}", e);
+                self.toasts.push(toast::Severity::Error, e);
+            }
+            QueueMessage::Exported(None) => {}
+            QueueMessage::Import => {
+                return Task::perform(
+                    async {
+                        FileDialog::new()
+                            .add_filter("M3U", &["m3u", "m3u8"])
+                            .add_filter(tr!("file-filter-text"), &["txt"])
+                            .pick_file()
+                    },
+                    |path| Message::Queue(QueueMessage::ImportFileChosen(path)),
+                );
+            }
+            QueueMessage::ImportFileChosen(Some(path)) => match queue::read_list(&path) {
+                Ok(inputs) => {
+                    let total = inputs.len();
+                    let mut queued = 0;
+                    for input in inputs {
+                        if input.is_file() && formats::is_supported(&input) {
+                            if self.options.skip_duplicates && self.skips_duplicate(&input) {
+                                continue;
+                            }
+                            self.queue.push(
+                                input,
+                                self.options.clone(),
+                                queue::Priority::default(),
+                            );
+                            queued += 1;
+                        } else {
+                            tracing::warn!("Skipped {} from the list", input.display());
+                        }
+                    }
+                    tracing::info!("Queued {} files from {}", queued, path.display());
+                    self.toasts.push(
+                        if queued == total {
+                            toast::Severity::Success
+                        } else {
+                            toast::Severity::Warning
+                        },
+                        tr!("toast-list-imported", count = queued, total = total),
+                    );
+                }
+                Err(e) => {
+                    tracing::warn!("{}", e);
+                    self.toasts.push(toast::Severity::Error, e);
+                }
+            },
+            QueueMessage::ImportFileChosen(None) => {}
+        }
+        Task::none()
+    }
+
+    pub fn queue_file(&mut self, path: PathBuf, options: ProcessingOptions) {
+        if options.skip_duplicates && self.skips_duplicate(&path) {
+            return;
+        }
+        if let Some(name) = path.file_name() {
+            self.toasts.push(
+                toast::Severity::Info,
+                tr!("toast-file-queued", name = name.to_string_lossy()),
+            );
+        }
+        self.queue.push(path, options, queue::Priority::default());
+    }
+
+    /// Whether `input` is a copy of a file already in the batch, which is
+    /// then left out and listed once the batch is done.
+    fn skips_duplicate(&mut self, input: &Path) -> bool {
+        match self.batch_seen.original_of(input) {
+            // The same file queued again is cleaned again.
+            Ok(Some(original)) if original != input => {
+                tracing::info!(
+                    "Skipped {}, a copy of {}",
+                    input.display(),
+                    original.display()
+                );
+                if let Some(name) = input.file_name() {
+                    self.toasts.push(
+                        toast::Severity::Info,
+                        tr!("toast-duplicate-skipped", name = name.to_string_lossy()),
+                    );
+                }
+                self.batch_duplicates.push((input.to_path_buf(), original));
+                true
+            }
+            Ok(_) => false,
+            // Cleaning reports the file if it really can't be read.
+            Err(e) => {
+                tracing::warn!("{}", e);
+                false
+            }
+        }
+    }
+
+    /// Selects the next file waiting and cleans it with its own options.
+    pub fn start_queued(&mut self) -> Task<Message> {
+        let Some(item) = self.queue.take_next() else {
+            return Task::none();
+        };
+        let select = self.select_file(item.input.clone());
+        if matches!(self.status, Status::Job(JobState::Ready(_))) {
+            let start = self.start_run(item.input.clone(), item.options);
+            // A file that can't be started, like one refused by its folder
+            // profile, doesn't hold up the files after it.
+            if let Status::Job(JobState::Failed { error, .. }) = &self.status {
+                self.stats.failed += 1;
+                self.batch_done += 1;
+                self.toasts.push(
+                    toast::Severity::Error,
+                    tr!(
+                        "toast-worker-failed",
+                        name = item.input.file_name().unwrap_or_default().to_string_lossy(),
+                        error = error.clone()
+                    ),
+                );
+                if self.queue.is_empty() && self.workers.is_empty() {
+                    self.finish_batch(None);
+                }
+                return Task::batch([select, start, self.start_queued()]);
+            }
+            return Task::batch([select, start, self.fill_workers()]);
+        }
+        select
+    }
+
+    /// Starts files waiting next to the selected one until as many are
+    /// cleaned at once as the concurrency allows.
+    fn fill_workers(&mut self) -> Task<Message> {
+        let mut starts = Vec::new();
+        while matches!(self.status, Status::Job(JobState::Processing(_)))
+            && self.workers.len() + 1 < self.batches.concurrency
+            && !self.control_paused
+            && self.schedule.is_none()
+        {
+            let Some(item) = self.queue.take_next() else {
+                break;
+            };
+            starts.push(self.start_worker(item.input, item.options));
+        }
+        Task::batch(starts)
+    }
+
+    /// Starts cleaning `input` next to the selected file.
+    fn start_worker(&mut self, input: PathBuf, mut options: ProcessingOptions) -> Task<Message> {
+        let denoiser = config::apply_folder_profile(&input, &mut options)
+            .and_then(|_| options.denoiser(download::installed_binary().ok().as_deref()));
+        match denoiser {
+            Ok(denoiser) => {
+                tracing::info!("Cleaning {} with {}", input.display(), options.backend);
+                if options.skip_duplicates
+                    && let Err(e) = self.batch_seen.original_of(&input)
+                {
+                    tracing::warn!("{}", e);
+                }
+                let cancel = cancel::CancelToken::new();
+                let started = SystemTime::now();
+                self.begin_run(&input, &options, started);
+                let id = self.next_worker;
+                self.next_worker += 1;
+                self.workers.insert(
+                    id,
+                    Run {
+                        input: input.clone(),
+                        options: options.clone(),
+                        started,
+                        phase: Phase::default(),
+                        progress: 0.0,
+                        cancel: cancel.clone(),
+                    },
+                );
+                Task::run(
+                    processing_process(input, denoiser, options, cancel),
+                    move |message| Message::Queue(QueueMessage::Worker(id, message)),
+                )
+            }
+            Err(e) => {
+                tracing::error!("Could not start cleaning {}: {}", input.display(), e);
+                self.stats.failed += 1;
+                self.toasts.push(
+                    toast::Severity::Error,
+                    tr!(
+                        "toast-worker-failed",
+                        name = input.file_name().unwrap_or_default().to_string_lossy(),
+                        error = e
+                    ),
+                );
+                Task::none()
+            }
+        }
+    }
+
+    /// Takes in the result of a file cleaned next to the selected one, and
+    /// starts the next file waiting in its place.
+    fn finish_worker(&mut self, id: u64, result: Result<RunReport, String>) -> Task<Message> {
+        let Some(run) = self.workers.remove(&id) else {
+            return Task::none();
+        };
+        self.notify_finished(&run.input, &result);
+        self.record_run(&run, &result);
+        self.batch_done += 1;
+        let send_summary = self.send_summary(&run.input, &result);
+        let name = run.input.file_name().unwrap_or_default().to_string_lossy();
+        let mut cancelled = false;
+        let output = match result {
+            Ok(report) => {
+                tracing::info!(
+                    "Saved {} after {:.1} s",
+                    report.output_path.display(),
+                    report.elapsed.as_secs_f32()
+                );
+                self.stats.record(&report);
+                if let Some(archive) = &self.archive
+                    && archive.contains(&run.input)
+                    && let Err(e) = archive.finish(&run.input)
+                {
+                    tracing::warn!("{}", e);
+                }
+                if self.after_cleaning.open_folder {
+                    reveal(&report.output_path);
+                }
+                Some(report.output_path)
+            }
+            Err(e) if e == cancel::CANCELLED => {
+                tracing::info!("Cleaning {} was cancelled", run.input.display());
+                cancelled = true;
+                None
+            }
+            Err(e) => {
+                tracing::error!("Cleaning {} failed: {}", run.input.display(), e);
+                self.stats.failed += 1;
+                self.toasts.push(
+                    toast::Severity::Error,
+                    tr!("toast-worker-failed", name = name, error = e),
+                );
+                None
+            }
+        };
+        let processing = matches!(self.status, Status::Job(JobState::Processing(_)));
+        if cancelled || self.control_paused || self.schedule.is_some() {
+            if !processing && self.queue.is_empty() && self.workers.is_empty() {
+                self.reset_batch();
+            }
+            return send_summary;
+        }
+        if !processing {
+            if self.queue.is_empty() && self.workers.is_empty() {
+                self.finish_batch(output.as_deref());
+            } else if self.is_idle() {
+                return Task::batch([send_summary, self.start_queued()]);
+            }
+            return send_summary;
+        }
+        Task::batch([send_summary, self.fill_workers()])
+    }
+
+    /// The express run, if any, then the files waiting in the order they'll
+    /// be cleaned.
+    pub fn view_queue(&self) -> Element<'_, Message> {
+        let header = row![
+            text(tr!("queue-title", count = self.queue.len()))
+                .size(14)
+                .width(Length::Fill),
+            button(text(tr!("queue-export")).size(12))
+                .on_press(Message::Queue(QueueMessage::Export))
+                .style(button::secondary),
+        ]
+        .push(self.is_idle().then(|| {
+            button(text(tr!("queue-start")).size(12)).on_press(Message::Queue(QueueMessage::Start))
+        }))
+        .spacing(10)
+        .align_y(Alignment::Center);
+        self.queue
+            .ordered()
+            .fold(
+                column![header]
+                    .push(self.view_schedule())
+                    .push(self.express.as_ref().map(view_express))
+                    .extend(self.workers.iter().map(|(id, run)| view_worker(*id, run)))
+                    .spacing(8),
+                |items, item| items.push(view_queue_item(item)),
+            )
+            .into()
+    }
+
+    /// When the files waiting start, or a form to set it while they could
+    /// be started now.
+    fn view_schedule(&self) -> Option<Element<'_, Message>> {
+        if let Some(schedule) = &self.schedule {
+            return Some(
+                row![
+                    text(tr!("schedule-waiting", time = schedule.time.as_str()))
+                        .size(12)
+                        .width(Length::Fill),
+                    button(text(tr!("schedule-cancel")).size(12))
+                        .on_press(Message::Queue(QueueMessage::CancelSchedule))
+                        .style(button::secondary),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center)
+                .into(),
+            );
+        }
+        if !self.is_idle() {
+            return None;
+        }
+        let form = &self.schedule_form;
+        let options = row![
+            checkbox(form.keep_awake)
+                .label(tr!("schedule-keep-awake"))
+                .on_toggle(|enabled| Message::Queue(QueueMessage::KeepAwakeToggled(enabled)))
+                .text_size(12)
+        ]
+        .push(power::WAKE_AVAILABLE.then(|| {
+            checkbox(form.wake)
+                .label(tr!("schedule-wake"))
+                .on_toggle(|enabled| Message::Queue(QueueMessage::WakeToggled(enabled)))
+                .text_size(12)
+        }))
+        .spacing(15);
+        Some(
+            column![
+                row![
+                    button(text(tr!("schedule-start-at")).size(12))
+                        .on_press(Message::Queue(QueueMessage::Schedule))
+                        .style(button::secondary),
+                    text_input("2:00", &form.time)
+                        .on_input(|time| Message::Queue(QueueMessage::ScheduleTimeChanged(time)))
+                        .on_submit(Message::Queue(QueueMessage::Schedule))
+                        .size(12)
+                        .width(Length::Fixed(70.0)),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+                options,
+            ]
+            .spacing(8)
+            .into(),
+        )
+    }
+}
+
+fn view_queue_item(item: &queue::Item) -> Element<'_, Message> {
+    let id = item.id;
+    let folder = match &item.options.output_dir {
+        Some(dir) => tr!("queue-in-folder", path = dir.display()),
+        None => tr!("queue-next-to-input"),
+    };
+    container(
+        row![
+            column![
+                text(item.input.file_name().unwrap_or_default().to_string_lossy()),
+                text(tr!(
+                    "queue-settings",
+                    backend = item.options.backend,
+                    reduction = format!("{:.0}", item.options.noise_reduction),
+                    folder = folder
+                ))
+                .size(11),
+            ]
+            .spacing(4)
+            .width(Length::Fill),
+            pick_list(queue::Priority::ALL, Some(item.priority), move |priority| {
+                Message::Queue(QueueMessage::SetPriority(id, priority))
+            })
+            .text_size(12),
+            button(text(tr!("queue-remove")).size(11))
+                .on_press(Message::Queue(QueueMessage::Remove(id)))
+                .style(button::secondary),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+    )
+    .padding(10)
+    .width(Length::Fill)
+    .style(container::bordered_box)
+    .into()
+}
+
+/// A file cleaned next to the selected one.
+fn view_worker(id: u64, run: &Run) -> Element<'_, Message> {
+    container(
+        row![
+            column![
+                text(run.input.file_name().unwrap_or_default().to_string_lossy()),
+                text(tr!(
+                    "status-processing",
+                    phase = run.phase.label(),
+                    percent = format!("{:.0}", run.progress)
+                ))
+                .size(11),
+                progress_bar(0.0..=100.0, run.progress).girth(6),
+            ]
+            .spacing(4)
+            .width(Length::Fill),
+            button(text(tr!("status-cancel")).size(11))
+                .on_press(Message::Queue(QueueMessage::CancelWorker(id)))
+                .style(button::secondary),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+    )
+    .padding(10)
+    .width(Length::Fill)
+    .style(container::bordered_box)
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings;
+    use dfn_core::denoiser;
+
+    #[test]
+    fn removing_the_last_file_calls_off_its_start() {
+        let mut gui = DfnGui::default();
+        gui.queue.push(
+            PathBuf::from("/audio/talk.wav"),
+            ProcessingOptions::default(),
+            queue::Priority::default(),
+        );
+        let _ = gui.update_queue(QueueMessage::ScheduleTimeChanged("2:00".to_string()));
+        let _ = gui.update_queue(QueueMessage::Schedule);
+        assert_eq!(
+            gui.schedule.as_ref().map(|schedule| schedule.time.as_str()),
+            Some("2:00")
+        );
+
+        let id = gui.queue.ordered().next().unwrap().id;
+        let _ = gui.update_queue(QueueMessage::Remove(id));
+        assert!(gui.queue.is_empty());
+        assert!(gui.schedule.is_none());
+    }
+
+    #[test]
+    fn start_times_must_be_times_of_day() {
+        let mut gui = DfnGui::default();
+        let _ = gui.update_queue(QueueMessage::ScheduleTimeChanged("25:00".to_string()));
+        let _ = gui.update_queue(QueueMessage::Schedule);
+        assert!(gui.schedule.is_none());
+    }
+
+    #[test]
+    fn lists_queue_the_files_that_can_be_cleaned() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("talk.wav"), b"").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), b"").unwrap();
+        let list = dir.path().join("list.txt");
+        std::fs::write(&list, "talk.wav\nnotes.txt\nmissing.wav\n").unwrap();
+        let mut gui = DfnGui::default();
+        gui.options.noise_reduction = 40.0;
+
+        let _ = gui.update_queue(QueueMessage::ImportFileChosen(Some(list)));
+        let queued: Vec<_> = gui.queue.ordered().collect();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].input, dir.path().join("talk.wav"));
+        assert_eq!(queued[0].options.noise_reduction, 40.0);
+    }
+
+    #[test]
+    fn copies_are_left_out_and_listed_when_the_batch_is_done() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = |name: &str, data: &[u8]| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, data).unwrap();
+            path
+        };
+        let talk = file("talk.wav", b"take one");
+        let copy = file("copy.wav", b"take one");
+        let other = file("other.wav", b"take two");
+        let mut gui = DfnGui::default();
+        gui.options.skip_duplicates = true;
+
+        for input in [&talk, &copy, &other, &talk] {
+            gui.queue_file(input.clone(), gui.options.clone());
+        }
+        let queued: Vec<_> = gui.queue.ordered().map(|item| item.input.clone()).collect();
+        assert_eq!(queued, [talk.clone(), other, talk.clone()]);
+
+        gui.finish_batch(None);
+        assert_eq!(gui.skipped_duplicates, [(copy.clone(), talk)]);
+
+        // The next batch doesn't remember this one.
+        let before = gui.queue.len();
+        gui.queue_file(copy, gui.options.clone());
+        assert_eq!(gui.queue.len(), before + 1);
+    }
+
+    #[test]
+    fn files_are_cleaned_as_many_at_once_as_set() {
+        let mut gui = DfnGui {
+            status: Status::Job(JobState::Idle),
+            batches: settings::Batches { concurrency: 2 },
+            window_focused: true,
+            ..DfnGui::default()
+        };
+        gui.options.backend = denoiser::Backend::Remote;
+        for name in ["a.mp3", "b.mp3", "c.mp3"] {
+            gui.queue.push(
+                PathBuf::from("/audio").join(name),
+                gui.options.clone(),
+                queue::Priority::default(),
+            );
+        }
+
+        let _ = gui.update_queue(QueueMessage::Start);
+        assert!(matches!(gui.status, Status::Job(JobState::Processing(_))));
+        assert_eq!(gui.workers.len(), 1);
+        assert_eq!(gui.queue.len(), 1);
+
+        // The next file waiting takes the place of one that's done.
+        let id = *gui.workers.keys().next().unwrap();
+        let _ = gui.update_queue(QueueMessage::Worker(
+            id,
+            ProcessingMessage::Finished(Err("The server is gone".to_string())),
+        ));
+        assert_eq!(gui.workers.len(), 1);
+        assert!(gui.queue.is_empty());
+        assert_eq!(gui.batch_done, 1);
+    }
+
+    #[test]
+    fn a_file_that_cannot_start_does_not_stall_the_batch() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(".dfn_gui.toml"),
+            "output_dir = \"/elsewhere\"\n",
+        )
+        .unwrap();
+        let mut gui = DfnGui {
+            status: Status::Job(JobState::Idle),
+            window_focused: true,
+            ..DfnGui::default()
+        };
+        gui.options.backend = denoiser::Backend::Remote;
+        for input in [
+            dir.path().join("refused.mp3"),
+            PathBuf::from("/audio/b.mp3"),
+        ] {
+            gui.queue
+                .push(input, gui.options.clone(), queue::Priority::default());
+        }
+
+        let _ = gui.update_queue(QueueMessage::Start);
+        let Status::Job(JobState::Processing(run)) = &gui.status else {
+            panic!("the next file was not started");
+        };
+        assert_eq!(run.input, Path::new("/audio/b.mp3"));
+        assert_eq!(gui.stats.failed, 1);
+        assert!(gui.queue.is_empty());
+    }
+}
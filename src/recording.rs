@@ -0,0 +1,264 @@
+//! Recording from an input device straight to a WAV file. Recording is only
+//! compiled in with the `recording` feature; without it [`Recorder::start`]
+//! always fails and the record controls are hidden.
+
+use crate::devices::Device;
+use crate::meter::Meter;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Whether this build can record audio.
+pub const AVAILABLE: bool = cfg!(feature = "recording");
+
+#[cfg(feature = "recording")]
+pub use imp::{Recorder, input_devices};
+#[cfg(not(feature = "recording"))]
+pub use stub::{Recorder, input_devices};
+
+/// Where a new recording started now is saved: the user's audio folder, or
+/// the app's data folder if there is none.
+pub fn new_recording_path() -> Result<PathBuf, String> {
+    let folder = directories::UserDirs::new()
+        .and_then(|dirs| dirs.audio_dir().map(Path::to_path_buf))
+        .or_else(|| dfn_core::data_dir().map(|dir| dir.join("recordings")))
+        .ok_or("Could not find a folder for recordings")?;
+    std::fs::create_dir_all(&folder)
+        .map_err(|e| format!("Could not create {}: {}", folder.display(), e))?;
+
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    Ok(folder.join(format!("recording-{}.wav", seconds)))
+}
+
+#[cfg(feature = "recording")]
+mod imp {
+    use super::*;
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+    use cpal::{FromSample, SampleFormat, SizedSample, Stream, StreamConfig};
+    use std::sync::mpsc;
+    use std::thread::JoinHandle;
+    use std::time::Instant;
+
+    /// Input devices of the default host, starting with the system default.
+    pub fn input_devices() -> Vec<Device> {
+        let mut devices = vec![Device::Default];
+        if let Ok(found) = cpal::default_host().input_devices() {
+            devices.extend(
+                found
+                    .filter_map(|device| device.name().ok())
+                    .map(Device::Named),
+            );
+        }
+        devices
+    }
+
+    /// A running recording. Captured audio is handed to a writer thread, which
+    /// saves it as 16-bit PCM and reports levels as it goes.
+    pub struct Recorder {
+        // Dropping the stream stops the capture.
+        stream: Stream,
+        writer: JoinHandle<Result<(), String>>,
+        path: PathBuf,
+        started: Instant,
+        meter_receiver: mpsc::Receiver<Meter>,
+        meter: Meter,
+        clipped: bool,
+    }
+
+    impl Recorder {
+        /// Starts recording `device` to `path`.
+        pub fn start(device: &Device, path: &Path) -> Result<Self, String> {
+            let host = cpal::default_host();
+            let device = match device {
+                Device::Default => host.default_input_device(),
+                Device::Named(name) => host.input_devices().ok().and_then(|mut devices| {
+                    devices.find(|device| device.name().ok().as_ref() == Some(name))
+                }),
+            }
+            .ok_or("The input device is not available")?;
+            let supported = device
+                .default_input_config()
+                .map_err(|e| format!("Could not configure the input device: {}", e))?;
+            let config: StreamConfig = supported.config();
+
+            let spec = hound::WavSpec {
+                channels: config.channels,
+                sample_rate: config.sample_rate.0,
+                bits_per_sample: 16,
+                sample_format: hound::SampleFormat::Int,
+            };
+            let mut wav = hound::WavWriter::create(path, spec)
+                .map_err(|e| format!("Could not create WAV file: {}", e))?;
+
+            let (sender, receiver) = mpsc::channel::<Result<Vec<f32>, String>>();
+            let (meter_sender, meter_receiver) = mpsc::channel();
+            let channels = config.channels as usize;
+            let writer = std::thread::spawn(move || {
+                for chunk in receiver {
+                    let chunk = chunk?;
+                    for &sample in &chunk {
+                        let value = (sample * 32768.0).round().clamp(-32768.0, 32767.0) as i16;
+                        wav.write_sample(value)
+                            .map_err(|e| format!("Could not write WAV samples: {}", e))?;
+                    }
+                    let _ = meter_sender.send(measure(&chunk, channels));
+                }
+                wav.finalize()
+                    .map_err(|e| format!("Could not finalize WAV file: {}", e))
+            });
+
+            let stream = match supported.sample_format() {
+                SampleFormat::F32 => build::<f32>(&device, &config, sender),
+                SampleFormat::I16 => build::<i16>(&device, &config, sender),
+                SampleFormat::U16 => build::<u16>(&device, &config, sender),
+                SampleFormat::I32 => build::<i32>(&device, &config, sender),
+                SampleFormat::U8 => build::<u8>(&device, &config, sender),
+                format => Err(format!("Unsupported input sample format {}", format)),
+            }
+            .and_then(|stream| {
+                stream
+                    .play()
+                    .map_err(|e| format!("Could not start recording: {}", e))
+                    .map(|_| stream)
+            });
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    // The writer ends once the stream is gone; don't leave
+                    // its empty file behind.
+                    let _ = writer.join();
+                    let _ = std::fs::remove_file(path);
+                    return Err(e);
+                }
+            };
+
+            Ok(Self {
+                stream,
+                writer,
+                path: path.to_path_buf(),
+                started: Instant::now(),
+                meter_receiver,
+                meter: Meter::default(),
+                clipped: false,
+            })
+        }
+
+        pub fn elapsed(&self) -> Duration {
+            self.started.elapsed()
+        }
+
+        /// Takes in the meter readings sent since the last call.
+        pub fn update_meter(&mut self) {
+            for meter in self.meter_receiver.try_iter() {
+                self.clipped |= meter.is_clipping();
+                self.meter = meter;
+            }
+        }
+
+        pub fn meter(&self) -> Meter {
+            self.meter
+        }
+
+        /// Whether the input clipped since recording started.
+        pub fn clipped(&self) -> bool {
+            self.clipped
+        }
+
+        /// Stops recording and returns the finished file.
+        pub fn stop(self) -> Result<PathBuf, String> {
+            drop(self.stream);
+            self.writer
+                .join()
+                .map_err(|_| "The recording writer crashed".to_string())??;
+            Ok(self.path)
+        }
+    }
+
+    /// Levels of a block of interleaved samples. Mono shows the same level on
+    /// both sides; channels beyond the second are ignored.
+    fn measure(samples: &[f32], channels: usize) -> Meter {
+        let channels = channels.max(1);
+        let mut peak = [0.0f32; 2];
+        let mut squares = [0.0f32; 2];
+        for frame in samples.chunks(channels) {
+            for side in 0..2 {
+                let sample = frame[side.min(frame.len() - 1)];
+                peak[side] = peak[side].max(sample.abs());
+                squares[side] += sample * sample;
+            }
+        }
+        let frames = (samples.len() / channels).max(1) as f32;
+        Meter {
+            peak,
+            rms: squares.map(|squares| (squares / frames).sqrt()),
+        }
+    }
+
+    fn build<T>(
+        device: &cpal::Device,
+        config: &StreamConfig,
+        sender: mpsc::Sender<Result<Vec<f32>, String>>,
+    ) -> Result<Stream, String>
+    where
+        T: SizedSample,
+        f32: FromSample<T>,
+    {
+        let error_sender = sender.clone();
+        device
+            .build_input_stream(
+                config,
+                move |data: &[T], _| {
+                    let _ = sender.send(Ok(data
+                        .iter()
+                        .map(|sample| sample.to_sample::<f32>())
+                        .collect()));
+                },
+                move |e| {
+                    let _ = error_sender.send(Err(format!("Recording failed: {}", e)));
+                },
+                None,
+            )
+            .map_err(|e| format!("Could not open the input device: {}", e))
+    }
+}
+
+#[cfg(not(feature = "recording"))]
+mod stub {
+    use super::*;
+
+    pub fn input_devices() -> Vec<Device> {
+        vec![Device::Default]
+    }
+
+    /// Stands in for the real recorder in builds without recording. It
+    /// can't be constructed.
+    pub enum Recorder {}
+
+    impl Recorder {
+        pub fn start(_device: &Device, _path: &Path) -> Result<Self, String> {
+            Err("This build was made without audio recording".to_string())
+        }
+
+        pub fn elapsed(&self) -> Duration {
+            match *self {}
+        }
+
+        pub fn update_meter(&mut self) {
+            match *self {}
+        }
+
+        pub fn meter(&self) -> Meter {
+            match *self {}
+        }
+
+        pub fn clipped(&self) -> bool {
+            match *self {}
+        }
+
+        pub fn stop(self) -> Result<PathBuf, String> {
+            match self {}
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! Recording from a microphone, with a level meter, and cleaning the
+//! recording once it's stopped.
+
+use crate::processing::ProcessingMessage;
+use crate::{DfnGui, JobState, Message, Status, format_duration, meter, recording, toast};
+use dfn_core::i18n::tr;
+use iced::widget::{button, canvas, column, row, text};
+use iced::{Alignment, Element, Length, Task};
+
+#[derive(Debug, Clone)]
+pub enum RecordingMessage {
+    Start,
+    /// Stop recording and clean the recording.
+    Stop,
+    Tick,
+}
+
+impl DfnGui {
+    pub fn update_recording(&mut self, message: RecordingMessage) -> Task<Message> {
+        match message {
+            RecordingMessage::Start => {
+                self.player = None;
+                self.recording_error = None;
+                match recording::new_recording_path()
+                    .and_then(|path| recording::Recorder::start(&self.input_device, &path))
+                {
+                    Ok(recorder) => self.recorder = Some(recorder),
+                    Err(e) => self.recording_error = Some(e),
+                }
+            }
+            RecordingMessage::Stop => {
+                if let Some(recorder) = self.recorder.take() {
+                    match recorder.stop() {
+                        // Don't take over the input of a run started meanwhile.
+                        Ok(path) if self.is_running_engine() => {
+                            self.toasts.push(
+                                toast::Severity::Success,
+                                tr!("recording-saved", path = path.display()),
+                            );
+                        }
+                        Ok(path) => {
+                            let select = self.select_file(path);
+                            if matches!(self.status, Status::Job(JobState::Ready(_))) {
+                                return Task::batch([
+                                    select,
+                                    self.update_processing(ProcessingMessage::Start),
+                                ]);
+                            }
+                            return select;
+                        }
+                        Err(e) => self.recording_error = Some(e),
+                    }
+                }
+            }
+            RecordingMessage::Tick => {
+                if let Some(recorder) = &mut self.recorder {
+                    recorder.update_meter();
+                }
+            }
+        }
+        Task::none()
+    }
+
+    pub fn view_recording(&self) -> Element<'_, Message> {
+        if let Some(recorder) = &self.recorder {
+            let controls = row![
+                button(text(tr!("recording-stop")))
+                    .on_press(Message::Recording(RecordingMessage::Stop))
+                    .style(button::danger),
+                text(tr!(
+                    "recording-elapsed",
+                    elapsed = format_duration(recorder.elapsed())
+                ))
+                .size(12),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center);
+            let levels = canvas(meter::LevelMeter {
+                meter: recorder.meter(),
+                clipped: recorder.clipped(),
+            })
+            .width(Length::Fill)
+            .height(12);
+            return column![controls, levels].spacing(5).into();
+        }
+
+        let mut controls = row![
+            button(text(tr!("recording-start")).size(12)).on_press_maybe(
+                self.is_idle()
+                    .then_some(Message::Recording(RecordingMessage::Start))
+            ),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+        if let Some(e) = &self.recording_error {
+            controls = controls.push(text(e).size(12).style(text::danger));
+        }
+        controls.into()
+    }
+}
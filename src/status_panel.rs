@@ -0,0 +1,663 @@
+//! What the clean screen tells of the selected file: how its run is going
+//! or went, what failed with the ways to recover and to report it, and the
+//! benchmark. The status bar under every screen sums up the versions and
+//! the session.
+
+use crate::clean_screen::CleanMessage;
+use crate::downloader::{DownloadMessage, view_offline};
+use crate::processing::{ExpressMessage, Failure, ProcessingMessage};
+use crate::{
+    Benchmark, DfnGui, EngineState, JobState, Message, Status, blocking_stream, cancel_button,
+    format_duration, format_elapsed, format_megabytes, open_externally, report, style, toast,
+};
+use dfn_core::i18n::tr;
+use dfn_core::pipeline::{self, OutputFormat, ProcessingOptions};
+use dfn_core::{benchmark, cancel, denoiser, download, engine, metrics};
+use futures_util::Stream;
+use iced::widget::{Row, button, column, container, progress_bar, row, scrollable, text};
+use iced::{Alignment, Element, Font, Length, Task};
+use rfd::FileDialog;
+use std::path::{Path, PathBuf};
+
+/// Lines of the engine output shown with an error; the rest is in the full
+/// log.
+const ERROR_PREVIEW_LINES: usize = 5;
+
+#[derive(Debug, Clone)]
+pub enum StatusMessage {
+    ToggleErrorDetails,
+    ShowFullLog(bool),
+    CopyError,
+    SaveError,
+    ErrorDestinationChosen(Option<PathBuf>),
+    /// Copy what the status bar shows, e.g. for a bug report.
+    CopyStatusBar,
+    /// Drop the file that failed and go back to picking one.
+    SkipFile,
+    /// Save a problem report as a zip.
+    SaveReport,
+    ReportDestinationChosen(Option<PathBuf>),
+    /// Open a new GitHub issue with the problem report filled in.
+    ReportOnGitHub,
+    StartBenchmark,
+    BenchmarkProgress(usize),
+    BenchmarkFinished(Result<benchmark::Summary, String>),
+}
+
+impl DfnGui {
+    pub fn update_status(&mut self, message: StatusMessage) -> Task<Message> {
+        match message {
+            StatusMessage::ToggleErrorDetails => {
+                self.show_error_details = !self.show_error_details;
+            }
+            StatusMessage::ShowFullLog(shown) => {
+                self.show_full_log = shown;
+            }
+            StatusMessage::SaveError => {
+                let name = format!("{}-error.log", env!("CARGO_PKG_NAME"));
+                return Task::perform(
+                    async move { FileDialog::new().set_file_name(name).save_file() },
+                    |path| Message::Status(StatusMessage::ErrorDestinationChosen(path)),
+                );
+            }
+            StatusMessage::ErrorDestinationChosen(Some(path)) => {
+                if let Some(e) = self.status.error() {
+                    match std::fs::write(&path, e) {
+                        Ok(()) => self.toasts.push(
+                            toast::Severity::Success,
+                            tr!("toast-logs-saved", path = path.display()),
+                        ),
+                        Err(e) => self.toasts.push(
+                            toast::Severity::Error,
+                            tr!("toast-logs-save-failed", error = e),
+                        ),
+                    }
+                }
+            }
+            StatusMessage::ErrorDestinationChosen(None) => {}
+            StatusMessage::CopyError => {
+                if let Some(e) = self.status.error() {
+                    let e = e.to_string();
+                    self.toasts
+                        .push(toast::Severity::Info, tr!("toast-error-copied"));
+                    return iced::clipboard::write(e);
+                }
+            }
+            StatusMessage::CopyStatusBar => {
+                self.toasts
+                    .push(toast::Severity::Info, tr!("toast-status-bar-copied"));
+                return iced::clipboard::write(self.status_bar_items().join(" · "));
+            }
+            StatusMessage::SaveReport => {
+                let name = format!("{}-report.zip", env!("CARGO_PKG_NAME"));
+                return Task::perform(
+                    async move {
+                        FileDialog::new()
+                            .set_file_name(name)
+                            .add_filter("Zip", &["zip"])
+                            .save_file()
+                    },
+                    |path| Message::Status(StatusMessage::ReportDestinationChosen(path)),
+                );
+            }
+            StatusMessage::ReportDestinationChosen(Some(path)) => {
+                match self.problem_report().save(&path) {
+                    Ok(()) => self.toasts.push(
+                        toast::Severity::Success,
+                        tr!("toast-report-saved", path = path.display()),
+                    ),
+                    Err(e) => self.toasts.push(toast::Severity::Error, e),
+                }
+            }
+            StatusMessage::ReportDestinationChosen(None) => {}
+            StatusMessage::ReportOnGitHub => {
+                open_externally(self.problem_report().issue_url().as_ref());
+            }
+            StatusMessage::SkipFile => {
+                self.forget_file();
+                self.status = Status::Job(JobState::Idle);
+            }
+            StatusMessage::StartBenchmark => match download::installed_binary() {
+                Ok(bin_path) => {
+                    // The results take the place of the selected file.
+                    self.forget_file();
+                    let benchmark = Benchmark::default();
+                    let cancel = benchmark.cancel.clone();
+                    let transcript = engine::Transcript::default();
+                    self.engine_transcript = Some(transcript.clone());
+                    self.status = Status::Job(JobState::Benchmarking(benchmark));
+                    return Task::run(
+                        benchmark_process(
+                            bin_path,
+                            self.options.engine_options(),
+                            cancel,
+                            transcript,
+                        ),
+                        Message::Status,
+                    );
+                }
+                Err(e) => {
+                    self.status = Status::Job(JobState::Failed {
+                        failure: Failure::MissingEngine,
+                        error: e,
+                        input: None,
+                    });
+                }
+            },
+            StatusMessage::BenchmarkProgress(done) => {
+                if let Status::Job(JobState::Benchmarking(benchmark)) = &mut self.status {
+                    benchmark.runs_done = done;
+                }
+            }
+            StatusMessage::BenchmarkFinished(_) if self.exit_when_idle => {
+                return self.exit();
+            }
+            StatusMessage::BenchmarkFinished(Err(e)) if e == cancel::CANCELLED => {
+                tracing::info!("Benchmark was cancelled");
+                self.status = Status::Job(JobState::Idle);
+            }
+            StatusMessage::BenchmarkFinished(result) => match result {
+                Ok(summary) => self.status = Status::Job(JobState::Benchmarked(summary)),
+                Err(e) => {
+                    tracing::error!("Benchmark failed: {}", e);
+                    self.show_error_details = false;
+                    self.show_full_log = false;
+                    self.status = Status::Job(JobState::Failed {
+                        failure: Failure::Benchmark,
+                        error: tr!("error-benchmark", error = e),
+                        input: None,
+                    });
+                }
+            },
+        }
+        Task::none()
+    }
+
+    pub fn view_status(&self) -> Element<'_, Message> {
+        let job = match &self.status {
+            Status::Engine {
+                state: EngineState::Failed(e),
+                ..
+            } => {
+                return self.view_error(
+                    e,
+                    row![
+                        button(text(tr!("error-retry-download")))
+                            .on_press(Message::Download(DownloadMessage::Start)),
+                        button(text(tr!("error-use-mirror")))
+                            .on_press(Message::Download(DownloadMessage::UseMirror))
+                            .style(button::secondary),
+                    ],
+                );
+            }
+            Status::Engine {
+                state: EngineState::Offline,
+                ..
+            } => return view_offline(),
+            Status::Engine { .. } => return text("").into(),
+            Status::Job(job) => job,
+        };
+        match job {
+            JobState::Idle => text(tr!("status-ready")).into(),
+            JobState::Ready(input) => column![
+                button(text(tr!("status-clean")))
+                    .on_press(Message::Processing(ProcessingMessage::Start))
+                    .padding(10),
+            ]
+            .push(
+                self.describe_output(input)
+                    .map(|estimate| text(estimate).size(12)),
+            )
+            .spacing(10)
+            .align_x(Alignment::Center)
+            .into(),
+            JobState::Adjusting { previous, .. } => column![
+                button(text(tr!("status-rerun")))
+                    .on_press(Message::Processing(ProcessingMessage::Start))
+                    .padding(10),
+                text(tr!(
+                    "status-adjusting",
+                    name = previous.file_name().unwrap_or_default().to_string_lossy()
+                ))
+                .size(12),
+            ]
+            .spacing(10)
+            .align_x(Alignment::Center)
+            .into(),
+            JobState::Processing(run) => column![
+                text(tr!(
+                    "status-processing",
+                    phase = run.phase.label(),
+                    percent = format!("{:.0}", run.progress)
+                )),
+                progress_bar(0.0..=100.0, run.progress),
+                row![
+                    cancel_button(),
+                    button(text(tr!("express-choose")))
+                        .on_press_maybe(
+                            self.express
+                                .is_none()
+                                .then_some(Message::Express(ExpressMessage::Choose))
+                        )
+                        .style(button::secondary),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10)
+            .align_x(Alignment::Center)
+            .into(),
+            JobState::Done { report, .. } => {
+                let path = &report.output_path;
+                let mut content = column![
+                    text(tr!("status-finished")).style(text::success),
+                    text(tr!("status-saved-to", path = path.display())).size(12),
+                ]
+                .spacing(10)
+                .align_x(Alignment::Center);
+                for other in &report.other_outputs {
+                    content = content
+                        .push(text(tr!("status-also-saved-to", path = other.display())).size(12));
+                }
+                content = content.push(
+                    text(tr!(
+                        "status-speed",
+                        factor = format!("{:.1}", report.realtime_factor()),
+                        elapsed = format_elapsed(report.elapsed),
+                        duration = format_duration(report.audio_duration),
+                    ))
+                    .size(12),
+                );
+                if let Some(provider) = report.provider {
+                    content =
+                        content.push(text(tr!("status-provider", provider = provider)).size(12));
+                }
+
+                if let Some(levels) = &report.levels {
+                    content = content.push(view_levels(levels));
+                }
+                if self.stats.runs > 1 {
+                    content = content.push(
+                        text(tr!(
+                            "status-session",
+                            factor = format!("{:.1}", self.stats.realtime_factor()),
+                            runs = self.stats.runs,
+                            duration = format_duration(self.stats.audio),
+                        ))
+                        .size(12),
+                    );
+                }
+
+                content
+                    .push(
+                        row![
+                            button(text(tr!("status-open-location")))
+                                .on_press(Message::RevealFile(path.clone())),
+                            button(text(tr!("status-adjust")))
+                                .on_press(Message::Processing(ProcessingMessage::Adjust))
+                                .style(button::secondary),
+                        ]
+                        .spacing(10),
+                    )
+                    .into()
+            }
+            JobState::Benchmarking(benchmark) => column![
+                text(tr!(
+                    "benchmark-progress",
+                    run = (benchmark.runs_done + 1).min(benchmark::RUNS),
+                    runs = benchmark::RUNS
+                )),
+                progress_bar(0.0..=benchmark::RUNS as f32, benchmark.runs_done as f32),
+                cancel_button(),
+            ]
+            .spacing(10)
+            .align_x(Alignment::Center)
+            .into(),
+            JobState::Benchmarked(summary) => column![
+                text(tr!("benchmark-complete")).size(20),
+                text(tr!(
+                    "benchmark-result",
+                    average = format!("{:.1}", summary.avg_realtime_factor),
+                    slowest = format!("{:.1}", summary.min_realtime_factor),
+                    runs = summary.runs
+                )),
+                text({
+                    let threads = summary
+                        .engine_options
+                        .threads
+                        .map(|threads| threads.to_string())
+                        .unwrap_or_else(|| tr!("benchmark-threads-all"));
+                    if summary.engine_options.low_priority {
+                        tr!("benchmark-setup-background", threads = threads)
+                    } else {
+                        tr!("benchmark-setup-normal", threads = threads)
+                    }
+                })
+                .size(12),
+            ]
+            .spacing(10)
+            .align_x(Alignment::Center)
+            .into(),
+            JobState::Failed {
+                failure,
+                error,
+                input,
+            } => {
+                let actions = match failure {
+                    Failure::Processing => row![
+                        button(text(tr!("error-retry-file"))).on_press_maybe(
+                            input
+                                .is_some()
+                                .then_some(Message::Processing(ProcessingMessage::Start))
+                        ),
+                        button(text(tr!("error-skip-file")))
+                            .on_press(Message::Status(StatusMessage::SkipFile))
+                            .style(button::secondary),
+                    ],
+                    Failure::MissingEngine => row![
+                        button(text(tr!("error-reinstall-engine")))
+                            .on_press(Message::Download(DownloadMessage::Start)),
+                    ],
+                    Failure::Input => {
+                        row![
+                            button(text(tr!("error-choose-file")))
+                                .on_press(Message::Clean(CleanMessage::SelectFile)),
+                        ]
+                    }
+                    Failure::Benchmark => {
+                        row![
+                            button(text(tr!("error-retry")))
+                                .on_press(Message::Status(StatusMessage::StartBenchmark)),
+                        ]
+                    }
+                };
+                self.view_error(error, actions)
+            }
+        }
+    }
+
+    /// How long `input` is and how big its output will be, also as FLAC
+    /// when it's written as WAV.
+    fn describe_output(&self, input: &Path) -> Option<String> {
+        let info = self.input_info.as_ref()?;
+        let estimate = pipeline::estimate_output(input, info, &self.options)?;
+        let duration = format_duration(info.duration?);
+        let size = format_megabytes(estimate.size);
+        if estimate.extension == "wav" {
+            let flac = ProcessingOptions {
+                output_format: OutputFormat::Flac,
+                ..self.options.clone()
+            };
+            if let Some(flac) = pipeline::estimate_output(input, info, &flac) {
+                return Some(tr!(
+                    "estimate-output-wav",
+                    duration = duration,
+                    size = size,
+                    flac = format_megabytes(flac.size)
+                ));
+            }
+        }
+        Some(tr!(
+            "estimate-output",
+            duration = duration,
+            size = size,
+            format = estimate.extension.to_uppercase()
+        ))
+    }
+
+    /// The error with its details, the ways to recover in `actions` and
+    /// the ways to report it.
+    fn view_error<'a>(&'a self, e: &'a str, actions: Row<'a, Message>) -> Element<'a, Message> {
+        // The first line is the summary; anything after it is the engine
+        // output captured for diagnosis.
+        let (summary, details) = e.split_once('\n').unwrap_or((e, ""));
+        let mut content = column![text(tr!("error-summary", error = summary)).style(text::danger),]
+            .spacing(10)
+            .align_x(Alignment::Center);
+
+        if !details.is_empty() {
+            content = content.push(
+                button(text(if self.show_error_details {
+                    tr!("error-hide-details")
+                } else {
+                    tr!("error-show-details")
+                }))
+                .on_press(Message::Status(StatusMessage::ToggleErrorDetails))
+                .style(button::text),
+            );
+            if self.show_error_details {
+                // The end of the output usually says what went wrong.
+                let lines: Vec<&str> = details.lines().collect();
+                let shown = lines[lines.len().saturating_sub(ERROR_PREVIEW_LINES)..].join("\n");
+                content = content.push(
+                    container(text(shown).size(12).font(Font::MONOSPACE))
+                        .padding(10)
+                        .width(Length::Fill)
+                        .style(container::bordered_box),
+                );
+                if lines.len() > ERROR_PREVIEW_LINES {
+                    content = content.push(
+                        text(tr!(
+                            "error-lines-hidden",
+                            count = lines.len() - ERROR_PREVIEW_LINES
+                        ))
+                        .size(12),
+                    );
+                }
+                content = content.push(
+                    button(text(tr!("error-view-full-log")))
+                        .on_press(Message::Status(StatusMessage::ShowFullLog(true)))
+                        .style(button::text),
+                );
+            }
+        }
+
+        content
+            .push(
+                actions
+                    .push(
+                        button(text(tr!("error-copy")))
+                            .on_press(Message::Status(StatusMessage::CopyError))
+                            .style(button::text),
+                    )
+                    .spacing(10),
+            )
+            .push(view_report_actions())
+            .into()
+    }
+
+    /// What the status bar shows: versions, backend, model and how many
+    /// files were cleaned.
+    fn status_bar_items(&self) -> Vec<String> {
+        let mut items = vec![tr!("status-bar-app", version = env!("CARGO_PKG_VERSION"))];
+        match self.options.backend {
+            denoiser::Backend::ExternalBinary => {
+                items.push(match &self.engine_version {
+                    Some(version) => tr!("status-bar-engine", version = version),
+                    None => tr!("status-bar-engine-missing"),
+                });
+                items.push(tr!("status-bar-model", model = engine::MODEL));
+            }
+            denoiser::Backend::InProcess => {
+                items.push(self.options.backend.to_string());
+                items.push(tr!("status-bar-model", model = engine::MODEL));
+            }
+            denoiser::Backend::Remote => {
+                items.push(self.options.backend.to_string());
+                items.push(tr!("status-bar-model-remote"));
+            }
+        }
+        let mut counts = tr!(
+            "status-bar-counts",
+            cleaned = self.stats.runs,
+            failed = self.stats.failed
+        );
+        if let Status::Job(JobState::Processing(_)) = self.status {
+            counts = tr!("status-bar-counts-running", counts = counts);
+        }
+        items.push(counts);
+        items
+    }
+
+    /// What's known about the app and the latest failure, for a bug report.
+    fn problem_report(&self) -> report::Report {
+        let log = self.status.error().map(str::to_string);
+        // Only the binary tells its version.
+        let engine_version = match self.options.backend {
+            denoiser::Backend::ExternalBinary => Some(
+                self.engine_version
+                    .clone()
+                    .unwrap_or_else(|| "not installed".to_string()),
+            ),
+            denoiser::Backend::InProcess | denoiser::Backend::Remote => None,
+        };
+        report::Report::new(
+            engine_version,
+            self.options.backend.to_string(),
+            self.engine_transcript
+                .as_ref()
+                .and_then(engine::Transcript::command),
+            log,
+            &self.options.server.token,
+        )
+    }
+
+    pub fn view_status_bar(&self) -> Element<'_, Message> {
+        container(
+            row![
+                text(self.status_bar_items().join(" · "))
+                    .size(11)
+                    .width(Length::Fill),
+                button(text(tr!("status-bar-copy")).size(11))
+                    .on_press(Message::Status(StatusMessage::CopyStatusBar))
+                    .padding([0, 4])
+                    .style(button::text),
+            ]
+            .align_y(Alignment::Center),
+        )
+        .padding([2, 8])
+        .width(Length::Fill)
+        .style(container::secondary)
+        .into()
+    }
+}
+
+/// The whole error `e` over the window, to be read, copied or saved.
+pub fn view_full_log(e: &str) -> Element<'_, Message> {
+    let dialog = column![
+        text(tr!("error-full-log")).size(18),
+        container(scrollable(text(e).size(12).font(Font::MONOSPACE)).width(Length::Fill))
+            .padding(10)
+            .height(Length::Fill)
+            .style(container::bordered_box),
+        row![
+            button(text(tr!("error-copy"))).on_press(Message::Status(StatusMessage::CopyError)),
+            button(text(tr!("error-save")))
+                .on_press(Message::Status(StatusMessage::SaveError))
+                .style(button::secondary),
+            iced::widget::space::horizontal(),
+            button(text(tr!("error-close-log")))
+                .on_press(Message::Status(StatusMessage::ShowFullLog(false)))
+                .style(button::text),
+        ]
+        .spacing(10),
+    ]
+    .spacing(10);
+    container(
+        container(dialog)
+            .padding(20)
+            .max_width(700)
+            .style(container::bordered_box),
+    )
+    .padding(40)
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .center_x(Length::Fill)
+    .center_y(Length::Fill)
+    .style(style::backdrop)
+    .into()
+}
+
+pub fn view_report_actions<'a>() -> Element<'a, Message> {
+    row![
+        text(tr!("report-problem")).size(12),
+        button(text(tr!("report-save")).size(12))
+            .on_press(Message::Status(StatusMessage::SaveReport))
+            .style(button::text),
+        button(text(tr!("report-github")).size(12))
+            .on_press(Message::Status(StatusMessage::ReportOnGitHub))
+            .style(button::text),
+    ]
+    .spacing(5)
+    .align_y(Alignment::Center)
+    .into()
+}
+
+/// Before/after table of measured levels.
+fn view_levels(levels: &metrics::Comparison) -> Element<'_, Message> {
+    let line = |label: String, before: f32, after: f32| {
+        text(format!(
+            "{:<12} {:>7.1} dB → {:>7.1} dB",
+            label, before, after
+        ))
+        .size(12)
+        .font(Font::MONOSPACE)
+    };
+    column![
+        line(
+            tr!("levels-noise-floor"),
+            levels.before.noise_floor_db,
+            levels.after.noise_floor_db
+        ),
+        line(tr!("levels-rms"), levels.before.rms_db, levels.after.rms_db),
+        line(
+            tr!("levels-peak"),
+            levels.before.peak_db,
+            levels.after.peak_db
+        ),
+        line(
+            tr!("levels-snr"),
+            levels.before.snr_db(),
+            levels.after.snr_db()
+        ),
+        text(tr!(
+            "levels-snr-improvement",
+            db = format!("{:.1}", levels.snr_improvement_db())
+        ))
+        .size(12),
+        text(tr!(
+            "levels-quality",
+            score = format!("{:.1}", levels.quality_score())
+        ))
+        .size(12),
+    ]
+    .push(levels.is_poor().then(|| {
+        text(tr!("levels-quality-poor"))
+            .size(12)
+            .style(text::warning)
+    }))
+    .spacing(2)
+    .align_x(Alignment::Center)
+    .into()
+}
+
+fn benchmark_process(
+    bin_path: PathBuf,
+    engine_options: engine::Options,
+    cancel: cancel::CancelToken,
+    transcript: engine::Transcript,
+) -> impl Stream<Item = StatusMessage> {
+    blocking_stream(
+        move |send| {
+            let result = benchmark::run(
+                &bin_path,
+                engine_options,
+                benchmark::RUNS,
+                &cancel,
+                &transcript,
+                &mut |done| send(StatusMessage::BenchmarkProgress(done)),
+            );
+            StatusMessage::BenchmarkFinished(result)
+        },
+        |e| StatusMessage::BenchmarkFinished(Err(e)),
+    )
+}
@@ -0,0 +1,103 @@
+//! Themes and the styles built from them. Colors come from the theme's
+//! palette rather than being written into the views, so every theme,
+//! including the high-contrast one, applies everywhere.
+
+use iced::theme::palette::{self, Extended, Pair};
+use iced::widget::{container, text_input as input};
+use iced::{Color, Theme};
+
+/// Name the high-contrast theme is shown and saved under.
+const HIGH_CONTRAST: &str = "High Contrast";
+
+/// Themes offered in the settings.
+pub fn themes() -> Vec<Theme> {
+    Theme::ALL
+        .iter()
+        .cloned()
+        .chain(std::iter::once(high_contrast()))
+        .collect()
+}
+
+/// Pure black and white with saturated accents and no in-between grays,
+/// for low vision.
+fn high_contrast() -> Theme {
+    let black = Pair::new(Color::BLACK, Color::WHITE);
+    let white = Pair::new(Color::WHITE, Color::BLACK);
+    let accent = |color: Color| Pair::new(color, Color::BLACK);
+
+    Theme::custom_with_fn(
+        HIGH_CONTRAST,
+        palette::Palette {
+            background: Color::BLACK,
+            text: Color::WHITE,
+            primary: Color::from_rgb(1.0, 1.0, 0.0),
+            success: Color::from_rgb(0.0, 1.0, 0.0),
+            warning: Color::from_rgb(1.0, 0.6, 0.0),
+            danger: Color::from_rgb(1.0, 0.3, 0.3),
+        },
+        |palette| Extended {
+            background: palette::Background {
+                base: black,
+                weakest: black,
+                weaker: black,
+                weak: black,
+                neutral: black,
+                strong: white,
+                stronger: white,
+                strongest: white,
+            },
+            primary: palette::Primary {
+                base: accent(palette.primary),
+                weak: accent(palette.primary),
+                strong: white,
+            },
+            secondary: palette::Secondary {
+                base: accent(Color::from_rgb(0.0, 1.0, 1.0)),
+                weak: accent(Color::from_rgb(0.0, 1.0, 1.0)),
+                strong: white,
+            },
+            success: palette::Success {
+                base: accent(palette.success),
+                weak: accent(palette.success),
+                strong: white,
+            },
+            warning: palette::Warning {
+                base: accent(palette.warning),
+                weak: accent(palette.warning),
+                strong: white,
+            },
+            danger: palette::Danger {
+                base: accent(palette.danger),
+                weak: accent(palette.danger),
+                strong: white,
+            },
+            is_dark: true,
+        },
+    )
+}
+
+pub fn is_high_contrast(theme: &Theme) -> bool {
+    theme.to_string() == HIGH_CONTRAST
+}
+
+/// Dims the window behind a dialog.
+pub fn backdrop(theme: &Theme) -> container::Style {
+    let background = theme.extended_palette().background.base.color;
+    container::Style::default().background(Color {
+        a: 0.8,
+        ..background
+    })
+}
+
+/// Text inputs, with a thick ring around the focused one in the
+/// high-contrast theme.
+pub fn text_input(theme: &Theme, status: input::Status) -> input::Style {
+    let mut style = input::default(theme, status);
+    if is_high_contrast(theme) {
+        style.border.width = match status {
+            input::Status::Focused { .. } => 3.0,
+            _ => 1.0,
+        };
+    }
+    style
+}
@@ -0,0 +1,111 @@
+//! Progress of a batch on the app's taskbar button or launcher icon, so it
+//! can be followed with the window in the background. Windows shows it on
+//! the taskbar button through `ITaskbarList3`. On Linux it's sent with the
+//! Unity launcher API, which Ubuntu's dock, Dash to Dock, Plank and KDE's
+//! task manager follow for the launcher of `dfn_gui.desktop` (or of the
+//! Flatpak's ID). The macOS Dock has no progress bar, so there the icon only
+//! bounces once the batch is done (see [`finished`]).
+
+use iced::Task;
+use iced::window;
+
+/// Shows `progress`, the share of the batch done from 0 to 1, or takes it
+/// away with `None`, on the button of `window`.
+#[cfg(target_os = "linux")]
+pub fn show<T: Send + 'static>(_window: window::Id, progress: Option<f32>) -> Task<T> {
+    Task::future(async move {
+        if let Err(e) = unity::update(progress).await {
+            tracing::debug!("Could not update the launcher: {}", e);
+        }
+    })
+    .discard()
+}
+
+#[cfg(windows)]
+pub fn show<T: Send + 'static>(window: window::Id, progress: Option<f32>) -> Task<T> {
+    window::run(window, move |window| taskbar_list::set(window, progress)).discard()
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn show<T: Send + 'static>(_window: window::Id, _progress: Option<f32>) -> Task<T> {
+    Task::none()
+}
+
+/// Draws attention to a finished batch: the Dock icon bounces on macOS, the
+/// taskbar button flashes on Windows and the window is marked urgent on
+/// Linux, until the window is brought forward.
+pub fn finished<T: Send + 'static>(window: window::Id) -> Task<T> {
+    window::request_user_attention(window, Some(window::UserAttention::Informational))
+}
+
+#[cfg(target_os = "linux")]
+mod unity {
+    use std::collections::HashMap;
+    use tokio::sync::OnceCell;
+    use zbus::zvariant::Value;
+
+    /// Kept open, as docks drop what a connection showed once it closes.
+    static CONNECTION: OnceCell<zbus::Connection> = OnceCell::const_new();
+
+    pub async fn update(progress: Option<f32>) -> Result<(), String> {
+        let connection = CONNECTION
+            .get_or_try_init(zbus::Connection::session)
+            .await
+            .map_err(|e| e.to_string())?;
+        let desktop_id = std::env::var("FLATPAK_ID").unwrap_or_else(|_| "dfn_gui".to_string());
+        let properties = HashMap::from([
+            (
+                "progress",
+                Value::from(f64::from(progress.unwrap_or_default())),
+            ),
+            ("progress-visible", Value::from(progress.is_some())),
+        ]);
+        connection
+            .emit_signal(
+                None::<()>,
+                "/org/deepfilternet/Gui/LauncherEntry",
+                "com.canonical.Unity.LauncherEntry",
+                "Update",
+                &(format!("application://{}.desktop", desktop_id), properties),
+            )
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(windows)]
+mod taskbar_list {
+    use iced::window::raw_window_handle::RawWindowHandle;
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::System::Com::{CLSCTX_INPROC_SERVER, CoCreateInstance};
+    use windows::Win32::UI::Shell::{ITaskbarList3, TBPF_NOPROGRESS, TBPF_NORMAL, TaskbarList};
+
+    pub fn set(window: &dyn iced::window::Window, progress: Option<f32>) {
+        let Ok(handle) = window.window_handle() else {
+            return;
+        };
+        let RawWindowHandle::Win32(handle) = handle.as_raw() else {
+            return;
+        };
+        let hwnd = HWND(handle.hwnd.get() as *mut _);
+        // SAFETY: runs on the window's thread, where winit has set up COM,
+        // with the handle of a window that's still open.
+        let result = unsafe {
+            CoCreateInstance::<_, ITaskbarList3>(&TaskbarList, None, CLSCTX_INPROC_SERVER).and_then(
+                |taskbar| {
+                    taskbar.HrInit()?;
+                    match progress {
+                        Some(progress) => {
+                            taskbar.SetProgressState(hwnd, TBPF_NORMAL)?;
+                            taskbar.SetProgressValue(hwnd, (progress * 1000.0) as u64, 1000)
+                        }
+                        None => taskbar.SetProgressState(hwnd, TBPF_NOPROGRESS),
+                    }
+                },
+            )
+        };
+        if let Err(e) = result {
+            tracing::debug!("Could not update the taskbar button: {}", e);
+        }
+    }
+}
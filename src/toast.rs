@@ -0,0 +1,140 @@
+//! Short notices laid over the window, stacked in its bottom right corner.
+//! They go away on their own after a while or when closed, and don't block
+//! the interface underneath.
+
+use iced::widget::{button, column, container, row, stack, text};
+use iced::{Alignment, Element, Length, Theme};
+use std::time::{Duration, Instant};
+
+/// Most toasts shown at once; the oldest make way for new ones.
+const MAX_VISIBLE: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// How long a toast stays up. Problems get more time to be read.
+    fn lifetime(self) -> Duration {
+        match self {
+            Severity::Info | Severity::Success => Duration::from_secs(4),
+            Severity::Warning | Severity::Error => Duration::from_secs(8),
+        }
+    }
+}
+
+struct Toast {
+    id: u64,
+    severity: Severity,
+    message: String,
+    shown: Instant,
+}
+
+#[derive(Default)]
+pub struct Toasts {
+    toasts: Vec<Toast>,
+    next_id: u64,
+}
+
+impl Toasts {
+    pub fn push(&mut self, severity: Severity, message: impl Into<String>) {
+        if self.toasts.len() == MAX_VISIBLE {
+            self.toasts.remove(0);
+        }
+        self.toasts.push(Toast {
+            id: self.next_id,
+            severity,
+            message: message.into(),
+            shown: Instant::now(),
+        });
+        self.next_id += 1;
+    }
+
+    pub fn dismiss(&mut self, id: u64) {
+        self.toasts.retain(|toast| toast.id != id);
+    }
+
+    /// Drops the toasts that have been up long enough by `now`.
+    pub fn expire(&mut self, now: Instant) {
+        self.toasts
+            .retain(|toast| now.duration_since(toast.shown) < toast.severity.lifetime());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.toasts.is_empty()
+    }
+
+    /// `content` with the toasts on top. `on_dismiss` is sent with the id of
+    /// a toast closed by hand.
+    pub fn view<'a, Message: Clone + 'a>(
+        &'a self,
+        content: Element<'a, Message>,
+        on_dismiss: fn(u64) -> Message,
+    ) -> Element<'a, Message> {
+        if self.toasts.is_empty() {
+            return content;
+        }
+
+        let toasts = self.toasts.iter().fold(
+            column![].spacing(8).align_x(Alignment::End),
+            |toasts, toast| {
+                let severity = toast.severity;
+                toasts.push(
+                    container(
+                        row![
+                            text(&toast.message).size(12).width(Length::Fill),
+                            button(text("×").size(12))
+                                .on_press(on_dismiss(toast.id))
+                                .padding([0, 4])
+                                .style(button::text),
+                        ]
+                        .spacing(8)
+                        .align_y(Alignment::Center),
+                    )
+                    .padding(10)
+                    .max_width(320)
+                    .style(move |theme: &Theme| style(theme, severity)),
+                )
+            },
+        );
+        stack![
+            content,
+            container(toasts)
+                .padding(16)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .align_right(Length::Fill)
+                .align_bottom(Length::Fill),
+        ]
+        .into()
+    }
+}
+
+fn style(theme: &Theme, severity: Severity) -> container::Style {
+    let palette = theme.extended_palette();
+    let accent = match severity {
+        Severity::Info => palette.primary.base.color,
+        Severity::Success => palette.success.base.color,
+        Severity::Warning => palette.warning.base.color,
+        Severity::Error => palette.danger.base.color,
+    };
+    container::Style {
+        background: Some(palette.background.weak.color.into()),
+        text_color: Some(palette.background.weak.text),
+        border: iced::Border {
+            color: accent,
+            width: 2.0,
+            radius: 6.0.into(),
+        },
+        shadow: iced::Shadow {
+            color: iced::Color::from_rgba(0.0, 0.0, 0.0, 0.3),
+            offset: iced::Vector::new(0.0, 2.0),
+            blur_radius: 8.0,
+        },
+        ..Default::default()
+    }
+}
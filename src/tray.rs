@@ -0,0 +1,189 @@
+//! Icon in the system tray showing whether audio is being cleaned, with a
+//! menu to bring the window back or quit. It's only compiled in with the
+//! `tray` feature on Linux, where it talks the StatusNotifierItem protocol
+//! over D-Bus; elsewhere [`events`] never yields anything.
+
+use futures_util::Stream;
+
+/// Whether this build can show a tray icon.
+pub const AVAILABLE: bool = cfg!(all(feature = "tray", target_os = "linux"));
+
+#[cfg(all(feature = "tray", target_os = "linux"))]
+pub use imp::{Tray, events};
+#[cfg(not(all(feature = "tray", target_os = "linux")))]
+pub use stub::{Tray, events};
+
+/// What the icon shows.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum State {
+    #[default]
+    Idle,
+    /// Cleaning, with progress in percent.
+    Busy(f32),
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(not(all(feature = "tray", target_os = "linux")), allow(dead_code))]
+pub enum Event {
+    /// The icon is up and can be updated through the [`Tray`].
+    Started(Tray),
+    Failed(String),
+    ShowWindow,
+    Quit,
+}
+
+#[cfg(all(feature = "tray", target_os = "linux"))]
+mod imp {
+    use super::*;
+    use dfn_core::i18n::tr;
+    use ksni::TrayMethods;
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    /// Shows the icon for as long as the stream and the [`Tray`] it starts
+    /// with are kept.
+    pub fn events() -> impl Stream<Item = Event> {
+        use futures_util::StreamExt;
+
+        futures_util::stream::once(async {
+            let (sender, receiver) = mpsc::unbounded_channel();
+            let icon = Icon {
+                state: State::Idle,
+                sender,
+            };
+            let started = match icon.spawn().await {
+                Ok(handle) => Event::Started(Tray {
+                    service: Arc::new(Service(handle)),
+                }),
+                Err(e) => Event::Failed(format!("Could not show the tray icon: {}", e)),
+            };
+            let actions = futures_util::stream::unfold(receiver, |mut receiver| async move {
+                receiver.recv().await.map(|event| (event, receiver))
+            });
+            futures_util::stream::once(async { started }).chain(actions)
+        })
+        .flatten()
+    }
+
+    /// The running icon. It goes away once the last clone is dropped.
+    #[derive(Clone)]
+    pub struct Tray {
+        service: Arc<Service>,
+    }
+
+    impl Tray {
+        pub fn set_state(&self, state: State) -> impl Future<Output = ()> + Send + 'static {
+            let service = self.service.clone();
+            async move {
+                service.0.update(|icon| icon.state = state).await;
+            }
+        }
+    }
+
+    impl std::fmt::Debug for Tray {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("Tray")
+        }
+    }
+
+    struct Service(ksni::Handle<Icon>);
+
+    impl Drop for Service {
+        fn drop(&mut self) {
+            // The service shuts down in the background.
+            drop(self.0.shutdown());
+        }
+    }
+
+    struct Icon {
+        state: State,
+        sender: mpsc::UnboundedSender<Event>,
+    }
+
+    impl ksni::Tray for Icon {
+        fn id(&self) -> String {
+            env!("CARGO_PKG_NAME").into()
+        }
+
+        fn title(&self) -> String {
+            tr!("app-title")
+        }
+
+        fn icon_name(&self) -> String {
+            "audio-x-generic".into()
+        }
+
+        fn tool_tip(&self) -> ksni::ToolTip {
+            ksni::ToolTip {
+                title: tr!("app-title"),
+                description: match self.state {
+                    State::Idle => tr!("tray-idle"),
+                    State::Busy(percent) => {
+                        tr!("tray-busy", percent = format!("{:.0}", percent))
+                    }
+                },
+                ..Default::default()
+            }
+        }
+
+        fn activate(&mut self, _x: i32, _y: i32) {
+            let _ = self.sender.send(Event::ShowWindow);
+        }
+
+        fn menu(&self) -> Vec<ksni::MenuItem<Self>> {
+            use ksni::menu::StandardItem;
+
+            vec![
+                StandardItem {
+                    label: match self.state {
+                        State::Idle => tr!("tray-idle"),
+                        State::Busy(percent) => {
+                            tr!("tray-busy", percent = format!("{:.0}", percent))
+                        }
+                    },
+                    enabled: false,
+                    ..Default::default()
+                }
+                .into(),
+                ksni::MenuItem::Separator,
+                StandardItem {
+                    label: tr!("tray-show-window"),
+                    activate: Box::new(|icon: &mut Self| {
+                        let _ = icon.sender.send(Event::ShowWindow);
+                    }),
+                    ..Default::default()
+                }
+                .into(),
+                StandardItem {
+                    label: tr!("tray-quit"),
+                    icon_name: "application-exit".into(),
+                    activate: Box::new(|icon: &mut Self| {
+                        let _ = icon.sender.send(Event::Quit);
+                    }),
+                    ..Default::default()
+                }
+                .into(),
+            ]
+        }
+    }
+}
+
+#[cfg(not(all(feature = "tray", target_os = "linux")))]
+mod stub {
+    use super::*;
+
+    pub fn events() -> impl Stream<Item = Event> {
+        futures_util::stream::empty()
+    }
+
+    /// Stands in for the real tray in builds without one. It can't be
+    /// constructed.
+    #[derive(Debug, Clone)]
+    pub enum Tray {}
+
+    impl Tray {
+        pub fn set_state(&self, _state: State) -> std::future::Ready<()> {
+            match *self {}
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! A microphone other apps can pick, such as a video call or OBS, carrying
+//! what the Live tab cleans. It's made through the PulseAudio tools, which
+//! PipeWire desktops have too through `pipewire-pulse`: a null sink the
+//! cleaned audio is played into with `pacat`, and a source remapped from
+//! its monitor that's listed as "DeepFilterNet Cleaned Mic". Only on Linux;
+//! elsewhere [`VirtualMic::create`] always fails.
+
+use std::io::Write;
+
+/// Whether this platform can offer a virtual microphone.
+pub const AVAILABLE: bool = cfg!(target_os = "linux");
+
+/// Name other apps list the microphone under.
+pub const MIC_NAME: &str = "DeepFilterNet Cleaned Mic";
+
+#[cfg(target_os = "linux")]
+pub use imp::VirtualMic;
+#[cfg(not(target_os = "linux"))]
+pub use stub::VirtualMic;
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use super::*;
+    use dfn_core::audio::ENGINE_SAMPLE_RATE;
+    use std::process::{Child, ChildStdin, Command, Stdio};
+
+    /// Node names, which also find modules left behind by a crash.
+    const SINK_NAME: &str = "dfn_cleaned_mic_feed";
+    const SOURCE_NAME: &str = "dfn_cleaned_mic";
+
+    /// How far ahead `pacat` buffers, in milliseconds. Short, as the audio
+    /// is live.
+    const LATENCY_MS: u32 = 30;
+
+    /// The microphone while it's offered. Dropping it takes it away again.
+    pub struct VirtualMic {
+        /// Indexes of the modules loaded for it, in the order they were.
+        modules: Vec<String>,
+        player: Option<Child>,
+    }
+
+    impl VirtualMic {
+        /// Makes the microphone, taking away any a crashed session left.
+        pub fn create() -> Result<Self, String> {
+            if let Ok(listed) = pactl(&["list", "short", "modules"]) {
+                for index in leftover_modules(&listed) {
+                    tracing::info!("Unloading leftover virtual microphone module {}", index);
+                    let _ = pactl(&["unload-module", &index]);
+                }
+            }
+            let mut mic = Self {
+                modules: Vec::new(),
+                player: None,
+            };
+            mic.modules.push(pactl(&[
+                "load-module",
+                "module-null-sink",
+                &format!("sink_name={}", SINK_NAME),
+                &format!(
+                    "sink_properties=\"device.description='{} (feed)'\"",
+                    MIC_NAME
+                ),
+                "channels=1",
+                &format!("rate={}", ENGINE_SAMPLE_RATE),
+            ])?);
+            mic.modules.push(pactl(&[
+                "load-module",
+                "module-remap-source",
+                &format!("master={}.monitor", SINK_NAME),
+                &format!("source_name={}", SOURCE_NAME),
+                &format!("source_properties=\"device.description='{}'\"", MIC_NAME),
+            ])?);
+            let player = Command::new("pacat")
+                .arg("--playback")
+                .arg("--raw")
+                .arg(format!("--device={}", SINK_NAME))
+                .arg("--format=float32le")
+                .arg(format!("--rate={}", ENGINE_SAMPLE_RATE))
+                .arg("--channels=1")
+                .arg(format!("--latency-msec={}", LATENCY_MS))
+                .arg(format!("--client-name={}", env!("CARGO_PKG_NAME")))
+                .arg(format!("--stream-name={}", MIC_NAME))
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .map_err(|e| format!("Could not run pacat: {}", e))?;
+            mic.player = Some(player);
+            tracing::info!("Offering the cleaned audio as {}", MIC_NAME);
+            Ok(mic)
+        }
+
+        /// Where the cleaned audio is written, as 32-bit float samples at
+        /// the engine's rate. Can only be taken once.
+        pub fn take_input(&mut self) -> Option<Box<dyn Write + Send>> {
+            let stdin: ChildStdin = self.player.as_mut()?.stdin.take()?;
+            Some(Box::new(stdin))
+        }
+    }
+
+    impl Drop for VirtualMic {
+        fn drop(&mut self) {
+            if let Some(mut player) = self.player.take() {
+                let _ = player.kill();
+                let _ = player.wait();
+            }
+            for index in self.modules.iter().rev() {
+                if let Err(e) = pactl(&["unload-module", index]) {
+                    tracing::warn!("{}", e);
+                }
+            }
+        }
+    }
+
+    /// Runs `pactl` with `args`, returning what it printed.
+    fn pactl(args: &[&str]) -> Result<String, String> {
+        let output = Command::new("pactl").args(args).output().map_err(|e| {
+            format!(
+                "Could not run pactl, which comes with PulseAudio and pipewire-pulse: {}",
+                e
+            )
+        })?;
+        if !output.status.success() {
+            return Err(format!(
+                "pactl {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Indexes of the modules of an earlier microphone in `listed`, the
+    /// output of `pactl list short modules`, newest first.
+    fn leftover_modules(listed: &str) -> Vec<String> {
+        let sink = format!("sink_name={}", SINK_NAME);
+        let source = format!("source_name={}", SOURCE_NAME);
+        let mut indexes: Vec<String> = listed
+            .lines()
+            .filter(|line| {
+                line.split_whitespace()
+                    .any(|arg| arg == sink || arg == source)
+            })
+            .filter_map(|line| line.split_whitespace().next())
+            .map(str::to_string)
+            .collect();
+        indexes.reverse();
+        indexes
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn only_modules_of_the_microphone_are_left_over() {
+            let listed = "\
+9\tmodule-null-sink\tsink_name=other
+536870913\tmodule-null-sink\tsink_name=dfn_cleaned_mic_feed channels=1
+536870914\tmodule-remap-source\tmaster=dfn_cleaned_mic_feed.monitor source_name=dfn_cleaned_mic
+12\tmodule-remap-source\tsource_name=dfn_cleaned_mic_2";
+            assert_eq!(leftover_modules(listed), ["536870914", "536870913"]);
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod stub {
+    use super::*;
+
+    /// Stands in for the virtual microphone where there's none. It can't be
+    /// constructed.
+    pub enum VirtualMic {}
+
+    impl VirtualMic {
+        pub fn create() -> Result<Self, String> {
+            Err("Virtual microphones are only offered on Linux".to_string())
+        }
+
+        pub fn take_input(&mut self) -> Option<Box<dyn Write + Send>> {
+            match *self {}
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Waveform overviews: peak extraction and the canvas that draws them.
+
+use dfn_core::audio::AudioBuffer;
+use iced::widget::canvas::{self, Frame, Geometry, Path};
+use iced::{Color, Point, Rectangle, Renderer, Size, Theme, mouse};
+
+/// Number of columns peaks are computed for. Canvases stretch them to fit.
+pub const COLUMNS: usize = 1000;
+
+/// Minimum and maximum sample of each column, over all channels.
+#[derive(Debug, Clone, Default)]
+pub struct Peaks {
+    pub columns: Vec<(f32, f32)>,
+}
+
+impl Peaks {
+    /// Downsamples `buffer` to `columns` min/max pairs.
+    pub fn compute(buffer: &AudioBuffer, columns: usize) -> Self {
+        let frames = buffer.frames();
+        if frames == 0 || columns == 0 {
+            return Self::default();
+        }
+
+        let columns = (0..columns)
+            .map(|column| {
+                let start = column * frames / columns;
+                let end = ((column + 1) * frames / columns).max(start + 1).min(frames);
+                buffer
+                    .channels
+                    .iter()
+                    .flat_map(|channel| &channel[start..end])
+                    .fold((0.0f32, 0.0f32), |(min, max), &sample| {
+                        (min.min(sample), max.max(sample))
+                    })
+            })
+            .collect();
+        Self { columns }
+    }
+}
+
+/// Input and, once processed, output waveforms stacked on top of each other,
+/// with the playback position as a vertical line.
+pub struct Waveform<'a, Message> {
+    pub input: Option<&'a Peaks>,
+    pub output: Option<&'a Peaks>,
+    /// Playback position as a fraction of the length.
+    pub position: Option<f32>,
+    /// Produces the message for a click at a fraction of the length. Without
+    /// it the waveform ignores clicks.
+    pub on_seek: Option<fn(f32) -> Message>,
+}
+
+impl<Message> canvas::Program<Message> for Waveform<'_, Message> {
+    type State = ();
+
+    fn update(
+        &self,
+        _state: &mut (),
+        event: &iced::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Option<canvas::Action<Message>> {
+        let on_seek = self.on_seek?;
+        match event {
+            iced::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let position = cursor.position_in(bounds)?;
+                let fraction = (position.x / bounds.width).clamp(0.0, 1.0);
+                Some(canvas::Action::publish(on_seek(fraction)).and_capture())
+            }
+            _ => None,
+        }
+    }
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let palette = theme.extended_palette();
+        let lanes: Vec<(&Peaks, Color)> = [
+            self.input
+                .map(|peaks| (peaks, palette.secondary.base.color)),
+            self.output.map(|peaks| (peaks, palette.primary.base.color)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        let lane_height = bounds.height / lanes.len().max(1) as f32;
+        for (lane, (peaks, color)) in lanes.iter().enumerate() {
+            draw_peaks(
+                &mut frame,
+                peaks,
+                *color,
+                Rectangle::new(
+                    Point::new(0.0, lane as f32 * lane_height),
+                    Size::new(bounds.width, lane_height),
+                ),
+            );
+        }
+
+        if let Some(position) = self.position {
+            let x = position.clamp(0.0, 1.0) * bounds.width;
+            frame.fill_rectangle(
+                Point::new(x, 0.0),
+                Size::new(1.0, bounds.height),
+                palette.background.base.text,
+            );
+        }
+
+        vec![frame.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        _state: &(),
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        if self.on_seek.is_some() && cursor.is_over(bounds) {
+            mouse::Interaction::Pointer
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+}
+
+fn draw_peaks(frame: &mut Frame, peaks: &Peaks, color: Color, area: Rectangle) {
+    if peaks.columns.is_empty() {
+        return;
+    }
+
+    let center = area.y + area.height / 2.0;
+    let scale = area.height / 2.0;
+    let column_width = area.width / peaks.columns.len() as f32;
+    let path = Path::new(|builder| {
+        for (column, (min, max)) in peaks.columns.iter().enumerate() {
+            let x = column as f32 * column_width;
+            // Keep silent stretches visible as a thin line.
+            let top = center - max.clamp(0.0, 1.0) * scale - 0.5;
+            let bottom = center - min.clamp(-1.0, 0.0) * scale + 0.5;
+            builder.rectangle(
+                Point::new(x, top),
+                Size::new(column_width.max(1.0), bottom - top),
+            );
+        }
+    });
+    frame.fill(&path, color);
+}
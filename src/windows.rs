@@ -0,0 +1,194 @@
+//! The app's windows. The first is opened at startup and New Window opens
+//! more, each with a file and queue of its own. They run in one process:
+//! the history and downloads are shared, and a setting changed in one window
+//! applies to all of them.
+
+use crate::{DfnGui, Message, WINDOW_SIZE, tray};
+use iced::{Element, Subscription, Task, Theme, window};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// The state of each open window, in the order they were opened.
+pub struct Windows {
+    windows: BTreeMap<window::Id, DfnGui>,
+}
+
+/// A message for the state of a window.
+pub type WindowsMessage = (window::Id, Message);
+
+impl Windows {
+    /// Opens the first window, with `files` selected and queued in it.
+    pub fn boot(files: Vec<PathBuf>) -> (Self, Task<WindowsMessage>) {
+        let (id, open) = window::open(window_settings());
+        let (window, task) = DfnGui::init(id, files, None);
+        (
+            Self {
+                windows: BTreeMap::from([(id, window)]),
+            },
+            Task::batch([open.discard(), route(id, task)]),
+        )
+    }
+
+    pub fn update(&mut self, message: WindowsMessage) -> Task<WindowsMessage> {
+        match message {
+            (opener, Message::NewWindow) => self.open(opener),
+            (id, Message::WindowClosed) => {
+                let Some(closed) = self.windows.remove(&id) else {
+                    return Task::none();
+                };
+                if self.windows.is_empty() {
+                    return iced::exit();
+                }
+                // The tray icon and the control socket go with the next one.
+                if !closed.secondary
+                    && let Some(next) = self.windows.values_mut().next()
+                {
+                    next.secondary = false;
+                }
+                Task::none()
+            }
+            (id, message) => {
+                // Quitting from the tray closes every window, each asking
+                // first if it's busy.
+                let others = if matches!(message, Message::Tray(tray::Event::Quit)) {
+                    self.windows
+                        .keys()
+                        .filter(|&&other| other != id)
+                        .copied()
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+                let Some(window) = self.windows.get_mut(&id) else {
+                    return Task::none();
+                };
+                let before = window.settings();
+                let task = route(id, window.update(message));
+                let settings = window.settings();
+                if settings != before {
+                    for (_, other) in self.windows.iter_mut().filter(|(other, _)| **other != id) {
+                        other.apply_settings(settings.clone());
+                    }
+                }
+                Task::batch(
+                    std::iter::once(task).chain(
+                        others
+                            .into_iter()
+                            .map(|other| self.update((other, Message::CloseRequested))),
+                    ),
+                )
+            }
+        }
+    }
+
+    /// Opens another window, sharing what it can with `opener`.
+    fn open(&mut self, opener: window::Id) -> Task<WindowsMessage> {
+        let Some(opener) = self.windows.get(&opener) else {
+            return Task::none();
+        };
+        let (id, open) = window::open(window_settings());
+        let (window, task) = DfnGui::init(id, Vec::new(), Some(opener));
+        self.windows.insert(id, window);
+        tracing::info!("Opened a new window");
+        Task::batch([open.discard(), route(id, task)])
+    }
+
+    pub fn view(&self, id: window::Id) -> Element<'_, WindowsMessage> {
+        match self.windows.get(&id) {
+            Some(window) => window.view().map(move |message| (id, message)),
+            None => iced::widget::space().into(),
+        }
+    }
+
+    pub fn title(&self, id: window::Id) -> String {
+        self.windows.get(&id).map(DfnGui::title).unwrap_or_default()
+    }
+
+    pub fn theme(&self, id: window::Id) -> Option<Theme> {
+        self.windows.get(&id).map(DfnGui::theme)
+    }
+
+    pub fn scale_factor(&self, id: window::Id) -> f32 {
+        self.windows.get(&id).map_or(1.0, DfnGui::scale_factor)
+    }
+
+    /// Events and close requests, sent to the window they're for, and the
+    /// subscriptions of each window.
+    pub fn subscription(&self) -> Subscription<WindowsMessage> {
+        let events = [
+            iced::event::listen_with(|event, status, id| match status {
+                iced::event::Status::Ignored => Some((id, Message::EventOccurred(event))),
+                iced::event::Status::Captured => None,
+            }),
+            window::close_requests().map(|id| (id, Message::CloseRequested)),
+            window::close_events().map(|id| (id, Message::WindowClosed)),
+        ];
+        Subscription::batch(
+            events.into_iter().chain(
+                self.windows
+                    .iter()
+                    .map(|(&id, window)| window.subscription().with(id)),
+            ),
+        )
+    }
+}
+
+fn window_settings() -> window::Settings {
+    window::Settings {
+        size: WINDOW_SIZE,
+        exit_on_close_request: false,
+        ..Default::default()
+    }
+}
+
+/// Sends what a task of window `id` produces back to it.
+fn route(id: window::Id, task: Task<Message>) -> Task<WindowsMessage> {
+    task.map(move |message| (id, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options_panel::OptionsMessage;
+
+    /// Two windows, the first opened first.
+    fn two_windows() -> (Windows, window::Id, window::Id) {
+        let (first, second) = (window::Id::unique(), window::Id::unique());
+        let windows = Windows {
+            windows: BTreeMap::from([
+                (first, DfnGui::default()),
+                (
+                    second,
+                    DfnGui {
+                        secondary: true,
+                        ..DfnGui::default()
+                    },
+                ),
+            ]),
+        };
+        (windows, first, second)
+    }
+
+    #[tokio::test]
+    async fn settings_changed_in_one_window_apply_to_the_others() {
+        let (mut windows, first, second) = two_windows();
+        let normalize = !windows.windows[&first].options.normalize;
+
+        let _ = windows.update((
+            second,
+            Message::Options(OptionsMessage::NormalizeToggled(normalize)),
+        ));
+
+        assert_eq!(windows.windows[&first].options.normalize, normalize);
+        assert_eq!(windows.windows[&second].options.normalize, normalize);
+    }
+
+    #[test]
+    fn the_next_window_takes_over_from_the_first_one_closed() {
+        let (mut windows, first, second) = two_windows();
+
+        let _ = windows.update((first, Message::WindowClosed));
+
+        assert!(!windows.windows[&second].secondary);
+    }
+}